@@ -127,6 +127,33 @@ impl Type for Person {
   }
 }
 
+/// The lookups encode the query, so the reference type is also encodable, in the same format as `Person`.
+impl Type for PersonRef<'_> {
+  type Ref<'a> = PersonRef<'a>;
+  type Error = dbutils::error::InsufficientBuffer;
+
+  fn encoded_len(&self) -> usize {
+    encoded_u64_varint_len(self.id) + self.name.len()
+  }
+
+  #[inline]
+  fn encode(&self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+    let id_size = encode_u64_varint(self.id, buf)?;
+    buf[id_size..].copy_from_slice(self.name.as_bytes());
+    Ok(id_size + self.name.len())
+  }
+
+  #[inline]
+  fn encode_to_buffer(
+    &self,
+    buf: &mut orderwal::types::VacantBuffer<'_>,
+  ) -> Result<usize, Self::Error> {
+    let id_size = buf.put_u64_varint(self.id)?;
+    buf.put_slice_unchecked(self.name.as_bytes());
+    Ok(id_size + self.name.len())
+  }
+}
+
 impl<'a> TypeRef<'a> for PersonRef<'a> {
   unsafe fn from_slice(src: &'a [u8]) -> Self {
     let (id_size, id) = decode_u64_varint(src).unwrap();
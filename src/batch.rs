@@ -1,11 +1,30 @@
+use core::{
+  cmp,
+  iter::Peekable,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds},
+};
+
+use among::Among;
+use dbutils::{
+  buffer::VacantBuffer,
+  equivalent::Comparable,
+  types::{KeyRef, MaybeStructured, Type, TypeRef},
+};
+use skl::{either::Either, KeySize};
+use std::vec::Vec;
+
 use crate::{
-  memtable::BaseTable,
-  wal::{KeyPointer, ValuePointer},
+  error::Error,
+  memtable::{BaseTable, Memtable, MemtableEntry},
+  types::base::Entry,
+  utils::encoded_bytes,
+  wal::{base::Reader, KeyPointer, ValuePointer},
 };
 
 use super::{
   sealed::{WithVersion, WithoutVersion},
-  types::{BufWriter, EncodedEntryMeta, EntryFlags},
+  types::{BufWriter, EncodedEntryMeta, EntryFlags, KeyComparator, KeyOrder},
 };
 
 /// An entry can be inserted into the WALs through [`Batch`].
@@ -14,7 +33,10 @@ pub struct BatchEntry<K, V, M: BaseTable> {
   pub(crate) value: Option<V>,
   pub(crate) flag: EntryFlags,
   pub(crate) meta: EncodedEntryMeta,
-  pointers: Option<(KeyPointer<M::Key>, Option<ValuePointer<M::Value>>)>,
+  pointers: Option<(
+    KeyPointer<M::Key, M::Comparator>,
+    Option<ValuePointer<M::Value>>,
+  )>,
   pub(crate) version: Option<u64>,
 }
 
@@ -152,14 +174,21 @@ where
   }
 
   #[inline]
-  pub(crate) fn take_pointer(
-    &mut self,
-  ) -> Option<(KeyPointer<M::Key>, Option<ValuePointer<M::Value>>)> {
-    self.pointers.take()
+  pub(crate) fn pointer(
+    &self,
+  ) -> Option<(
+    KeyPointer<M::Key, M::Comparator>,
+    Option<ValuePointer<M::Value>>,
+  )> {
+    self.pointers
   }
 
   #[inline]
-  pub(crate) fn set_pointer(&mut self, kp: KeyPointer<M::Key>, vp: Option<ValuePointer<M::Value>>) {
+  pub(crate) fn set_pointer(
+    &mut self,
+    kp: KeyPointer<M::Key, M::Comparator>,
+    vp: Option<ValuePointer<M::Value>>,
+  ) {
     self.pointers = Some((kp, vp));
   }
 
@@ -174,6 +203,109 @@ where
   }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Condition {
+  Absent,
+  Present,
+  Version(u64),
+}
+
+/// A precondition which must hold before a [`Batch`] is inserted into the WALs.
+///
+/// The guards are checked against the latest values in the WALs, if any guard fails,
+/// the whole batch is rejected with [`BatchError::GuardFailed`](crate::error::BatchError::GuardFailed).
+pub struct BatchGuard<K, M> {
+  pub(crate) key: K,
+  pub(crate) condition: Condition,
+  _m: PhantomData<M>,
+}
+
+impl<K, M> BatchGuard<K, M> {
+  /// Creates a guard which holds only if the key is absent.
+  #[inline]
+  pub const fn absent(key: K) -> Self {
+    Self {
+      key,
+      condition: Condition::Absent,
+      _m: PhantomData,
+    }
+  }
+
+  /// Creates a guard which holds only if the key is present.
+  #[inline]
+  pub const fn present(key: K) -> Self {
+    Self {
+      key,
+      condition: Condition::Present,
+      _m: PhantomData,
+    }
+  }
+
+  /// Returns the key.
+  #[inline]
+  pub const fn key(&self) -> &K {
+    &self.key
+  }
+}
+
+impl<K, M> BatchGuard<K, M>
+where
+  M: BaseTable,
+  for<'a> M::Item<'a>: WithVersion,
+{
+  /// Creates a guard which holds only if the latest version of the key is `version`.
+  #[inline]
+  pub const fn at_version(key: K, version: u64) -> Self {
+    Self {
+      key,
+      condition: Condition::Version(version),
+      _m: PhantomData,
+    }
+  }
+}
+
+/// A [`Batch`] with the preconditions which must hold before it is inserted into the WALs.
+pub struct GuardedBatch<B, K, M> {
+  batch: B,
+  guards: Vec<BatchGuard<K, M>>,
+}
+
+impl<B, K, M> GuardedBatch<B, K, M> {
+  /// Creates a new guarded batch without any guards.
+  #[inline]
+  pub const fn new(batch: B) -> Self {
+    Self {
+      batch,
+      guards: Vec::new(),
+    }
+  }
+
+  /// Adds a guard to the batch.
+  #[inline]
+  pub fn with_guard(mut self, guard: BatchGuard<K, M>) -> Self {
+    self.guards.push(guard);
+    self
+  }
+
+  /// Adds a guard to the batch.
+  #[inline]
+  pub fn push_guard(&mut self, guard: BatchGuard<K, M>) {
+    self.guards.push(guard);
+  }
+
+  /// Returns the guards of the batch.
+  #[inline]
+  pub fn guards(&self) -> &[BatchGuard<K, M>] {
+    &self.guards
+  }
+
+  /// Consumes the guarded batch and returns the inner batch.
+  #[inline]
+  pub fn into_inner(self) -> B {
+    self.batch
+  }
+}
+
 /// A trait for batch insertions.
 pub trait Batch<M: BaseTable> {
   /// Any type that can be converted into a key.
@@ -196,6 +328,12 @@ pub trait Batch<M: BaseTable> {
     Self::Key: 'a,
     Self::Value: 'a,
     M: 'a;
+
+  /// Returns the preconditions which must hold before the batch is inserted.
+  #[inline]
+  fn guards(&self) -> &[BatchGuard<Self::Key, M>] {
+    &[]
+  }
 }
 
 impl<K, V, M, T> Batch<M> for T
@@ -224,3 +362,630 @@ where
     IntoIterator::into_iter(self)
   }
 }
+
+impl<B, M> Batch<M> for GuardedBatch<B, B::Key, M>
+where
+  M: BaseTable,
+  B: Batch<M>,
+{
+  type Key = B::Key;
+  type Value = B::Value;
+
+  type IterMut<'a>
+    = B::IterMut<'a>
+  where
+    Self: 'a,
+    Self::Key: 'a,
+    Self::Value: 'a,
+    M: 'a;
+
+  #[inline]
+  fn iter_mut<'a>(&'a mut self) -> Self::IterMut<'a>
+  where
+    Self: 'a,
+    Self::Key: 'a,
+    Self::Value: 'a,
+    M: 'a,
+  {
+    self.batch.iter_mut()
+  }
+
+  #[inline]
+  fn guards(&self) -> &[BatchGuard<Self::Key, M>] {
+    &self.guards
+  }
+}
+
+/// The encoded layout of an entry in a [`PreparedBatch`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PreparedEntry {
+  pub(crate) flag: EntryFlags,
+  pub(crate) version: Option<u64>,
+  pub(crate) key_offset: usize,
+  pub(crate) klen: usize,
+  pub(crate) vlen: usize,
+}
+
+/// An owned batch whose entries are encoded only once, in the same layout as the batch in the WALs.
+///
+/// Inserting a prepared batch into the WALs only copies the encoded entries and computes the checksum,
+/// so the batch can be built on other threads to keep the critical section of the writer short. The batch
+/// is not consumed by the insertion, so it can be kept for retrying or inserted into other WALs.
+///
+/// ## Example
+///
+/// ```rust
+/// use orderwal::{base::{OrderWal, Reader, Writer}, batch::PreparedBatch, Builder};
+///
+/// let mut wal = Builder::new()
+///   .with_capacity(1024)
+///   .alloc::<OrderWal<str, str>>()
+///   .unwrap();
+///
+/// let batch = std::thread::spawn(|| {
+///   let mut batch = PreparedBatch::new();
+///   batch.insert("a", "1").unwrap();
+///   batch.insert("b", "2").unwrap();
+///   batch.remove("c").unwrap();
+///   batch
+/// })
+/// .join()
+/// .unwrap();
+///
+/// wal.insert_prepared_batch(&batch).unwrap();
+/// assert_eq!(wal.get("a").unwrap().value(), "1");
+/// assert_eq!(wal.get("b").unwrap().value(), "2");
+/// ```
+pub struct PreparedBatch<M> {
+  buf: Vec<u8>,
+  entries: Vec<PreparedEntry>,
+  _m: PhantomData<M>,
+}
+
+impl<M> Default for PreparedBatch<M> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<M> Clone for PreparedBatch<M> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      buf: self.buf.clone(),
+      entries: self.entries.clone(),
+      _m: PhantomData,
+    }
+  }
+}
+
+impl<M> core::fmt::Debug for PreparedBatch<M> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("PreparedBatch")
+      .field("entries", &self.entries.len())
+      .field("encoded_size", &self.buf.len())
+      .finish()
+  }
+}
+
+impl<M> PreparedBatch<M> {
+  /// Creates an empty batch.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      buf: Vec::new(),
+      entries: Vec::new(),
+      _m: PhantomData,
+    }
+  }
+
+  /// Returns the number of the entries in the batch.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns `true` if the batch is empty.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Returns the size of the encoded entries in the batch.
+  #[inline]
+  pub fn encoded_size(&self) -> usize {
+    self.buf.len()
+  }
+
+  /// Removes all the entries in the batch, the allocated buffer is kept for reuse.
+  #[inline]
+  pub fn clear(&mut self) {
+    self.buf.clear();
+    self.entries.clear();
+  }
+
+  #[inline]
+  pub(crate) fn encoded(&self) -> &[u8] {
+    &self.buf
+  }
+
+  #[inline]
+  pub(crate) fn entries(&self) -> &[PreparedEntry] {
+    &self.entries
+  }
+}
+
+impl<M> PreparedBatch<M>
+where
+  M: BaseTable,
+  M::Key: Type,
+  M::Value: Type,
+{
+  fn push<'a>(
+    &mut self,
+    version: Option<u64>,
+    key: MaybeStructured<'a, M::Key>,
+    value: Option<MaybeStructured<'a, M::Value>>,
+  ) -> Result<(), Among<<M::Key as Type>::Error, <M::Value as Type>::Error, Error<M>>> {
+    let klen = key.encoded_len();
+    let vlen = value.as_ref().map_or(0, |v| v.encoded_len());
+    if klen > u32::MAX as usize {
+      return Err(Among::Right(Error::key_too_large(klen as u64, u32::MAX)));
+    }
+    if vlen > u32::MAX as usize {
+      return Err(Among::Right(Error::value_too_large(vlen as u64, u32::MAX)));
+    }
+
+    let mut flag = if value.is_some() {
+      EntryFlags::empty()
+    } else {
+      EntryFlags::REMOVED
+    };
+    if version.is_some() {
+      flag |= EntryFlags::VERSIONED;
+    }
+
+    let meta = EncodedEntryMeta::batch(klen, vlen, version.is_some());
+    let start = self.buf.len();
+    self.buf.resize(start + meta.entry_size as usize, 0);
+    let result = {
+      let buf = &mut self.buf[start..];
+      let (header, rest) = buf.split_at_mut(meta.key_offset());
+      let (key_buf, rest) = rest.split_at_mut(meta.value_offset() - meta.key_offset());
+      let value_buf = &mut rest[..meta.checksum_offset() - meta.value_offset()];
+
+      let mut vb = VacantBuffer::from(header);
+      vb.put_u64_varint_unchecked(meta.packed_kvlen);
+      vb.put_u8_unchecked(flag.bits());
+      if let Some(version) = version {
+        vb.put_u64_le_unchecked(version);
+      }
+
+      key
+        .write(&mut VacantBuffer::from(key_buf))
+        .map_err(Among::Left)
+        .and_then(|_| match value {
+          Some(value) => value
+            .write(&mut VacantBuffer::from(value_buf))
+            .map(|_| ())
+            .map_err(Among::Middle),
+          None => Ok(()),
+        })
+    };
+    // The buffers above are released, so the entry can be discarded.
+    if let Err(e) = result {
+      self.buf.truncate(start);
+      return Err(e);
+    }
+
+    self.entries.push(PreparedEntry {
+      flag,
+      version,
+      key_offset: start + meta.key_offset(),
+      klen,
+      vlen,
+    });
+    Ok(())
+  }
+}
+
+impl<M> PreparedBatch<M>
+where
+  M: BaseTable,
+  M::Key: Type,
+  M::Value: Type,
+  for<'a> M::Item<'a>: WithoutVersion,
+{
+  /// Encodes a key-value pair into the batch.
+  #[inline]
+  pub fn insert<'a>(
+    &mut self,
+    key: impl Into<MaybeStructured<'a, M::Key>>,
+    value: impl Into<MaybeStructured<'a, M::Value>>,
+  ) -> Result<(), Among<<M::Key as Type>::Error, <M::Value as Type>::Error, Error<M>>>
+  where
+    M::Key: 'a,
+    M::Value: 'a,
+  {
+    self.push(None, key.into(), Some(value.into()))
+  }
+
+  /// Encodes a tombstone of the key into the batch.
+  #[inline]
+  pub fn remove<'a>(
+    &mut self,
+    key: impl Into<MaybeStructured<'a, M::Key>>,
+  ) -> Result<(), Either<<M::Key as Type>::Error, Error<M>>>
+  where
+    M::Key: 'a,
+  {
+    self.push(None, key.into(), None).map_err(either_err)
+  }
+}
+
+impl<M> PreparedBatch<M>
+where
+  M: BaseTable,
+  M::Key: Type,
+  M::Value: Type,
+  for<'a> M::Item<'a>: WithVersion,
+{
+  /// Encodes a key-value pair with version into the batch.
+  #[inline]
+  pub fn insert_with_version<'a>(
+    &mut self,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, M::Key>>,
+    value: impl Into<MaybeStructured<'a, M::Value>>,
+  ) -> Result<(), Among<<M::Key as Type>::Error, <M::Value as Type>::Error, Error<M>>>
+  where
+    M::Key: 'a,
+    M::Value: 'a,
+  {
+    self.push(Some(version), key.into(), Some(value.into()))
+  }
+
+  /// Encodes a tombstone of the key with version into the batch.
+  #[inline]
+  pub fn remove_with_version<'a>(
+    &mut self,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, M::Key>>,
+  ) -> Result<(), Either<<M::Key as Type>::Error, Error<M>>>
+  where
+    M::Key: 'a,
+  {
+    self
+      .push(Some(version), key.into(), None)
+      .map_err(either_err)
+  }
+}
+
+#[inline]
+fn either_err<K, V, E>(e: Among<K, V, E>) -> Either<K, E> {
+  match e {
+    Among::Left(e) => Either::Left(e),
+    Among::Middle(_) => unreachable!("tombstones do not have values"),
+    Among::Right(e) => Either::Right(e),
+  }
+}
+
+/// A batch which keeps its entries sorted by the keys, so the entries can be queried before the batch is inserted.
+///
+/// The keys and values are encoded and checked against the size limits when they are added to the batch,
+/// the later writes to the same key overwrite the earlier ones. The batch can be inserted into the WALs
+/// by [`Writer::insert_batch_with_builders`](crate::base::Writer::insert_batch_with_builders).
+///
+/// ## Example
+///
+/// ```rust
+/// use orderwal::{base::{OrderWal, Reader, Writer}, batch::WriteBatch, Builder};
+///
+/// let mut wal = Builder::new()
+///   .with_capacity(1024)
+///   .alloc::<OrderWal<str, str>>()
+///   .unwrap();
+/// wal.insert("a", "1").unwrap();
+/// wal.insert("b", "2").unwrap();
+///
+/// let mut batch = WriteBatch::new().with_maximum_key_size(wal.maximum_key_size());
+/// batch.insert("b", "3").unwrap();
+/// batch.remove("a").unwrap();
+///
+/// // The batch shadows the entries in the WAL.
+/// assert!(batch.get(&wal, "a").is_none());
+/// assert_eq!(batch.get(&wal, "b").unwrap(), "3");
+///
+/// wal.insert_batch_with_builders(&mut batch).unwrap();
+/// assert!(!wal.contains_key("a"));
+/// assert_eq!(wal.get("b").unwrap().value(), "3");
+/// ```
+pub struct WriteBatch<M: BaseTable> {
+  entries: Vec<BatchEntry<Vec<u8>, Vec<u8>, M>>,
+  maximum_key_size: KeySize,
+  maximum_value_size: u32,
+  size: u64,
+}
+
+impl<M: BaseTable> Default for WriteBatch<M> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<M: BaseTable> WriteBatch<M> {
+  /// Creates an empty batch with the default size limits.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      entries: Vec::new(),
+      maximum_key_size: KeySize::new(),
+      maximum_value_size: u32::MAX,
+      size: 0,
+    }
+  }
+
+  /// Set the maximum key size of the entries in the batch, it should be the same as the WAL's.
+  #[inline]
+  pub const fn with_maximum_key_size(mut self, size: KeySize) -> Self {
+    self.maximum_key_size = size;
+    self
+  }
+
+  /// Set the maximum value size of the entries in the batch, it should be the same as the WAL's.
+  #[inline]
+  pub const fn with_maximum_value_size(mut self, size: u32) -> Self {
+    self.maximum_value_size = size;
+    self
+  }
+
+  /// Returns the number of the entries in the batch.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns `true` if the batch is empty.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Returns the total size of the encoded keys and values in the batch.
+  #[inline]
+  pub const fn size(&self) -> u64 {
+    self.size
+  }
+
+  /// Removes all the entries in the batch.
+  #[inline]
+  pub fn clear(&mut self) {
+    self.entries.clear();
+    self.size = 0;
+  }
+}
+
+impl<M> WriteBatch<M>
+where
+  M: BaseTable,
+  M::Key: Type,
+  M::Value: Type,
+  for<'a> <M::Key as Type>::Ref<'a>: KeyRef<'a, M::Key>,
+  for<'a> M::Item<'a>: WithoutVersion,
+{
+  /// Inserts a key-value pair into the batch.
+  pub fn insert<'a>(
+    &mut self,
+    key: impl Into<MaybeStructured<'a, M::Key>>,
+    value: impl Into<MaybeStructured<'a, M::Value>>,
+  ) -> Result<(), Among<<M::Key as Type>::Error, <M::Value as Type>::Error, Error<M>>>
+  where
+    M::Key: 'a,
+    M::Value: 'a,
+  {
+    let key = self.encode_key(&key.into()).map_err(|e| match e {
+      Either::Left(e) => Among::Left(e),
+      Either::Right(e) => Among::Right(e),
+    })?;
+    let value = encoded_bytes(&value.into())
+      .map_err(Among::Middle)?
+      .into_owned();
+    if value.len() > self.maximum_value_size as usize {
+      return Err(Among::Right(Error::value_too_large(
+        value.len() as u64,
+        self.maximum_value_size,
+      )));
+    }
+
+    self.put(BatchEntry::new(key, value));
+    Ok(())
+  }
+
+  /// Removes a key from the batch, the key will be removed from the WAL when the batch is inserted.
+  pub fn remove<'a>(
+    &mut self,
+    key: impl Into<MaybeStructured<'a, M::Key>>,
+  ) -> Result<(), Either<<M::Key as Type>::Error, Error<M>>>
+  where
+    M::Key: 'a,
+  {
+    let key = self.encode_key(&key.into())?;
+    self.put(BatchEntry::tombstone(key));
+    Ok(())
+  }
+
+  /// Returns the value associated with the key, the entries in the batch shadow the ones in the `reader`.
+  pub fn get<'a, Q, R>(&'a self, reader: &'a R, key: &Q) -> Option<<M::Value as Type>::Ref<'a>>
+  where
+    R: Reader<Memtable = M>,
+    Q: ?Sized
+      + Comparable<<M::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <M::Key as Type>::Ref<'b>>,
+    M: Memtable<Comparator = KeyOrder>,
+    for<'b> M::Item<'b>: MemtableEntry<'b>,
+    M::Key: Ord,
+  {
+    let idx = partition_point(&self.entries, |ent| key.compare(&decode(&ent.key)).is_gt());
+    match self.entries.get(idx) {
+      Some(ent) if key.compare(&decode(&ent.key)).is_eq() => ent.value.as_deref().map(decode),
+      _ => reader.get(key).map(|ent| *ent.value()),
+    }
+  }
+
+  /// Returns an iterator over a subset of the entries, the entries in the batch shadow the ones in the `reader`.
+  pub fn range<'a, Q, B, R>(
+    &'a self,
+    reader: &'a R,
+    range: B,
+  ) -> WriteBatchRange<'a, crate::wal::base::Range<'a, B, Q, M>, M>
+  where
+    R: Reader<Memtable = M>,
+    B: RangeBounds<Q>,
+    Q: ?Sized + Comparable<<M::Key as Type>::Ref<'a>>,
+    M: Memtable<Comparator = KeyOrder>,
+    for<'b> M::Item<'b>: MemtableEntry<'b>,
+    M::Key: Ord,
+  {
+    let start = match range.start_bound() {
+      Bound::Included(q) => {
+        partition_point(&self.entries, |ent| q.compare(&decode(&ent.key)).is_gt())
+      }
+      Bound::Excluded(q) => {
+        partition_point(&self.entries, |ent| q.compare(&decode(&ent.key)).is_ge())
+      }
+      Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+      Bound::Included(q) => {
+        partition_point(&self.entries, |ent| q.compare(&decode(&ent.key)).is_ge())
+      }
+      Bound::Excluded(q) => {
+        partition_point(&self.entries, |ent| q.compare(&decode(&ent.key)).is_gt())
+      }
+      Bound::Unbounded => self.entries.len(),
+    };
+
+    WriteBatchRange {
+      batch: &self.entries[start..end.max(start)],
+      iter: reader.range(range).peekable(),
+    }
+  }
+
+  fn encode_key(
+    &self,
+    key: &MaybeStructured<'_, M::Key>,
+  ) -> Result<Vec<u8>, Either<<M::Key as Type>::Error, Error<M>>> {
+    let key = encoded_bytes(key).map_err(Either::Left)?.into_owned();
+    let maximum_key_size = self.maximum_key_size.to_u32();
+    if key.len() > maximum_key_size as usize {
+      return Err(Either::Right(Error::key_too_large(
+        key.len() as u64,
+        maximum_key_size,
+      )));
+    }
+    Ok(key)
+  }
+
+  fn put(&mut self, ent: BatchEntry<Vec<u8>, Vec<u8>, M>) {
+    let size = (ent.key.len() + ent.value.as_ref().map_or(0, Vec::len)) as u64;
+    match self.entries.binary_search_by(|old| {
+      // SAFETY: the keys are encoded by the key type.
+      unsafe { <M::Comparator as KeyComparator<M::Key>>::compare(&old.key, &ent.key) }
+    }) {
+      Ok(idx) => {
+        let old = core::mem::replace(&mut self.entries[idx], ent);
+        self.size =
+          self.size + size - (old.key.len() + old.value.as_ref().map_or(0, Vec::len)) as u64;
+      }
+      Err(idx) => {
+        self.entries.insert(idx, ent);
+        self.size += size;
+      }
+    }
+  }
+}
+
+impl<'a, M: BaseTable> IntoIterator for &'a mut WriteBatch<M> {
+  type Item = &'a mut BatchEntry<Vec<u8>, Vec<u8>, M>;
+  type IntoIter = core::slice::IterMut<'a, BatchEntry<Vec<u8>, Vec<u8>, M>>;
+
+  #[inline]
+  fn into_iter(self) -> Self::IntoIter {
+    self.entries.iter_mut()
+  }
+}
+
+/// An iterator over a subset of the entries in a [`WriteBatch`] and the underlying WAL.
+pub struct WriteBatchRange<'a, I, M>
+where
+  M: BaseTable,
+  I: Iterator,
+{
+  batch: &'a [BatchEntry<Vec<u8>, Vec<u8>, M>],
+  iter: Peekable<I>,
+}
+
+impl<'a, I, M> Iterator for WriteBatchRange<'a, I, M>
+where
+  M: Memtable + 'a,
+  M::Key: Type,
+  M::Value: Type,
+  for<'b> <M::Key as Type>::Ref<'b>: KeyRef<'b, M::Key>,
+  for<'b> M::Item<'b>: MemtableEntry<'b>,
+  I: Iterator<Item = Entry<'a, M::Item<'a>>>,
+{
+  type Item = (<M::Key as Type>::Ref<'a>, <M::Value as Type>::Ref<'a>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let Some((ent, rest)) = self.batch.split_first() else {
+        return self.iter.next().map(|ent| (*ent.key(), *ent.value()));
+      };
+
+      if let Some(next) = self.iter.peek() {
+        // SAFETY: the keys are encoded by the key type.
+        match unsafe {
+          <<M::Key as Type>::Ref<'_> as KeyRef<'_, M::Key>>::compare_binary(
+            next.raw_key(),
+            &ent.key,
+          )
+        } {
+          cmp::Ordering::Less => {
+            return self.iter.next().map(|ent| (*ent.key(), *ent.value()));
+          }
+          cmp::Ordering::Equal => {
+            self.iter.next();
+          }
+          cmp::Ordering::Greater => {}
+        }
+      }
+
+      self.batch = rest;
+      if let Some(value) = ent.value.as_deref() {
+        return Some((decode(&ent.key), decode(value)));
+      }
+    }
+  }
+}
+
+/// Returns the index of the first entry which does not match the predicate, the entries must be partitioned by the predicate.
+fn partition_point<'a, T>(entries: &'a [T], mut pred: impl FnMut(&'a T) -> bool) -> usize {
+  let (mut lo, mut hi) = (0, entries.len());
+  while lo < hi {
+    let mid = lo + (hi - lo) / 2;
+    if pred(&entries[mid]) {
+      lo = mid + 1;
+    } else {
+      hi = mid;
+    }
+  }
+  lo
+}
+
+#[inline]
+fn decode<'a, T: TypeRef<'a>>(src: &'a [u8]) -> T {
+  // SAFETY: the bytes in the write batch are encoded by the key or value type.
+  unsafe { T::from_slice(src) }
+}
@@ -6,6 +6,7 @@ use super::{
   memtable::BaseTable,
   options::{arena_options, Options},
   sealed::Constructable,
+  types::MergeOperator,
 };
 
 #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
@@ -276,6 +277,66 @@ where
     self.opts.sync()
   }
 
+  /// Returns the maximum number of versions per key which are retained by the multiple version WALs.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, multiple_version::DefaultTable};
+  ///
+  /// let options = Builder::<DefaultTable<[u8], [u8]>>::new().with_maximum_versions_per_key(Some(10));
+  /// assert_eq!(options.maximum_versions_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn maximum_versions_per_key(&self) -> Option<u32> {
+    self.opts.maximum_versions_per_key()
+  }
+
+  /// Returns the window of versions, counted from the latest version of the WAL,
+  /// which is retained by the multiple version WALs.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, multiple_version::DefaultTable};
+  ///
+  /// let options = Builder::<DefaultTable<[u8], [u8]>>::new().with_version_window(Some(100));
+  /// assert_eq!(options.version_window(), Some(100));
+  /// ```
+  #[inline]
+  pub const fn version_window(&self) -> Option<u64> {
+    self.opts.version_window()
+  }
+
+  /// Returns the merge operator which folds the merge operands of the WAL.
+  ///
+  /// The default value is `None`.
+  #[inline]
+  pub const fn merge_operator(&self) -> Option<&'static dyn MergeOperator> {
+    self.opts.merge_operator()
+  }
+
+  /// Returns the bits per key of the Bloom filter over the keys of the WAL.
+  ///
+  /// The default value is `None`, which means the filter is disabled.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, base::DefaultTable};
+  ///
+  /// let options = Builder::<DefaultTable<[u8], [u8]>>::new().with_bloom_filter_bits_per_key(Some(10));
+  /// assert_eq!(options.bloom_filter_bits_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn bloom_filter_bits_per_key(&self) -> Option<u32> {
+    self.opts.bloom_filter_bits_per_key()
+  }
+
   /// Sets the capacity of the WAL.
   ///
   /// This configuration will be ignored when using file-backed memory maps.
@@ -364,6 +425,78 @@ where
     self.opts = self.opts.with_magic_version(version);
     self
   }
+
+  /// Sets the maximum number of versions per key which are retained by the multiple version WALs.
+  ///
+  /// See [`Options::with_maximum_versions_per_key`] for more details.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, multiple_version::DefaultTable};
+  ///
+  /// let options = Builder::<DefaultTable<[u8], [u8]>>::new().with_maximum_versions_per_key(Some(10));
+  /// assert_eq!(options.maximum_versions_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn with_maximum_versions_per_key(mut self, max: Option<u32>) -> Self {
+    self.opts = self.opts.with_maximum_versions_per_key(max);
+    self
+  }
+
+  /// Sets the window of versions, counted from the latest version of the WAL,
+  /// which is retained by the multiple version WALs.
+  ///
+  /// See [`Options::with_version_window`] for more details.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, multiple_version::DefaultTable};
+  ///
+  /// let options = Builder::<DefaultTable<[u8], [u8]>>::new().with_version_window(Some(100));
+  /// assert_eq!(options.version_window(), Some(100));
+  /// ```
+  #[inline]
+  pub const fn with_version_window(mut self, window: Option<u64>) -> Self {
+    self.opts = self.opts.with_version_window(window);
+    self
+  }
+
+  /// Sets the merge operator which folds the merge operands written by `merge` over the existing values.
+  ///
+  /// See [`Options::with_merge_operator`] for more details.
+  ///
+  /// The default value is `None`.
+  #[inline]
+  pub const fn with_merge_operator(mut self, operator: Option<&'static dyn MergeOperator>) -> Self {
+    self.opts = self.opts.with_merge_operator(operator);
+    self
+  }
+
+  /// Sets the bits per key of the Bloom filter over the keys of the WAL, `None` or `0` disables the filter.
+  ///
+  /// See [`Options::with_bloom_filter_bits_per_key`] for more details.
+  ///
+  /// The default value is `None`.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, base::DefaultTable};
+  ///
+  /// let options = Builder::<DefaultTable<[u8], [u8]>>::new().with_bloom_filter_bits_per_key(Some(10));
+  /// assert_eq!(options.bloom_filter_bits_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn with_bloom_filter_bits_per_key(mut self, bits: Option<u32>) -> Self {
+    self.opts = self.opts.with_bloom_filter_bits_per_key(bits);
+    self
+  }
 }
 
 impl<M, S> Builder<M, S>
@@ -1,5 +1,9 @@
 use super::*;
-use crate::{options::ArenaOptionsExt, sealed::Immutable};
+use crate::{
+  options::{detect_version, versioned_arena_options, ArenaOptionsExt},
+  sealed::Immutable,
+  CURRENT_VERSION,
+};
 
 use dbutils::{
   checksum::BuildChecksumer,
@@ -278,6 +282,63 @@ where
     self.opts.populate = populate;
     self
   }
+
+  /// Sets if the memtable index of the WAL is persisted to a sidecar file next to the WAL file.
+  ///
+  /// See [`Options::with_persisted_index`] for more details.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::{Builder, multiple_version::LinkedTable};
+  ///
+  /// let opts = Builder::<LinkedTable<[u8], [u8]>>::new().with_persisted_index(true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn with_persisted_index(mut self, persisted_index: bool) -> Self {
+    self.opts.persisted_index = persisted_index;
+    self
+  }
+
+  /// Sets the number of the threads used to replay the WAL when opening it.
+  ///
+  /// See [`Options::with_replay_threads`] for more details.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::{Builder, multiple_version::LinkedTable};
+  ///
+  /// let opts = Builder::<LinkedTable<[u8], [u8]>>::new().with_replay_threads(4);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn with_replay_threads(mut self, threads: usize) -> Self {
+    self.opts.replay_threads = threads;
+    self
+  }
+
+  /// Sets whether the memtable is replayed in the background when opening the WAL.
+  ///
+  /// See [`Options::with_lazy_replay`] for more details.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::{Builder, multiple_version::LinkedTable};
+  ///
+  /// let opts = Builder::<LinkedTable<[u8], [u8]>>::new().with_lazy_replay(true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn with_lazy_replay(mut self, lazy_replay: bool) -> Self {
+    self.opts.lazy_replay = lazy_replay;
+    self
+  }
 }
 
 impl<M, S> Builder<M, S>
@@ -458,6 +519,57 @@ where
   pub const fn populate(&self) -> bool {
     self.opts.populate
   }
+
+  /// Returns if the memtable index of the WAL is persisted to a sidecar file next to the WAL file.
+  ///
+  /// ## Examples
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, multiple_version::LinkedTable};
+  ///
+  /// let opts = Builder::<LinkedTable<[u8], [u8]>>::new().with_persisted_index(true);
+  /// assert_eq!(opts.persisted_index(), true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn persisted_index(&self) -> bool {
+    self.opts.persisted_index
+  }
+
+  /// Returns the number of the threads used to replay the WAL when opening it.
+  ///
+  /// ## Examples
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, multiple_version::LinkedTable};
+  ///
+  /// let opts = Builder::<LinkedTable<[u8], [u8]>>::new().with_replay_threads(4);
+  /// assert_eq!(opts.replay_threads(), 4);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn replay_threads(&self) -> usize {
+    self.opts.replay_threads
+  }
+
+  /// Returns whether the memtable is replayed in the background when opening the WAL.
+  ///
+  /// ## Examples
+  ///
+  /// ```rust
+  /// use orderwal::{Builder, multiple_version::LinkedTable};
+  ///
+  /// let opts = Builder::<LinkedTable<[u8], [u8]>>::new().with_lazy_replay(true);
+  /// assert_eq!(opts.lazy_replay(), true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn lazy_replay(&self) -> bool {
+    self.opts.lazy_replay
+  }
 }
 
 impl<M, S> Builder<M, S>
@@ -529,7 +641,10 @@ where
   #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
   pub unsafe fn map<'a, W, P>(self, path: P) -> Result<W, Error<W::Memtable>>
   where
-    S: BuildChecksumer,
+    S: BuildChecksumer + Sync,
+    M: Sync + 'static,
+    M::Error: Send,
+    W::Wal: Send + Sync,
     P: AsRef<std::path::Path>,
     W: Constructable<Memtable = M, Checksumer = S> + Immutable,
     M::Key: Type + Ord + 'static,
@@ -578,7 +693,10 @@ where
   ) -> Result<W, Either<E, Error<W::Memtable>>>
   where
     PB: FnOnce() -> Result<std::path::PathBuf, E>,
-    S: BuildChecksumer,
+    S: BuildChecksumer + Sync,
+    M: Sync + 'static,
+    M::Error: Send,
+    W::Wal: Send + Sync,
     W: Constructable<Memtable = M, Checksumer = S> + Immutable,
     M::Key: Type + Ord + 'static,
     <M::Key as Type>::Ref<'a>: KeyRef<'a, M::Key>,
@@ -589,15 +707,22 @@ where
       memtable_opts,
     } = self;
 
-    arena_options(opts.reserved())
+    let path = path_builder().map_err(Either::Left)?;
+    let version = detect_version(&path, opts.reserved()).map_err(|e| Either::Right(e.into()))?;
+
+    versioned_arena_options(version, opts.reserved())
       .merge(&opts)
       .with_read(true)
-      .map_with_path_builder(path_builder)
-      .map_err(|e| e.map_right(Into::into))
+      .map(path)
+      .map_err(|e| Either::Right(e.into()))
       .and_then(|arena| {
-        W::replay(arena, Options::new(), memtable_opts, true, cks)
-          .map(Constructable::from_core)
-          .map_err(Either::Right)
+        let opts = Options::new()
+          .with_merge_operator(opts.merge_operator())
+          .with_bloom_filter_bits_per_key(opts.bloom_filter_bits_per_key())
+          .with_persisted_index(opts.persisted_index())
+          .with_replay_threads(opts.replay_threads())
+          .with_lazy_replay(opts.lazy_replay());
+        W::open(arena, opts, memtable_opts, true, cks).map_err(Either::Right)
       })
   }
 
@@ -633,7 +758,10 @@ where
   #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
   pub unsafe fn map_mut<'a, W, P>(self, path: P) -> Result<W, Error<W::Memtable>>
   where
-    S: BuildChecksumer,
+    S: BuildChecksumer + Sync,
+    M: Sync + 'static,
+    M::Error: Send,
+    W::Wal: Send + Sync,
     P: AsRef<std::path::Path>,
     W: Constructable<Memtable = M, Checksumer = S>,
     M::Key: Type + Ord + 'static,
@@ -681,7 +809,10 @@ where
   ) -> Result<W, Either<E, Error<W::Memtable>>>
   where
     PB: FnOnce() -> Result<std::path::PathBuf, E>,
-    S: BuildChecksumer,
+    S: BuildChecksumer + Sync,
+    M: Sync + 'static,
+    M::Error: Send,
+    W::Wal: Send + Sync,
     W: Constructable<Memtable = M, Checksumer = S>,
     M::Key: Type + Ord + 'static,
     <M::Key as Type>::Ref<'a>: KeyRef<'a, M::Key>,
@@ -694,7 +825,13 @@ where
       memtable_opts,
     } = self;
 
-    arena_options(opts.reserved())
+    let version = if exist {
+      detect_version(&path, opts.reserved()).map_err(|e| Either::Right(e.into()))?
+    } else {
+      CURRENT_VERSION
+    };
+
+    versioned_arena_options(version, opts.reserved())
       .merge(&opts)
       .map_mut(path)
       .map_err(Into::into)
@@ -702,7 +839,7 @@ where
         if !exist {
           W::new_in(arena, opts, memtable_opts, cks).map(W::from_core)
         } else {
-          W::replay(arena, opts, memtable_opts, false, cks).map(W::from_core)
+          W::open(arena, opts, memtable_opts, false, cks)
         }
       })
       .map_err(Either::Right)
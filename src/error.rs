@@ -19,6 +19,8 @@ pub enum BatchError {
   },
   /// Larger encoding size than the expected batch encoding size.
   LargerEncodedSize(u32),
+  /// Returned when a guard of the batch does not hold, contains the index of the guard.
+  GuardFailed(usize),
 }
 
 impl core::fmt::Display for BatchError {
@@ -38,6 +40,7 @@ impl core::fmt::Display for BatchError {
           size
         )
       }
+      Self::GuardFailed(idx) => write!(f, "the guard at index {} of the batch does not hold", idx),
     }
   }
 }
@@ -79,6 +82,9 @@ pub enum Error<T: BaseTable> {
   /// The WAL is read-only.
   ReadOnly,
 
+  /// Returned when writing or replaying merge operands without a merge operator.
+  MissingMergeOperator,
+
   /// Unknown WAL kind.
   #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
   #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
@@ -158,6 +164,7 @@ where
       ),
       Self::Batch(e) => write!(f, "{e}"),
       Self::ReadOnly => write!(f, "The WAL is read-only"),
+      Self::MissingMergeOperator => write!(f, "merge operator is not configured"),
 
       #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
       Self::UnknownKind(e) => write!(f, "{e}"),
@@ -207,6 +214,7 @@ where
       Self::EntryTooLarge { .. } => None,
       Self::Batch(e) => Some(e),
       Self::ReadOnly => None,
+      Self::MissingMergeOperator => None,
 
       #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
       Self::UnknownKind(e) => Some(e),
@@ -325,12 +333,24 @@ impl<T: BaseTable> Error<T> {
     Self::Batch(BatchError::LargerEncodedSize(size))
   }
 
+  /// Create a new batch guard failed error.
+  #[inline]
+  pub(crate) const fn guard_failed(idx: usize) -> Self {
+    Self::Batch(BatchError::GuardFailed(idx))
+  }
+
   /// Create a read-only error.
   #[inline]
   pub(crate) const fn read_only() -> Self {
     Self::ReadOnly
   }
 
+  /// Create a missing merge operator error.
+  #[inline]
+  pub(crate) const fn missing_merge_operator() -> Self {
+    Self::MissingMergeOperator
+  }
+
   #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
   #[inline]
   pub(crate) fn magic_text_mismatch() -> Self {
@@ -348,6 +368,15 @@ impl<T: BaseTable> Error<T> {
       "magic version of orderwal does not match",
     ))
   }
+
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[inline]
+  pub(crate) fn comparator_mismatch() -> Self {
+    Self::IO(std::io::Error::new(
+      std::io::ErrorKind::InvalidData,
+      "comparator of orderwal does not match",
+    ))
+  }
 }
 
 /// Unknown WAL kind error.
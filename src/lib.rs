@@ -31,14 +31,23 @@ pub use dbutils::checksum::XxHash64;
 
 const RECORD_FLAG_SIZE: usize = mem::size_of::<Flags>();
 const CHECKSUM_SIZE: usize = mem::size_of::<u64>();
-const CURRENT_VERSION: u16 = 0;
+const CURRENT_VERSION: u16 = 1;
+/// The format version of the WALs created before the comparator id was recorded in the header.
+const LEGACY_VERSION: u16 = 0;
 const MAGIC_TEXT: [u8; 5] = *b"order";
 const MAGIC_TEXT_SIZE: usize = MAGIC_TEXT.len();
 const WAL_KIND_SIZE: usize = mem::size_of::<types::Kind>();
 const MAGIC_VERSION_SIZE: usize = mem::size_of::<u16>();
-const HEADER_SIZE: usize = MAGIC_TEXT_SIZE + WAL_KIND_SIZE + MAGIC_VERSION_SIZE;
+/// The identity of the comparator, `0` if the keys are ordered by the key type.
+const COMPARATOR_ID_SIZE: usize = mem::size_of::<u64>();
+const LEGACY_HEADER_SIZE: usize = MAGIC_TEXT_SIZE + WAL_KIND_SIZE + MAGIC_VERSION_SIZE;
+const HEADER_SIZE: usize = LEGACY_HEADER_SIZE + COMPARATOR_ID_SIZE;
 /// The mvcc version size.
 const VERSION_SIZE: usize = mem::size_of::<u64>();
+/// The transaction id size.
+const TRANSACTION_ID_SIZE: usize = mem::size_of::<u64>();
+/// The expiration time size, which is the milliseconds since the UNIX epoch.
+const EXPIRATION_SIZE: usize = mem::size_of::<u64>();
 
 /// Error types.
 pub mod error;
@@ -67,6 +76,8 @@ pub mod memtable;
 mod sealed;
 pub use sealed::Immutable;
 
+mod sync;
+
 /// The utilities functions.
 pub mod utils;
 
@@ -77,5 +88,7 @@ bitflags::bitflags! {
     const COMMITTED = 0b00000001;
     /// Second bit: 1 indicates batching, 0 indicates single entry
     const BATCHING = 0b00000010;
+    /// Third bit: 1 indicates the entry of the record expires, the expiration time follows the transaction id
+    const EXPIRING = 0b00000100;
   }
 }
@@ -1,10 +1,11 @@
 use core::ops::{Bound, RangeBounds};
 use dbutils::equivalent::Comparable;
+use ref_cast::RefCast;
 
 use crate::{
   sealed::{WithVersion, WithoutVersion},
-  types::Kind,
-  wal::{KeyPointer, ValuePointer},
+  types::{KeyComparator, Kind},
+  wal::{KeyPointer, Slice, ValuePointer},
 };
 
 /// Memtable implementation based on linked based [`SkipMap`][`crossbeam_skiplist`].
@@ -15,6 +16,15 @@ pub mod linked;
 /// Memtable implementation based on ARNEA based [`SkipMap`](skl).
 pub mod arena;
 
+/// Memtable implementation based on [`BTreeMap`](std::collections::BTreeMap) protected by a lightweight lock.
+pub mod btree;
+
+/// Memtable implementation based on adaptive radix tree, for the byte string keys.
+pub mod art;
+
+/// Memtable implementation based on sorted array, for the WALs which are rarely written after replaying.
+pub mod sorted;
+
 /// Sum type for different memtable implementations.
 pub mod alternative;
 
@@ -24,9 +34,11 @@ pub trait BaseEntry<'a>: Sized {
   type Key: ?Sized;
   /// The value type.
   type Value: ?Sized;
+  /// The comparator which orders the keys.
+  type Comparator;
 
   /// Returns the key in the entry.
-  fn key(&self) -> KeyPointer<Self::Key>;
+  fn key(&self) -> KeyPointer<Self::Key, Self::Comparator>;
 
   /// Returns the next entry in the memory table.
   fn next(&mut self) -> Option<Self>;
@@ -58,6 +70,9 @@ pub trait BaseTable {
   /// The value type.
   type Value: ?Sized;
 
+  /// The comparator which orders the keys, [`KeyOrder`](crate::types::KeyOrder) orders the keys by the key type.
+  type Comparator: KeyComparator<Self::Key>;
+
   /// The configuration options for the memtable.
   type Options;
 
@@ -65,7 +80,8 @@ pub trait BaseTable {
   type Error;
 
   /// The item returned by the iterator or query methods.
-  type Item<'a>: BaseEntry<'a, Key = Self::Key, Value = Self::Value> + Clone
+  type Item<'a>: BaseEntry<'a, Key = Self::Key, Value = Self::Value, Comparator = Self::Comparator>
+    + Clone
   where
     Self: 'a;
 
@@ -79,7 +95,7 @@ pub trait BaseTable {
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Creates a new memtable with the specified options.
   fn new(opts: Self::Options) -> Result<Self, Self::Error>
@@ -90,16 +106,49 @@ pub trait BaseTable {
   fn insert(
     &self,
     version: Option<u64>,
-    kp: KeyPointer<Self::Key>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
     vp: ValuePointer<Self::Value>,
   ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static;
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static;
 
   /// Removes the pointer associated with the key.
-  fn remove(&self, version: Option<u64>, key: KeyPointer<Self::Key>) -> Result<(), Self::Error>
+  fn remove(
+    &self,
+    version: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static;
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static;
+
+  /// Makes sure the next `additional` inserts or removals cannot fail, so the entries of a transaction
+  /// are either all applied, or none of them is.
+  ///
+  /// The memtables which never fail to write do nothing.
+  #[inline]
+  fn reserve(&self, additional: usize) -> Result<(), Self::Error> {
+    let _ = additional;
+    Ok(())
+  }
+
+  /// Returns the version and the pointer of the latest value associated with the key which is visible at the specified version,
+  /// the version is always `0` for the memtables which do not support multiple versions.
+  ///
+  /// It is used by the merge operands and the batch guards. The default implementation returns `None`,
+  /// which treats every key as absent: the merge operands are folded over no value, and the guards see
+  /// no existing value, so the memtables used with them must override it.
+  #[inline]
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    let _ = (version, key);
+    None
+  }
 
   /// Returns the kind of the memtable.
   fn kind() -> Kind;
@@ -121,32 +170,53 @@ where
   /// Returns the upper bound of the memtable.
   fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the lower bound of the memtable.
   fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the first pointer in the memtable.
   fn first(&self) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord;
+    KeyPointer<Self::Key, Self::Comparator>: Ord;
 
   /// Returns the last pointer in the memtable.
   fn last(&self) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord;
+    KeyPointer<Self::Key, Self::Comparator>: Ord;
 
   /// Returns the pointer associated with the key.
   fn get<Q>(&self, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns `true` if the memtable contains the specified pointer.
   fn contains<Q>(&self, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  /// Returns the pointer associated with the encoded key.
+  ///
+  /// The default implementation compares the encoded key with the comparator, the memtables which
+  /// can locate a key by its bytes, e.g. the radix tree, override it.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn get_by_bytes(&self, key: &[u8]) -> Option<Self::Item<'_>> {
+    self.get(Slice::<Self::Key, Self::Comparator>::ref_cast(key))
+  }
+
+  /// Returns `true` if the memtable contains the encoded key.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn contains_by_bytes(&self, key: &[u8]) -> bool {
+    self.contains(Slice::<Self::Key, Self::Comparator>::ref_cast(key))
+  }
 
   /// Returns an iterator over the memtable.
   fn iter(&self) -> Self::Iterator<'_>;
@@ -155,7 +225,7 @@ where
   fn range<'a, Q, R>(&'a self, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 }
 
 /// A memory table which is used to store pointers to the underlying entries.
@@ -164,24 +234,25 @@ where
   for<'a> Self::Item<'a>: VersionedMemtableEntry<'a>,
 {
   /// The item returned by the iterator or query methods.
-  type VersionedItem<'a>: VersionedMemtableEntry<'a, Key = Self::Key, Value = Self::Value> + Clone
+  type VersionedItem<'a>: VersionedMemtableEntry<'a, Key = Self::Key, Value = Self::Value, Comparator = Self::Comparator>
+    + Clone
   where
-    KeyPointer<Self::Key>: 'a,
+    KeyPointer<Self::Key, Self::Comparator>: 'a,
     Self: 'a;
 
   /// The iterator type which can yields all the entries in the memtable.
   type IterAll<'a>: DoubleEndedIterator<Item = Self::VersionedItem<'a>>
   where
-    KeyPointer<Self::Key>: 'a,
+    KeyPointer<Self::Key, Self::Comparator>: 'a,
     Self: 'a;
 
   /// The range iterator type which can yields all the entries in the memtable.
   type RangeAll<'a, Q, R>: DoubleEndedIterator<Item = Self::VersionedItem<'a>>
   where
-    KeyPointer<Self::Key>: 'a,
+    KeyPointer<Self::Key, Self::Comparator>: 'a,
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the maximum version of the memtable.
   fn maximum_version(&self) -> u64;
@@ -195,7 +266,7 @@ where
   /// Returns the upper bound of the memtable.
   fn upper_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the upper bound of the memtable.
   fn upper_bound_versioned<Q>(
@@ -204,12 +275,12 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the lower bound of the memtable.
   fn lower_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the lower bound of the memtable.
   fn lower_bound_versioned<Q>(
@@ -218,47 +289,90 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the first pointer in the memtable.
   fn first(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord;
+    KeyPointer<Self::Key, Self::Comparator>: Ord;
 
   /// Returns the first pointer in the memtable.
   fn first_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<Self::Key>: Ord;
+    KeyPointer<Self::Key, Self::Comparator>: Ord;
 
   /// Returns the last pointer in the memtable.
   fn last(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord;
+    KeyPointer<Self::Key, Self::Comparator>: Ord;
 
   /// Returns the last pointer in the memtable.
   fn last_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<Self::Key>: Ord;
+    KeyPointer<Self::Key, Self::Comparator>: Ord;
 
   /// Returns the pointer associated with the key.
   fn get<Q>(&self, version: u64, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns the pointer associated with the key.
   fn get_versioned<Q>(&self, version: u64, key: &Q) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns `true` if the memtable contains the specified pointer.
   fn contains<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns `true` if the memtable contains the specified pointer.
   fn contains_versioned<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  /// Returns the pointer associated with the encoded key.
+  ///
+  /// The default implementation compares the encoded key with the comparator, the memtables which
+  /// can locate a key by its bytes, e.g. the radix tree, override it.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn get_by_bytes(&self, version: u64, key: &[u8]) -> Option<Self::Item<'_>> {
+    self.get(version, Slice::<Self::Key, Self::Comparator>::ref_cast(key))
+  }
+
+  /// Returns the pointer associated with the encoded key.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn get_versioned_by_bytes(
+    &self,
+    version: u64,
+    key: &[u8],
+  ) -> Option<Self::VersionedItem<'_>> {
+    self.get_versioned(version, Slice::<Self::Key, Self::Comparator>::ref_cast(key))
+  }
+
+  /// Returns `true` if the memtable contains the encoded key.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn contains_by_bytes(&self, version: u64, key: &[u8]) -> bool {
+    self.contains(version, Slice::<Self::Key, Self::Comparator>::ref_cast(key))
+  }
+
+  /// Returns `true` if the memtable contains the encoded key.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn contains_versioned_by_bytes(&self, version: u64, key: &[u8]) -> bool {
+    self.contains_versioned(version, Slice::<Self::Key, Self::Comparator>::ref_cast(key))
+  }
 
   /// Returns an iterator over the memtable.
   fn iter(&self, version: u64) -> Self::Iterator<'_>;
@@ -270,11 +384,11 @@ where
   fn range<'a, Q, R>(&'a self, version: u64, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   /// Returns an iterator over all the entries in a subset of the memtable.
   fn range_all_versions<'a, Q, R>(&'a self, version: u64, range: R) -> Self::RangeAll<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 }
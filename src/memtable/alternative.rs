@@ -40,28 +40,29 @@ macro_rules! iter {
     Linked($linked:ident),
   } -> $ent:ident) => {
     /// A sum type of iter for different memtable implementations.
+    #[allow(clippy::large_enum_variant)]
     #[non_exhaustive]
-    pub enum $name<'a, K, V>
+    pub enum $name<'a, K, V, C = KeyOrder>
     where
       K: ?Sized + Type + Ord,
-      KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+      C: KeyComparator<K>,
       V: ?Sized + Type,
     {
       /// Arena iter
-      Arena($arena<'a, KeyPointer<K>, ValuePointer<V>>),
+      Arena($arena<'a, KeyPointer<K, C>, ValuePointer<V>>),
       /// Linked iter
       #[cfg(feature = "std")]
       #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-      Linked($linked<'a, KeyPointer<K>, ValuePointer<V>>),
+      Linked($linked<'a, KeyPointer<K, C>, ValuePointer<V>>),
     }
 
-    impl<'a, K, V> Iterator for $name<'a, K, V>
+    impl<'a, K, V, C> Iterator for $name<'a, K, V, C>
     where
       K: ?Sized + Type + Ord + 'static,
-      KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+      C: KeyComparator<K>,
       V: ?Sized + Type + 'static,
     {
-      type Item = $ent<'a, K, V>;
+      type Item = $ent<'a, K, V, C>;
 
       #[inline]
       fn next(&mut self) -> Option<Self::Item> {
@@ -69,10 +70,10 @@ macro_rules! iter {
       }
     }
 
-    impl<'a, K, V> DoubleEndedIterator for $name<'a, K, V>
+    impl<K, V, C> DoubleEndedIterator for $name<'_, K, V, C>
     where
       K: ?Sized + Type + Ord + 'static,
-      KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+      C: KeyComparator<K>,
       V: ?Sized + Type + 'static,
     {
       #[inline]
@@ -89,32 +90,33 @@ macro_rules! range {
     Linked($linked:ident),
   } -> $ent:ident) => {
     /// A sum type of range for different memtable implementations.
+    #[allow(clippy::large_enum_variant)]
     #[non_exhaustive]
-    pub enum $name<'a, K, V, Q, R>
+    pub enum $name<'a, K, V, Q, R, C = KeyOrder>
     where
       R: RangeBounds<Q>,
-      Q: ?Sized + Comparable<KeyPointer<K>>,
+      Q: ?Sized + Comparable<KeyPointer<K, C>>,
       K: ?Sized + Type + Ord,
-      KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+      C: KeyComparator<K>,
       V: ?Sized + Type,
     {
       /// Arena range
-      Arena($arena<'a, KeyPointer<K>, ValuePointer<V>, Q, R>),
+      Arena($arena<'a, KeyPointer<K, C>, ValuePointer<V>, Q, R>),
       #[cfg(feature = "std")]
       #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
       /// Linked range
-      Linked($linked<'a, Q, R, KeyPointer<K>, ValuePointer<V>>),
+      Linked($linked<'a, Q, R, KeyPointer<K, C>, ValuePointer<V>>),
     }
 
-    impl<'a, K, V, Q, R> Iterator for $name<'a, K, V, Q, R>
+    impl<'a, K, V, Q, R, C> Iterator for $name<'a, K, V, Q, R, C>
     where
       R: RangeBounds<Q>,
-      Q: ?Sized + Comparable<KeyPointer<K>>,
+      Q: ?Sized + Comparable<KeyPointer<K, C>>,
       K: ?Sized + Type + Ord + 'a,
-      KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+      C: KeyComparator<K>,
       V: ?Sized + Type + 'a,
     {
-      type Item = $ent<'a, K, V>;
+      type Item = $ent<'a, K, V, C>;
 
       #[inline]
       fn next(&mut self) -> Option<Self::Item> {
@@ -122,12 +124,12 @@ macro_rules! range {
       }
     }
 
-    impl<'a, K, V, Q, R> DoubleEndedIterator for $name<'a, K, V, Q, R>
+    impl<'a, K, V, Q, R, C> DoubleEndedIterator for $name<'a, K, V, Q, R, C>
     where
       R: RangeBounds<Q>,
-      Q: ?Sized + Comparable<KeyPointer<K>>,
+      Q: ?Sized + Comparable<KeyPointer<K, C>>,
       K: ?Sized + Type + Ord + 'a,
-      KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+      C: KeyComparator<K>,
       V: ?Sized + Type + 'a,
     {
       fn next_back(&mut self) -> Option<Self::Item> {
@@ -145,20 +147,20 @@ macro_rules! base_entry {
     /// A sum type of entry for different memtable implementations.
     #[derive(Debug)]
     #[non_exhaustive]
-    pub enum $name<'a, K, V>
+    pub enum $name<'a, K, V, C = KeyOrder>
     where
       K: ?Sized,
       V: ?Sized,
     {
       /// Arena entry
-      Arena($arena<'a, KeyPointer<K>, ValuePointer<V>>),
+      Arena($arena<'a, KeyPointer<K, C>, ValuePointer<V>>),
       /// Linked entry
       #[cfg(feature = "std")]
       #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-      Linked($linked<'a, KeyPointer<K>, ValuePointer<V>>),
+      Linked($linked<'a, KeyPointer<K, C>, ValuePointer<V>>),
     }
 
-    impl<K: ?Sized, V: ?Sized> Clone for $name<'_, K, V> {
+    impl<K: ?Sized, V: ?Sized, C> Clone for $name<'_, K, V, C> {
       #[inline]
       fn clone(&self) -> Self {
         match self {
@@ -169,18 +171,20 @@ macro_rules! base_entry {
       }
     }
 
-    impl<'a, K, V> BaseEntry<'a> for $name<'a, K, V>
+    impl<'a, K, V, C> BaseEntry<'a> for $name<'a, K, V, C>
     where
       K: ?Sized + Type + Ord,
-      KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+      C: KeyComparator<K>,
       V: ?Sized + Type,
     {
       type Key = K;
 
       type Value = V;
 
+      type Comparator = C;
+
       #[inline]
-      fn key(&self) -> KeyPointer<Self::Key> {
+      fn key(&self) -> KeyPointer<Self::Key, Self::Comparator> {
         *match_op!(self.key())
       }
 
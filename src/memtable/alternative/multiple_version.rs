@@ -12,7 +12,7 @@ use crate::{
     BaseEntry, BaseTable, MultipleVersionMemtable, VersionedMemtableEntry,
   },
   sealed::WithVersion,
-  types::Kind,
+  types::{KeyComparator, KeyOrder, Kind},
   wal::{KeyPointer, ValuePointer},
 };
 
@@ -39,11 +39,11 @@ base_entry!(
   }
 );
 
-impl<'a, K, V> VersionedMemtableEntry<'a> for Entry<'a, K, V>
+impl<'a, K, V, C> VersionedMemtableEntry<'a> for Entry<'a, K, V, C>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn value(&self) -> Option<ValuePointer<Self::Value>> {
@@ -56,7 +56,7 @@ where
   }
 }
 
-impl<K: ?Sized, V: ?Sized> WithVersion for Entry<'_, K, V> {}
+impl<K: ?Sized, V: ?Sized, C> WithVersion for Entry<'_, K, V, C> {}
 
 base_entry!(
   enum VersionedEntry {
@@ -65,11 +65,11 @@ base_entry!(
   }
 );
 
-impl<'a, K, V> VersionedMemtableEntry<'a> for VersionedEntry<'a, K, V>
+impl<'a, K, V, C> VersionedMemtableEntry<'a> for VersionedEntry<'a, K, V, C>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn value(&self) -> Option<ValuePointer<Self::Value>> {
@@ -82,7 +82,7 @@ where
   }
 }
 
-impl<K: ?Sized, V: ?Sized> WithVersion for VersionedEntry<'_, K, V> {}
+impl<K: ?Sized, V: ?Sized, C> WithVersion for VersionedEntry<'_, K, V, C> {}
 
 iter!(
   enum Iter {
@@ -113,47 +113,49 @@ range!(
 );
 
 /// A sum type for different memtable implementations.
+#[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
-pub enum MultipleVersionTable<K: ?Sized, V: ?Sized> {
+pub enum MultipleVersionTable<K: ?Sized, V: ?Sized, C = KeyOrder> {
   /// Arena memtable
-  Arena(ArenaTable<K, V>),
+  Arena(ArenaTable<K, V, C>),
   /// Linked memtable
   #[cfg(feature = "std")]
   #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-  Linked(LinkedTable<K, V>),
+  Linked(LinkedTable<K, V, C>),
 }
 
-impl<K, V> BaseTable for MultipleVersionTable<K, V>
+impl<K, V, C> BaseTable for MultipleVersionTable<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   type Key = K;
 
   type Value = V;
+  type Comparator = C;
 
   type Options = TableOptions;
 
   type Error = super::Error;
 
   type Item<'a>
-    = Entry<'a, K, V>
+    = Entry<'a, K, V, C>
   where
     Self: 'a;
 
   type Iterator<'a>
-    = Iter<'a, K, V>
+    = Iter<'a, K, V, C>
   where
     Self: 'a;
 
   type Range<'a, Q, R>
-    = Range<'a, K, V, Q, R>
+    = Range<'a, K, V, Q, R, C>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   #[inline]
   fn new(opts: Self::Options) -> Result<Self, Self::Error>
@@ -167,55 +169,76 @@ where
   fn insert(
     &self,
     version: Option<u64>,
-    kp: KeyPointer<Self::Key>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
     vp: ValuePointer<Self::Value>,
   ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     match_op!(update(self.insert(version, kp, vp)))
   }
 
   #[inline]
-  fn remove(&self, version: Option<u64>, key: KeyPointer<Self::Key>) -> Result<(), Self::Error>
+  fn remove(
+    &self,
+    version: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     match_op!(update(self.remove(version, key)))
   }
 
+  #[inline]
+  fn reserve(&self, additional: usize) -> Result<(), Self::Error> {
+    match_op!(update(self.reserve(additional)))
+  }
+
+  #[inline]
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    match_op!(self.latest(version, key))
+  }
+
   #[inline]
   fn kind() -> Kind {
     Kind::MultipleVersion
   }
 }
 
-impl<K, V> MultipleVersionMemtable for MultipleVersionTable<K, V>
+impl<K, V, C> MultipleVersionMemtable for MultipleVersionTable<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   type VersionedItem<'a>
-    = VersionedEntry<'a, K, V>
+    = VersionedEntry<'a, K, V, C>
   where
-    KeyPointer<Self::Key>: 'a,
+    KeyPointer<Self::Key, Self::Comparator>: 'a,
     Self: 'a;
 
   type IterAll<'a>
-    = IterAll<'a, K, V>
+    = IterAll<'a, K, V, C>
   where
-    KeyPointer<Self::Key>: 'a,
+    KeyPointer<Self::Key, Self::Comparator>: 'a,
     Self: 'a;
 
   type RangeAll<'a, Q, R>
-    = RangeAll<'a, K, V, Q, R>
+    = RangeAll<'a, K, V, Q, R, C>
   where
-    KeyPointer<Self::Key>: 'a,
+    KeyPointer<Self::Key, Self::Comparator>: 'a,
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   #[inline]
   fn maximum_version(&self) -> u64 {
@@ -235,7 +258,7 @@ where
   #[inline]
   fn upper_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.upper_bound(version, bound).map(Item))
   }
@@ -246,7 +269,7 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self
       .upper_bound_versioned(version, bound)
@@ -256,7 +279,7 @@ where
   #[inline]
   fn lower_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.lower_bound(version, bound).map(Item))
   }
@@ -267,7 +290,7 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self
       .lower_bound_versioned(version, bound)
@@ -277,14 +300,14 @@ where
   #[inline]
   fn first(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     match_op!(self.first(version).map(Item))
   }
 
   fn first_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     match_op!(self.first_versioned(version).map(VersionedItem))
   }
@@ -292,14 +315,14 @@ where
   #[inline]
   fn last(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     match_op!(self.last(version).map(Item))
   }
 
   fn last_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     match_op!(self.last_versioned(version).map(VersionedItem))
   }
@@ -307,14 +330,14 @@ where
   #[inline]
   fn get<Q>(&self, version: u64, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.get(version, key).map(Item))
   }
 
   fn get_versioned<Q>(&self, version: u64, key: &Q) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.get_versioned(version, key).map(VersionedItem))
   }
@@ -322,14 +345,14 @@ where
   #[inline]
   fn contains<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.contains(version, key))
   }
 
   fn contains_versioned<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.contains_versioned(version, key))
   }
@@ -347,7 +370,7 @@ where
   fn range<'a, Q, R>(&'a self, version: u64, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(Dispatch::Range(self.range(version, range)))
   }
@@ -355,7 +378,7 @@ where
   fn range_all_versions<'a, Q, R>(&'a self, version: u64, range: R) -> Self::RangeAll<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(Dispatch::RangeAll(self.range_all_versions(version, range)))
   }
@@ -9,7 +9,7 @@ use crate::{
     BaseEntry, BaseTable, Memtable, MemtableEntry,
   },
   sealed::WithoutVersion,
-  types::Kind,
+  types::{KeyComparator, KeyOrder, Kind},
   wal::{KeyPointer, ValuePointer},
 };
 
@@ -33,11 +33,11 @@ base_entry!(
   }
 );
 
-impl<'a, K, V> MemtableEntry<'a> for Entry<'a, K, V>
+impl<'a, K, V, C> MemtableEntry<'a> for Entry<'a, K, V, C>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn value(&self) -> ValuePointer<Self::Value> {
@@ -45,7 +45,7 @@ where
   }
 }
 
-impl<K: ?Sized, V: ?Sized> WithoutVersion for Entry<'_, K, V> {}
+impl<K: ?Sized, V: ?Sized, C> WithoutVersion for Entry<'_, K, V, C> {}
 
 iter!(
   enum Iter {
@@ -62,47 +62,49 @@ range!(
 );
 
 /// A sum type for different memtable implementations.
+#[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
-pub enum Table<K: ?Sized, V: ?Sized> {
+pub enum Table<K: ?Sized, V: ?Sized, C = KeyOrder> {
   /// Arena memtable
-  Arena(ArenaTable<K, V>),
+  Arena(ArenaTable<K, V, C>),
   /// Linked memtable
   #[cfg(feature = "std")]
   #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-  Linked(LinkedTable<K, V>),
+  Linked(LinkedTable<K, V, C>),
 }
 
-impl<K, V> BaseTable for Table<K, V>
+impl<K, V, C> BaseTable for Table<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   type Key = K;
 
   type Value = V;
+  type Comparator = C;
 
   type Options = TableOptions;
 
   type Error = super::Error;
 
   type Item<'a>
-    = Entry<'a, K, V>
+    = Entry<'a, K, V, C>
   where
     Self: 'a;
 
   type Iterator<'a>
-    = Iter<'a, K, V>
+    = Iter<'a, K, V, C>
   where
     Self: 'a;
 
   type Range<'a, Q, R>
-    = Range<'a, K, V, Q, R>
+    = Range<'a, K, V, Q, R, C>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   #[inline]
   fn new(opts: Self::Options) -> Result<Self, Self::Error>
@@ -116,35 +118,56 @@ where
   fn insert(
     &self,
     version: Option<u64>,
-    kp: KeyPointer<Self::Key>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
     vp: ValuePointer<Self::Value>,
   ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     match_op!(update(self.insert(version, kp, vp)))
   }
 
   #[inline]
-  fn remove(&self, version: Option<u64>, key: KeyPointer<Self::Key>) -> Result<(), Self::Error>
+  fn remove(
+    &self,
+    version: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     match_op!(update(self.remove(version, key)))
   }
 
+  #[inline]
+  fn reserve(&self, additional: usize) -> Result<(), Self::Error> {
+    match_op!(update(self.reserve(additional)))
+  }
+
+  #[inline]
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    match_op!(self.latest(version, key))
+  }
+
   #[inline]
   fn kind() -> Kind {
     Kind::Plain
   }
 }
 
-impl<K, V> Memtable for Table<K, V>
+impl<K, V, C> Memtable for Table<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn len(&self) -> usize {
@@ -154,7 +177,7 @@ where
   #[inline]
   fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.upper_bound(bound).map(Item))
   }
@@ -162,7 +185,7 @@ where
   #[inline]
   fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.lower_bound(bound).map(Item))
   }
@@ -170,7 +193,7 @@ where
   #[inline]
   fn first(&self) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     match_op!(self.first().map(Item))
   }
@@ -178,7 +201,7 @@ where
   #[inline]
   fn last(&self) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     match_op!(self.last().map(Item))
   }
@@ -186,7 +209,7 @@ where
   #[inline]
   fn get<Q>(&self, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.get(key).map(Item))
   }
@@ -194,7 +217,7 @@ where
   #[inline]
   fn contains<Q>(&self, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(self.contains(key))
   }
@@ -208,7 +231,7 @@ where
   fn range<'a, Q, R>(&'a self, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     match_op!(Dispatch::Range(self.range(range)))
   }
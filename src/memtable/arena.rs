@@ -3,26 +3,141 @@ macro_rules! memmap_or_not {
   ($opts:ident($arena_opts:ident)) => {{
     if $opts.map_anon() {
       $arena_opts
-        .map_anon::<KeyPointer<K>, ValuePointer<V>, _>()
+        .map_anon::<KeyPointer<K, C>, ValuePointer<V>, _>()
         .map_err(skl::error::Error::IO)
     } else {
-      $arena_opts.alloc::<KeyPointer<K>, ValuePointer<V>, _>()
+      $arena_opts.alloc::<KeyPointer<K, C>, ValuePointer<V>, _>()
     }
-    .map(|map| Self { map })
   }};
 }
 
 #[cfg(not(all(feature = "memmap", not(target_family = "wasm"))))]
 macro_rules! memmap_or_not {
   ($opts:ident($arena_opts:ident)) => {{
-    $arena_opts
-      .alloc::<KeyPointer<K>, ValuePointer<V>, _>()
-      .map(|map| Self { map })
+    $arena_opts.alloc::<KeyPointer<K, C>, ValuePointer<V>, _>()
   }};
 }
 
+use core::{
+  marker::PhantomData,
+  ptr,
+  sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+
+use skl::error::Error;
+use std::boxed::Box;
+
 pub use skl::Height;
 
+/// The maximum number of skiplists can be chained by a table, the capacity is doubled by each of them,
+/// so it is far more than enough to reach the maximum capacity of an ARENA.
+const MAX_CHAIN_LEN: usize = 32;
+
+/// The skiplists of a table, only the last one is used.
+///
+/// When the last skiplist is full, a new one with a doubled capacity is chained, and all the entries are copied into it.
+/// The previous ones are kept alive until the table is dropped, because the entries returned to the readers may borrow them.
+struct Chain<M> {
+  maps: [AtomicPtr<M>; MAX_CHAIN_LEN],
+  current: AtomicUsize,
+  _m: PhantomData<Box<M>>,
+}
+
+impl<M> Chain<M> {
+  #[inline]
+  fn new(map: M) -> Self {
+    let maps = core::array::from_fn(|_| AtomicPtr::new(ptr::null_mut()));
+    maps[0].store(Box::into_raw(Box::new(map)), Ordering::Release);
+    Self {
+      maps,
+      current: AtomicUsize::new(0),
+      _m: PhantomData,
+    }
+  }
+
+  /// Returns the skiplist in use.
+  #[inline]
+  fn current(&self) -> &M {
+    let idx = self.current.load(Ordering::Acquire);
+    // SAFETY: the skiplists are never freed until the chain is dropped.
+    unsafe { &*self.maps[idx].load(Ordering::Acquire) }
+  }
+
+  /// Runs the write operation on the skiplist in use, if it is full, a larger skiplist built by `grow` is chained,
+  /// then the operation is retried.
+  ///
+  /// The memtables only have one writer, so no write can happen while growing.
+  fn write<T>(
+    &self,
+    mut op: impl FnMut(&M) -> Result<T, Error>,
+    grow: impl Fn(&M) -> Result<M, Error>,
+  ) -> Result<T, Error> {
+    loop {
+      let map = self.current();
+      match op(map) {
+        Err(Error::Arena(skl::error::ArenaError::InsufficientSpace { .. }))
+          if self.current.load(Ordering::Acquire) + 1 < MAX_CHAIN_LEN =>
+        {
+          self.chain(grow(map)?);
+        }
+        res => return res,
+      }
+    }
+  }
+
+  /// Makes sure the skiplist in use has at least `size` bytes left, by chaining larger skiplists built by `grow`,
+  /// so the writes which take no more than `size` bytes cannot fail.
+  fn reserve(
+    &self,
+    size: usize,
+    remaining: impl Fn(&M) -> usize,
+    grow: impl Fn(&M) -> Result<M, Error>,
+  ) -> Result<(), Error> {
+    loop {
+      let map = self.current();
+      let available = remaining(map);
+      if available >= size {
+        return Ok(());
+      }
+
+      if self.current.load(Ordering::Acquire) + 1 >= MAX_CHAIN_LEN {
+        return Err(Error::Arena(skl::error::ArenaError::InsufficientSpace {
+          requested: size.min(u32::MAX as usize) as u32,
+          available: available as u32,
+        }));
+      }
+
+      self.chain(grow(map)?);
+    }
+  }
+
+  /// Chains the skiplist, which is used from now on.
+  #[inline]
+  fn chain(&self, map: M) {
+    let next = self.current.load(Ordering::Acquire) + 1;
+    self.maps[next].store(Box::into_raw(Box::new(map)), Ordering::Release);
+    self.current.store(next, Ordering::Release);
+  }
+}
+
+impl<M> Drop for Chain<M> {
+  fn drop(&mut self) {
+    for map in self.maps.iter_mut() {
+      let map = *map.get_mut();
+      if !map.is_null() {
+        // SAFETY: the pointer is created by `Box::into_raw`, and it is only freed here.
+        drop(unsafe { Box::from_raw(map) });
+      }
+    }
+  }
+}
+
+/// Returns the capacity of the skiplist which is chained when the skiplist with `capacity` is full.
+#[inline]
+fn grown_capacity(capacity: usize) -> u32 {
+  (capacity as u64 * 2).min(u32::MAX as u64) as u32
+}
+
 /// Options to configure the [`Table`] or [`MultipleVersionTable`].
 #[derive(Debug, Copy, Clone)]
 pub struct TableOptions {
@@ -49,7 +164,10 @@ impl TableOptions {
     }
   }
 
-  /// Sets the capacity of the table.
+  /// Sets the initial capacity of the table.
+  ///
+  /// The table is not limited by the capacity, when it is full, a new ARENA with a doubled capacity is
+  /// allocated and the entries are moved into it.
   ///
   /// Default is `8KB`.
   #[inline]
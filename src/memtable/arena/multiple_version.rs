@@ -8,7 +8,7 @@ use dbutils::{
 use skl::{
   either::Either,
   multiple_version::{sync::SkipMap, Map as _},
-  Options,
+  Arena as _, Options,
 };
 
 pub use skl::multiple_version::sync::{Entry, Iter, IterAll, Range, RangeAll, VersionedEntry};
@@ -16,20 +16,22 @@ pub use skl::multiple_version::sync::{Entry, Iter, IterAll, Range, RangeAll, Ver
 use crate::{
   memtable::{BaseEntry, BaseTable, MultipleVersionMemtable, VersionedMemtableEntry},
   sealed::WithVersion,
-  types::Kind,
+  types::{KeyComparator, KeyOrder, Kind},
   wal::{KeyPointer, ValuePointer},
 };
 
-use super::TableOptions;
+use super::{grown_capacity, Chain, TableOptions};
 
-impl<'a, K, V> BaseEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> BaseEntry<'a> for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
 
   #[inline]
   fn next(&mut self) -> Option<Self> {
@@ -42,16 +44,17 @@ where
   }
 
   #[inline]
-  fn key(&self) -> KeyPointer<K> {
+  fn key(&self) -> KeyPointer<K, C> {
     *Entry::key(self)
   }
 }
 
-impl<'a, K, V> VersionedMemtableEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> VersionedMemtableEntry<'a> for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn value(&self) -> Option<ValuePointer<Self::Value>> {
@@ -64,21 +67,23 @@ where
   }
 }
 
-impl<K, V> WithVersion for Entry<'_, KeyPointer<K>, ValuePointer<V>>
+impl<K, V, C> WithVersion for Entry<'_, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized,
   V: ?Sized,
 {
 }
 
-impl<'a, K, V> BaseEntry<'a> for VersionedEntry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> BaseEntry<'a> for VersionedEntry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
 
   #[inline]
   fn next(&mut self) -> Option<Self> {
@@ -91,16 +96,18 @@ where
   }
 
   #[inline]
-  fn key(&self) -> KeyPointer<K> {
+  fn key(&self) -> KeyPointer<K, C> {
     *VersionedEntry::key(self)
   }
 }
 
-impl<'a, K, V> VersionedMemtableEntry<'a> for VersionedEntry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> VersionedMemtableEntry<'a>
+  for VersionedEntry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn version(&self) -> u64 {
@@ -113,7 +120,7 @@ where
   }
 }
 
-impl<K, V> WithVersion for VersionedEntry<'_, KeyPointer<K>, ValuePointer<V>>
+impl<K, V, C> WithVersion for VersionedEntry<'_, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized,
   V: ?Sized,
@@ -121,35 +128,90 @@ where
 }
 
 /// A memory table implementation based on ARENA [`SkipMap`](skl).
-pub struct MultipleVersionTable<K: ?Sized, V: ?Sized> {
-  map: SkipMap<KeyPointer<K>, ValuePointer<V>>,
+pub struct MultipleVersionTable<K: ?Sized, V: ?Sized, C = KeyOrder> {
+  maps: Chain<SkipMap<KeyPointer<K, C>, ValuePointer<V>>>,
+  opts: TableOptions,
 }
 
-impl<K, V> BaseTable for MultipleVersionTable<K, V>
+impl<K, V, C> MultipleVersionTable<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  for<'a> KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
+{
+  fn new_map(
+    opts: TableOptions,
+  ) -> Result<SkipMap<KeyPointer<K, C>, ValuePointer<V>>, skl::error::Error> {
+    let arena_opts = Options::new()
+      .with_capacity(opts.capacity())
+      .with_freelist(skl::Freelist::None)
+      .with_unify(false)
+      .with_max_height(opts.max_height());
+
+    memmap_or_not!(opts(arena_opts))
+  }
+
+  /// Returns a larger skiplist which contains all the versions of the entries of the full one, including the tombstones.
+  fn grow(
+    &self,
+    map: &SkipMap<KeyPointer<K, C>, ValuePointer<V>>,
+  ) -> Result<SkipMap<KeyPointer<K, C>, ValuePointer<V>>, skl::error::Error> {
+    let new = Self::new_map(self.opts.with_capacity(grown_capacity(map.capacity())))?;
+    for ent in map.iter_all_versions(u64::MAX) {
+      let version = ent.version();
+      let res = match VersionedEntry::value(&ent) {
+        Some(vp) => new
+          .insert(version, VersionedEntry::key(&ent), vp)
+          .map(|_| ())
+          .map_err(|e| match e {
+            Among::Right(e) => e,
+            _ => unreachable!(),
+          }),
+        None => match new.get_or_remove(version, VersionedEntry::key(&ent)) {
+          Err(Either::Right(e)) => Err(e),
+          Err(Either::Left(_)) => unreachable!(),
+          _ => Ok(()),
+        },
+      };
+      res?;
+    }
+    Ok(new)
+  }
+
+  #[inline]
+  fn map(&self) -> &SkipMap<KeyPointer<K, C>, ValuePointer<V>> {
+    self.maps.current()
+  }
+}
+
+impl<K, V, C> BaseTable for MultipleVersionTable<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
+  V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
 
   type Item<'a>
-    = Entry<'a, KeyPointer<K>, ValuePointer<V>>
+    = Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
   where
     Self: 'a;
 
   type Iterator<'a>
-    = Iter<'a, KeyPointer<K>, ValuePointer<V>>
+    = Iter<'a, KeyPointer<K, C>, ValuePointer<V>>
   where
     Self: 'a;
 
   type Range<'a, Q, R>
-    = Range<'a, KeyPointer<K>, ValuePointer<V>, Q, R>
+    = Range<'a, KeyPointer<K, C>, ValuePointer<V>, Q, R>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<K>>;
+    Q: ?Sized + Comparable<KeyPointer<K, C>>;
 
   type Options = TableOptions;
 
@@ -157,43 +219,74 @@ where
 
   #[inline]
   fn new(opts: Self::Options) -> Result<Self, Self::Error> {
-    let arena_opts = Options::new()
-      .with_capacity(opts.capacity())
-      .with_freelist(skl::Freelist::None)
-      .with_unify(false)
-      .with_max_height(opts.max_height());
-
-    memmap_or_not!(opts(arena_opts))
+    Self::new_map(opts).map(|map| Self {
+      maps: Chain::new(map),
+      opts,
+    })
   }
 
   fn insert(
     &self,
     version: Option<u64>,
-    kp: KeyPointer<K>,
+    kp: KeyPointer<K, C>,
     vp: ValuePointer<V>,
   ) -> Result<(), Self::Error>
   where
-    KeyPointer<K>: Ord + 'static,
+    KeyPointer<K, C>: Ord + 'static,
   {
-    self
-      .map
-      .insert(version.unwrap_or(0), &kp, &vp)
-      .map(|_| ())
-      .map_err(|e| match e {
-        Among::Right(e) => e,
-        _ => unreachable!(),
-      })
-  }
-
-  fn remove(&self, version: Option<u64>, key: KeyPointer<K>) -> Result<(), Self::Error>
+    let version = version.unwrap_or(0);
+    self.maps.write(
+      |map| {
+        map
+          .insert(version, &kp, &vp)
+          .map(|_| ())
+          .map_err(|e| match e {
+            Among::Right(e) => e,
+            _ => unreachable!(),
+          })
+      },
+      |map| self.grow(map),
+    )
+  }
+
+  fn remove(&self, version: Option<u64>, key: KeyPointer<K, C>) -> Result<(), Self::Error>
   where
-    KeyPointer<K>: Ord + 'static,
+    KeyPointer<K, C>: Ord + 'static,
   {
-    match self.map.get_or_remove(version.unwrap_or(0), &key) {
-      Err(Either::Right(e)) => Err(e),
-      Err(Either::Left(_)) => unreachable!(),
-      _ => Ok(()),
-    }
+    let version = version.unwrap_or(0);
+    self.maps.write(
+      |map| match map.get_or_remove(version, &key) {
+        Err(Either::Right(e)) => Err(e),
+        Err(Either::Left(_)) => unreachable!(),
+        _ => Ok(()),
+      },
+      |map| self.grow(map),
+    )
+  }
+
+  fn reserve(&self, additional: usize) -> Result<(), Self::Error> {
+    let node_size = SkipMap::<KeyPointer<K, C>, ValuePointer<V>>::estimated_node_size(
+      self.opts.max_height(),
+      KeyPointer::<K, C>::ENCODED_LEN,
+      ValuePointer::<V>::MAX_ENCODED_LEN,
+    );
+    self.maps.reserve(
+      additional.saturating_mul(node_size),
+      |map| map.remaining(),
+      |map| self.grow(map),
+    )
+  }
+
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    MultipleVersionMemtable::get(self, version.unwrap_or(0), key)
+      .and_then(|ent| VersionedMemtableEntry::value(&ent).map(|vp| (ent.version(), vp)))
   }
 
   #[inline]
@@ -202,49 +295,50 @@ where
   }
 }
 
-impl<K, V> MultipleVersionMemtable for MultipleVersionTable<K, V>
+impl<K, V, C> MultipleVersionMemtable for MultipleVersionTable<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  for<'a> KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   type VersionedItem<'a>
-    = VersionedEntry<'a, KeyPointer<K>, ValuePointer<V>>
+    = VersionedEntry<'a, KeyPointer<K, C>, ValuePointer<V>>
   where
     Self: 'a;
 
   type IterAll<'a>
-    = IterAll<'a, KeyPointer<K>, ValuePointer<V>>
+    = IterAll<'a, KeyPointer<K, C>, ValuePointer<V>>
   where
     Self: 'a;
 
   type RangeAll<'a, Q, R>
-    = RangeAll<'a, KeyPointer<K>, ValuePointer<V>, Q, R>
+    = RangeAll<'a, KeyPointer<K, C>, ValuePointer<V>, Q, R>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<K>>;
+    Q: ?Sized + Comparable<KeyPointer<K, C>>;
 
   #[inline]
   fn maximum_version(&self) -> u64 {
-    self.map.maximum_version()
+    self.map().maximum_version()
   }
 
   #[inline]
   fn minimum_version(&self) -> u64 {
-    self.map.minimum_version()
+    self.map().minimum_version()
   }
 
   #[inline]
   fn may_contain_version(&self, version: u64) -> bool {
-    self.map.may_contain_version(version)
+    self.map().may_contain_version(version)
   }
 
   fn upper_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.upper_bound(version, bound)
+    self.map().upper_bound(version, bound)
   }
 
   fn upper_bound_versioned<Q>(
@@ -253,16 +347,16 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.upper_bound_versioned(version, bound)
+    self.map().upper_bound_versioned(version, bound)
   }
 
   fn lower_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.lower_bound(version, bound)
+    self.map().lower_bound(version, bound)
   }
 
   fn lower_bound_versioned<Q>(
@@ -271,88 +365,88 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.lower_bound_versioned(version, bound)
+    self.map().lower_bound_versioned(version, bound)
   }
 
   fn first(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<K>: Ord,
+    KeyPointer<K, C>: Ord,
   {
-    self.map.first(version)
+    self.map().first(version)
   }
 
   fn first_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<K>: Ord,
+    KeyPointer<K, C>: Ord,
   {
-    self.map.first_versioned(version)
+    self.map().first_versioned(version)
   }
 
   fn last(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<K>: Ord,
+    KeyPointer<K, C>: Ord,
   {
-    self.map.last(version)
+    self.map().last(version)
   }
 
   fn last_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<K>: Ord,
+    KeyPointer<K, C>: Ord,
   {
-    self.map.last_versioned(version)
+    self.map().last_versioned(version)
   }
 
   fn get<Q>(&self, version: u64, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.get(version, key)
+    self.map().get(version, key)
   }
 
   fn get_versioned<Q>(&self, version: u64, key: &Q) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.get_versioned(version, key)
+    self.map().get_versioned(version, key)
   }
 
   fn contains<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.contains_key(version, key)
+    self.map().contains_key(version, key)
   }
 
   fn contains_versioned<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.contains_key_versioned(version, key)
+    self.map().contains_key_versioned(version, key)
   }
 
   fn iter(&self, version: u64) -> Self::Iterator<'_> {
-    self.map.iter(version)
+    self.map().iter(version)
   }
 
   fn iter_all_versions(&self, version: u64) -> Self::IterAll<'_> {
-    self.map.iter_all_versions(version)
+    self.map().iter_all_versions(version)
   }
 
   fn range<'a, Q, R>(&'a self, version: u64, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.range(version, range)
+    self.map().range(version, range)
   }
 
   fn range_all_versions<'a, Q, R>(&'a self, version: u64, range: R) -> Self::RangeAll<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<K>>,
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
   {
-    self.map.range_all_versions(version, range)
+    self.map().range_all_versions(version, range)
   }
 }
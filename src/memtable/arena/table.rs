@@ -14,22 +14,24 @@ use skl::{
 use crate::{
   memtable::{BaseEntry, BaseTable, Memtable, MemtableEntry},
   sealed::WithoutVersion,
-  types::Kind,
+  types::{KeyComparator, KeyOrder, Kind},
   wal::{KeyPointer, ValuePointer},
 };
 
-use super::TableOptions;
+use super::{grown_capacity, Chain, TableOptions};
 
 pub use skl::map::sync::{Entry, Iter, Range};
 
-impl<'a, K, V> BaseEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> BaseEntry<'a> for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
 
   #[inline]
   fn next(&mut self) -> Option<Self> {
@@ -42,16 +44,17 @@ where
   }
 
   #[inline]
-  fn key(&self) -> KeyPointer<K> {
+  fn key(&self) -> KeyPointer<K, C> {
     *EntryRef::key(self)
   }
 }
 
-impl<'a, K, V> MemtableEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> MemtableEntry<'a> for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
-  KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn value(&self) -> ValuePointer<V> {
@@ -59,76 +62,154 @@ where
   }
 }
 
-impl<K: ?Sized, V: ?Sized> WithoutVersion for Entry<'_, KeyPointer<K>, ValuePointer<V>> {}
+impl<K: ?Sized, V: ?Sized, C> WithoutVersion for Entry<'_, KeyPointer<K, C>, ValuePointer<V>> {}
 
 /// A memory table implementation based on ARENA [`SkipMap`](skl).
-pub struct Table<K: ?Sized, V: ?Sized> {
-  map: SkipMap<KeyPointer<K>, ValuePointer<V>>,
+pub struct Table<K: ?Sized, V: ?Sized, C = KeyOrder> {
+  maps: Chain<SkipMap<KeyPointer<K, C>, ValuePointer<V>>>,
+  opts: TableOptions,
 }
 
-impl<K, V> BaseTable for Table<K, V>
+impl<K, V, C> Table<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  for<'a> KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
+{
+  fn new_map(
+    opts: TableOptions,
+  ) -> Result<SkipMap<KeyPointer<K, C>, ValuePointer<V>>, skl::error::Error> {
+    let arena_opts = Options::new()
+      .with_capacity(opts.capacity())
+      .with_freelist(skl::Freelist::None)
+      .with_unify(false)
+      .with_max_height(opts.max_height());
+
+    memmap_or_not!(opts(arena_opts))
+  }
+
+  /// Returns a larger skiplist which contains all the entries of the full one.
+  fn grow(
+    &self,
+    map: &SkipMap<KeyPointer<K, C>, ValuePointer<V>>,
+  ) -> Result<SkipMap<KeyPointer<K, C>, ValuePointer<V>>, skl::error::Error> {
+    let new = Self::new_map(self.opts.with_capacity(grown_capacity(map.capacity())))?;
+    for ent in map.iter() {
+      new
+        .insert(EntryRef::key(&ent), EntryRef::value(&ent))
+        .map_err(|e| match e {
+          Among::Right(e) => e,
+          _ => unreachable!(),
+        })?;
+    }
+    Ok(new)
+  }
+
+  #[inline]
+  fn map(&self) -> &SkipMap<KeyPointer<K, C>, ValuePointer<V>> {
+    self.maps.current()
+  }
+}
+
+impl<K, V, C> BaseTable for Table<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
+  V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
   type Item<'a>
-    = Entry<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Entry<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type Iterator<'a>
-    = Iter<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Iter<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type Range<'a, Q, R>
-    = Range<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>, Q, R>
+    = Range<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>, Q, R>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   type Options = TableOptions;
   type Error = skl::error::Error;
 
   #[inline]
   fn new(opts: Self::Options) -> Result<Self, Self::Error> {
-    let arena_opts = Options::new()
-      .with_capacity(opts.capacity())
-      .with_freelist(skl::Freelist::None)
-      .with_unify(false)
-      .with_max_height(opts.max_height());
-
-    memmap_or_not!(opts(arena_opts))
+    Self::new_map(opts).map(|map| Self {
+      maps: Chain::new(map),
+      opts,
+    })
   }
 
   fn insert(
     &self,
     _: Option<u64>,
-    kp: KeyPointer<Self::Key>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
     vp: ValuePointer<Self::Value>,
   ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
-    self.map.insert(&kp, &vp).map(|_| ()).map_err(|e| match e {
-      Among::Right(e) => e,
-      _ => unreachable!(),
-    })
+    self.maps.write(
+      |map| {
+        map.insert(&kp, &vp).map(|_| ()).map_err(|e| match e {
+          Among::Right(e) => e,
+          _ => unreachable!(),
+        })
+      },
+      |map| self.grow(map),
+    )
   }
 
-  fn remove(&self, _: Option<u64>, key: KeyPointer<Self::Key>) -> Result<(), Self::Error>
+  fn remove(
+    &self,
+    _: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
-    match self.map.get_or_remove(&key) {
-      Err(Either::Right(e)) => Err(e),
-      Err(Either::Left(_)) => unreachable!(),
-      _ => Ok(()),
-    }
+    self.maps.write(
+      |map| match map.remove(&key) {
+        Err(Either::Right(e)) => Err(e),
+        Err(Either::Left(_)) => unreachable!(),
+        _ => Ok(()),
+      },
+      |map| self.grow(map),
+    )
+  }
+
+  fn reserve(&self, additional: usize) -> Result<(), Self::Error> {
+    let node_size = SkipMap::<KeyPointer<K, C>, ValuePointer<V>>::estimated_node_size(
+      self.opts.max_height(),
+      KeyPointer::<K, C>::ENCODED_LEN,
+      ValuePointer::<V>::MAX_ENCODED_LEN,
+    );
+    self.maps.reserve(
+      additional.saturating_mul(node_size),
+      |map| map.remaining(),
+      |map| self.grow(map),
+    )
+  }
+
+  fn latest(
+    &self,
+    _: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    Memtable::get(self, key).map(|ent| (0, MemtableEntry::value(&ent)))
   }
 
   #[inline]
@@ -137,68 +218,69 @@ where
   }
 }
 
-impl<K, V> Memtable for Table<K, V>
+impl<K, V, C> Memtable for Table<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
-  for<'a> KeyPointer<K>: Type<Ref<'a> = KeyPointer<K>> + KeyRef<'a, KeyPointer<K>>,
+  for<'a> KeyPointer<K, C>: Type<Ref<'a> = KeyPointer<K, C>> + KeyRef<'a, KeyPointer<K, C>>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn len(&self) -> usize {
-    self.map.len()
+    self.map().len()
   }
 
   fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
-    self.map.upper_bound(bound)
+    self.map().upper_bound(bound)
   }
 
   fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
-    self.map.lower_bound(bound)
+    self.map().lower_bound(bound)
   }
 
   fn first(&self) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
-    self.map.first()
+    self.map().first()
   }
 
   fn last(&self) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
-    self.map.last()
+    self.map().last()
   }
 
   fn get<Q>(&self, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
-    self.map.get(key)
+    self.map().get(key)
   }
 
   fn contains<Q>(&self, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
-    self.map.contains_key(key)
+    self.map().contains_key(key)
   }
 
   fn iter(&self) -> Self::Iterator<'_> {
-    self.map.iter()
+    self.map().iter()
   }
 
   fn range<'a, Q, R>(&'a self, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
-    self.map.range(range)
+    self.map().range(range)
   }
 }
@@ -0,0 +1,698 @@
+use core::{mem, ops::Bound};
+use std::{boxed::Box, string::String, vec::Vec};
+
+use dbutils::{
+  equivalent::Comparable,
+  types::{KeyRef, Type},
+};
+
+use crate::{types::KeyOrder, wal::KeyPointer};
+
+use super::btree::{above_start, below_end};
+
+/// The multiple version memtable implementation.
+pub mod multiple_version;
+/// The memtable implementation.
+pub mod table;
+
+pub use multiple_version::MultipleVersionTable;
+pub use table::Table;
+
+/// The key types whose encoded bytes are in the same order as the keys.
+///
+/// The radix tree orders the keys byte by byte, so only the keys ordered lexicographically
+/// by their encoded bytes can be stored in the ART based memtables.
+pub trait LexicographicKey: Type + Ord {}
+
+impl LexicographicKey for [u8] {}
+
+impl<const N: usize> LexicographicKey for [u8; N] {}
+
+impl LexicographicKey for Vec<u8> {}
+
+impl LexicographicKey for str {}
+
+impl LexicographicKey for String {}
+
+struct Leaf<K: ?Sized, T> {
+  key: KeyPointer<K, KeyOrder>,
+  value: T,
+}
+
+enum Node<K: ?Sized, T> {
+  Leaf(Box<Leaf<K, T>>),
+  Inner(Box<Inner<K, T>>),
+}
+
+impl<K: ?Sized, T> Node<K, T> {
+  /// Returns the smallest key in the subtree.
+  #[inline]
+  fn min(&self) -> &KeyPointer<K, KeyOrder> {
+    match self {
+      Self::Leaf(leaf) => &leaf.key,
+      Self::Inner(inner) => &inner.min,
+    }
+  }
+}
+
+struct Inner<K: ?Sized, T> {
+  /// The compressed path, the bytes shared by all the keys in the subtree after the parent's edge.
+  prefix: Vec<u8>,
+  /// The key which ends at this node, it is smaller than all the keys in the children.
+  leaf: Option<Leaf<K, T>>,
+  children: Children<K, T>,
+  /// The smallest key in the subtree, the comparator based searches use it to choose the child.
+  min: KeyPointer<K, KeyOrder>,
+}
+
+impl<K: ?Sized, T> Inner<K, T> {
+  #[inline]
+  fn update_min(&mut self) {
+    if let Some(min) = self
+      .leaf
+      .as_ref()
+      .map(|leaf| leaf.key)
+      .or_else(|| self.children.first().map(|node| *node.min()))
+    {
+      self.min = min;
+    }
+  }
+}
+
+/// The children of an inner node, the node grows and shrinks between four kinds by the number of children.
+enum Children<K: ?Sized, T> {
+  N4(Sorted<K, T, 4>),
+  N16(Box<Sorted<K, T, 16>>),
+  N48(Box<Indexed<K, T>>),
+  N256(Box<[Option<Node<K, T>>; 256]>),
+}
+
+struct Sorted<K: ?Sized, T, const N: usize> {
+  len: usize,
+  keys: [u8; N],
+  nodes: [Option<Node<K, T>>; N],
+}
+
+impl<K: ?Sized, T, const N: usize> Sorted<K, T, N> {
+  #[inline]
+  fn new() -> Self {
+    Self {
+      len: 0,
+      keys: [0; N],
+      nodes: core::array::from_fn(|_| None),
+    }
+  }
+
+  #[inline]
+  fn position(&self, byte: u8) -> Option<usize> {
+    self.keys[..self.len].iter().position(|b| *b == byte)
+  }
+
+  /// Inserts the child, the node must not be full.
+  fn insert(&mut self, byte: u8, node: Node<K, T>) {
+    let idx = self.keys[..self.len].partition_point(|b| *b < byte);
+    self.keys.copy_within(idx..self.len, idx + 1);
+    self.nodes[idx..=self.len].rotate_right(1);
+    self.keys[idx] = byte;
+    self.nodes[idx] = Some(node);
+    self.len += 1;
+  }
+
+  fn remove(&mut self, byte: u8) -> Option<Node<K, T>> {
+    let idx = self.position(byte)?;
+    let node = self.nodes[idx].take();
+    self.keys.copy_within(idx + 1..self.len, idx);
+    self.nodes[idx..self.len].rotate_left(1);
+    self.len -= 1;
+    node
+  }
+
+  fn drain(&mut self) -> impl Iterator<Item = (u8, Node<K, T>)> + '_ {
+    let len = mem::take(&mut self.len);
+    self.keys[..len]
+      .iter()
+      .zip(self.nodes[..len].iter_mut())
+      .filter_map(|(b, node)| node.take().map(|node| (*b, node)))
+  }
+}
+
+struct Indexed<K: ?Sized, T> {
+  len: usize,
+  /// The slot of the child plus one, `0` means there is no child for the byte.
+  index: [u8; 256],
+  nodes: [Option<Node<K, T>>; 48],
+}
+
+impl<K: ?Sized, T> Children<K, T> {
+  #[inline]
+  fn new() -> Self {
+    Self::N4(Sorted::new())
+  }
+
+  fn len(&self) -> usize {
+    match self {
+      Self::N4(n) => n.len,
+      Self::N16(n) => n.len,
+      Self::N48(n) => n.len,
+      Self::N256(n) => n.iter().filter(|node| node.is_some()).count(),
+    }
+  }
+
+  fn get(&self, byte: u8) -> Option<&Node<K, T>> {
+    match self {
+      Self::N4(n) => n.position(byte).and_then(|idx| n.nodes[idx].as_ref()),
+      Self::N16(n) => n.position(byte).and_then(|idx| n.nodes[idx].as_ref()),
+      Self::N48(n) => match n.index[byte as usize] {
+        0 => None,
+        slot => n.nodes[slot as usize - 1].as_ref(),
+      },
+      Self::N256(n) => n[byte as usize].as_ref(),
+    }
+  }
+
+  fn get_mut(&mut self, byte: u8) -> Option<&mut Option<Node<K, T>>> {
+    match self {
+      Self::N4(n) => n.position(byte).map(|idx| &mut n.nodes[idx]),
+      Self::N16(n) => n.position(byte).map(|idx| &mut n.nodes[idx]),
+      Self::N48(n) => match n.index[byte as usize] {
+        0 => None,
+        slot => Some(&mut n.nodes[slot as usize - 1]),
+      },
+      Self::N256(n) => n[byte as usize].is_some().then(|| &mut n[byte as usize]),
+    }
+  }
+
+  /// Returns the child with the smallest byte.
+  fn first(&self) -> Option<&Node<K, T>> {
+    match self {
+      Self::N4(n) => n.nodes[0].as_ref(),
+      Self::N16(n) => n.nodes[0].as_ref(),
+      Self::N48(n) => n
+        .index
+        .iter()
+        .find(|slot| **slot != 0)
+        .and_then(|slot| n.nodes[*slot as usize - 1].as_ref()),
+      Self::N256(n) => n.iter().find_map(Option::as_ref),
+    }
+  }
+
+  /// Returns the bytes of the children in ascending order.
+  fn keys<'a>(&'a self, buf: &'a mut [u8; 256]) -> &'a [u8] {
+    match self {
+      Self::N4(n) => &n.keys[..n.len],
+      Self::N16(n) => &n.keys[..n.len],
+      Self::N48(n) => {
+        let mut len = 0;
+        for (b, slot) in n.index.iter().enumerate() {
+          if *slot != 0 {
+            buf[len] = b as u8;
+            len += 1;
+          }
+        }
+        &buf[..len]
+      }
+      Self::N256(n) => {
+        let mut len = 0;
+        for (b, node) in n.iter().enumerate() {
+          if node.is_some() {
+            buf[len] = b as u8;
+            len += 1;
+          }
+        }
+        &buf[..len]
+      }
+    }
+  }
+
+  /// Inserts a child whose byte is not in the node, grows the node if it is full.
+  fn insert(&mut self, byte: u8, node: Node<K, T>) {
+    match self {
+      Self::N4(n) if n.len < 4 => n.insert(byte, node),
+      Self::N4(n) => {
+        let mut grown = Box::new(Sorted::<K, T, 16>::new());
+        for (b, child) in n.drain() {
+          grown.insert(b, child);
+        }
+        grown.insert(byte, node);
+        *self = Self::N16(grown);
+      }
+      Self::N16(n) if n.len < 16 => n.insert(byte, node),
+      Self::N16(n) => {
+        let mut grown = Box::new(Indexed {
+          len: 0,
+          index: [0; 256],
+          nodes: core::array::from_fn(|_| None),
+        });
+        for (b, child) in n.drain() {
+          grown.nodes[grown.len] = Some(child);
+          grown.len += 1;
+          grown.index[b as usize] = grown.len as u8;
+        }
+        *self = Self::N48(grown);
+        self.insert(byte, node);
+      }
+      Self::N48(n) if n.len < 48 => {
+        let slot = n
+          .nodes
+          .iter()
+          .position(Option::is_none)
+          .expect("node48 is not full");
+        n.nodes[slot] = Some(node);
+        n.index[byte as usize] = slot as u8 + 1;
+        n.len += 1;
+      }
+      Self::N48(n) => {
+        let mut grown = Box::new(core::array::from_fn(|_| None));
+        for (b, slot) in n.index.iter().enumerate() {
+          if *slot != 0 {
+            grown[b] = n.nodes[*slot as usize - 1].take();
+          }
+        }
+        grown[byte as usize] = Some(node);
+        *self = Self::N256(grown);
+      }
+      Self::N256(n) => n[byte as usize] = Some(node),
+    }
+  }
+
+  /// Removes the child, shrinks the node if it becomes sparse.
+  fn remove(&mut self, byte: u8) -> Option<Node<K, T>> {
+    let node = match self {
+      Self::N4(n) => n.remove(byte),
+      Self::N16(n) => n.remove(byte),
+      Self::N48(n) => match mem::take(&mut n.index[byte as usize]) {
+        0 => None,
+        slot => {
+          n.len -= 1;
+          n.nodes[slot as usize - 1].take()
+        }
+      },
+      Self::N256(n) => n[byte as usize].take(),
+    };
+
+    match self {
+      Self::N16(n) if n.len <= 3 => {
+        let mut shrunk = Sorted::<K, T, 4>::new();
+        for (b, child) in n.drain() {
+          shrunk.insert(b, child);
+        }
+        *self = Self::N4(shrunk);
+      }
+      Self::N48(n) if n.len <= 12 => {
+        let mut shrunk = Box::new(Sorted::<K, T, 16>::new());
+        for b in 0..=255u8 {
+          let slot = mem::take(&mut n.index[b as usize]);
+          if slot != 0 {
+            if let Some(child) = n.nodes[slot as usize - 1].take() {
+              shrunk.insert(b, child);
+            }
+          }
+        }
+        *self = Self::N16(shrunk);
+      }
+      Self::N256(n) if n.iter().filter(|node| node.is_some()).count() <= 37 => {
+        let mut shrunk = Box::new(Indexed {
+          len: 0,
+          index: [0; 256],
+          nodes: core::array::from_fn(|_| None),
+        });
+        for (b, child) in n.iter_mut().enumerate() {
+          if let Some(child) = child.take() {
+            shrunk.nodes[shrunk.len] = Some(child);
+            shrunk.len += 1;
+            shrunk.index[b] = shrunk.len as u8;
+          }
+        }
+        *self = Self::N48(shrunk);
+      }
+      _ => {}
+    }
+
+    node
+  }
+
+  /// Removes and returns the only child.
+  fn take_only(&mut self) -> Option<(u8, Node<K, T>)> {
+    let mut buf = [0; 256];
+    let byte = *self.keys(&mut buf).first()?;
+    self.remove(byte).map(|node| (byte, node))
+  }
+}
+
+#[inline]
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+  a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// An adaptive radix tree, which maps the keys to `T`.
+///
+/// The point operations by the key pointers or the encoded keys walk down the tree byte by byte, the searches
+/// by other queries compare the query with the smallest key of the children to choose the path.
+pub(super) struct Tree<K: ?Sized, T> {
+  root: Option<Node<K, T>>,
+  len: usize,
+}
+
+impl<K: ?Sized, T> Tree<K, T> {
+  #[inline]
+  pub(super) const fn new() -> Self {
+    Self { root: None, len: 0 }
+  }
+
+  #[inline]
+  pub(super) const fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Returns the value of the key.
+  #[inline]
+  pub(super) fn get(&self, key: &KeyPointer<K, KeyOrder>) -> Option<&T> {
+    self.get_by_bytes(key.as_slice()).map(|(_, v)| v)
+  }
+
+  /// Returns the key and the value whose encoded key is `bytes`, by walking down the tree byte by byte.
+  pub(super) fn get_by_bytes(&self, bytes: &[u8]) -> Option<(&KeyPointer<K, KeyOrder>, &T)> {
+    let mut node = self.root.as_ref()?;
+    let mut depth = 0;
+    loop {
+      match node {
+        Node::Leaf(leaf) => {
+          return (leaf.key.as_slice() == bytes).then_some((&leaf.key, &leaf.value))
+        }
+        Node::Inner(inner) => {
+          if !bytes[depth..].starts_with(&inner.prefix) {
+            return None;
+          }
+          depth += inner.prefix.len();
+          if bytes.len() == depth {
+            return inner.leaf.as_ref().map(|leaf| (&leaf.key, &leaf.value));
+          }
+          node = inner.children.get(bytes[depth])?;
+          depth += 1;
+        }
+      }
+    }
+  }
+
+  /// Returns the value of the key mutably.
+  pub(super) fn get_mut(&mut self, key: &KeyPointer<K, KeyOrder>) -> Option<&mut T> {
+    let bytes = key.as_slice();
+    let mut node = self.root.as_mut()?;
+    let mut depth = 0;
+    loop {
+      match node {
+        Node::Leaf(leaf) => return (leaf.key.as_slice() == bytes).then_some(&mut leaf.value),
+        Node::Inner(inner) => {
+          if !bytes[depth..].starts_with(&inner.prefix) {
+            return None;
+          }
+          depth += inner.prefix.len();
+          if bytes.len() == depth {
+            return inner.leaf.as_mut().map(|leaf| &mut leaf.value);
+          }
+          node = inner.children.get_mut(bytes[depth])?.as_mut()?;
+          depth += 1;
+        }
+      }
+    }
+  }
+
+  /// Inserts the key and the value, returns the old value if the key exists, the key is also replaced.
+  pub(super) fn insert(&mut self, key: KeyPointer<K, KeyOrder>, value: T) -> Option<T> {
+    let old = Self::insert_in(&mut self.root, key.as_slice(), 0, Leaf { key, value });
+    if old.is_none() {
+      self.len += 1;
+    }
+    old
+  }
+
+  fn insert_in(
+    slot: &mut Option<Node<K, T>>,
+    bytes: &[u8],
+    depth: usize,
+    new: Leaf<K, T>,
+  ) -> Option<T> {
+    match slot {
+      None => {
+        *slot = Some(Node::Leaf(Box::new(new)));
+        None
+      }
+      Some(Node::Leaf(leaf)) if leaf.key.as_slice() == bytes => {
+        leaf.key = new.key;
+        Some(mem::replace(&mut leaf.value, new.value))
+      }
+      Some(Node::Leaf(leaf)) => {
+        let existing = leaf.key.as_slice();
+        let split = depth + common_prefix_len(&existing[depth..], &bytes[depth..]);
+        let mut inner = Box::new(Inner {
+          prefix: bytes[depth..split].to_vec(),
+          leaf: None,
+          children: Children::new(),
+          min: new.key,
+        });
+        let Some(Node::Leaf(leaf)) = slot.take() else {
+          unreachable!()
+        };
+        for (key, leaf) in [(existing, *leaf), (bytes, new)] {
+          match key.get(split) {
+            Some(byte) => inner.children.insert(*byte, Node::Leaf(Box::new(leaf))),
+            None => inner.leaf = Some(leaf),
+          }
+        }
+        inner.update_min();
+        *slot = Some(Node::Inner(inner));
+        None
+      }
+      Some(Node::Inner(inner)) => {
+        let matched = common_prefix_len(&inner.prefix, &bytes[depth..]);
+        if matched < inner.prefix.len() {
+          let Some(Node::Inner(mut child)) = slot.take() else {
+            unreachable!()
+          };
+          let byte = child.prefix[matched];
+          let mut parent = Box::new(Inner {
+            prefix: child.prefix[..matched].to_vec(),
+            leaf: None,
+            children: Children::new(),
+            min: new.key,
+          });
+          child.prefix.drain(..=matched);
+          parent.children.insert(byte, Node::Inner(child));
+          match bytes.get(depth + matched) {
+            Some(byte) => parent.children.insert(*byte, Node::Leaf(Box::new(new))),
+            None => parent.leaf = Some(new),
+          }
+          parent.update_min();
+          *slot = Some(Node::Inner(parent));
+          return None;
+        }
+
+        let depth = depth + matched;
+        let old = match bytes.get(depth) {
+          None => inner.leaf.replace(new).map(|leaf| leaf.value),
+          Some(byte) => match inner.children.get_mut(*byte) {
+            Some(child) => Self::insert_in(child, bytes, depth + 1, new),
+            None => {
+              inner.children.insert(*byte, Node::Leaf(Box::new(new)));
+              None
+            }
+          },
+        };
+        inner.update_min();
+        old
+      }
+    }
+  }
+
+  /// Removes the key, returns the value if the key exists.
+  pub(super) fn remove(&mut self, key: &KeyPointer<K, KeyOrder>) -> Option<T> {
+    let old = Self::remove_in(&mut self.root, key.as_slice(), 0);
+    if old.is_some() {
+      self.len -= 1;
+    }
+    old
+  }
+
+  fn remove_in(slot: &mut Option<Node<K, T>>, bytes: &[u8], depth: usize) -> Option<T> {
+    let inner = match slot {
+      None => return None,
+      Some(Node::Leaf(leaf)) => {
+        if leaf.key.as_slice() != bytes {
+          return None;
+        }
+        let Some(Node::Leaf(leaf)) = slot.take() else {
+          unreachable!()
+        };
+        return Some(leaf.value);
+      }
+      Some(Node::Inner(inner)) => inner,
+    };
+
+    if !bytes[depth..].starts_with(&inner.prefix) {
+      return None;
+    }
+    let depth = depth + inner.prefix.len();
+    let old = match bytes.get(depth) {
+      None => inner.leaf.take().map(|leaf| leaf.value),
+      Some(byte) => {
+        let child = inner.children.get_mut(*byte)?;
+        let old = Self::remove_in(child, bytes, depth + 1);
+        if child.is_none() {
+          inner.children.remove(*byte);
+        }
+        old
+      }
+    };
+
+    if old.is_some() {
+      match (inner.children.len(), inner.leaf.is_some()) {
+        (0, true) => {
+          let leaf = inner.leaf.take().unwrap();
+          *slot = Some(Node::Leaf(Box::new(leaf)));
+        }
+        (0, false) => *slot = None,
+        (1, false) => {
+          let (byte, child) = inner.children.take_only().unwrap();
+          *slot = Some(match child {
+            Node::Leaf(leaf) => Node::Leaf(leaf),
+            Node::Inner(mut child) => {
+              let mut prefix = mem::take(&mut inner.prefix);
+              prefix.push(byte);
+              prefix.append(&mut child.prefix);
+              child.prefix = prefix;
+              Node::Inner(child)
+            }
+          });
+        }
+        _ => inner.update_min(),
+      }
+    }
+    old
+  }
+}
+
+impl<K, T> Tree<K, T>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+{
+  /// Returns the key and the value which are equal to the query.
+  pub(super) fn find<Q>(&self, key: &Q) -> Option<(&KeyPointer<K, KeyOrder>, &T)>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self
+      .find_from(Bound::Included(key), |k, v| Some((k, v)))
+      .filter(|(k, _)| key.compare(k).is_eq())
+  }
+
+  /// Visits the keys within the lower bound in ascending order, until `f` returns `Some`.
+  pub(super) fn find_from<'a, Q, R>(
+    &'a self,
+    lower: Bound<&Q>,
+    mut f: impl FnMut(&'a KeyPointer<K, KeyOrder>, &'a T) -> Option<R>,
+  ) -> Option<R>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    Self::find_in(self.root.as_ref()?, lower, &mut f)
+  }
+
+  fn find_in<'a, Q, R>(
+    node: &'a Node<K, T>,
+    lower: Bound<&Q>,
+    f: &mut impl FnMut(&'a KeyPointer<K, KeyOrder>, &'a T) -> Option<R>,
+  ) -> Option<R>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    let inner = match node {
+      Node::Leaf(leaf) => {
+        return above_start(lower, &leaf.key)
+          .then(|| f(&leaf.key, &leaf.value))
+          .flatten()
+      }
+      Node::Inner(inner) => inner,
+    };
+
+    if let Some(leaf) = &inner.leaf {
+      if above_start(lower, &leaf.key) {
+        if let Some(r) = f(&leaf.key, &leaf.value) {
+          return Some(r);
+        }
+      }
+    }
+
+    let children = &inner.children;
+    let mut buf = [0; 256];
+    let keys = children.keys(&mut buf);
+    let child = move |byte: &u8| children.get(*byte).expect("child exists");
+    // The children before `idx` have smaller keys than the bound, except the last one
+    // which may be partially within the bound.
+    let idx = keys.partition_point(|byte| !above_start(lower, child(byte).min()));
+    if idx > 0 {
+      if let Some(r) = Self::find_in(child(&keys[idx - 1]), lower, f) {
+        return Some(r);
+      }
+    }
+    keys[idx..]
+      .iter()
+      .find_map(|byte| Self::find_in(child(byte), Bound::<&Q>::Unbounded, f))
+  }
+
+  /// Visits the keys within the upper bound in descending order, until `f` returns `Some`.
+  pub(super) fn rfind_from<'a, Q, R>(
+    &'a self,
+    upper: Bound<&Q>,
+    mut f: impl FnMut(&'a KeyPointer<K, KeyOrder>, &'a T) -> Option<R>,
+  ) -> Option<R>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    Self::rfind_in(self.root.as_ref()?, upper, &mut f)
+  }
+
+  fn rfind_in<'a, Q, R>(
+    node: &'a Node<K, T>,
+    upper: Bound<&Q>,
+    f: &mut impl FnMut(&'a KeyPointer<K, KeyOrder>, &'a T) -> Option<R>,
+  ) -> Option<R>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    let inner = match node {
+      Node::Leaf(leaf) => {
+        return below_end(upper, &leaf.key)
+          .then(|| f(&leaf.key, &leaf.value))
+          .flatten()
+      }
+      Node::Inner(inner) => inner,
+    };
+
+    let children = &inner.children;
+    let mut buf = [0; 256];
+    let keys = children.keys(&mut buf);
+    let child = move |byte: &u8| children.get(*byte).expect("child exists");
+    // The children before `idx` have smallest keys within the bound,
+    // the last one of them may be partially within the bound.
+    let idx = keys.partition_point(|byte| below_end(upper, child(byte).min()));
+    if idx > 0 {
+      if let Some(r) = Self::rfind_in(child(&keys[idx - 1]), upper, f) {
+        return Some(r);
+      }
+      if let Some(r) = keys[..idx - 1]
+        .iter()
+        .rev()
+        .find_map(|byte| Self::rfind_in(child(byte), Bound::<&Q>::Unbounded, f))
+      {
+        return Some(r);
+      }
+    }
+
+    inner
+      .leaf
+      .as_ref()
+      .filter(|leaf| below_end(upper, &leaf.key))
+      .and_then(|leaf| f(&leaf.key, &leaf.value))
+  }
+}
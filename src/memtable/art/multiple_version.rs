@@ -0,0 +1,834 @@
+use core::{
+  cmp,
+  convert::Infallible,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds, RangeFull},
+};
+use std::collections::BTreeMap;
+
+use dbutils::{equivalent::Comparable, types::KeyRef};
+
+use crate::{
+  memtable::{self, BaseEntry, VersionedMemtableEntry},
+  sealed::WithVersion,
+  sync::RwLock,
+  types::{KeyOrder, Kind},
+  wal::{KeyPointer, ValuePointer},
+};
+
+use super::{
+  super::btree::{above_start, below_end},
+  LexicographicKey, Tree,
+};
+
+/// The versions of a key, `None` values are the tombstones.
+type Versions<V> = BTreeMap<u64, Option<ValuePointer<V>>>;
+
+struct Inner<K: ?Sized, V: ?Sized> {
+  map: Tree<K, Versions<V>>,
+  min_version: u64,
+  max_version: u64,
+}
+
+/// An memory table implementation based on an adaptive radix tree, which is protected by a lightweight lock.
+pub struct MultipleVersionTable<K: ?Sized, V: ?Sized>(RwLock<Inner<K, V>>);
+
+impl<K, V> Default for MultipleVersionTable<K, V>
+where
+  K: ?Sized,
+  V: ?Sized,
+{
+  #[inline]
+  fn default() -> Self {
+    Self(RwLock::new(Inner {
+      map: Tree::new(),
+      min_version: u64::MAX,
+      max_version: 0,
+    }))
+  }
+}
+
+impl<K, V> MultipleVersionTable<K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+{
+  fn update(&self, version: u64, kp: KeyPointer<K, KeyOrder>, vp: Option<ValuePointer<V>>) {
+    let mut inner = self.0.write();
+    match inner.map.get_mut(&kp) {
+      Some(versions) => {
+        versions.insert(version, vp);
+      }
+      None => {
+        inner.map.insert(kp, BTreeMap::from([(version, vp)]));
+      }
+    }
+    inner.min_version = inner.min_version.min(version);
+    inner.max_version = inner.max_version.max(version);
+  }
+
+  /// Returns the first entry within the lower bound whose latest value visible at the version is not removed.
+  fn first_visible<Q>(&self, lower: Bound<&Q>, version: u64) -> Option<Entry<'_, K, V>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self.0.read().map.find_from(lower, |k, versions| {
+      Entry::visible(self, k, versions, version)
+    })
+  }
+
+  /// Returns the last entry within the upper bound whose latest value visible at the version is not removed.
+  fn last_visible<Q>(&self, upper: Bound<&Q>, version: u64) -> Option<Entry<'_, K, V>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self.0.read().map.rfind_from(upper, |k, versions| {
+      Entry::visible(self, k, versions, version)
+    })
+  }
+
+  /// Returns the newest version visible at the version of the first key within the lower bound.
+  fn first_versioned<Q>(&self, lower: Bound<&Q>, version: u64) -> Option<VersionedEntry<'_, K, V>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self.0.read().map.find_from(lower, |k, versions| {
+      versions
+        .range(..=version)
+        .next_back()
+        .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+    })
+  }
+
+  /// Returns the newest version visible at the version of the last key within the upper bound.
+  fn last_versioned<Q>(&self, upper: Bound<&Q>, version: u64) -> Option<VersionedEntry<'_, K, V>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self.0.read().map.rfind_from(upper, |k, versions| {
+      versions
+        .range(..=version)
+        .next_back()
+        .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+    })
+  }
+
+  /// Returns the oldest version visible at the version of the last key within the upper bound,
+  /// which is the last one when iterating all the versions.
+  fn tail_versioned<Q>(&self, upper: Bound<&Q>, version: u64) -> Option<VersionedEntry<'_, K, V>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self.0.read().map.rfind_from(upper, |k, versions| {
+      versions
+        .range(..=version)
+        .next()
+        .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+    })
+  }
+
+  /// Returns the entry after the version of the key, the versions of a key are in descending order.
+  fn next_versioned(
+    &self,
+    key: &KeyPointer<K, KeyOrder>,
+    current: u64,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V>> {
+    let older = self.0.read().map.get(key).and_then(|versions| {
+      versions
+        .range(..current)
+        .next_back()
+        .map(|(v, vp)| VersionedEntry::new(self, *key, *v, *vp, version))
+    });
+
+    older.or_else(|| self.first_versioned(Bound::Excluded(key), version))
+  }
+
+  /// Returns the entry before the version of the key, the versions of a key are in descending order.
+  fn prev_versioned(
+    &self,
+    key: &KeyPointer<K, KeyOrder>,
+    current: u64,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V>> {
+    let newer = self.0.read().map.get(key).and_then(|versions| {
+      versions
+        .range((Bound::Excluded(current), Bound::Included(version)))
+        .next()
+        .map(|(v, vp)| VersionedEntry::new(self, *key, *v, *vp, version))
+    });
+
+    newer.or_else(|| self.tail_versioned(Bound::Excluded(key), version))
+  }
+}
+
+/// An entry in the [`MultipleVersionTable`], which is the latest value of a key visible at the query version.
+pub struct Entry<'a, K: ?Sized, V: ?Sized> {
+  table: &'a MultipleVersionTable<K, V>,
+  key: KeyPointer<K, KeyOrder>,
+  value: ValuePointer<V>,
+  version: u64,
+  query_version: u64,
+}
+
+impl<K: ?Sized, V: ?Sized> core::fmt::Debug for Entry<'_, K, V> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Entry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .field("version", &self.version)
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> Clone for Entry<'_, K, V> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+      version: self.version,
+      query_version: self.query_version,
+    }
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized> Entry<'a, K, V> {
+  #[inline]
+  fn visible(
+    table: &'a MultipleVersionTable<K, V>,
+    key: &KeyPointer<K, KeyOrder>,
+    versions: &Versions<V>,
+    query_version: u64,
+  ) -> Option<Self> {
+    match versions.range(..=query_version).next_back() {
+      Some((version, Some(value))) => Some(Self {
+        table,
+        key: *key,
+        value: *value,
+        version: *version,
+        query_version,
+      }),
+      _ => None,
+    }
+  }
+}
+
+impl<'a, K, V> BaseEntry<'a> for Entry<'a, K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = KeyOrder;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self
+      .table
+      .first_visible(Bound::Excluded(&self.key), self.query_version)
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self
+      .table
+      .last_visible(Bound::Excluded(&self.key), self.query_version)
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, KeyOrder> {
+    self.key
+  }
+}
+
+impl<'a, K, V> VersionedMemtableEntry<'a> for Entry<'a, K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+{
+  #[inline]
+  fn value(&self) -> Option<ValuePointer<V>> {
+    Some(self.value)
+  }
+
+  #[inline]
+  fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> WithVersion for Entry<'_, K, V> {}
+
+/// A versioned entry in the [`MultipleVersionTable`], the value is `None` if the entry is a tombstone.
+pub struct VersionedEntry<'a, K: ?Sized, V: ?Sized> {
+  table: &'a MultipleVersionTable<K, V>,
+  key: KeyPointer<K, KeyOrder>,
+  value: Option<ValuePointer<V>>,
+  version: u64,
+  query_version: u64,
+}
+
+impl<K: ?Sized, V: ?Sized> core::fmt::Debug for VersionedEntry<'_, K, V> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("VersionedEntry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .field("version", &self.version)
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> Clone for VersionedEntry<'_, K, V> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+      version: self.version,
+      query_version: self.query_version,
+    }
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized> VersionedEntry<'a, K, V> {
+  #[inline]
+  const fn new(
+    table: &'a MultipleVersionTable<K, V>,
+    key: KeyPointer<K, KeyOrder>,
+    version: u64,
+    value: Option<ValuePointer<V>>,
+    query_version: u64,
+  ) -> Self {
+    Self {
+      table,
+      key,
+      value,
+      version,
+      query_version,
+    }
+  }
+}
+
+impl<'a, K, V> BaseEntry<'a> for VersionedEntry<'a, K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = KeyOrder;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self
+      .table
+      .next_versioned(&self.key, self.version, self.query_version)
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self
+      .table
+      .prev_versioned(&self.key, self.version, self.query_version)
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, KeyOrder> {
+    self.key
+  }
+}
+
+impl<'a, K, V> VersionedMemtableEntry<'a> for VersionedEntry<'a, K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+{
+  #[inline]
+  fn value(&self) -> Option<ValuePointer<V>> {
+    self.value
+  }
+
+  #[inline]
+  fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> WithVersion for VersionedEntry<'_, K, V> {}
+
+/// An iterator over the entries of the [`MultipleVersionTable`].
+pub type Iter<'a, K, V> = Range<'a, K, V, KeyPointer<K, KeyOrder>, RangeFull>;
+
+/// An iterator over a subset of the entries of the [`MultipleVersionTable`].
+///
+/// The iterator does not hold the lock, every step looks up the table again.
+pub struct Range<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R> {
+  table: &'a MultipleVersionTable<K, V>,
+  version: u64,
+  range: R,
+  head: Option<KeyPointer<K, KeyOrder>>,
+  tail: Option<KeyPointer<K, KeyOrder>>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R> Range<'a, K, V, Q, R> {
+  #[inline]
+  const fn new(table: &'a MultipleVersionTable<K, V>, version: u64, range: R) -> Self {
+    Self {
+      table,
+      version,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R> Iterator for Range<'a, K, V, Q, R>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  R: RangeBounds<Q>,
+{
+  type Item = Entry<'a, K, V>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some(head) => self
+        .table
+        .first_visible(Bound::Excluded(head), self.version),
+      None => self
+        .table
+        .first_visible(self.range.start_bound(), self.version),
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self.tail.as_ref().map_or(true, |tail| ent.key.lt(tail));
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R> DoubleEndedIterator for Range<'_, K, V, Q, R>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  R: RangeBounds<Q>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some(tail) => self.table.last_visible(Bound::Excluded(tail), self.version),
+      None => self
+        .table
+        .last_visible(self.range.end_bound(), self.version),
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self.head.as_ref().map_or(true, |head| ent.key.gt(head));
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some(ent.key);
+    Some(ent)
+  }
+}
+
+/// An iterator over all the versions of the entries of the [`MultipleVersionTable`].
+pub type IterAll<'a, K, V> = RangeAll<'a, K, V, KeyPointer<K, KeyOrder>, RangeFull>;
+
+/// An iterator over all the versions of a subset of the entries of the [`MultipleVersionTable`].
+///
+/// The entries are yielded in ascending order of keys, and in descending order of versions for the same key.
+pub struct RangeAll<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R> {
+  table: &'a MultipleVersionTable<K, V>,
+  version: u64,
+  range: R,
+  head: Option<(KeyPointer<K, KeyOrder>, u64)>,
+  tail: Option<(KeyPointer<K, KeyOrder>, u64)>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R> RangeAll<'a, K, V, Q, R> {
+  #[inline]
+  const fn new(table: &'a MultipleVersionTable<K, V>, version: u64, range: R) -> Self {
+    Self {
+      table,
+      version,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R> Iterator for RangeAll<'a, K, V, Q, R>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  R: RangeBounds<Q>,
+{
+  type Item = VersionedEntry<'a, K, V>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some((key, version)) => self.table.next_versioned(key, *version, self.version),
+      None => self
+        .table
+        .first_versioned(self.range.start_bound(), self.version),
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self
+        .tail
+        .as_ref()
+        .map_or(true, |(key, version)| match ent.key.cmp(key) {
+          cmp::Ordering::Less => true,
+          cmp::Ordering::Equal => ent.version > *version,
+          cmp::Ordering::Greater => false,
+        });
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some((ent.key, ent.version));
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R> DoubleEndedIterator for RangeAll<'_, K, V, Q, R>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  R: RangeBounds<Q>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some((key, version)) => self.table.prev_versioned(key, *version, self.version),
+      None => self
+        .table
+        .tail_versioned(self.range.end_bound(), self.version),
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self
+        .head
+        .as_ref()
+        .map_or(true, |(key, version)| match ent.key.cmp(key) {
+          cmp::Ordering::Greater => true,
+          cmp::Ordering::Equal => ent.version < *version,
+          cmp::Ordering::Less => false,
+        });
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some((ent.key, ent.version));
+    Some(ent)
+  }
+}
+
+impl<K, V> memtable::BaseTable for MultipleVersionTable<K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + 'static,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = KeyOrder;
+  type Item<'a>
+    = Entry<'a, Self::Key, Self::Value>
+  where
+    Self: 'a;
+
+  type Iterator<'a>
+    = Iter<'a, Self::Key, Self::Value>
+  where
+    Self: 'a;
+
+  type Range<'a, Q, R>
+    = Range<'a, Self::Key, Self::Value, Q, R>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>;
+
+  type Options = ();
+  type Error = Infallible;
+
+  fn new(_: Self::Options) -> Result<Self, Self::Error>
+  where
+    Self: Sized,
+  {
+    Ok(Self::default())
+  }
+
+  #[inline]
+  fn insert(
+    &self,
+    version: Option<u64>,
+    kp: KeyPointer<Self::Key, KeyOrder>,
+    vp: ValuePointer<Self::Value>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord + 'static,
+  {
+    self.update(version.unwrap_or(0), kp, Some(vp));
+    Ok(())
+  }
+
+  #[inline]
+  fn remove(
+    &self,
+    version: Option<u64>,
+    key: KeyPointer<Self::Key, KeyOrder>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord + 'static,
+  {
+    self.update(version.unwrap_or(0), key, None);
+    Ok(())
+  }
+
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, KeyOrder>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord + 'static,
+  {
+    let inner = self.0.read();
+    match inner
+      .map
+      .get(key)?
+      .range(..=version.unwrap_or(0))
+      .next_back()
+    {
+      Some((version, Some(vp))) => Some((*version, *vp)),
+      _ => None,
+    }
+  }
+
+  #[inline]
+  fn kind() -> Kind {
+    Kind::MultipleVersion
+  }
+}
+
+impl<K, V> memtable::MultipleVersionMemtable for MultipleVersionTable<K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + 'static,
+{
+  type VersionedItem<'a>
+    = VersionedEntry<'a, Self::Key, Self::Value>
+  where
+    Self: 'a;
+
+  type IterAll<'a>
+    = IterAll<'a, Self::Key, Self::Value>
+  where
+    Self: 'a;
+
+  type RangeAll<'a, Q, R>
+    = RangeAll<'a, Self::Key, Self::Value, Q, R>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>;
+
+  #[inline]
+  fn maximum_version(&self) -> u64 {
+    self.0.read().max_version
+  }
+
+  #[inline]
+  fn minimum_version(&self) -> u64 {
+    self.0.read().min_version
+  }
+
+  #[inline]
+  fn may_contain_version(&self, version: u64) -> bool {
+    version >= self.minimum_version()
+  }
+
+  fn upper_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.last_visible(bound, version)
+  }
+
+  fn upper_bound_versioned<Q>(
+    &self,
+    version: u64,
+    bound: Bound<&Q>,
+  ) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.last_versioned(bound, version)
+  }
+
+  fn lower_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.first_visible(bound, version)
+  }
+
+  fn lower_bound_versioned<Q>(
+    &self,
+    version: u64,
+    bound: Bound<&Q>,
+  ) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.first_versioned(bound, version)
+  }
+
+  fn first(&self, version: u64) -> Option<Self::Item<'_>>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord,
+  {
+    self.first_visible(Bound::<&KeyPointer<K, KeyOrder>>::Unbounded, version)
+  }
+
+  fn first_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord,
+  {
+    MultipleVersionTable::first_versioned(
+      self,
+      Bound::<&KeyPointer<K, KeyOrder>>::Unbounded,
+      version,
+    )
+  }
+
+  fn last(&self, version: u64) -> Option<Self::Item<'_>>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord,
+  {
+    self.last_visible(Bound::<&KeyPointer<K, KeyOrder>>::Unbounded, version)
+  }
+
+  fn last_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord,
+  {
+    self.tail_versioned(Bound::<&KeyPointer<K, KeyOrder>>::Unbounded, version)
+  }
+
+  fn get<Q>(&self, version: u64, key: &Q) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    let inner = self.0.read();
+    let (k, versions) = inner.map.find(key)?;
+    Entry::visible(self, k, versions, version)
+  }
+
+  fn get_versioned<Q>(&self, version: u64, key: &Q) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    let inner = self.0.read();
+    let (k, versions) = inner.map.find(key)?;
+    versions
+      .range(..=version)
+      .next_back()
+      .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+  }
+
+  fn contains<Q>(&self, version: u64, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    memtable::MultipleVersionMemtable::get(self, version, key).is_some()
+  }
+
+  fn contains_versioned<Q>(&self, version: u64, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.get_versioned(version, key).is_some()
+  }
+
+  unsafe fn get_by_bytes(&self, version: u64, key: &[u8]) -> Option<Self::Item<'_>> {
+    let inner = self.0.read();
+    let (k, versions) = inner.map.get_by_bytes(key)?;
+    Entry::visible(self, k, versions, version)
+  }
+
+  unsafe fn get_versioned_by_bytes(
+    &self,
+    version: u64,
+    key: &[u8],
+  ) -> Option<Self::VersionedItem<'_>> {
+    let inner = self.0.read();
+    let (k, versions) = inner.map.get_by_bytes(key)?;
+    versions
+      .range(..=version)
+      .next_back()
+      .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+  }
+
+  unsafe fn contains_by_bytes(&self, version: u64, key: &[u8]) -> bool {
+    memtable::MultipleVersionMemtable::get_by_bytes(self, version, key).is_some()
+  }
+
+  unsafe fn contains_versioned_by_bytes(&self, version: u64, key: &[u8]) -> bool {
+    self.get_versioned_by_bytes(version, key).is_some()
+  }
+
+  fn iter(&self, version: u64) -> Self::Iterator<'_> {
+    Range::new(self, version, ..)
+  }
+
+  fn iter_all_versions(&self, version: u64) -> Self::IterAll<'_> {
+    RangeAll::new(self, version, ..)
+  }
+
+  fn range<'a, Q, R>(&'a self, version: u64, range: R) -> Self::Range<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    Range::new(self, version, range)
+  }
+
+  fn range_all_versions<'a, Q, R>(&'a self, version: u64, range: R) -> Self::RangeAll<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    RangeAll::new(self, version, range)
+  }
+}
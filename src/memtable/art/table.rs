@@ -0,0 +1,393 @@
+use core::{
+  convert::Infallible,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds, RangeFull},
+};
+
+use dbutils::{
+  equivalent::Comparable,
+  types::{KeyRef, Type},
+};
+
+use crate::{
+  memtable,
+  sealed::WithoutVersion,
+  sync::RwLock,
+  types::{KeyOrder, Kind},
+  wal::{KeyPointer, ValuePointer},
+};
+
+use super::{
+  super::btree::{above_start, below_end},
+  LexicographicKey, Tree,
+};
+
+/// An memory table implementation based on an adaptive radix tree, which is protected by a lightweight lock.
+///
+/// The keys sharing long prefixes are compared only once for the shared bytes, it suits the byte string keys,
+/// e.g. URLs and paths.
+pub struct Table<K: ?Sized, V: ?Sized>(RwLock<Tree<K, ValuePointer<V>>>);
+
+impl<K: ?Sized, V: ?Sized> core::fmt::Debug for Table<K, V> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Table")
+      .field("len", &self.0.read().len())
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> Default for Table<K, V> {
+  #[inline]
+  fn default() -> Self {
+    Self(RwLock::new(Tree::new()))
+  }
+}
+
+impl<K, V> Table<K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+{
+  /// Returns the first entry whose key is within the lower bound.
+  fn first_in<Q>(&self, lower: Bound<&Q>) -> Option<Entry<'_, K, V>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self
+      .0
+      .read()
+      .find_from(lower, |k, v| Some(Entry::new(self, *k, *v)))
+  }
+
+  /// Returns the last entry whose key is within the upper bound.
+  fn last_in<Q>(&self, upper: Bound<&Q>) -> Option<Entry<'_, K, V>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  {
+    self
+      .0
+      .read()
+      .rfind_from(upper, |k, v| Some(Entry::new(self, *k, *v)))
+  }
+}
+
+/// An entry in the [`Table`].
+pub struct Entry<'a, K: ?Sized, V: ?Sized> {
+  table: &'a Table<K, V>,
+  key: KeyPointer<K, KeyOrder>,
+  value: ValuePointer<V>,
+}
+
+impl<K: ?Sized, V: ?Sized> Clone for Entry<'_, K, V> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+    }
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> core::fmt::Debug for Entry<'_, K, V> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Entry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .finish()
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized> Entry<'a, K, V> {
+  #[inline]
+  const fn new(
+    table: &'a Table<K, V>,
+    key: KeyPointer<K, KeyOrder>,
+    value: ValuePointer<V>,
+  ) -> Self {
+    Self { table, key, value }
+  }
+}
+
+impl<'a, K, V> memtable::BaseEntry<'a> for Entry<'a, K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = KeyOrder;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self.table.first_in(Bound::Excluded(&self.key))
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self.table.last_in(Bound::Excluded(&self.key))
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, KeyOrder> {
+    self.key
+  }
+}
+
+impl<'a, K, V> memtable::MemtableEntry<'a> for Entry<'a, K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+{
+  #[inline]
+  fn value(&self) -> ValuePointer<V> {
+    self.value
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> WithoutVersion for Entry<'_, K, V> {}
+
+/// An iterator over the entries of the [`Table`].
+pub type Iter<'a, K, V> = Range<'a, K, V, KeyPointer<K, KeyOrder>, RangeFull>;
+
+/// An iterator over a subset of the entries of the [`Table`].
+///
+/// The iterator does not hold the lock, every step looks up the table again,
+/// so the entries inserted during the iteration may be yielded.
+pub struct Range<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R> {
+  table: &'a Table<K, V>,
+  range: R,
+  head: Option<KeyPointer<K, KeyOrder>>,
+  tail: Option<KeyPointer<K, KeyOrder>>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R> Range<'a, K, V, Q, R> {
+  #[inline]
+  const fn new(table: &'a Table<K, V>, range: R) -> Self {
+    Self {
+      table,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R> Iterator for Range<'a, K, V, Q, R>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  R: RangeBounds<Q>,
+{
+  type Item = Entry<'a, K, V>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some(head) => self.table.first_in(Bound::Excluded(head)),
+      None => self.table.first_in(self.range.start_bound()),
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self.tail.as_ref().map_or(true, |tail| ent.key.lt(tail));
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R> DoubleEndedIterator for Range<'_, K, V, Q, R>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, KeyOrder>>,
+  R: RangeBounds<Q>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some(tail) => self.table.last_in(Bound::Excluded(tail)),
+      None => self.table.last_in(self.range.end_bound()),
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self.head.as_ref().map_or(true, |head| ent.key.gt(head));
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V> memtable::BaseTable for Table<K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + Type + 'static,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = KeyOrder;
+  type Item<'a>
+    = Entry<'a, Self::Key, Self::Value>
+  where
+    Self: 'a;
+
+  type Iterator<'a>
+    = Iter<'a, Self::Key, Self::Value>
+  where
+    Self: 'a;
+
+  type Range<'a, Q, R>
+    = Range<'a, Self::Key, Self::Value, Q, R>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>;
+
+  type Options = ();
+  type Error = Infallible;
+
+  fn new(_: Self::Options) -> Result<Self, Self::Error>
+  where
+    Self: Sized,
+  {
+    Ok(Self::default())
+  }
+
+  #[inline]
+  fn insert(
+    &self,
+    _: Option<u64>,
+    kp: KeyPointer<Self::Key, KeyOrder>,
+    vp: ValuePointer<Self::Value>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord + 'static,
+  {
+    self.0.write().insert(kp, vp);
+    Ok(())
+  }
+
+  #[inline]
+  fn remove(&self, _: Option<u64>, key: KeyPointer<Self::Key, KeyOrder>) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord + 'static,
+  {
+    self.0.write().remove(&key);
+    Ok(())
+  }
+
+  fn latest(
+    &self,
+    _: Option<u64>,
+    key: &KeyPointer<Self::Key, KeyOrder>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, KeyOrder>: Ord + 'static,
+  {
+    self.0.read().get(key).map(|vp| (0, *vp))
+  }
+
+  #[inline]
+  fn kind() -> Kind {
+    Kind::Plain
+  }
+}
+
+impl<K, V> memtable::Memtable for Table<K, V>
+where
+  K: ?Sized + LexicographicKey + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + Type + 'static,
+{
+  #[inline]
+  fn len(&self) -> usize {
+    self.0.read().len()
+  }
+
+  #[inline]
+  fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.last_in(bound)
+  }
+
+  #[inline]
+  fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.first_in(bound)
+  }
+
+  #[inline]
+  fn first(&self) -> Option<Self::Item<'_>> {
+    self.first_in(Bound::<&KeyPointer<K, KeyOrder>>::Unbounded)
+  }
+
+  #[inline]
+  fn last(&self) -> Option<Self::Item<'_>> {
+    self.last_in(Bound::<&KeyPointer<K, KeyOrder>>::Unbounded)
+  }
+
+  #[inline]
+  fn get<Q>(&self, key: &Q) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self
+      .0
+      .read()
+      .find(key)
+      .map(|(k, v)| Entry::new(self, *k, *v))
+  }
+
+  #[inline]
+  fn contains<Q>(&self, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    self.0.read().find(key).is_some()
+  }
+
+  #[inline]
+  unsafe fn get_by_bytes(&self, key: &[u8]) -> Option<Self::Item<'_>> {
+    self
+      .0
+      .read()
+      .get_by_bytes(key)
+      .map(|(k, v)| Entry::new(self, *k, *v))
+  }
+
+  #[inline]
+  unsafe fn contains_by_bytes(&self, key: &[u8]) -> bool {
+    self.0.read().get_by_bytes(key).is_some()
+  }
+
+  #[inline]
+  fn iter(&self) -> Self::Iterator<'_> {
+    Range::new(self, ..)
+  }
+
+  #[inline]
+  fn range<'a, Q, R>(&'a self, range: R) -> Self::Range<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, KeyOrder>>,
+  {
+    Range::new(self, range)
+  }
+}
@@ -0,0 +1,150 @@
+use core::{borrow::Borrow, cmp, ops::Bound};
+
+use dbutils::equivalent::Comparable;
+
+use crate::{types::KeyComparator, wal::KeyPointer};
+
+/// The multiple version memtable implementation.
+pub mod multiple_version;
+/// The memtable implementation.
+pub mod table;
+
+pub use multiple_version::MultipleVersionTable;
+pub use table::Table;
+
+/// A key used to look up the maps. The keys in the maps can be borrowed as it,
+/// so the maps can be looked up by any `Q: Comparable<KeyPointer<K, C>>`.
+trait Lookup<K: ?Sized, C> {
+  /// Compares this key with a key in the map.
+  fn compare(&self, key: &KeyPointer<K, C>) -> cmp::Ordering;
+
+  /// Returns the key in the map, or `None` if this key is a probe.
+  fn as_key(&self) -> Option<&KeyPointer<K, C>>;
+}
+
+impl<K, C> Lookup<K, C> for KeyPointer<K, C>
+where
+  K: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn compare(&self, key: &KeyPointer<K, C>) -> cmp::Ordering {
+    self.cmp(key)
+  }
+
+  #[inline]
+  fn as_key(&self) -> Option<&KeyPointer<K, C>> {
+    Some(self)
+  }
+}
+
+/// Wraps a borrowed query key, so it can be used as a [`Lookup`] trait object.
+struct Probe<'a, Q: ?Sized>(&'a Q);
+
+impl<K, C, Q> Lookup<K, C> for Probe<'_, Q>
+where
+  K: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+{
+  #[inline]
+  fn compare(&self, key: &KeyPointer<K, C>) -> cmp::Ordering {
+    Comparable::compare(self.0, key)
+  }
+
+  #[inline]
+  fn as_key(&self) -> Option<&KeyPointer<K, C>> {
+    None
+  }
+}
+
+impl<'a, K, C> Borrow<dyn Lookup<K, C> + 'a> for KeyPointer<K, C>
+where
+  K: ?Sized + 'static,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn borrow(&self) -> &(dyn Lookup<K, C> + 'a) {
+    self
+  }
+}
+
+impl<K: ?Sized, C> PartialEq for dyn Lookup<K, C> + '_ {
+  #[inline]
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other).is_eq()
+  }
+}
+
+impl<K: ?Sized, C> Eq for dyn Lookup<K, C> + '_ {}
+
+impl<K: ?Sized, C> PartialOrd for dyn Lookup<K, C> + '_ {
+  #[inline]
+  fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<K: ?Sized, C> Ord for dyn Lookup<K, C> + '_ {
+  #[inline]
+  fn cmp(&self, other: &Self) -> cmp::Ordering {
+    match (self.as_key(), other.as_key()) {
+      (_, Some(key)) => self.compare(key),
+      (Some(key), None) => other.compare(key).reverse(),
+      // Two probes cannot be compared, they are only compared by `BTreeMap::range` to check its bounds,
+      // so a probe is ordered before the other one, and the range is searched by comparing each bound
+      // with the keys in the map.
+      (None, None) => cmp::Ordering::Less,
+    }
+  }
+}
+
+/// Converts the bound of a query key to the bound of a [`Lookup`] trait object.
+#[inline]
+fn probe_bound<'a, Q: ?Sized>(bound: Bound<&'a Q>) -> Bound<Probe<'a, Q>> {
+  match bound {
+    Bound::Included(q) => Bound::Included(Probe(q)),
+    Bound::Excluded(q) => Bound::Excluded(Probe(q)),
+    Bound::Unbounded => Bound::Unbounded,
+  }
+}
+
+#[inline]
+fn as_lookup<'a, K, C, Q>(bound: &'a Bound<Probe<'_, Q>>) -> Bound<&'a (dyn Lookup<K, C> + 'a)>
+where
+  K: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+{
+  match bound {
+    Bound::Included(q) => Bound::Included(q as &dyn Lookup<K, C>),
+    Bound::Excluded(q) => Bound::Excluded(q as &dyn Lookup<K, C>),
+    Bound::Unbounded => Bound::Unbounded,
+  }
+}
+
+/// Returns `true` if the key is not beyond the end bound of a range.
+#[inline]
+pub(crate) fn below_end<K, C, Q>(end: Bound<&Q>, key: &KeyPointer<K, C>) -> bool
+where
+  K: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+{
+  match end {
+    Bound::Included(q) => q.compare(key).is_ge(),
+    Bound::Excluded(q) => q.compare(key).is_gt(),
+    Bound::Unbounded => true,
+  }
+}
+
+/// Returns `true` if the key is not beyond the start bound of a range.
+#[inline]
+pub(crate) fn above_start<K, C, Q>(start: Bound<&Q>, key: &KeyPointer<K, C>) -> bool
+where
+  K: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+{
+  match start {
+    Bound::Included(q) => q.compare(key).is_le(),
+    Bound::Excluded(q) => q.compare(key).is_lt(),
+    Bound::Unbounded => true,
+  }
+}
@@ -0,0 +1,835 @@
+use core::{
+  cmp,
+  convert::Infallible,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds, RangeFull},
+};
+use std::collections::BTreeMap;
+
+use dbutils::{
+  equivalent::Comparable,
+  types::{KeyRef, Type},
+};
+
+use crate::{
+  memtable::{self, BaseEntry, VersionedMemtableEntry},
+  sealed::WithVersion,
+  sync::RwLock,
+  types::{KeyComparator, KeyOrder, Kind},
+  wal::{KeyPointer, ValuePointer},
+};
+
+use super::{above_start, as_lookup, below_end, probe_bound, Lookup, Probe};
+
+/// The versions of a key, `None` values are the tombstones.
+type Versions<V> = BTreeMap<u64, Option<ValuePointer<V>>>;
+
+struct Inner<K: ?Sized, V: ?Sized, C> {
+  map: BTreeMap<KeyPointer<K, C>, Versions<V>>,
+  min_version: u64,
+  max_version: u64,
+}
+
+/// An memory table implementation based on [`BTreeMap`], which is protected by a lightweight lock.
+pub struct MultipleVersionTable<K: ?Sized, V: ?Sized, C = KeyOrder>(RwLock<Inner<K, V, C>>);
+
+impl<K, V, C> Default for MultipleVersionTable<K, V, C>
+where
+  K: ?Sized,
+  V: ?Sized,
+{
+  #[inline]
+  fn default() -> Self {
+    Self(RwLock::new(Inner {
+      map: BTreeMap::new(),
+      min_version: u64::MAX,
+      max_version: 0,
+    }))
+  }
+}
+
+impl<K, V, C> MultipleVersionTable<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  fn update(&self, version: u64, kp: KeyPointer<K, C>, vp: Option<ValuePointer<V>>) {
+    let mut inner = self.0.write();
+    inner.map.entry(kp).or_default().insert(version, vp);
+    inner.min_version = inner.min_version.min(version);
+    inner.max_version = inner.max_version.max(version);
+  }
+
+  /// Returns the first entry within the lower bound whose latest value visible at the version is not removed.
+  fn first_visible(
+    &self,
+    lower: Bound<&dyn Lookup<K, C>>,
+    version: u64,
+  ) -> Option<Entry<'_, K, V, C>> {
+    let inner = self.0.read();
+    inner
+      .map
+      .range::<dyn Lookup<K, C>, _>((lower, Bound::Unbounded))
+      .find_map(|(k, versions)| Entry::visible(self, k, versions, version))
+  }
+
+  /// Returns the last entry within the upper bound whose latest value visible at the version is not removed.
+  fn last_visible(
+    &self,
+    upper: Bound<&dyn Lookup<K, C>>,
+    version: u64,
+  ) -> Option<Entry<'_, K, V, C>> {
+    let inner = self.0.read();
+    inner
+      .map
+      .range::<dyn Lookup<K, C>, _>((Bound::Unbounded, upper))
+      .rev()
+      .find_map(|(k, versions)| Entry::visible(self, k, versions, version))
+  }
+
+  /// Returns the newest version visible at the version of the first key within the lower bound.
+  fn first_versioned(
+    &self,
+    lower: Bound<&dyn Lookup<K, C>>,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>> {
+    let inner = self.0.read();
+    inner
+      .map
+      .range::<dyn Lookup<K, C>, _>((lower, Bound::Unbounded))
+      .find_map(|(k, versions)| {
+        versions
+          .range(..=version)
+          .next_back()
+          .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+      })
+  }
+
+  /// Returns the newest version visible at the version of the last key within the upper bound.
+  fn last_versioned(
+    &self,
+    upper: Bound<&dyn Lookup<K, C>>,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>> {
+    let inner = self.0.read();
+    inner
+      .map
+      .range::<dyn Lookup<K, C>, _>((Bound::Unbounded, upper))
+      .rev()
+      .find_map(|(k, versions)| {
+        versions
+          .range(..=version)
+          .next_back()
+          .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+      })
+  }
+
+  /// Returns the oldest version visible at the version of the last key within the upper bound,
+  /// which is the last one when iterating all the versions.
+  fn tail_versioned(
+    &self,
+    upper: Bound<&dyn Lookup<K, C>>,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>> {
+    let inner = self.0.read();
+    inner
+      .map
+      .range::<dyn Lookup<K, C>, _>((Bound::Unbounded, upper))
+      .rev()
+      .find_map(|(k, versions)| {
+        versions
+          .range(..=version)
+          .next()
+          .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+      })
+  }
+
+  /// Returns the entry after the version of the key, the versions of a key are in descending order.
+  fn next_versioned(
+    &self,
+    key: &KeyPointer<K, C>,
+    current: u64,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>> {
+    let older = self.0.read().map.get(key).and_then(|versions| {
+      versions
+        .range(..current)
+        .next_back()
+        .map(|(v, vp)| VersionedEntry::new(self, *key, *v, *vp, version))
+    });
+
+    older.or_else(|| self.first_versioned(Bound::Excluded(key), version))
+  }
+
+  /// Returns the entry before the version of the key, the versions of a key are in descending order.
+  fn prev_versioned(
+    &self,
+    key: &KeyPointer<K, C>,
+    current: u64,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>> {
+    let newer = self.0.read().map.get(key).and_then(|versions| {
+      versions
+        .range((Bound::Excluded(current), Bound::Included(version)))
+        .next()
+        .map(|(v, vp)| VersionedEntry::new(self, *key, *v, *vp, version))
+    });
+
+    newer.or_else(|| self.tail_versioned(Bound::Excluded(key), version))
+  }
+}
+
+/// An entry in the [`MultipleVersionTable`], which is the latest value of a key visible at the query version.
+pub struct Entry<'a, K: ?Sized, V: ?Sized, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  key: KeyPointer<K, C>,
+  value: ValuePointer<V>,
+  version: u64,
+  query_version: u64,
+}
+
+impl<K: ?Sized, V: ?Sized, C> core::fmt::Debug for Entry<'_, K, V, C> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Entry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .field("version", &self.version)
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> Clone for Entry<'_, K, V, C> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+      version: self.version,
+      query_version: self.query_version,
+    }
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized, C> Entry<'a, K, V, C> {
+  #[inline]
+  fn visible(
+    table: &'a MultipleVersionTable<K, V, C>,
+    key: &KeyPointer<K, C>,
+    versions: &Versions<V>,
+    query_version: u64,
+  ) -> Option<Self> {
+    match versions.range(..=query_version).next_back() {
+      Some((version, Some(value))) => Some(Self {
+        table,
+        key: *key,
+        value: *value,
+        version: *version,
+        query_version,
+      }),
+      _ => None,
+    }
+  }
+}
+
+impl<'a, K, V, C> BaseEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self
+      .table
+      .first_visible(Bound::Excluded(&self.key), self.query_version)
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self
+      .table
+      .last_visible(Bound::Excluded(&self.key), self.query_version)
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, C> {
+    self.key
+  }
+}
+
+impl<'a, K, V, C> VersionedMemtableEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn value(&self) -> Option<ValuePointer<V>> {
+    Some(self.value)
+  }
+
+  #[inline]
+  fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> WithVersion for Entry<'_, K, V, C> {}
+
+/// A versioned entry in the [`MultipleVersionTable`], the value is `None` if the entry is a tombstone.
+pub struct VersionedEntry<'a, K: ?Sized, V: ?Sized, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  key: KeyPointer<K, C>,
+  value: Option<ValuePointer<V>>,
+  version: u64,
+  query_version: u64,
+}
+
+impl<K: ?Sized, V: ?Sized, C> core::fmt::Debug for VersionedEntry<'_, K, V, C> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("VersionedEntry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .field("version", &self.version)
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> Clone for VersionedEntry<'_, K, V, C> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+      version: self.version,
+      query_version: self.query_version,
+    }
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized, C> VersionedEntry<'a, K, V, C> {
+  #[inline]
+  const fn new(
+    table: &'a MultipleVersionTable<K, V, C>,
+    key: KeyPointer<K, C>,
+    version: u64,
+    value: Option<ValuePointer<V>>,
+    query_version: u64,
+  ) -> Self {
+    Self {
+      table,
+      key,
+      value,
+      version,
+      query_version,
+    }
+  }
+}
+
+impl<'a, K, V, C> BaseEntry<'a> for VersionedEntry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self
+      .table
+      .next_versioned(&self.key, self.version, self.query_version)
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self
+      .table
+      .prev_versioned(&self.key, self.version, self.query_version)
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, C> {
+    self.key
+  }
+}
+
+impl<'a, K, V, C> VersionedMemtableEntry<'a> for VersionedEntry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn value(&self) -> Option<ValuePointer<V>> {
+    self.value
+  }
+
+  #[inline]
+  fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> WithVersion for VersionedEntry<'_, K, V, C> {}
+
+/// An iterator over the entries of the [`MultipleVersionTable`].
+pub type Iter<'a, K, V, C = KeyOrder> = Range<'a, K, V, KeyPointer<K, C>, RangeFull, C>;
+
+/// An iterator over a subset of the entries of the [`MultipleVersionTable`].
+///
+/// The iterator does not hold the lock, every step looks up the table again.
+pub struct Range<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  version: u64,
+  range: R,
+  head: Option<KeyPointer<K, C>>,
+  tail: Option<KeyPointer<K, C>>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C> Range<'a, K, V, Q, R, C> {
+  #[inline]
+  const fn new(table: &'a MultipleVersionTable<K, V, C>, version: u64, range: R) -> Self {
+    Self {
+      table,
+      version,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R, C> Iterator for Range<'a, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  type Item = Entry<'a, K, V, C>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some(head) => self
+        .table
+        .first_visible(Bound::Excluded(head), self.version),
+      None => {
+        let start = probe_bound(self.range.start_bound());
+        self.table.first_visible(as_lookup(&start), self.version)
+      }
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self.tail.as_ref().map_or(true, |tail| ent.key.lt(tail));
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R, C> DoubleEndedIterator for Range<'_, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some(tail) => self.table.last_visible(Bound::Excluded(tail), self.version),
+      None => {
+        let end = probe_bound(self.range.end_bound());
+        self.table.last_visible(as_lookup(&end), self.version)
+      }
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self.head.as_ref().map_or(true, |head| ent.key.gt(head));
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some(ent.key);
+    Some(ent)
+  }
+}
+
+/// An iterator over all the versions of the entries of the [`MultipleVersionTable`].
+pub type IterAll<'a, K, V, C = KeyOrder> = RangeAll<'a, K, V, KeyPointer<K, C>, RangeFull, C>;
+
+/// An iterator over all the versions of a subset of the entries of the [`MultipleVersionTable`].
+///
+/// The entries are yielded in ascending order of keys, and in descending order of versions for the same key.
+pub struct RangeAll<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  version: u64,
+  range: R,
+  head: Option<(KeyPointer<K, C>, u64)>,
+  tail: Option<(KeyPointer<K, C>, u64)>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C> RangeAll<'a, K, V, Q, R, C> {
+  #[inline]
+  const fn new(table: &'a MultipleVersionTable<K, V, C>, version: u64, range: R) -> Self {
+    Self {
+      table,
+      version,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R, C> Iterator for RangeAll<'a, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  type Item = VersionedEntry<'a, K, V, C>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some((key, version)) => self.table.next_versioned(key, *version, self.version),
+      None => {
+        let start = probe_bound(self.range.start_bound());
+        self.table.first_versioned(as_lookup(&start), self.version)
+      }
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self
+        .tail
+        .as_ref()
+        .map_or(true, |(key, version)| match ent.key.cmp(key) {
+          cmp::Ordering::Less => true,
+          cmp::Ordering::Equal => ent.version > *version,
+          cmp::Ordering::Greater => false,
+        });
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some((ent.key, ent.version));
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R, C> DoubleEndedIterator for RangeAll<'_, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some((key, version)) => self.table.prev_versioned(key, *version, self.version),
+      None => {
+        let end = probe_bound(self.range.end_bound());
+        self.table.tail_versioned(as_lookup(&end), self.version)
+      }
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self
+        .head
+        .as_ref()
+        .map_or(true, |(key, version)| match ent.key.cmp(key) {
+          cmp::Ordering::Greater => true,
+          cmp::Ordering::Equal => ent.version < *version,
+          cmp::Ordering::Less => false,
+        });
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some((ent.key, ent.version));
+    Some(ent)
+  }
+}
+
+impl<K, V, C> memtable::BaseTable for MultipleVersionTable<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + 'static,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+  type Item<'a>
+    = Entry<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Iterator<'a>
+    = Iter<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Range<'a, Q, R>
+    = Range<'a, Self::Key, Self::Value, Q, R, Self::Comparator>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  type Options = ();
+  type Error = Infallible;
+
+  fn new(_: Self::Options) -> Result<Self, Self::Error>
+  where
+    Self: Sized,
+  {
+    Ok(Self::default())
+  }
+
+  #[inline]
+  fn insert(
+    &self,
+    version: Option<u64>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
+    vp: ValuePointer<Self::Value>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.update(version.unwrap_or(0), kp, Some(vp));
+    Ok(())
+  }
+
+  #[inline]
+  fn remove(
+    &self,
+    version: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.update(version.unwrap_or(0), key, None);
+    Ok(())
+  }
+
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    let inner = self.0.read();
+    match inner
+      .map
+      .get(key)?
+      .range(..=version.unwrap_or(0))
+      .next_back()
+    {
+      Some((version, Some(vp))) => Some((*version, *vp)),
+      _ => None,
+    }
+  }
+
+  #[inline]
+  fn kind() -> Kind {
+    Kind::MultipleVersion
+  }
+}
+
+impl<K, V, C> memtable::MultipleVersionMemtable for MultipleVersionTable<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + 'static,
+  C: KeyComparator<K>,
+{
+  type VersionedItem<'a>
+    = VersionedEntry<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type IterAll<'a>
+    = IterAll<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type RangeAll<'a, Q, R>
+    = RangeAll<'a, Self::Key, Self::Value, Q, R, Self::Comparator>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  #[inline]
+  fn maximum_version(&self) -> u64 {
+    self.0.read().max_version
+  }
+
+  #[inline]
+  fn minimum_version(&self) -> u64 {
+    self.0.read().min_version
+  }
+
+  #[inline]
+  fn may_contain_version(&self, version: u64) -> bool {
+    version >= self.minimum_version()
+  }
+
+  fn upper_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.last_visible(as_lookup(&probe_bound(bound)), version)
+  }
+
+  fn upper_bound_versioned<Q>(
+    &self,
+    version: u64,
+    bound: Bound<&Q>,
+  ) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.last_versioned(as_lookup(&probe_bound(bound)), version)
+  }
+
+  fn lower_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.first_visible(as_lookup(&probe_bound(bound)), version)
+  }
+
+  fn lower_bound_versioned<Q>(
+    &self,
+    version: u64,
+    bound: Bound<&Q>,
+  ) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.first_versioned(as_lookup(&probe_bound(bound)), version)
+  }
+
+  fn first(&self, version: u64) -> Option<Self::Item<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    self.first_visible(Bound::Unbounded, version)
+  }
+
+  fn first_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    MultipleVersionTable::first_versioned(self, Bound::Unbounded, version)
+  }
+
+  fn last(&self, version: u64) -> Option<Self::Item<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    self.last_visible(Bound::Unbounded, version)
+  }
+
+  fn last_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    self.tail_versioned(Bound::Unbounded, version)
+  }
+
+  fn get<Q>(&self, version: u64, key: &Q) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    let inner = self.0.read();
+    let (k, versions) = inner.map.get_key_value::<dyn Lookup<K, C>>(&Probe(key))?;
+    Entry::visible(self, k, versions, version)
+  }
+
+  fn get_versioned<Q>(&self, version: u64, key: &Q) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    let inner = self.0.read();
+    let (k, versions) = inner.map.get_key_value::<dyn Lookup<K, C>>(&Probe(key))?;
+    versions
+      .range(..=version)
+      .next_back()
+      .map(|(v, vp)| VersionedEntry::new(self, *k, *v, *vp, version))
+  }
+
+  fn contains<Q>(&self, version: u64, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    memtable::MultipleVersionMemtable::get(self, version, key).is_some()
+  }
+
+  fn contains_versioned<Q>(&self, version: u64, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.get_versioned(version, key).is_some()
+  }
+
+  fn iter(&self, version: u64) -> Self::Iterator<'_> {
+    Range::new(self, version, ..)
+  }
+
+  fn iter_all_versions(&self, version: u64) -> Self::IterAll<'_> {
+    RangeAll::new(self, version, ..)
+  }
+
+  fn range<'a, Q, R>(&'a self, version: u64, range: R) -> Self::Range<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    Range::new(self, version, range)
+  }
+
+  fn range_all_versions<'a, Q, R>(&'a self, version: u64, range: R) -> Self::RangeAll<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    RangeAll::new(self, version, range)
+  }
+}
@@ -0,0 +1,399 @@
+use core::{
+  convert::Infallible,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds, RangeFull},
+};
+use std::collections::BTreeMap;
+
+use dbutils::{
+  equivalent::Comparable,
+  types::{KeyRef, Type},
+};
+
+use crate::{
+  memtable,
+  sealed::WithoutVersion,
+  sync::RwLock,
+  types::{KeyComparator, KeyOrder, Kind},
+  wal::{KeyPointer, ValuePointer},
+};
+
+use super::{above_start, as_lookup, below_end, probe_bound, Lookup, Probe};
+
+/// An memory table implementation based on [`BTreeMap`], which is protected by a lightweight lock.
+pub struct Table<K: ?Sized, V: ?Sized, C = KeyOrder>(
+  RwLock<BTreeMap<KeyPointer<K, C>, ValuePointer<V>>>,
+);
+
+impl<K, V, C> core::fmt::Debug for Table<K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_tuple("Table").field(&*self.0.read()).finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> Default for Table<K, V, C> {
+  #[inline]
+  fn default() -> Self {
+    Self(RwLock::new(BTreeMap::new()))
+  }
+}
+
+impl<K, V, C> Table<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  /// Returns the first entry whose key is within the lower bound.
+  fn first_in(&self, lower: Bound<&dyn Lookup<K, C>>) -> Option<Entry<'_, K, V, C>> {
+    self
+      .0
+      .read()
+      .range::<dyn Lookup<K, C>, _>((lower, Bound::Unbounded))
+      .next()
+      .map(|(k, v)| Entry::new(self, *k, *v))
+  }
+
+  /// Returns the last entry whose key is within the upper bound.
+  fn last_in(&self, upper: Bound<&dyn Lookup<K, C>>) -> Option<Entry<'_, K, V, C>> {
+    self
+      .0
+      .read()
+      .range::<dyn Lookup<K, C>, _>((Bound::Unbounded, upper))
+      .next_back()
+      .map(|(k, v)| Entry::new(self, *k, *v))
+  }
+}
+
+/// An entry in the [`Table`].
+pub struct Entry<'a, K: ?Sized, V: ?Sized, C = KeyOrder> {
+  table: &'a Table<K, V, C>,
+  key: KeyPointer<K, C>,
+  value: ValuePointer<V>,
+}
+
+impl<K: ?Sized, V: ?Sized, C> Clone for Entry<'_, K, V, C> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+    }
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> core::fmt::Debug for Entry<'_, K, V, C> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Entry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .finish()
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized, C> Entry<'a, K, V, C> {
+  #[inline]
+  const fn new(table: &'a Table<K, V, C>, key: KeyPointer<K, C>, value: ValuePointer<V>) -> Self {
+    Self { table, key, value }
+  }
+}
+
+impl<'a, K, V, C> memtable::BaseEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self.table.first_in(Bound::Excluded(&self.key))
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self.table.last_in(Bound::Excluded(&self.key))
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, C> {
+    self.key
+  }
+}
+
+impl<'a, K, V, C> memtable::MemtableEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn value(&self) -> ValuePointer<V> {
+    self.value
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> WithoutVersion for Entry<'_, K, V, C> {}
+
+/// An iterator over the entries of the [`Table`].
+pub type Iter<'a, K, V, C = KeyOrder> = Range<'a, K, V, KeyPointer<K, C>, RangeFull, C>;
+
+/// An iterator over a subset of the entries of the [`Table`].
+///
+/// The iterator does not hold the lock, every step looks up the table again,
+/// so the entries inserted during the iteration may be yielded.
+pub struct Range<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C = KeyOrder> {
+  table: &'a Table<K, V, C>,
+  range: R,
+  head: Option<KeyPointer<K, C>>,
+  tail: Option<KeyPointer<K, C>>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C> Range<'a, K, V, Q, R, C> {
+  #[inline]
+  const fn new(table: &'a Table<K, V, C>, range: R) -> Self {
+    Self {
+      table,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R, C> Iterator for Range<'a, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  type Item = Entry<'a, K, V, C>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some(head) => self.table.first_in(Bound::Excluded(head)),
+      None => {
+        let start = probe_bound(self.range.start_bound());
+        self.table.first_in(as_lookup(&start))
+      }
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self.tail.as_ref().map_or(true, |tail| ent.key.lt(tail));
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R, C> DoubleEndedIterator for Range<'_, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some(tail) => self.table.last_in(Bound::Excluded(tail)),
+      None => {
+        let end = probe_bound(self.range.end_bound());
+        self.table.last_in(as_lookup(&end))
+      }
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self.head.as_ref().map_or(true, |head| ent.key.gt(head));
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, C> memtable::BaseTable for Table<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+  type Item<'a>
+    = Entry<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Iterator<'a>
+    = Iter<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Range<'a, Q, R>
+    = Range<'a, Self::Key, Self::Value, Q, R, Self::Comparator>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  type Options = ();
+  type Error = Infallible;
+
+  fn new(_: Self::Options) -> Result<Self, Self::Error>
+  where
+    Self: Sized,
+  {
+    Ok(Self::default())
+  }
+
+  #[inline]
+  fn insert(
+    &self,
+    _: Option<u64>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
+    vp: ValuePointer<Self::Value>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.0.write().insert(kp, vp);
+    Ok(())
+  }
+
+  #[inline]
+  fn remove(
+    &self,
+    _: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.0.write().remove(&key);
+    Ok(())
+  }
+
+  fn latest(
+    &self,
+    _: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.0.read().get(key).map(|vp| (0, *vp))
+  }
+
+  #[inline]
+  fn kind() -> Kind {
+    Kind::Plain
+  }
+}
+
+impl<K, V, C> memtable::Memtable for Table<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn len(&self) -> usize {
+    self.0.read().len()
+  }
+
+  #[inline]
+  fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.last_in(as_lookup(&probe_bound(bound)))
+  }
+
+  #[inline]
+  fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.first_in(as_lookup(&probe_bound(bound)))
+  }
+
+  #[inline]
+  fn first(&self) -> Option<Self::Item<'_>> {
+    self
+      .0
+      .read()
+      .first_key_value()
+      .map(|(k, v)| Entry::new(self, *k, *v))
+  }
+
+  #[inline]
+  fn last(&self) -> Option<Self::Item<'_>> {
+    self
+      .0
+      .read()
+      .last_key_value()
+      .map(|(k, v)| Entry::new(self, *k, *v))
+  }
+
+  #[inline]
+  fn get<Q>(&self, key: &Q) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self
+      .0
+      .read()
+      .get_key_value::<dyn Lookup<K, C>>(&Probe(key))
+      .map(|(k, v)| Entry::new(self, *k, *v))
+  }
+
+  #[inline]
+  fn contains<Q>(&self, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.0.read().contains_key::<dyn Lookup<K, C>>(&Probe(key))
+  }
+
+  #[inline]
+  fn iter(&self) -> Self::Iterator<'_> {
+    Range::new(self, ..)
+  }
+
+  #[inline]
+  fn range<'a, Q, R>(&'a self, range: R) -> Self::Range<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    Range::new(self, range)
+  }
+}
@@ -14,14 +14,16 @@ use dbutils::{
 use crate::{
   memtable::{self, BaseEntry, VersionedMemtableEntry},
   sealed::WithVersion,
-  types::Kind,
+  types::{KeyComparator, KeyOrder, Kind},
   wal::{KeyPointer, ValuePointer},
 };
 
 /// An memory table implementation based on [`crossbeam_skiplist::SkipSet`].
-pub struct MultipleVersionTable<K: ?Sized, V: ?Sized>(SkipMap<KeyPointer<K>, ValuePointer<V>>);
+pub struct MultipleVersionTable<K: ?Sized, V: ?Sized, C = KeyOrder>(
+  SkipMap<KeyPointer<K, C>, ValuePointer<V>>,
+);
 
-impl<K, V> Default for MultipleVersionTable<K, V>
+impl<K, V, C> Default for MultipleVersionTable<K, V, C>
 where
   K: ?Sized,
   V: ?Sized,
@@ -32,14 +34,16 @@ where
   }
 }
 
-impl<'a, K, V> BaseEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> BaseEntry<'a> for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
   K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
 
   #[inline]
   fn next(&mut self) -> Option<Self> {
@@ -52,16 +56,18 @@ where
   }
 
   #[inline]
-  fn key(&self) -> KeyPointer<K> {
+  fn key(&self) -> KeyPointer<K, C> {
     *self.key()
   }
 }
 
-impl<'a, K, V> memtable::VersionedMemtableEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> memtable::VersionedMemtableEntry<'a>
+  for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
   K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn value(&self) -> Option<ValuePointer<V>> {
@@ -74,21 +80,23 @@ where
   }
 }
 
-impl<K, V> WithVersion for Entry<'_, KeyPointer<K>, ValuePointer<V>>
+impl<K, V, C> WithVersion for Entry<'_, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized,
   V: ?Sized,
 {
 }
 
-impl<'a, K, V> BaseEntry<'a> for VersionedEntry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> BaseEntry<'a> for VersionedEntry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
   K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
 
   #[inline]
   fn next(&mut self) -> Option<Self> {
@@ -101,16 +109,18 @@ where
   }
 
   #[inline]
-  fn key(&self) -> KeyPointer<K> {
+  fn key(&self) -> KeyPointer<K, C> {
     *self.key()
   }
 }
 
-impl<'a, K, V> VersionedMemtableEntry<'a> for VersionedEntry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> VersionedMemtableEntry<'a>
+  for VersionedEntry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
   K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn version(&self) -> u64 {
@@ -123,37 +133,39 @@ where
   }
 }
 
-impl<K, V> WithVersion for VersionedEntry<'_, KeyPointer<K>, ValuePointer<V>>
+impl<K, V, C> WithVersion for VersionedEntry<'_, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized,
   V: ?Sized,
 {
 }
 
-impl<K, V> memtable::BaseTable for MultipleVersionTable<K, V>
+impl<K, V, C> memtable::BaseTable for MultipleVersionTable<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized + 'static,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
   type Item<'a>
-    = Entry<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Entry<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type Iterator<'a>
-    = Iter<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Iter<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type Range<'a, Q, R>
-    = Range<'a, Q, R, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Range<'a, Q, R, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   type Options = ();
   type Error = Infallible;
@@ -169,53 +181,70 @@ where
   fn insert(
     &self,
     version: Option<u64>,
-    kp: KeyPointer<Self::Key>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
     vp: ValuePointer<Self::Value>,
   ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     self.0.insert_unchecked(version.unwrap_or(0), kp, vp);
     Ok(())
   }
 
   #[inline]
-  fn remove(&self, version: Option<u64>, key: KeyPointer<Self::Key>) -> Result<(), Self::Error>
+  fn remove(
+    &self,
+    version: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     self.0.remove_unchecked(version.unwrap_or(0), key);
     Ok(())
   }
 
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    memtable::MultipleVersionMemtable::get(self, version.unwrap_or(0), key)
+      .and_then(|ent| VersionedMemtableEntry::value(&ent).map(|vp| (ent.version(), vp)))
+  }
+
   #[inline]
   fn kind() -> Kind {
     Kind::MultipleVersion
   }
 }
 
-impl<K, V> memtable::MultipleVersionMemtable for MultipleVersionTable<K, V>
+impl<K, V, C> memtable::MultipleVersionMemtable for MultipleVersionTable<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized + 'static,
+  C: KeyComparator<K>,
 {
   type VersionedItem<'a>
-    = VersionedEntry<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = VersionedEntry<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type IterAll<'a>
-    = IterAll<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = IterAll<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type RangeAll<'a, Q, R>
-    = RangeAll<'a, Q, R, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = RangeAll<'a, Q, R, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   #[inline]
   fn maximum_version(&self) -> u64 {
@@ -234,7 +263,7 @@ where
 
   fn upper_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.upper_bound(version, bound)
   }
@@ -245,14 +274,14 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.upper_bound_versioned(version, bound)
   }
 
   fn lower_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.lower_bound(version, bound)
   }
@@ -263,63 +292,63 @@ where
     bound: Bound<&Q>,
   ) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.lower_bound_versioned(version, bound)
   }
 
   fn first(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     self.0.front(version)
   }
 
   fn first_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     self.0.front_versioned(version)
   }
 
   fn last(&self, version: u64) -> Option<Self::Item<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     self.0.back(version)
   }
 
   fn last_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
   where
-    KeyPointer<Self::Key>: Ord,
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
   {
     self.0.back_versioned(version)
   }
 
   fn get<Q>(&self, version: u64, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.get(version, key)
   }
 
   fn get_versioned<Q>(&self, version: u64, key: &Q) -> Option<Self::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.get_versioned(version, key)
   }
 
   fn contains<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.contains_key(version, key)
   }
 
   fn contains_versioned<Q>(&self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.contains_key_versioned(version, key)
   }
@@ -335,7 +364,7 @@ where
   fn range<'a, Q, R>(&'a self, version: u64, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.range(version, range)
   }
@@ -343,7 +372,7 @@ where
   fn range_all_versions<'a, Q, R>(&'a self, version: u64, range: R) -> Self::RangeAll<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.range_all_versions(version, range)
   }
@@ -9,41 +9,43 @@ use dbutils::{
 use crate::{
   memtable,
   sealed::WithoutVersion,
-  types::Kind,
+  types::{KeyComparator, KeyOrder, Kind},
   wal::{KeyPointer, ValuePointer},
 };
 
 pub use crossbeam_skiplist::map::{Entry, Iter, Range};
 
 /// An memory table implementation based on [`crossbeam_skiplist::SkipMap`].
-pub struct Table<K: ?Sized, V: ?Sized>(SkipMap<KeyPointer<K>, ValuePointer<V>>);
+pub struct Table<K: ?Sized, V: ?Sized, C = KeyOrder>(SkipMap<KeyPointer<K, C>, ValuePointer<V>>);
 
-impl<K, V> core::fmt::Debug for Table<K, V>
+impl<K, V, C> core::fmt::Debug for Table<K, V, C>
 where
-  K: ?Sized + Type + Ord,
-  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  K: ?Sized,
   V: ?Sized,
+  C: KeyComparator<K>,
 {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
     f.debug_tuple("Table").field(&self.0).finish()
   }
 }
 
-impl<K: ?Sized, V: ?Sized> Default for Table<K, V> {
+impl<K: ?Sized, V: ?Sized, C> Default for Table<K, V, C> {
   #[inline]
   fn default() -> Self {
     Self(SkipMap::new())
   }
 }
 
-impl<'a, K, V> memtable::BaseEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> memtable::BaseEntry<'a> for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
   K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
 
   #[inline]
   fn next(&mut self) -> Option<Self> {
@@ -56,16 +58,17 @@ where
   }
 
   #[inline]
-  fn key(&self) -> KeyPointer<K> {
+  fn key(&self) -> KeyPointer<K, C> {
     *self.key()
   }
 }
 
-impl<'a, K, V> memtable::MemtableEntry<'a> for Entry<'a, KeyPointer<K>, ValuePointer<V>>
+impl<'a, K, V, C> memtable::MemtableEntry<'a> for Entry<'a, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized + Type + Ord,
   K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized + Type,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn value(&self) -> ValuePointer<V> {
@@ -73,37 +76,39 @@ where
   }
 }
 
-impl<K, V> WithoutVersion for Entry<'_, KeyPointer<K>, ValuePointer<V>>
+impl<K, V, C> WithoutVersion for Entry<'_, KeyPointer<K, C>, ValuePointer<V>>
 where
   K: ?Sized,
   V: ?Sized,
 {
 }
 
-impl<K, V> memtable::BaseTable for Table<K, V>
+impl<K, V, C> memtable::BaseTable for Table<K, V, C>
 where
   K: ?Sized + Type + Ord,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   type Key = K;
   type Value = V;
+  type Comparator = C;
   type Item<'a>
-    = Entry<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Entry<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type Iterator<'a>
-    = Iter<'a, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Iter<'a, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a;
 
   type Range<'a, Q, R>
-    = Range<'a, Q, R, KeyPointer<Self::Key>, ValuePointer<Self::Value>>
+    = Range<'a, Q, R, KeyPointer<Self::Key, Self::Comparator>, ValuePointer<Self::Value>>
   where
     Self: 'a,
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>;
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
 
   type Options = ();
   type Error = Infallible;
@@ -119,36 +124,52 @@ where
   fn insert(
     &self,
     _: Option<u64>,
-    kp: KeyPointer<Self::Key>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
     vp: ValuePointer<Self::Value>,
   ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     self.0.insert(kp, vp);
     Ok(())
   }
 
   #[inline]
-  fn remove(&self, _: Option<u64>, key: KeyPointer<Self::Key>) -> Result<(), Self::Error>
+  fn remove(
+    &self,
+    _: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
   where
-    KeyPointer<Self::Key>: Ord + 'static,
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
   {
     self.0.remove(&key);
     Ok(())
   }
 
+  fn latest(
+    &self,
+    _: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    memtable::Memtable::get(self, key).map(|ent| (0, memtable::MemtableEntry::value(&ent)))
+  }
+
   #[inline]
   fn kind() -> Kind {
     Kind::Plain
   }
 }
 
-impl<K, V> memtable::Memtable for Table<K, V>
+impl<K, V, C> memtable::Memtable for Table<K, V, C>
 where
   K: ?Sized + Type + Ord + 'static,
   for<'a> K::Ref<'a>: KeyRef<'a, K>,
   V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn len(&self) -> usize {
@@ -158,7 +179,7 @@ where
   #[inline]
   fn upper_bound<Q>(&self, bound: core::ops::Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.upper_bound(bound)
   }
@@ -166,7 +187,7 @@ where
   #[inline]
   fn lower_bound<Q>(&self, bound: core::ops::Bound<&Q>) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.lower_bound(bound)
   }
@@ -184,7 +205,7 @@ where
   #[inline]
   fn get<Q>(&self, key: &Q) -> Option<Self::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.get(key)
   }
@@ -192,7 +213,7 @@ where
   #[inline]
   fn contains<Q>(&self, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.contains_key(key)
   }
@@ -206,7 +227,7 @@ where
   fn range<'a, Q, R>(&'a self, range: R) -> Self::Range<'a, Q, R>
   where
     R: RangeBounds<Q> + 'a,
-    Q: ?Sized + Comparable<KeyPointer<Self::Key>>,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
   {
     self.0.range(range)
   }
@@ -0,0 +1,180 @@
+use core::{cmp, mem, ops::Bound};
+use std::vec::Vec;
+
+use dbutils::equivalent::Comparable;
+
+use crate::{
+  sync::{ReadGuard, RwLock},
+  wal::KeyPointer,
+};
+
+/// The multiple version memtable implementation.
+pub mod multiple_version;
+/// The memtable implementation.
+pub mod table;
+
+pub use multiple_version::MultipleVersionTable;
+pub use table::Table;
+
+/// The minimum number of the merged updates kept out of the sorted entries.
+const MIN_UPDATES: usize = 64;
+
+/// An update which is staged in the array before it is merged into the sorted entries.
+pub(super) trait Staged<T>: Sized {
+  /// Compares the updates, an update replaces the earlier one which is equal to it.
+  fn compare(&self, other: &Self) -> cmp::Ordering;
+
+  /// Merges the sorted updates, which contain at most one update equal to each other, into the sorted entries.
+  fn merge(entries: Vec<T>, updates: Vec<Self>) -> Vec<T>;
+}
+
+pub(super) struct State<T, U> {
+  entries: Vec<T>,
+  /// The sorted updates which are not merged into the entries yet, they replace the entries of the same keys.
+  updates: Vec<U>,
+  /// The updates in the order of arrival.
+  staged: Vec<U>,
+}
+
+impl<T, U> State<T, U> {
+  /// Returns the sorted entries.
+  #[inline]
+  pub(super) fn entries(&self) -> &[T] {
+    &self.entries
+  }
+
+  /// Returns the sorted updates which are not merged into the entries yet.
+  #[inline]
+  pub(super) fn updates(&self) -> &[U] {
+    &self.updates
+  }
+}
+
+/// A sorted array, the updates are appended to a staging buffer, and sorted before the next lookup.
+///
+/// Replaying a WAL only appends, so building the array costs a single sort, and once it
+/// is built, the lookups are binary searches over a contiguous array without any extra
+/// allocation per entry.
+///
+/// After that, the sorted updates are kept in a second, smaller array, which the lookups search as well.
+/// The first lookup after `m` updates merges them into the second array, which costs `O(u + m log m)` for `u`
+/// updates in it, and only when it grows beyond about the square root of the `n` entries, it is merged into
+/// the entries, which costs `O(n)`. So interleaving the writes and the reads costs `O(√n)` per write amortized,
+/// instead of a merge of the whole array each time.
+pub(super) struct SortedArray<T, U>(RwLock<State<T, U>>);
+
+impl<T, U> SortedArray<T, U> {
+  #[inline]
+  pub(super) const fn new() -> Self {
+    Self(RwLock::new(State {
+      entries: Vec::new(),
+      updates: Vec::new(),
+      staged: Vec::new(),
+    }))
+  }
+
+  /// Stages an update, which is visible to the next lookup.
+  #[inline]
+  pub(super) fn stage(&self, update: U) {
+    self.0.write().staged.push(update);
+  }
+}
+
+impl<T, U: Staged<T>> SortedArray<T, U> {
+  /// Merges the staged updates if any, and locks the sorted entries and updates for reading.
+  pub(super) fn read(&self) -> ReadGuard<'_, State<T, U>> {
+    loop {
+      let state = self.0.read();
+      if state.staged.is_empty() {
+        return state;
+      }
+      drop(state);
+
+      let mut state = self.0.write();
+      if !state.staged.is_empty() {
+        let staged = mem::take(&mut state.staged);
+        let updates = merge_updates(mem::take(&mut state.updates), staged);
+        if updates.len() > maximum_updates(state.entries.len()) {
+          let entries = mem::take(&mut state.entries);
+          let mut entries = U::merge(entries, updates);
+          entries.shrink_to_fit();
+          state.entries = entries;
+        } else {
+          state.updates = updates;
+        }
+      }
+    }
+  }
+}
+
+/// Returns the maximum number of the updates kept out of `len` entries, which is about the square root of it.
+#[inline]
+const fn maximum_updates(len: usize) -> usize {
+  let sqrt = 1 << ((usize::BITS - len.leading_zeros()) / 2);
+  if sqrt > MIN_UPDATES {
+    sqrt
+  } else {
+    MIN_UPDATES
+  }
+}
+
+/// Merges the staged updates, which are in the order of arrival, into the sorted updates.
+fn merge_updates<T, U: Staged<T>>(updates: Vec<U>, mut staged: Vec<U>) -> Vec<U> {
+  // The sort is stable, so the equal updates are still in the order of arrival.
+  staged.sort_by(U::compare);
+
+  let mut merged = Vec::with_capacity(updates.len() + staged.len());
+  let mut updates = updates.into_iter().peekable();
+  let mut staged = staged.into_iter().peekable();
+  while let Some(mut update) = staged.next() {
+    while let Some(next) = staged.next_if(|next| next.compare(&update).is_eq()) {
+      update = next;
+    }
+
+    while let Some(older) = updates.next_if(|older| older.compare(&update).is_le()) {
+      if older.compare(&update).is_lt() {
+        merged.push(older);
+      }
+    }
+
+    merged.push(update);
+  }
+  merged.extend(updates);
+  merged
+}
+
+/// Returns the index of the first entry whose key is within the lower bound.
+#[inline]
+pub(super) fn lower_index<K, C, Q, T>(
+  entries: &[T],
+  lower: Bound<&Q>,
+  key: impl Fn(&T) -> &KeyPointer<K, C>,
+) -> usize
+where
+  K: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+{
+  match lower {
+    Bound::Included(q) => entries.partition_point(|ent| q.compare(key(ent)).is_gt()),
+    Bound::Excluded(q) => entries.partition_point(|ent| q.compare(key(ent)).is_ge()),
+    Bound::Unbounded => 0,
+  }
+}
+
+/// Returns the index after the last entry whose key is within the upper bound.
+#[inline]
+pub(super) fn upper_index<K, C, Q, T>(
+  entries: &[T],
+  upper: Bound<&Q>,
+  key: impl Fn(&T) -> &KeyPointer<K, C>,
+) -> usize
+where
+  K: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+{
+  match upper {
+    Bound::Included(q) => entries.partition_point(|ent| q.compare(key(ent)).is_ge()),
+    Bound::Excluded(q) => entries.partition_point(|ent| q.compare(key(ent)).is_gt()),
+    Bound::Unbounded => entries.len(),
+  }
+}
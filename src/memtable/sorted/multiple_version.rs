@@ -0,0 +1,1041 @@
+use core::{
+  cmp,
+  convert::Infallible,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds, RangeFull},
+  sync::atomic::{AtomicU64, Ordering},
+};
+use std::vec::Vec;
+
+use dbutils::{
+  equivalent::Comparable,
+  types::{KeyRef, Type},
+};
+
+use crate::{
+  memtable::{self, BaseEntry, VersionedMemtableEntry},
+  sealed::WithVersion,
+  types::{KeyComparator, KeyOrder, Kind},
+  wal::{KeyPointer, ValuePointer},
+};
+
+use super::{
+  super::btree::{above_start, below_end},
+  lower_index, upper_index, SortedArray, Staged,
+};
+
+/// A version of a key, the value is `None` if the version is a tombstone.
+struct Record<K: ?Sized, V: ?Sized, C> {
+  key: KeyPointer<K, C>,
+  version: u64,
+  value: Option<ValuePointer<V>>,
+}
+
+impl<K: ?Sized, V: ?Sized, C> Clone for Record<K, V, C> {
+  #[inline]
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> Copy for Record<K, V, C> {}
+
+impl<K, V, C> Record<K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  /// The records are in ascending order of keys, and in descending order of versions for the same key.
+  #[inline]
+  fn cmp(&self, other: &Self) -> cmp::Ordering {
+    self
+      .key
+      .cmp(&other.key)
+      .then_with(|| other.version.cmp(&self.version))
+  }
+}
+
+impl<K, V, C> Staged<Self> for Record<K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn compare(&self, other: &Self) -> cmp::Ordering {
+    self.cmp(other)
+  }
+
+  #[inline]
+  fn merge(entries: Vec<Self>, updates: Vec<Self>) -> Vec<Self> {
+    merge_records(entries.len() + updates.len(), entries, updates)
+  }
+}
+
+/// Merges the sorted records, the updates replace the entries of the same versions.
+fn merge_records<K, V, C>(
+  capacity: usize,
+  entries: impl IntoIterator<Item = Record<K, V, C>>,
+  updates: impl IntoIterator<Item = Record<K, V, C>>,
+) -> Vec<Record<K, V, C>>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  let mut merged = Vec::with_capacity(capacity);
+  let mut entries = entries.into_iter().peekable();
+  for update in updates {
+    while let Some(ent) = entries.next_if(|ent| ent.cmp(&update).is_le()) {
+      if ent.cmp(&update).is_lt() {
+        merged.push(ent);
+      }
+    }
+
+    merged.push(update);
+  }
+  merged.extend(entries);
+  merged
+}
+
+/// An memory table implementation based on a sorted array, which is protected by a lightweight lock.
+///
+/// Each version takes a key pointer, a value pointer and the version, which are 72 bytes on 64-bit targets,
+/// the updates are merged into the array in the same way as [`Table`](super::Table), see it for the cases it suits.
+pub struct MultipleVersionTable<K: ?Sized, V: ?Sized, C = KeyOrder> {
+  array: SortedArray<Record<K, V, C>, Record<K, V, C>>,
+  min_version: AtomicU64,
+  max_version: AtomicU64,
+}
+
+impl<K: ?Sized, V: ?Sized, C> Default for MultipleVersionTable<K, V, C> {
+  #[inline]
+  fn default() -> Self {
+    Self {
+      array: SortedArray::new(),
+      min_version: AtomicU64::new(u64::MAX),
+      max_version: AtomicU64::new(0),
+    }
+  }
+}
+
+impl<K, V, C> MultipleVersionTable<K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  fn update(&self, version: u64, key: KeyPointer<K, C>, value: Option<ValuePointer<V>>) {
+    self.array.stage(Record {
+      key,
+      version,
+      value,
+    });
+    self.min_version.fetch_min(version, Ordering::AcqRel);
+    self.max_version.fetch_max(version, Ordering::AcqRel);
+  }
+
+  /// Walks the keys from the lower bound in ascending order, until `f` returns an entry.
+  fn find_from<'a, Q, T>(
+    &'a self,
+    lower: Bound<&Q>,
+    mut f: impl FnMut(&'a Self, &[Record<K, V, C>]) -> Option<T>,
+  ) -> Option<T>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    let state = self.array.read();
+    let (entries, updates) = (state.entries(), state.updates());
+    let mut idx = lower_index(entries, lower, |r| &r.key);
+    let mut update_idx = lower_index(updates, lower, |r| &r.key);
+    loop {
+      let (older, newer) = match (entries.get(idx), updates.get(update_idx)) {
+        (None, None) => return None,
+        (Some(ent), Some(update)) => match ent.key.cmp(&update.key) {
+          cmp::Ordering::Less => (versions_from(entries, idx), &[][..]),
+          cmp::Ordering::Equal => (
+            versions_from(entries, idx),
+            versions_from(updates, update_idx),
+          ),
+          cmp::Ordering::Greater => (&[][..], versions_from(updates, update_idx)),
+        },
+        (Some(_), None) => (versions_from(entries, idx), &[][..]),
+        (None, Some(_)) => (&[][..], versions_from(updates, update_idx)),
+      };
+      idx += older.len();
+      update_idx += newer.len();
+
+      if let Some(ent) = with_versions(older, newer, |versions| f(self, versions)) {
+        return Some(ent);
+      }
+    }
+  }
+
+  /// Walks the keys from the upper bound in descending order, until `f` returns an entry.
+  fn rfind_from<'a, Q, T>(
+    &'a self,
+    upper: Bound<&Q>,
+    mut f: impl FnMut(&'a Self, &[Record<K, V, C>]) -> Option<T>,
+  ) -> Option<T>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    let state = self.array.read();
+    let (entries, updates) = (state.entries(), state.updates());
+    let mut idx = upper_index(entries, upper, |r| &r.key);
+    let mut update_idx = upper_index(updates, upper, |r| &r.key);
+    loop {
+      let ent = idx.checked_sub(1).map(|idx| &entries[idx]);
+      let (older, newer) = match (ent, update_idx.checked_sub(1).map(|idx| &updates[idx])) {
+        (None, None) => return None,
+        (Some(ent), Some(update)) => match ent.key.cmp(&update.key) {
+          cmp::Ordering::Greater => (versions_to(entries, idx), &[][..]),
+          cmp::Ordering::Equal => (versions_to(entries, idx), versions_to(updates, update_idx)),
+          cmp::Ordering::Less => (&[][..], versions_to(updates, update_idx)),
+        },
+        (Some(_), None) => (versions_to(entries, idx), &[][..]),
+        (None, Some(_)) => (&[][..], versions_to(updates, update_idx)),
+      };
+      idx -= older.len();
+      update_idx -= newer.len();
+
+      if let Some(ent) = with_versions(older, newer, |versions| f(self, versions)) {
+        return Some(ent);
+      }
+    }
+  }
+
+  /// Returns the versions of the key equal to the given key.
+  fn find<'a, Q, T>(
+    &'a self,
+    key: &Q,
+    f: impl FnOnce(&'a Self, &[Record<K, V, C>]) -> Option<T>,
+  ) -> Option<T>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    let state = self.array.read();
+    let older = versions_of(state.entries(), key);
+    let newer = versions_of(state.updates(), key);
+    if older.is_empty() && newer.is_empty() {
+      return None;
+    }
+
+    with_versions(older, newer, |versions| f(self, versions))
+  }
+
+  /// Returns the first entry within the lower bound whose latest value visible at the version is not removed.
+  fn first_visible<Q>(&self, lower: Bound<&Q>, version: u64) -> Option<Entry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    self.find_from(lower, |this, versions| {
+      Entry::visible(this, versions, version)
+    })
+  }
+
+  /// Returns the last entry within the upper bound whose latest value visible at the version is not removed.
+  fn last_visible<Q>(&self, upper: Bound<&Q>, version: u64) -> Option<Entry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    self.rfind_from(upper, |this, versions| {
+      Entry::visible(this, versions, version)
+    })
+  }
+
+  /// Returns the newest version visible at the version of the first key within the lower bound.
+  fn first_versioned<Q>(
+    &self,
+    lower: Bound<&Q>,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    self.find_from(lower, |this, versions| {
+      newest(versions, version).map(|r| VersionedEntry::new(this, r, version))
+    })
+  }
+
+  /// Returns the newest version visible at the version of the last key within the upper bound.
+  fn last_versioned<Q>(&self, upper: Bound<&Q>, version: u64) -> Option<VersionedEntry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    self.rfind_from(upper, |this, versions| {
+      newest(versions, version).map(|r| VersionedEntry::new(this, r, version))
+    })
+  }
+
+  /// Returns the oldest version visible at the version of the last key within the upper bound,
+  /// which is the last one when iterating all the versions.
+  fn tail_versioned<Q>(&self, upper: Bound<&Q>, version: u64) -> Option<VersionedEntry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    self.rfind_from(upper, |this, versions| {
+      versions
+        .last()
+        .filter(|r| r.version <= version)
+        .map(|r| VersionedEntry::new(this, r, version))
+    })
+  }
+
+  /// Returns the entry after the version of the key, the versions of a key are in descending order.
+  fn next_versioned(
+    &self,
+    key: &KeyPointer<K, C>,
+    current: u64,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>> {
+    let older = self.find(key, |this, versions| {
+      let idx = versions.partition_point(|r| r.version >= current);
+      versions
+        .get(idx)
+        .map(|r| VersionedEntry::new(this, r, version))
+    });
+
+    older.or_else(|| self.first_versioned(Bound::Excluded(key), version))
+  }
+
+  /// Returns the entry before the version of the key, the versions of a key are in descending order.
+  fn prev_versioned(
+    &self,
+    key: &KeyPointer<K, C>,
+    current: u64,
+    version: u64,
+  ) -> Option<VersionedEntry<'_, K, V, C>> {
+    let newer = self.find(key, |this, versions| {
+      let idx = versions.partition_point(|r| r.version > current);
+      idx
+        .checked_sub(1)
+        .map(|idx| &versions[idx])
+        .filter(|r| r.version <= version)
+        .map(|r| VersionedEntry::new(this, r, version))
+    });
+
+    newer.or_else(|| self.tail_versioned(Bound::Excluded(key), version))
+  }
+}
+
+/// Returns the versions of the key at `idx`, which is the first version of the key.
+#[inline]
+fn versions_from<K, V, C>(entries: &[Record<K, V, C>], idx: usize) -> &[Record<K, V, C>]
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  let key = &entries[idx].key;
+  let len = entries[idx..].partition_point(|r| r.key.eq(key));
+  &entries[idx..idx + len]
+}
+
+/// Returns the versions of the key equal to the given key, which is empty if the key does not exist.
+#[inline]
+fn versions_of<'a, K, V, C, Q>(entries: &'a [Record<K, V, C>], key: &Q) -> &'a [Record<K, V, C>]
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+{
+  let idx = lower_index(entries, Bound::Included(key), |r| &r.key);
+  match entries.get(idx) {
+    Some(r) if key.compare(&r.key).is_eq() => versions_from(entries, idx),
+    _ => &[],
+  }
+}
+
+/// Calls `f` with the versions of a key in the entries and in the updates which are not merged yet,
+/// the versions are merged only if the key is in both of them.
+#[inline]
+fn with_versions<K, V, C, T>(
+  older: &[Record<K, V, C>],
+  newer: &[Record<K, V, C>],
+  f: impl FnOnce(&[Record<K, V, C>]) -> Option<T>,
+) -> Option<T>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  if newer.is_empty() {
+    f(older)
+  } else if older.is_empty() {
+    f(newer)
+  } else {
+    f(&merge_records(
+      older.len() + newer.len(),
+      older.iter().copied(),
+      newer.iter().copied(),
+    ))
+  }
+}
+
+/// Returns the versions of the key at `idx - 1`, which is the last version of the key.
+#[inline]
+fn versions_to<K, V, C>(entries: &[Record<K, V, C>], idx: usize) -> &[Record<K, V, C>]
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  let key = &entries[idx - 1].key;
+  let start = entries[..idx].partition_point(|r| r.key.lt(key));
+  &entries[start..idx]
+}
+
+/// Returns the newest version visible at the version.
+#[inline]
+fn newest<K: ?Sized, V: ?Sized, C>(
+  versions: &[Record<K, V, C>],
+  version: u64,
+) -> Option<&Record<K, V, C>> {
+  versions.get(versions.partition_point(|r| r.version > version))
+}
+
+/// An entry in the [`MultipleVersionTable`], which is the latest value of a key visible at the query version.
+pub struct Entry<'a, K: ?Sized, V: ?Sized, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  key: KeyPointer<K, C>,
+  value: ValuePointer<V>,
+  version: u64,
+  query_version: u64,
+}
+
+impl<K: ?Sized, V: ?Sized, C> core::fmt::Debug for Entry<'_, K, V, C> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Entry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .field("version", &self.version)
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> Clone for Entry<'_, K, V, C> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+      version: self.version,
+      query_version: self.query_version,
+    }
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized, C> Entry<'a, K, V, C> {
+  #[inline]
+  fn visible(
+    table: &'a MultipleVersionTable<K, V, C>,
+    versions: &[Record<K, V, C>],
+    query_version: u64,
+  ) -> Option<Self> {
+    let record = newest(versions, query_version)?;
+    record.value.map(|value| Self {
+      table,
+      key: record.key,
+      value,
+      version: record.version,
+      query_version,
+    })
+  }
+}
+
+impl<'a, K, V, C> BaseEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self
+      .table
+      .first_visible(Bound::Excluded(&self.key), self.query_version)
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self
+      .table
+      .last_visible(Bound::Excluded(&self.key), self.query_version)
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, C> {
+    self.key
+  }
+}
+
+impl<'a, K, V, C> VersionedMemtableEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn value(&self) -> Option<ValuePointer<V>> {
+    Some(self.value)
+  }
+
+  #[inline]
+  fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> WithVersion for Entry<'_, K, V, C> {}
+
+/// A versioned entry in the [`MultipleVersionTable`], the value is `None` if the entry is a tombstone.
+pub struct VersionedEntry<'a, K: ?Sized, V: ?Sized, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  key: KeyPointer<K, C>,
+  value: Option<ValuePointer<V>>,
+  version: u64,
+  query_version: u64,
+}
+
+impl<K: ?Sized, V: ?Sized, C> core::fmt::Debug for VersionedEntry<'_, K, V, C> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("VersionedEntry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .field("version", &self.version)
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> Clone for VersionedEntry<'_, K, V, C> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+      version: self.version,
+      query_version: self.query_version,
+    }
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized, C> VersionedEntry<'a, K, V, C> {
+  #[inline]
+  const fn new(
+    table: &'a MultipleVersionTable<K, V, C>,
+    record: &Record<K, V, C>,
+    query_version: u64,
+  ) -> Self {
+    Self {
+      table,
+      key: record.key,
+      value: record.value,
+      version: record.version,
+      query_version,
+    }
+  }
+}
+
+impl<'a, K, V, C> BaseEntry<'a> for VersionedEntry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self
+      .table
+      .next_versioned(&self.key, self.version, self.query_version)
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self
+      .table
+      .prev_versioned(&self.key, self.version, self.query_version)
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, C> {
+    self.key
+  }
+}
+
+impl<'a, K, V, C> VersionedMemtableEntry<'a> for VersionedEntry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn value(&self) -> Option<ValuePointer<V>> {
+    self.value
+  }
+
+  #[inline]
+  fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> WithVersion for VersionedEntry<'_, K, V, C> {}
+
+/// An iterator over the entries of the [`MultipleVersionTable`].
+pub type Iter<'a, K, V, C = KeyOrder> = Range<'a, K, V, KeyPointer<K, C>, RangeFull, C>;
+
+/// An iterator over a subset of the entries of the [`MultipleVersionTable`].
+///
+/// The iterator does not hold the lock, every step looks up the table again.
+pub struct Range<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  version: u64,
+  range: R,
+  head: Option<KeyPointer<K, C>>,
+  tail: Option<KeyPointer<K, C>>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C> Range<'a, K, V, Q, R, C> {
+  #[inline]
+  const fn new(table: &'a MultipleVersionTable<K, V, C>, version: u64, range: R) -> Self {
+    Self {
+      table,
+      version,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R, C> Iterator for Range<'a, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  type Item = Entry<'a, K, V, C>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some(head) => self
+        .table
+        .first_visible(Bound::Excluded(head), self.version),
+      None => self
+        .table
+        .first_visible(self.range.start_bound(), self.version),
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self.tail.as_ref().map_or(true, |tail| ent.key.lt(tail));
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R, C> DoubleEndedIterator for Range<'_, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some(tail) => self.table.last_visible(Bound::Excluded(tail), self.version),
+      None => self
+        .table
+        .last_visible(self.range.end_bound(), self.version),
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self.head.as_ref().map_or(true, |head| ent.key.gt(head));
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some(ent.key);
+    Some(ent)
+  }
+}
+
+/// An iterator over all the versions of the entries of the [`MultipleVersionTable`].
+pub type IterAll<'a, K, V, C = KeyOrder> = RangeAll<'a, K, V, KeyPointer<K, C>, RangeFull, C>;
+
+/// An iterator over all the versions of a subset of the entries of the [`MultipleVersionTable`].
+///
+/// The entries are yielded in ascending order of keys, and in descending order of versions for the same key.
+pub struct RangeAll<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C = KeyOrder> {
+  table: &'a MultipleVersionTable<K, V, C>,
+  version: u64,
+  range: R,
+  head: Option<(KeyPointer<K, C>, u64)>,
+  tail: Option<(KeyPointer<K, C>, u64)>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C> RangeAll<'a, K, V, Q, R, C> {
+  #[inline]
+  const fn new(table: &'a MultipleVersionTable<K, V, C>, version: u64, range: R) -> Self {
+    Self {
+      table,
+      version,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R, C> Iterator for RangeAll<'a, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  type Item = VersionedEntry<'a, K, V, C>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some((key, version)) => self.table.next_versioned(key, *version, self.version),
+      None => self
+        .table
+        .first_versioned(self.range.start_bound(), self.version),
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self
+        .tail
+        .as_ref()
+        .map_or(true, |(key, version)| match ent.key.cmp(key) {
+          cmp::Ordering::Less => true,
+          cmp::Ordering::Equal => ent.version > *version,
+          cmp::Ordering::Greater => false,
+        });
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some((ent.key, ent.version));
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R, C> DoubleEndedIterator for RangeAll<'_, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some((key, version)) => self.table.prev_versioned(key, *version, self.version),
+      None => self
+        .table
+        .tail_versioned(self.range.end_bound(), self.version),
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self
+        .head
+        .as_ref()
+        .map_or(true, |(key, version)| match ent.key.cmp(key) {
+          cmp::Ordering::Greater => true,
+          cmp::Ordering::Equal => ent.version < *version,
+          cmp::Ordering::Less => false,
+        });
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some((ent.key, ent.version));
+    Some(ent)
+  }
+}
+
+impl<K, V, C> memtable::BaseTable for MultipleVersionTable<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + 'static,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+  type Item<'a>
+    = Entry<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Iterator<'a>
+    = Iter<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Range<'a, Q, R>
+    = Range<'a, Self::Key, Self::Value, Q, R, Self::Comparator>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  type Options = ();
+  type Error = Infallible;
+
+  fn new(_: Self::Options) -> Result<Self, Self::Error>
+  where
+    Self: Sized,
+  {
+    Ok(Self::default())
+  }
+
+  #[inline]
+  fn insert(
+    &self,
+    version: Option<u64>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
+    vp: ValuePointer<Self::Value>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.update(version.unwrap_or(0), kp, Some(vp));
+    Ok(())
+  }
+
+  #[inline]
+  fn remove(
+    &self,
+    version: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.update(version.unwrap_or(0), key, None);
+    Ok(())
+  }
+
+  fn latest(
+    &self,
+    version: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.find(key, |_, versions| {
+      let record = newest(versions, version.unwrap_or(0))?;
+      record.value.map(|vp| (record.version, vp))
+    })
+  }
+
+  #[inline]
+  fn kind() -> Kind {
+    Kind::MultipleVersion
+  }
+}
+
+impl<K, V, C> memtable::MultipleVersionMemtable for MultipleVersionTable<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + 'static,
+  C: KeyComparator<K>,
+{
+  type VersionedItem<'a>
+    = VersionedEntry<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type IterAll<'a>
+    = IterAll<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type RangeAll<'a, Q, R>
+    = RangeAll<'a, Self::Key, Self::Value, Q, R, Self::Comparator>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  #[inline]
+  fn maximum_version(&self) -> u64 {
+    self.max_version.load(Ordering::Acquire)
+  }
+
+  #[inline]
+  fn minimum_version(&self) -> u64 {
+    self.min_version.load(Ordering::Acquire)
+  }
+
+  #[inline]
+  fn may_contain_version(&self, version: u64) -> bool {
+    version >= self.minimum_version()
+  }
+
+  fn upper_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.last_visible(bound, version)
+  }
+
+  fn upper_bound_versioned<Q>(
+    &self,
+    version: u64,
+    bound: Bound<&Q>,
+  ) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.last_versioned(bound, version)
+  }
+
+  fn lower_bound<Q>(&self, version: u64, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.first_visible(bound, version)
+  }
+
+  fn lower_bound_versioned<Q>(
+    &self,
+    version: u64,
+    bound: Bound<&Q>,
+  ) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.first_versioned(bound, version)
+  }
+
+  fn first(&self, version: u64) -> Option<Self::Item<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    self.first_visible(Bound::<&KeyPointer<K, C>>::Unbounded, version)
+  }
+
+  fn first_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    MultipleVersionTable::first_versioned(self, Bound::<&KeyPointer<K, C>>::Unbounded, version)
+  }
+
+  fn last(&self, version: u64) -> Option<Self::Item<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    self.last_visible(Bound::<&KeyPointer<K, C>>::Unbounded, version)
+  }
+
+  fn last_versioned(&self, version: u64) -> Option<Self::VersionedItem<'_>>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord,
+  {
+    self.tail_versioned(Bound::<&KeyPointer<K, C>>::Unbounded, version)
+  }
+
+  fn get<Q>(&self, version: u64, key: &Q) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.find(key, |this, versions| {
+      Entry::visible(this, versions, version)
+    })
+  }
+
+  fn get_versioned<Q>(&self, version: u64, key: &Q) -> Option<Self::VersionedItem<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.find(key, |this, versions| {
+      newest(versions, version).map(|r| VersionedEntry::new(this, r, version))
+    })
+  }
+
+  fn contains<Q>(&self, version: u64, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    memtable::MultipleVersionMemtable::get(self, version, key).is_some()
+  }
+
+  fn contains_versioned<Q>(&self, version: u64, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.get_versioned(version, key).is_some()
+  }
+
+  fn iter(&self, version: u64) -> Self::Iterator<'_> {
+    Range::new(self, version, ..)
+  }
+
+  fn iter_all_versions(&self, version: u64) -> Self::IterAll<'_> {
+    RangeAll::new(self, version, ..)
+  }
+
+  fn range<'a, Q, R>(&'a self, version: u64, range: R) -> Self::Range<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    Range::new(self, version, range)
+  }
+
+  fn range_all_versions<'a, Q, R>(&'a self, version: u64, range: R) -> Self::RangeAll<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    RangeAll::new(self, version, range)
+  }
+}
@@ -0,0 +1,514 @@
+use core::{
+  cmp,
+  convert::Infallible,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds, RangeFull},
+};
+use std::vec::Vec;
+
+use dbutils::{
+  equivalent::Comparable,
+  types::{KeyRef, Type},
+};
+
+use crate::{
+  memtable,
+  sealed::WithoutVersion,
+  types::{KeyComparator, KeyOrder, Kind},
+  wal::{KeyPointer, ValuePointer},
+};
+
+use super::{
+  super::btree::{above_start, below_end},
+  lower_index, upper_index, SortedArray, Staged,
+};
+
+/// An insertion or a removal of a key, which is staged before it is merged into the [`Table`].
+struct Update<K: ?Sized, V: ?Sized, C> {
+  key: KeyPointer<K, C>,
+  value: Option<ValuePointer<V>>,
+}
+
+impl<K, V, C> Staged<(KeyPointer<K, C>, ValuePointer<V>)> for Update<K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn compare(&self, other: &Self) -> cmp::Ordering {
+    self.key.cmp(&other.key)
+  }
+
+  fn merge(
+    entries: Vec<(KeyPointer<K, C>, ValuePointer<V>)>,
+    updates: Vec<Self>,
+  ) -> Vec<(KeyPointer<K, C>, ValuePointer<V>)> {
+    let mut merged = Vec::with_capacity(entries.len() + updates.len());
+    let mut entries = entries.into_iter().peekable();
+    for update in updates {
+      while let Some(ent) = entries.next_if(|(k, _)| k.le(&update.key)) {
+        if ent.0.lt(&update.key) {
+          merged.push(ent);
+        }
+      }
+
+      if let Some(value) = update.value {
+        merged.push((update.key, value));
+      }
+    }
+    merged.extend(entries);
+    merged
+  }
+}
+
+/// An memory table implementation based on a sorted array, which is protected by a lightweight lock.
+///
+/// Each entry takes a key pointer and a value pointer, which are 56 bytes on 64-bit targets, without any
+/// per-node overhead, and the lookups are binary searches, so it suits the WALs opened read-only,
+/// e.g. by [`OrderWalReader`](crate::swmr::base::OrderWalReader), whose memtables are built once when replaying
+/// and never written after. The entries are not two bare pointers, the pointers carry the lengths, the flags,
+/// the expiration time and the merge operands, the saving is the per-node overhead of the skiplists and the trees.
+///
+/// The table can still be written, the updates are sorted into a second array which is about the square root
+/// of the table in size, and the lookups search both of them, so interleaving the writes and the reads costs
+/// `O(√n)` per write amortized for `n` entries.
+pub struct Table<K: ?Sized, V: ?Sized, C = KeyOrder>(
+  SortedArray<(KeyPointer<K, C>, ValuePointer<V>), Update<K, V, C>>,
+);
+
+impl<K, V, C> core::fmt::Debug for Table<K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Table").field("len", &self.count()).finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> Default for Table<K, V, C> {
+  #[inline]
+  fn default() -> Self {
+    Self(SortedArray::new())
+  }
+}
+
+impl<K, V, C> Table<K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  /// Returns the number of the entries, counting the updates which are not merged yet.
+  fn count(&self) -> usize {
+    let state = self.0.read();
+    let entries = state.entries();
+    state.updates().iter().fold(entries.len(), |len, update| {
+      let exists = entries
+        .binary_search_by(|(k, _)| k.cmp(&update.key))
+        .is_ok();
+      match (exists, update.value.is_some()) {
+        (false, true) => len + 1,
+        (true, false) => len - 1,
+        _ => len,
+      }
+    })
+  }
+
+  /// Returns the first entry whose key is within the lower bound.
+  fn first_in<Q>(&self, lower: Bound<&Q>) -> Option<Entry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    let state = self.0.read();
+    let (entries, updates) = (state.entries(), state.updates());
+    let mut idx = lower_index(entries, lower, |(k, _)| k);
+    let mut update_idx = lower_index(updates, lower, |update| &update.key);
+    loop {
+      let update = match (entries.get(idx), updates.get(update_idx)) {
+        (None, None) => return None,
+        (Some((k, v)), None) => return Some(Entry::new(self, *k, *v)),
+        (Some((k, v)), Some(update)) if k.lt(&update.key) => return Some(Entry::new(self, *k, *v)),
+        (ent, Some(update)) => {
+          // The update replaces the entry of the same key.
+          if ent.is_some_and(|(k, _)| k.eq(&update.key)) {
+            idx += 1;
+          }
+          update
+        }
+      };
+
+      match update.value {
+        Some(value) => return Some(Entry::new(self, update.key, value)),
+        None => update_idx += 1,
+      }
+    }
+  }
+
+  /// Returns the last entry whose key is within the upper bound.
+  fn last_in<Q>(&self, upper: Bound<&Q>) -> Option<Entry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    let state = self.0.read();
+    let (entries, updates) = (state.entries(), state.updates());
+    let mut idx = upper_index(entries, upper, |(k, _)| k);
+    let mut update_idx = upper_index(updates, upper, |update| &update.key);
+    loop {
+      let ent = idx.checked_sub(1).map(|idx| &entries[idx]);
+      let update = match (ent, update_idx.checked_sub(1).map(|idx| &updates[idx])) {
+        (None, None) => return None,
+        (Some((k, v)), None) => return Some(Entry::new(self, *k, *v)),
+        (Some((k, v)), Some(update)) if k.gt(&update.key) => return Some(Entry::new(self, *k, *v)),
+        (ent, Some(update)) => {
+          // The update replaces the entry of the same key.
+          if ent.is_some_and(|(k, _)| k.eq(&update.key)) {
+            idx -= 1;
+          }
+          update
+        }
+      };
+
+      match update.value {
+        Some(value) => return Some(Entry::new(self, update.key, value)),
+        None => update_idx -= 1,
+      }
+    }
+  }
+
+  /// Returns the entry whose key is equal to the given key.
+  fn find<Q>(&self, key: &Q) -> Option<Entry<'_, K, V, C>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  {
+    let state = self.0.read();
+    let updates = state.updates();
+    if let Ok(idx) = updates.binary_search_by(|update| key.compare(&update.key).reverse()) {
+      let update = &updates[idx];
+      return update
+        .value
+        .map(|value| Entry::new(self, update.key, value));
+    }
+
+    let entries = state.entries();
+    entries
+      .binary_search_by(|(k, _)| key.compare(k).reverse())
+      .ok()
+      .map(|idx| Entry::new(self, entries[idx].0, entries[idx].1))
+  }
+}
+
+/// An entry in the [`Table`].
+pub struct Entry<'a, K: ?Sized, V: ?Sized, C = KeyOrder> {
+  table: &'a Table<K, V, C>,
+  key: KeyPointer<K, C>,
+  value: ValuePointer<V>,
+}
+
+impl<K: ?Sized, V: ?Sized, C> Clone for Entry<'_, K, V, C> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      table: self.table,
+      key: self.key,
+      value: self.value,
+    }
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> core::fmt::Debug for Entry<'_, K, V, C> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Entry")
+      .field("key", &self.key)
+      .field("value", &self.value)
+      .finish()
+  }
+}
+
+impl<'a, K: ?Sized, V: ?Sized, C> Entry<'a, K, V, C> {
+  #[inline]
+  const fn new(table: &'a Table<K, V, C>, key: KeyPointer<K, C>, value: ValuePointer<V>) -> Self {
+    Self { table, key, value }
+  }
+}
+
+impl<'a, K, V, C> memtable::BaseEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self> {
+    self.table.first_in(Bound::Excluded(&self.key))
+  }
+
+  #[inline]
+  fn prev(&mut self) -> Option<Self> {
+    self.table.last_in(Bound::Excluded(&self.key))
+  }
+
+  #[inline]
+  fn key(&self) -> KeyPointer<K, C> {
+    self.key
+  }
+}
+
+impl<'a, K, V, C> memtable::MemtableEntry<'a> for Entry<'a, K, V, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn value(&self) -> ValuePointer<V> {
+    self.value
+  }
+}
+
+impl<K: ?Sized, V: ?Sized, C> WithoutVersion for Entry<'_, K, V, C> {}
+
+/// An iterator over the entries of the [`Table`].
+pub type Iter<'a, K, V, C = KeyOrder> = Range<'a, K, V, KeyPointer<K, C>, RangeFull, C>;
+
+/// An iterator over a subset of the entries of the [`Table`].
+///
+/// The iterator does not hold the lock, every step looks up the table again.
+pub struct Range<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C = KeyOrder> {
+  table: &'a Table<K, V, C>,
+  range: R,
+  head: Option<KeyPointer<K, C>>,
+  tail: Option<KeyPointer<K, C>>,
+  _q: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K: ?Sized, V: ?Sized, Q: ?Sized, R, C> Range<'a, K, V, Q, R, C> {
+  #[inline]
+  const fn new(table: &'a Table<K, V, C>, range: R) -> Self {
+    Self {
+      table,
+      range,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+}
+
+impl<'a, K, V, Q, R, C> Iterator for Range<'a, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  type Item = Entry<'a, K, V, C>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let ent = match &self.head {
+      Some(head) => self.table.first_in(Bound::Excluded(head)),
+      None => self.table.first_in(self.range.start_bound()),
+    }?;
+
+    let in_range = below_end(self.range.end_bound(), &ent.key)
+      && self.tail.as_ref().map_or(true, |tail| ent.key.lt(tail));
+    if !in_range {
+      return None;
+    }
+
+    self.head = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, Q, R, C> DoubleEndedIterator for Range<'_, K, V, Q, R, C>
+where
+  K: ?Sized + Type + Ord,
+  for<'b> K::Ref<'b>: KeyRef<'b, K>,
+  V: ?Sized,
+  Q: ?Sized + Comparable<KeyPointer<K, C>>,
+  R: RangeBounds<Q>,
+  C: KeyComparator<K>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    let ent = match &self.tail {
+      Some(tail) => self.table.last_in(Bound::Excluded(tail)),
+      None => self.table.last_in(self.range.end_bound()),
+    }?;
+
+    let in_range = above_start(self.range.start_bound(), &ent.key)
+      && self.head.as_ref().map_or(true, |head| ent.key.gt(head));
+    if !in_range {
+      return None;
+    }
+
+    self.tail = Some(ent.key);
+    Some(ent)
+  }
+}
+
+impl<K, V, C> memtable::BaseTable for Table<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
+{
+  type Key = K;
+  type Value = V;
+  type Comparator = C;
+  type Item<'a>
+    = Entry<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Iterator<'a>
+    = Iter<'a, Self::Key, Self::Value, Self::Comparator>
+  where
+    Self: 'a;
+
+  type Range<'a, Q, R>
+    = Range<'a, Self::Key, Self::Value, Q, R, Self::Comparator>
+  where
+    Self: 'a,
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>;
+
+  type Options = ();
+  type Error = Infallible;
+
+  fn new(_: Self::Options) -> Result<Self, Self::Error>
+  where
+    Self: Sized,
+  {
+    Ok(Self::default())
+  }
+
+  #[inline]
+  fn insert(
+    &self,
+    _: Option<u64>,
+    kp: KeyPointer<Self::Key, Self::Comparator>,
+    vp: ValuePointer<Self::Value>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.0.stage(Update {
+      key: kp,
+      value: Some(vp),
+    });
+    Ok(())
+  }
+
+  #[inline]
+  fn remove(
+    &self,
+    _: Option<u64>,
+    key: KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Result<(), Self::Error>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.0.stage(Update { key, value: None });
+    Ok(())
+  }
+
+  fn latest(
+    &self,
+    _: Option<u64>,
+    key: &KeyPointer<Self::Key, Self::Comparator>,
+  ) -> Option<(u64, ValuePointer<Self::Value>)>
+  where
+    KeyPointer<Self::Key, Self::Comparator>: Ord + 'static,
+  {
+    self.find(key).map(|ent| (0, ent.value))
+  }
+
+  #[inline]
+  fn kind() -> Kind {
+    Kind::Plain
+  }
+}
+
+impl<K, V, C> memtable::Memtable for Table<K, V, C>
+where
+  K: ?Sized + Type + Ord + 'static,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+  V: ?Sized + Type + 'static,
+  C: KeyComparator<K>,
+{
+  #[inline]
+  fn len(&self) -> usize {
+    self.count()
+  }
+
+  #[inline]
+  fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.last_in(bound)
+  }
+
+  #[inline]
+  fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.first_in(bound)
+  }
+
+  #[inline]
+  fn first(&self) -> Option<Self::Item<'_>> {
+    self.first_in(Bound::<&KeyPointer<K, C>>::Unbounded)
+  }
+
+  #[inline]
+  fn last(&self) -> Option<Self::Item<'_>> {
+    self.last_in(Bound::<&KeyPointer<K, C>>::Unbounded)
+  }
+
+  #[inline]
+  fn get<Q>(&self, key: &Q) -> Option<Self::Item<'_>>
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.find(key)
+  }
+
+  #[inline]
+  fn contains<Q>(&self, key: &Q) -> bool
+  where
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    self.find(key).is_some()
+  }
+
+  #[inline]
+  fn iter(&self) -> Self::Iterator<'_> {
+    Range::new(self, ..)
+  }
+
+  #[inline]
+  fn range<'a, Q, R>(&'a self, range: R) -> Self::Range<'a, Q, R>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<KeyPointer<Self::Key, Self::Comparator>>,
+  {
+    Range::new(self, range)
+  }
+}
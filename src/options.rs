@@ -1,7 +1,10 @@
 use rarena_allocator::{Freelist, Options as ArenaOptions};
 pub use skl::KeySize;
 
-use super::{CURRENT_VERSION, HEADER_SIZE};
+use super::{
+  types::MergeOperator, wal::Retention, CURRENT_VERSION, HEADER_SIZE, LEGACY_HEADER_SIZE,
+  LEGACY_VERSION,
+};
 
 #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
 #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
@@ -19,6 +22,10 @@ pub struct Options {
   magic_version: u16,
   cap: Option<u32>,
   reserved: u32,
+  maximum_versions_per_key: Option<u32>,
+  version_window: Option<u64>,
+  merge_operator: Option<&'static dyn MergeOperator>,
+  bloom_filter_bits_per_key: Option<u32>,
 
   #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
   pub(crate) lock_meta: bool,
@@ -41,6 +48,12 @@ pub struct Options {
   pub(crate) populate: bool,
   #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
   pub(crate) huge: Option<u8>,
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  pub(crate) persisted_index: bool,
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  pub(crate) replay_threads: usize,
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  pub(crate) lazy_replay: bool,
 }
 
 impl Default for Options {
@@ -72,6 +85,10 @@ impl Options {
       magic_version: 0,
       cap: None,
       reserved: 0,
+      maximum_versions_per_key: None,
+      version_window: None,
+      merge_operator: None,
+      bloom_filter_bits_per_key: None,
       #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
       lock_meta: false,
       #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
@@ -92,6 +109,12 @@ impl Options {
       populate: false,
       #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
       huge: None,
+      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+      persisted_index: false,
+      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+      replay_threads: 1,
+      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+      lazy_replay: false,
     }
   }
 
@@ -224,6 +247,66 @@ impl Options {
     self.sync
   }
 
+  /// Returns the maximum number of versions per key which are retained by the multiple version WALs.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::Options;
+  ///
+  /// let options = Options::new().with_maximum_versions_per_key(Some(10));
+  /// assert_eq!(options.maximum_versions_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn maximum_versions_per_key(&self) -> Option<u32> {
+    self.maximum_versions_per_key
+  }
+
+  /// Returns the window of versions, counted from the latest version of the WAL,
+  /// which is retained by the multiple version WALs.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::Options;
+  ///
+  /// let options = Options::new().with_version_window(Some(100));
+  /// assert_eq!(options.version_window(), Some(100));
+  /// ```
+  #[inline]
+  pub const fn version_window(&self) -> Option<u64> {
+    self.version_window
+  }
+
+  /// Returns the merge operator which folds the merge operands of the WAL.
+  ///
+  /// The default value is `None`.
+  #[inline]
+  pub const fn merge_operator(&self) -> Option<&'static dyn MergeOperator> {
+    self.merge_operator
+  }
+
+  /// Returns the bits per key of the Bloom filter over the keys of the WAL.
+  ///
+  /// The default value is `None`, which means the filter is disabled.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::Options;
+  ///
+  /// let options = Options::new().with_bloom_filter_bits_per_key(Some(10));
+  /// assert_eq!(options.bloom_filter_bits_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn bloom_filter_bits_per_key(&self) -> Option<u32> {
+    self.bloom_filter_bits_per_key
+  }
+
   /// Sets the capacity of the WAL.
   ///
   /// This configuration will be ignored when using file-backed memory maps.
@@ -311,13 +394,156 @@ impl Options {
     self.magic_version = version;
     self
   }
+
+  /// Sets the maximum number of versions per key which are retained by the multiple version WALs.
+  ///
+  /// When a key has more versions than the limit, the older ones are pruned. The versions are counted from
+  /// the latest version of the WAL, so a pruned version is hidden from all the reads, including the point lookups
+  /// and the reads at an older query version. The latest version of a key is always retained,
+  /// so `Some(0)` behaves the same as `Some(1)`.
+  ///
+  /// The limit is not enforced on insert, the versions are only pruned when they are read, relative to the latest
+  /// version of the WAL. The WAL and the memtable are append-only, so nothing is physically removed, the pruned
+  /// versions still take space in both of them until the WAL is rewritten (e.g. by `export_snapshot`),
+  /// this option does not bound the memory usage.
+  ///
+  /// This configuration has no effect on the WALs which do not support multiple versions.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::Options;
+  ///
+  /// let options = Options::new().with_maximum_versions_per_key(Some(10));
+  /// assert_eq!(options.maximum_versions_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn with_maximum_versions_per_key(mut self, max: Option<u32>) -> Self {
+    self.maximum_versions_per_key = max;
+    self
+  }
+
+  /// Sets the window of versions which are retained by the multiple version WALs.
+  ///
+  /// The window is measured in versions, not in time, there is no policy based on the age of the entries.
+  /// When the latest version of the WAL is `v`, the versions older than `v - window` are pruned, except the latest
+  /// version of each key, which is always retained. Only if the versions are [`HlcTimestamp`](crate::types::HlcTimestamp)s,
+  /// the window can stand for an age, a window of `ms` milliseconds is `HlcTimestamp::new(ms, 0).version()`.
+  ///
+  /// Same as [`with_maximum_versions_per_key`](Options::with_maximum_versions_per_key), the window is only applied
+  /// when the versions are read, the pruned versions are hidden from all the reads, but nothing is physically removed,
+  /// they still take space in the WAL and the memtable.
+  ///
+  /// This configuration has no effect on the WALs which do not support multiple versions.
+  ///
+  /// The default value is `None`, which means all versions are retained.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::Options;
+  ///
+  /// let options = Options::new().with_version_window(Some(100));
+  /// assert_eq!(options.version_window(), Some(100));
+  /// ```
+  #[inline]
+  pub const fn with_version_window(mut self, window: Option<u64>) -> Self {
+    self.version_window = window;
+    self
+  }
+
+  /// Sets the merge operator which folds the merge operands written by `merge` over the existing values.
+  ///
+  /// The merge operands are stored in the WAL, so the same merge operator must be set when reopening the WAL,
+  /// otherwise, replaying the merge operands returns an error.
+  ///
+  /// The default value is `None`.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{types::MergeOperator, Options};
+  ///
+  /// struct Append;
+  ///
+  /// impl MergeOperator for Append {
+  ///   fn name(&self) -> &str {
+  ///     "append"
+  ///   }
+  ///
+  ///   fn full_merge(&self, _: &[u8], existing: Option<&[u8]>, operands: &[&[u8]]) -> Vec<u8> {
+  ///     let mut value = existing.unwrap_or_default().to_vec();
+  ///     operands.iter().for_each(|op| value.extend_from_slice(op));
+  ///     value
+  ///   }
+  /// }
+  ///
+  /// let options = Options::new().with_merge_operator(Some(&Append));
+  /// assert_eq!(options.merge_operator().unwrap().name(), "append");
+  /// ```
+  #[inline]
+  pub const fn with_merge_operator(mut self, operator: Option<&'static dyn MergeOperator>) -> Self {
+    self.merge_operator = operator;
+    self
+  }
+
+  /// Sets the bits per key of the Bloom filter over the keys of the WAL, `None` or `0` disables the filter.
+  ///
+  /// The filter is built when the WAL is opened and updated by every write, then the point lookups by
+  /// the encoded keys, e.g. `get_by_bytes` and `contains_key_by_bytes`, consult it first, and return
+  /// immediately for the keys which are never written. The typed point lookups, e.g. `get` and `contains_key`,
+  /// encode the query first, so they consult the filter as well.
+  ///
+  /// The filter hashes the encoded keys, so it is disabled if the memtable has a custom comparator,
+  /// which may treat different encoded keys as equal.
+  ///
+  /// About `10` bits per key gives a false positive rate of `1%`.
+  ///
+  /// The default value is `None`.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::Options;
+  ///
+  /// let options = Options::new().with_bloom_filter_bits_per_key(Some(10));
+  /// assert_eq!(options.bloom_filter_bits_per_key(), Some(10));
+  /// ```
+  #[inline]
+  pub const fn with_bloom_filter_bits_per_key(mut self, bits: Option<u32>) -> Self {
+    self.bloom_filter_bits_per_key = bits;
+    self
+  }
+
+  #[inline]
+  pub(crate) const fn retention(&self) -> Retention {
+    Retention::new(self.maximum_versions_per_key, self.version_window)
+  }
 }
 
 #[inline]
 pub(crate) const fn arena_options(reserved: u32) -> ArenaOptions {
+  versioned_arena_options(CURRENT_VERSION, reserved)
+}
+
+/// Returns the arena options of a WAL in the given format version.
+#[inline]
+pub(crate) const fn versioned_arena_options(version: u16, reserved: u32) -> ArenaOptions {
   ArenaOptions::new()
-    .with_magic_version(CURRENT_VERSION)
+    .with_magic_version(version)
     .with_freelist(Freelist::None)
-    .with_reserved((HEADER_SIZE + reserved as usize) as u32)
+    .with_reserved((header_size(version) + reserved as usize) as u32)
     .with_unify(true)
 }
+
+/// Returns the size of the WAL header in the given format version.
+#[inline]
+pub(crate) const fn header_size(version: u16) -> usize {
+  if version == LEGACY_VERSION {
+    LEGACY_HEADER_SIZE
+  } else {
+    HEADER_SIZE
+  }
+}
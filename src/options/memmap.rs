@@ -268,6 +268,99 @@ impl Options {
     self.populate = populate;
     self
   }
+
+  /// Sets if the memtable index of the WAL is persisted to a sidecar file next to the WAL file.
+  ///
+  /// The index is the offsets of the entries in the WAL, it is written to `<path of the WAL>.index`
+  /// when the writable WAL is dropped, or on [`persist_index`](crate::base::Writer::persist_index).
+  /// When opening the WAL, if the index matches the tail of the indexed records, the indexed entries
+  /// are inserted into the memtable without decoding and checksumming the records again, and
+  /// only the records after the index are replayed. Otherwise, the index is ignored and the
+  /// whole WAL is replayed.
+  ///
+  /// Default is `false`.
+  ///
+  /// This option has no effect on vec backed `Wal`.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::Options;
+  ///
+  /// let opts = Options::new().with_persisted_index(true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn with_persisted_index(mut self, persisted_index: bool) -> Self {
+    self.persisted_index = persisted_index;
+    self
+  }
+
+  /// Sets the number of the threads used to replay the WAL when opening it.
+  ///
+  /// If it is greater than `1`, the boundaries of the records are scanned first, then the checksums
+  /// of the records are verified by the worker threads, and the memtable writes are partitioned
+  /// by the hash of the encoded key, one partition per worker thread, so the writes to the same key,
+  /// including the merge operands, are applied in the order they are written. Hence, the keys which are equal
+  /// must have the same encoded bytes, and the memtable should support concurrent writes to benefit from it.
+  ///
+  /// Default is `1`, which means the WAL is replayed on the current thread.
+  ///
+  /// This option has no effect on vec backed `Wal`, or if the memtable has a custom comparator, because
+  /// the writes are distributed by the hash of the encoded key, and the comparator may treat different
+  /// encoded keys as equal.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::Options;
+  ///
+  /// let opts = Options::new().with_replay_threads(4);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn with_replay_threads(mut self, threads: usize) -> Self {
+    self.replay_threads = threads;
+    self
+  }
+
+  /// Sets whether the memtable is replayed in the background when opening the WAL.
+  ///
+  /// If it is `true`, only the header is validated before the WAL is returned. Then a background thread
+  /// scans the boundaries of the records, truncates the tail which is not committed, verifies the checksums
+  /// and replays the memtable.
+  ///
+  /// Until the replay completes:
+  /// - the reads do not block, they only see the entries replayed so far, use `is_ready` to tell if the
+  ///   memtable is complete;
+  /// - the plain writes only wait for the scan of the boundaries, then they are appended to the WAL, and
+  ///   they are visible after the replay completes;
+  /// - the conditional writes, the merges, the range deletions, the transactions and the methods returning
+  ///   the written entries block until the replay completes.
+  ///
+  /// Use `wait_ready` to wait for the replay, or to get its error. If the scan or the replay fails, the
+  /// writes return a corrupted error.
+  ///
+  /// Default is `false`.
+  ///
+  /// This option has no effect on vec backed `Wal`.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::Options;
+  ///
+  /// let opts = Options::new().with_lazy_replay(true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn with_lazy_replay(mut self, lazy_replay: bool) -> Self {
+    self.lazy_replay = lazy_replay;
+    self
+  }
 }
 
 impl Options {
@@ -445,6 +538,57 @@ impl Options {
   pub const fn populate(&self) -> bool {
     self.populate
   }
+
+  /// Returns if the memtable index of the WAL is persisted to a sidecar file next to the WAL file.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::Options;
+  ///
+  /// let opts = Options::new().with_persisted_index(true);
+  /// assert_eq!(opts.persisted_index(), true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn persisted_index(&self) -> bool {
+    self.persisted_index
+  }
+
+  /// Returns the number of the threads used to replay the WAL when opening it.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::Options;
+  ///
+  /// let opts = Options::new().with_replay_threads(4);
+  /// assert_eq!(opts.replay_threads(), 4);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn replay_threads(&self) -> usize {
+    self.replay_threads
+  }
+
+  /// Returns whether the memtable is replayed in the background when opening the WAL.
+  ///
+  /// ## Example
+  ///
+  /// ```
+  /// use orderwal::Options;
+  ///
+  /// let opts = Options::new().with_lazy_replay(true);
+  /// assert_eq!(opts.lazy_replay(), true);
+  /// ```
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  pub const fn lazy_replay(&self) -> bool {
+    self.lazy_replay
+  }
 }
 
 pub(crate) trait ArenaOptionsExt {
@@ -473,3 +617,39 @@ impl ArenaOptionsExt for ArenaOptions {
     }
   }
 }
+
+/// The magic text the arena writes right after the reserved bytes.
+const ARENA_MAGIC_TEXT: [u8; 2] = *b"al";
+/// The offset of the arena magic text after the reserved bytes.
+const ARENA_MAGIC_TEXT_OFFSET: usize = 2;
+
+/// Detects the format version of an existing WAL file.
+///
+/// The arena records the format version as its magic version right after the reserved bytes,
+/// and the reserved bytes start with the WAL header, so the version is the one whose header
+/// size puts the arena magic text and a matching magic version in place.
+///
+/// Returns the current version if the file is not in any known layout, so that opening it
+/// reports the error of the current version.
+pub(crate) fn detect_version(path: &std::path::Path, reserved: u32) -> std::io::Result<u16> {
+  use std::io::Read;
+
+  let max = header_size(CURRENT_VERSION) + reserved as usize + ARENA_MAGIC_TEXT_OFFSET + 4;
+  let mut buf = std::vec::Vec::with_capacity(max);
+  std::fs::File::open(path)?
+    .take(max as u64)
+    .read_to_end(&mut buf)?;
+
+  let matches = |version: u16| {
+    let offset = header_size(version) + reserved as usize + ARENA_MAGIC_TEXT_OFFSET;
+    buf.len() >= offset + 4
+      && buf[offset..offset + 2] == ARENA_MAGIC_TEXT
+      && buf[offset + 2..offset + 4] == version.to_le_bytes()
+  };
+
+  Ok(if matches(CURRENT_VERSION) || !matches(LEGACY_VERSION) {
+    CURRENT_VERSION
+  } else {
+    LEGACY_VERSION
+  })
+}
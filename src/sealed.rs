@@ -1,8 +1,11 @@
 use core::{
+  cmp,
   ops::{Bound, RangeBounds},
   ptr::NonNull,
 };
 
+use std::vec::Vec;
+
 use among::Among;
 use dbutils::{
   buffer::VacantBuffer,
@@ -10,26 +13,35 @@ use dbutils::{
   leb128::encoded_u64_varint_len,
   types::{KeyRef, Type},
 };
-use rarena_allocator::{either::Either, Allocator, ArenaPosition, Buffer};
+use rarena_allocator::{either::Either, Allocator, ArenaPosition, Buffer, BytesRefMut};
 use skl::KeySize;
 
 use crate::{
-  memtable::{MemtableEntry, VersionedMemtableEntry},
+  memtable::{BaseEntry, MemtableEntry, VersionedMemtableEntry},
   utils::merge_lengths,
-  wal::{KeyPointer, ValuePointer},
+  wal::{
+    BloomFilter, KeyPointer, MergeOperands, Pruning, RangeDeletions, Retention, Transaction,
+    ValuePointer,
+  },
 };
 
 use super::{
-  batch::Batch,
+  batch::{Batch, BatchGuard, Condition, PreparedBatch},
   checksum::{BuildChecksumer, Checksumer},
   error::Error,
   memtable::{BaseTable, Memtable, MultipleVersionMemtable},
-  options::Options,
-  types::{BufWriter, EncodedEntryMeta, EntryFlags},
-  Flags, CHECKSUM_SIZE, HEADER_SIZE, MAGIC_TEXT, MAGIC_TEXT_SIZE, RECORD_FLAG_SIZE, VERSION_SIZE,
-  WAL_KIND_SIZE,
+  options::{header_size, Options},
+  types::{BufWriter, EncodedEntryMeta, EntryFlags, KeyComparator, Kind, MergeOperator},
+  Flags, CHECKSUM_SIZE, HEADER_SIZE, MAGIC_TEXT, MAGIC_TEXT_SIZE, MAGIC_VERSION_SIZE,
+  RECORD_FLAG_SIZE, TRANSACTION_ID_SIZE, VERSION_SIZE, WAL_KIND_SIZE,
 };
 
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+use crate::wal::Readiness;
+
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+mod replay;
+
 /// A marker trait which indicates that such pointer has a version.
 pub trait WithVersion {}
 
@@ -45,13 +57,22 @@ pub trait WalReader<S> {
 
   fn memtable(&self) -> &Self::Memtable;
 
+  fn range_deletions(&self) -> &RangeDeletions;
+
   /// Returns the number of entries in the WAL.
   fn len(&self) -> usize
   where
     Self::Memtable: Memtable,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
-    self.memtable().len()
+    let coverage = WalReader::range_deletions(self).coverage();
+    if coverage.is_empty() {
+      return self.memtable().len();
+    }
+
+    Memtable::iter(self.memtable())
+      .filter(|ent| !coverage.covers_entry(ent))
+      .count()
   }
 
   /// Returns `true` if the WAL is empty.
@@ -61,7 +82,12 @@ pub trait WalReader<S> {
     Self::Memtable: Memtable,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
-    self.memtable().is_empty()
+    let coverage = WalReader::range_deletions(self).coverage();
+    if coverage.is_empty() {
+      return self.memtable().is_empty();
+    }
+
+    Memtable::iter(self.memtable()).all(|ent| coverage.covers_entry(&ent))
   }
 
   #[inline]
@@ -77,7 +103,10 @@ pub trait WalReader<S> {
   fn range<'a, Q, R>(&'a self, range: R) -> <Self::Memtable as BaseTable>::Range<'a, Q, R>
   where
     R: RangeBounds<Q>,
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: Memtable,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
   {
@@ -95,6 +124,7 @@ pub trait WalReader<S> {
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
     Memtable::first(self.memtable())
+      .and_then(|ent| skip_deleted(WalReader::range_deletions(self), ent, BaseEntry::next))
   }
 
   /// Returns the last key-value pair in the map. The key in this pair is the maximum key in the wal.
@@ -108,39 +138,96 @@ pub trait WalReader<S> {
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
     Memtable::last(self.memtable())
+      .and_then(|ent| skip_deleted(WalReader::range_deletions(self), ent, BaseEntry::prev))
   }
 
   /// Returns `true` if the WAL contains the specified key.
   fn contains_key<Q>(&self, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: Memtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
-    Memtable::contains(self.memtable(), key)
+    if WalReader::range_deletions(self).coverage().is_empty() {
+      return Memtable::contains(self.memtable(), key);
+    }
+
+    WalReader::get(self, key).is_some()
   }
 
   /// Returns the value associated with the key.
   #[inline]
   fn get<Q>(&self, key: &Q) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
+    Self::Memtable: Memtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
+  {
+    Memtable::get(self.memtable(), key).filter(|ent| {
+      !WalReader::range_deletions(self)
+        .coverage()
+        .covers_entry(ent)
+    })
+  }
+
+  /// Returns `true` if the WAL contains the encoded key.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  unsafe fn contains_key_by_bytes(&self, key: &[u8]) -> bool
+  where
+    Self::Memtable: Memtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
+  {
+    if WalReader::range_deletions(self).coverage().is_empty() {
+      return self.memtable().contains_by_bytes(key);
+    }
+
+    WalReader::get_by_bytes(self, key).is_some()
+  }
+
+  /// Returns the value associated with the encoded key.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn get_by_bytes(&self, key: &[u8]) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
+  where
     Self::Memtable: Memtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
-    Memtable::get(self.memtable(), key)
+    self.memtable().get_by_bytes(key).filter(|ent| {
+      !WalReader::range_deletions(self)
+        .coverage()
+        .covers_entry(ent)
+    })
   }
 
   #[inline]
   fn upper_bound<Q>(&self, bound: Bound<&Q>) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: Memtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
@@ -148,12 +235,16 @@ pub trait WalReader<S> {
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
     Memtable::upper_bound(self.memtable(), bound)
+      .and_then(|ent| skip_deleted(WalReader::range_deletions(self), ent, BaseEntry::prev))
   }
 
   #[inline]
   fn lower_bound<Q>(&self, bound: Bound<&Q>) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: Memtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
@@ -161,6 +252,7 @@ pub trait WalReader<S> {
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
     Memtable::lower_bound(self.memtable(), bound)
+      .and_then(|ent| skip_deleted(WalReader::range_deletions(self), ent, BaseEntry::next))
   }
 }
 
@@ -170,6 +262,25 @@ pub trait MultipleVersionWalReader<S> {
 
   fn memtable(&self) -> &Self::Memtable;
 
+  fn range_deletions(&self) -> &RangeDeletions;
+
+  fn retention(&self) -> Retention;
+
+  /// Returns the retention policy anchored at the current latest version of the WAL.
+  #[inline]
+  fn pruning(&self) -> Pruning<'_, Self::Memtable>
+  where
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+    for<'a> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>:
+      VersionedMemtableEntry<'a>,
+  {
+    Pruning::new(self.memtable(), self.retention())
+  }
+
   #[inline]
   fn iter(&self, version: u64) -> <Self::Memtable as BaseTable>::Iterator<'_>
   where
@@ -186,7 +297,10 @@ pub trait MultipleVersionWalReader<S> {
   fn range<Q, R>(&self, version: u64, range: R) -> <Self::Memtable as BaseTable>::Range<'_, Q, R>
   where
     R: RangeBounds<Q>,
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
@@ -219,7 +333,10 @@ pub trait MultipleVersionWalReader<S> {
   ) -> <Self::Memtable as MultipleVersionMemtable>::RangeAll<'_, Q, R>
   where
     R: RangeBounds<Q>,
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
@@ -239,7 +356,10 @@ pub trait MultipleVersionWalReader<S> {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().first(version)
+    let ent = self.memtable().first(version)?;
+    self.skip_deleted(version, ent, |key| {
+      self.memtable().lower_bound(version, Bound::Excluded(key))
+    })
   }
 
   /// Returns the first key-value pair in the map. The key in this pair is the minimum key in the wal.
@@ -258,7 +378,12 @@ pub trait MultipleVersionWalReader<S> {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().first_versioned(version)
+    let ent = self.memtable().first_versioned(version)?;
+    self.skip_deleted_versioned(version, ent, |key| {
+      self
+        .memtable()
+        .lower_bound_versioned(version, Bound::Excluded(key))
+    })
   }
 
   /// Returns the last key-value pair in the map. The key in this pair is the maximum key in the wal.
@@ -270,7 +395,10 @@ pub trait MultipleVersionWalReader<S> {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().last(version)
+    let ent = self.memtable().last(version)?;
+    self.skip_deleted(version, ent, |key| {
+      self.memtable().upper_bound(version, Bound::Excluded(key))
+    })
   }
 
   /// Returns the last key-value pair in the map. The key in this pair is the maximum key in the wal.
@@ -288,70 +416,143 @@ pub trait MultipleVersionWalReader<S> {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().last_versioned(version)
+    let ent = self.memtable().last_versioned(version)?;
+    self.skip_deleted_versioned(version, ent, |key| {
+      self
+        .memtable()
+        .upper_bound_versioned(version, Bound::Excluded(key))
+    })
   }
 
-  /// Returns `true` if the WAL contains the specified key.
-  fn contains_key<Q>(&self, version: u64, key: &Q) -> bool
+  /// Returns the entry associated with the key. The returned entry is the latest version of the key.
+  #[inline]
+  fn get<Q>(&self, version: u64, key: &Q) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+    for<'a> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>:
+      VersionedMemtableEntry<'a>,
   {
-    self.memtable().contains(version, key)
+    self.memtable().get(version, key).filter(|ent| {
+      !MultipleVersionWalReader::range_deletions(self)
+        .coverage()
+        .covers_versioned_entry(version, ent)
+        && !self.pruning().prunes(ent)
+    })
   }
 
-  /// Returns `true` if the WAL contains the specified key.
+  /// Returns `true` if the WAL contains the encoded key.
   ///
-  /// Compared to [`contains_key`](MultipleVersionWalReader::contains_key), this method returns a versioned item, which means that the returned item
-  /// may already be marked as removed.
-  fn contains_key_versioned<Q>(&self, version: u64, key: &Q) -> bool
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  unsafe fn contains_key_by_bytes(&self, version: u64, key: &[u8]) -> bool
+  where
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+    for<'a> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>:
+      VersionedMemtableEntry<'a>,
+  {
+    if MultipleVersionWalReader::range_deletions(self)
+      .coverage()
+      .is_empty()
+      && self.pruning().is_empty()
+    {
+      return self.memtable().contains_by_bytes(version, key);
+    }
+
+    MultipleVersionWalReader::get_by_bytes(self, version, key).is_some()
+  }
+
+  /// Returns `true` if the WAL contains the encoded key, even if the latest is marked as removed.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  unsafe fn contains_key_versioned_by_bytes(&self, version: u64, key: &[u8]) -> bool
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+    for<'a> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>:
+      VersionedMemtableEntry<'a>,
   {
-    self.memtable().contains_versioned(version, key)
+    if MultipleVersionWalReader::range_deletions(self)
+      .coverage()
+      .is_empty()
+      && self.pruning().is_empty()
+    {
+      return self.memtable().contains_versioned_by_bytes(version, key);
+    }
+
+    self.get_versioned_by_bytes(version, key).is_some()
   }
 
-  /// Returns the entry associated with the key. The returned entry is the latest version of the key.
+  /// Returns the entry associated with the encoded key.
+  ///
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
   #[inline]
-  fn get<Q>(&self, version: u64, key: &Q) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
+  unsafe fn get_by_bytes(
+    &self,
+    version: u64,
+    key: &[u8],
+  ) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+    for<'a> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>:
+      VersionedMemtableEntry<'a>,
   {
-    self.memtable().get(version, key)
+    self.memtable().get_by_bytes(version, key).filter(|ent| {
+      !MultipleVersionWalReader::range_deletions(self)
+        .coverage()
+        .covers_versioned_entry(version, ent)
+        && !self.pruning().prunes(ent)
+    })
   }
 
-  /// Returns the value associated with the key.
+  /// Returns the entry associated with the encoded key, which may already be marked as removed.
   ///
-  /// Compared to [`get`](MultipleVersionWalReader::get), this method returns a versioned item, which means that the returned item
-  /// may already be marked as removed.
-  fn get_versioned<Q>(
+  /// ## Safety
+  /// - The given `key` must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn get_versioned_by_bytes(
     &self,
     version: u64,
-    key: &Q,
+    key: &[u8],
   ) -> Option<<Self::Memtable as MultipleVersionMemtable>::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+    for<'a> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>:
+      VersionedMemtableEntry<'a>,
   {
-    self.memtable().get_versioned(version, key)
+    self
+      .memtable()
+      .get_versioned_by_bytes(version, key)
+      .filter(|ent| {
+        !MultipleVersionWalReader::range_deletions(self)
+          .coverage()
+          .covers_versioned_entry(version, ent)
+          && !self.pruning().prunes(ent)
+      })
   }
 
   fn upper_bound<Q>(
@@ -360,14 +561,20 @@ pub trait MultipleVersionWalReader<S> {
     bound: Bound<&Q>,
   ) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().upper_bound(version, bound)
+    let ent = self.memtable().upper_bound(version, bound)?;
+    self.skip_deleted(version, ent, |key| {
+      self.memtable().upper_bound(version, Bound::Excluded(key))
+    })
   }
 
   fn upper_bound_versioned<Q>(
@@ -376,14 +583,22 @@ pub trait MultipleVersionWalReader<S> {
     bound: Bound<&Q>,
   ) -> Option<<Self::Memtable as MultipleVersionMemtable>::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().upper_bound_versioned(version, bound)
+    let ent = self.memtable().upper_bound_versioned(version, bound)?;
+    self.skip_deleted_versioned(version, ent, |key| {
+      self
+        .memtable()
+        .upper_bound_versioned(version, Bound::Excluded(key))
+    })
   }
 
   fn lower_bound<Q>(
@@ -392,14 +607,20 @@ pub trait MultipleVersionWalReader<S> {
     bound: Bound<&Q>,
   ) -> Option<<Self::Memtable as BaseTable>::Item<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().lower_bound(version, bound)
+    let ent = self.memtable().lower_bound(version, bound)?;
+    self.skip_deleted(version, ent, |key| {
+      self.memtable().lower_bound(version, Bound::Excluded(key))
+    })
   }
 
   fn lower_bound_versioned<Q>(
@@ -408,22 +629,151 @@ pub trait MultipleVersionWalReader<S> {
     bound: Bound<&Q>,
   ) -> Option<<Self::Memtable as MultipleVersionMemtable>::VersionedItem<'_>>
   where
-    Q: ?Sized + Comparable<KeyPointer<<Self::Memtable as BaseTable>::Key>>,
+    Q: ?Sized
+      + Comparable<
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      >,
     Self::Memtable: MultipleVersionMemtable,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
   {
-    self.memtable().lower_bound_versioned(version, bound)
+    let ent = self.memtable().lower_bound_versioned(version, bound)?;
+    self.skip_deleted_versioned(version, ent, |key| {
+      self
+        .memtable()
+        .lower_bound_versioned(version, Bound::Excluded(key))
+    })
+  }
+
+  /// Moves to the neighbouring keys by `step` until it reaches an entry which is not removed by the range tombstones.
+  #[inline]
+  fn skip_deleted<'a>(
+    &'a self,
+    version: u64,
+    mut ent: <Self::Memtable as BaseTable>::Item<'a>,
+    step: impl Fn(
+      &KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+    ) -> Option<<Self::Memtable as BaseTable>::Item<'a>>,
+  ) -> Option<<Self::Memtable as BaseTable>::Item<'a>>
+  where
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let coverage = MultipleVersionWalReader::range_deletions(self).coverage();
+    let pruning = self.pruning();
+    while coverage.covers_versioned_entry(version, &ent) || pruning.prunes(&ent) {
+      ent = step(&ent.key())?;
+    }
+    Some(ent)
+  }
+
+  /// Moves to the neighbouring keys by `step` until it reaches an entry which is not removed by the range tombstones.
+  #[inline]
+  fn skip_deleted_versioned<'a>(
+    &'a self,
+    version: u64,
+    mut ent: <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>,
+    step: impl Fn(
+      &KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+    ) -> Option<<Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>>,
+  ) -> Option<<Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>>
+  where
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let coverage = MultipleVersionWalReader::range_deletions(self).coverage();
+    let pruning = self.pruning();
+    loop {
+      // The floor of the key is computed once for both versions of it.
+      let key = ent.key();
+      let floor = pruning.floor(&key);
+      if !coverage.covers_versioned_entry(version, &ent) && ent.version() >= floor {
+        return Some(ent);
+      }
+
+      // The entry may be an older version of the key, while the latest version is neither removed nor pruned.
+      if let Some(latest) = self.memtable().get_versioned(version, &key) {
+        if !coverage.covers_versioned_entry(version, &latest) && latest.version() >= floor {
+          return Some(latest);
+        }
+      }
+
+      ent = step(&key)?;
+    }
   }
 }
 
 pub trait Wal<S> {
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  type Allocator: Allocator<Path = std::sync::Arc<std::path::PathBuf>>;
+  #[cfg(not(all(feature = "memmap", not(target_family = "wasm"))))]
   type Allocator: Allocator;
   type Memtable;
 
-  fn construct(arena: Self::Allocator, base: Self::Memtable, opts: Options, checksumer: S) -> Self;
+  #[allow(clippy::too_many_arguments)]
+  fn construct(
+    arena: Self::Allocator,
+    base: Self::Memtable,
+    range_deletions: RangeDeletions,
+    merge_operands: MergeOperands,
+    bloom_filter: Option<BloomFilter>,
+    opts: Options,
+    checksumer: S,
+    next_transaction_id: u64,
+  ) -> Self;
+
+  /// Returns a new transaction id, which is never returned again by this WAL.
+  fn next_transaction_id(&self) -> u64;
+
+  /// Moves the next transaction id forward, so it is not less than `next`.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  fn advance_transaction_id(&self, next: u64);
+
+  /// Returns the readiness of the WAL, which is not ready until the replay in the background completes.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  fn readiness(
+    &self,
+  ) -> &Readiness<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Value>
+  where
+    Self::Memtable: BaseTable;
+
+  /// Blocks until the memtable is replayed, it returns immediately if the WAL is not replayed in the background.
+  #[inline]
+  fn wait_replayed(&self)
+  where
+    Self::Memtable: BaseTable,
+  {
+    #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+    self.readiness().wait();
+  }
+
+  /// Blocks until the tail of the WAL is known, so the records can be appended after it.
+  ///
+  /// It returns immediately if the WAL is not replayed in the background, or if the boundaries of the records
+  /// are already scanned by the replay.
+  #[inline]
+  fn wait_scanned(&self) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Memtable: BaseTable,
+  {
+    #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+    return self.readiness().wait_scanned();
+
+    #[cfg(not(all(feature = "memmap", not(target_family = "wasm"))))]
+    Ok(())
+  }
 
   fn allocator(&self) -> &Self::Allocator;
 
@@ -433,8 +783,31 @@ pub trait Wal<S> {
 
   fn memtable_mut(&mut self) -> &mut Self::Memtable;
 
+  fn range_deletions(&self) -> &RangeDeletions;
+
+  fn merge_operands(&self) -> &MergeOperands;
+
+  fn bloom_filter(&self) -> Option<&BloomFilter>;
+
   fn hasher(&self) -> &S;
 
+  /// Returns `false` if the encoded key is definitely not written to the WAL.
+  #[inline]
+  fn may_contain_key(&self, key: &[u8]) -> bool
+  where
+    Self::Memtable: BaseTable,
+  {
+    // The filter is filled by the replay, so it cannot tell the absence before the replay completes.
+    #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+    if !self.readiness().is_ready() {
+      return true;
+    }
+
+    self
+      .bloom_filter()
+      .map_or(true, |filter| filter.may_contain(key))
+  }
+
   /// Returns `true` if this WAL instance is read-only.
   #[inline]
   fn read_only(&self) -> bool {
@@ -492,7 +865,7 @@ pub trait Wal<S> {
 
     let allocator = self.allocator();
     let reserved_slice = allocator.reserved_slice();
-    &reserved_slice[HEADER_SIZE..]
+    &reserved_slice[header_size(allocator.magic_version())..]
   }
 
   /// Returns the mutable reference to the reserved slice.
@@ -512,7 +885,7 @@ pub trait Wal<S> {
 
     let allocator = self.allocator();
     let reserved_slice = allocator.reserved_slice_mut();
-    &mut reserved_slice[HEADER_SIZE..]
+    &mut reserved_slice[header_size(allocator.magic_version())..]
   }
 
   /// Flushes the to disk.
@@ -529,6 +902,26 @@ pub trait Wal<S> {
     }
   }
 
+  /// Writes the index of the WAL to the sidecar file, it is a no-op if the WAL is not backed by a file.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  fn persist_index(&self) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Memtable: BaseTable,
+  {
+    if self.read_only() {
+      return Err(Error::read_only());
+    }
+    // The records after the truncated tail must not be indexed.
+    self.wait_scanned()?;
+
+    match self.allocator().path() {
+      Some(path) => crate::wal::Index::persist(&crate::wal::index_path(path), self.allocator())
+        .map_err(Into::into),
+      None => Ok(()),
+    }
+  }
+
   /// Flushes the to disk.
   #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
   #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
@@ -547,7 +940,7 @@ pub trait Wal<S> {
   fn insert_pointer<'a>(
     &'a self,
     version: Option<u64>,
-    kp: KeyPointer<<Self::Memtable as BaseTable>::Key>,
+    kp: KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
     vp: Option<ValuePointer<<Self::Memtable as BaseTable>::Value>>,
   ) -> Result<(), Error<Self::Memtable>>
   where
@@ -557,6 +950,21 @@ pub trait Wal<S> {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type + 'static,
   {
+    if let Some(filter) = self.bloom_filter() {
+      filter.insert(kp.as_slice());
+    }
+
+    if vp.is_some_and(|vp| vp.is_expiring()) {
+      self.range_deletions().mark_expiring();
+    }
+
+    // The writes appended while replaying are applied after the replayed ones.
+    #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+    let Some((version, kp, vp)) = self.readiness().defer((version, kp, vp))?
+    else {
+      return Ok(());
+    };
+
     let t = self.memtable();
     if let Some(vp) = vp {
       t.insert(version, kp, vp).map_err(Error::memtable)
@@ -571,7 +979,7 @@ pub trait Wal<S> {
     mut ptrs: impl Iterator<
       Item = (
         Option<u64>,
-        KeyPointer<<Self::Memtable as BaseTable>::Key>,
+        KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
         Option<ValuePointer<<Self::Memtable as BaseTable>::Value>>,
       ),
     >,
@@ -602,7 +1010,7 @@ pub trait Wal<S> {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type + 'static,
   {
-    self.update(version, kb, Some(vb))
+    self.update(version, kb, Some(vb), None).map(|_| ())
   }
 
   fn remove<'a, KE>(
@@ -619,35 +1027,25 @@ pub trait Wal<S> {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type + 'static,
   {
-    struct Noop;
-
-    impl super::types::BufWriterOnce for Noop {
-      type Error = ();
-
-      #[inline(never)]
-      #[cold]
-      fn encoded_len(&self) -> usize {
-        0
-      }
-
-      #[inline(never)]
-      #[cold]
-      fn write_once(self, _: &mut VacantBuffer<'_>) -> Result<usize, Self::Error> {
-        Ok(0)
-      }
-    }
-
     self
-      .update::<KE, Noop>(version, kb, None)
+      .update::<KE, Noop>(version, kb, None, None)
+      .map(|_| ())
       .map_err(Among::into_left_right)
   }
 
+  /// Appends an entry and inserts it into the memtable, returns the pointer of the written key.
+  ///
+  /// If `expire_at` is set, the entry expires at such time, in milliseconds since the UNIX epoch.
   fn update<'a, KE, VE>(
     &'a self,
     version: Option<u64>,
     kb: KE,
     vb: Option<VE>,
-  ) -> Result<(), Among<KE::Error, VE::Error, Error<Self::Memtable>>>
+    expire_at: Option<u64>,
+  ) -> Result<
+    KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+    Among<KE::Error, VE::Error, Error<Self::Memtable>>,
+  >
   where
     KE: super::types::BufWriterOnce,
     VE: super::types::BufWriterOnce,
@@ -662,40 +1060,311 @@ pub trait Wal<S> {
       return Err(Among::Right(Error::read_only()));
     }
 
-    let res = {
-      let klen = kb.encoded_len();
-      let (vlen, remove) = vb
-        .as_ref()
-        .map(|vb| (vb.encoded_len(), false))
-        .unwrap_or((0, true));
-      let encoded_entry_meta = check(
-        klen,
-        vlen,
-        version.is_some(),
-        self.maximum_key_size().to_u32(),
-        self.maximum_value_size(),
-        self.read_only(),
-      )
-      .map_err(Either::Right)?;
+    self
+      .append(version, kb, vb, EntryFlags::empty(), None, expire_at)
+      .and_then(|(offset, kp, vp)| {
+        self
+          .insert_pointer(version, kp, vp)
+          .map(|_| kp)
+          .map_err(|e| {
+            unsafe {
+              self.allocator().rewind(ArenaPosition::Start(offset as u32));
+            };
+            Among::Right(e)
+          })
+      })
+  }
 
-      let allocator = self.allocator();
+  /// Removes all the entries in `[start, end)` which are written before, by appending a range tombstone.
+  fn delete_range<'a, KE>(
+    &'a self,
+    version: Option<u64>,
+    start: KE,
+    end: KE,
+  ) -> Result<(), Either<KE::Error, Error<Self::Memtable>>>
+  where
+    KE: super::types::BufWriterOnce,
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    if self.read_only() {
+      return Err(Either::Right(Error::read_only()));
+    }
+    self.wait_replayed();
+
+    // The end bound is a key, but it is written as the value of the record, so it is limited by both sizes.
+    let maximum_end_size = min_u64(
+      self.maximum_key_size().to_u32() as u64,
+      self.maximum_value_size() as u64,
+    ) as u32;
+    let end_len = end.encoded_len();
+    if end_len as u64 > maximum_end_size as u64 {
+      return Err(Either::Right(Error::key_too_large(
+        end_len as u64,
+        maximum_end_size,
+      )));
+    }
 
-      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
-      let is_ondisk = allocator.is_ondisk();
+    let (offset, start, end) = self
+      .append(
+        version,
+        start,
+        Some(end),
+        EntryFlags::RANGE_DELETION,
+        None,
+        None,
+      )
+      .map_err(Among::into_left_right)?;
+    // The end bound is always written, so the value pointer must exist.
+    let end = end.expect("the end bound of a range tombstone must be written");
+    let compare = key_comparator::<Self::Memtable>();
+
+    // An empty range removes nothing, so its record is discarded.
+    // SAFETY: both bounds are encoded by the key type.
+    if unsafe { compare(start.as_slice(), end.as_slice()) }.is_ge() {
+      unsafe {
+        self.allocator().rewind(ArenaPosition::Start(offset as u32));
+      }
+      return Ok(());
+    }
 
-      let buf = allocator.alloc_bytes(encoded_entry_meta.entry_size);
-      let mut cks = self.hasher().build_checksumer();
+    self.range_deletions().insert(version, start, end, compare);
+    Ok(())
+  }
 
-      match buf {
-        Err(e) => Err(Among::Right(Error::from_insufficient_space(e))),
-        Ok(mut buf) => {
-          unsafe {
-            // We allocate the buffer with the exact size, so it's safe to write to the buffer.
-            let flag = Flags::COMMITTED.bits();
+  /// Appends a merge operand, which is folded over the latest value of the key by the merge operator when it is read.
+  fn merge<'a, KE, VE>(
+    &'a self,
+    version: Option<u64>,
+    kb: KE,
+    vb: VE,
+  ) -> Result<(), Among<KE::Error, VE::Error, Error<Self::Memtable>>>
+  where
+    KE: super::types::BufWriterOnce,
+    VE: super::types::BufWriterOnce,
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    if self.read_only() {
+      return Err(Among::Right(Error::read_only()));
+    }
 
-            cks.update(&[flag]);
+    let operator = self
+      .options()
+      .merge_operator()
+      .ok_or(Among::Right(Error::missing_merge_operator()))?;
+    // The operand is linked to the latest value, which is not known before the replay completes.
+    self.wait_replayed();
 
-            buf.put_u8_unchecked(Flags::empty().bits());
+    self
+      .append(version, kb, Some(vb), EntryFlags::MERGE, None, None)
+      .and_then(|(offset, kp, vp)| {
+        // The operand is always written, so the value pointer must exist.
+        let vp = vp.expect("the operand of a merge record must be written");
+        let vp = link_merge_operand(
+          self.memtable(),
+          self.range_deletions(),
+          self.merge_operands(),
+          operator,
+          version,
+          kp,
+          vp,
+        );
+        self.insert_pointer(version, kp, Some(vp)).map_err(|e| {
+          unsafe {
+            self.allocator().rewind(ArenaPosition::Start(offset as u32));
+          };
+          Among::Right(e)
+        })
+      })
+  }
+
+  /// Starts a new transaction.
+  #[inline]
+  fn begin_transaction(&self) -> Result<Transaction<Self::Memtable>, Error<Self::Memtable>>
+  where
+    Self::Memtable: BaseTable,
+  {
+    if self.read_only() {
+      return Err(Error::read_only());
+    }
+    // The ids of the transactions in the WAL are not known before the replay completes.
+    self.wait_replayed();
+
+    Ok(Transaction::new(
+      self.next_transaction_id(),
+      self as *const Self as *const () as usize,
+    ))
+  }
+
+  /// Appends an entry which belongs to the transaction, the entry is invisible until the transaction is committed.
+  fn transaction_update<KE, VE>(
+    &self,
+    txn: &mut Transaction<Self::Memtable>,
+    version: Option<u64>,
+    kb: KE,
+    vb: Option<VE>,
+  ) -> Result<(), Among<KE::Error, VE::Error, Error<Self::Memtable>>>
+  where
+    KE: super::types::BufWriterOnce,
+    VE: super::types::BufWriterOnce,
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+  {
+    txn.check_owner(self as *const Self as *const () as usize);
+
+    let (_, kp, vp) = self.append(version, kb, vb, EntryFlags::empty(), Some(txn.id()), None)?;
+    txn.push(version, kp, vp);
+    Ok(())
+  }
+
+  /// Appends the commit marker of the transaction, then makes all the entries of the transaction visible,
+  /// the entries are positioned at the commit marker, so the range tombstones written before it do not remove them.
+  fn commit_transaction<'a>(
+    &'a self,
+    txn: Transaction<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    txn.check_owner(self as *const Self as *const () as usize);
+
+    // Reserves the space of all the entries before the commit marker is appended, so once the transaction
+    // is committed, none of its entries can fail to be applied.
+    let id = txn.id();
+    let entries = txn.into_entries();
+    self
+      .memtable()
+      .reserve(entries.len())
+      .map_err(Error::memtable)?;
+
+    let (_, marker, _) = self
+      .append::<Noop, Noop>(
+        None,
+        Noop,
+        None,
+        EntryFlags::TRANSACTION_COMMIT,
+        Some(id),
+        None,
+      )
+      .map_err(noop_err)?;
+
+    self.range_deletions().commit(
+      entries
+        .iter()
+        .map(|(_, kp, vp)| vp.map_or_else(|| kp.as_ptr(), |vp| vp.as_ptr())),
+      marker.as_ptr(),
+    );
+    self.insert_pointers(entries.into_iter())
+  }
+
+  /// Appends the abort marker of the transaction, all the entries of the transaction are discarded.
+  fn abort_transaction(&self, txn: Transaction<Self::Memtable>) -> Result<(), Error<Self::Memtable>>
+  where
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+  {
+    txn.check_owner(self as *const Self as *const () as usize);
+
+    self
+      .append::<Noop, Noop>(
+        None,
+        Noop,
+        None,
+        EntryFlags::TRANSACTION_ABORT,
+        Some(txn.id()),
+        None,
+      )
+      .map(|_| ())
+      .map_err(noop_err)
+  }
+
+  /// Appends a record to the WAL, returns the offset of the record and the pointers to the key and value.
+  ///
+  /// If `txn` is set, the record belongs to the transaction with such id. If `expire_at` is set, the entry
+  /// expires at such time, in milliseconds since the UNIX epoch.
+  #[allow(clippy::type_complexity)]
+  fn append<KE, VE>(
+    &self,
+    version: Option<u64>,
+    kb: KE,
+    vb: Option<VE>,
+    kind: EntryFlags,
+    txn: Option<u64>,
+    expire_at: Option<u64>,
+  ) -> Result<
+    (
+      usize,
+      KeyPointer<<Self::Memtable as BaseTable>::Key, <Self::Memtable as BaseTable>::Comparator>,
+      Option<ValuePointer<<Self::Memtable as BaseTable>::Value>>,
+    ),
+    Among<KE::Error, VE::Error, Error<Self::Memtable>>,
+  >
+  where
+    KE: super::types::BufWriterOnce,
+    VE: super::types::BufWriterOnce,
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+  {
+    self.wait_scanned().map_err(Among::Right)?;
+
+    {
+      let klen = kb.encoded_len();
+      let (vlen, remove) = vb
+        .as_ref()
+        .map(|vb| (vb.encoded_len(), false))
+        .unwrap_or((0, true));
+      let encoded_entry_meta = check(
+        klen,
+        vlen,
+        version.is_some(),
+        txn.is_some(),
+        self.maximum_key_size().to_u32(),
+        self.maximum_value_size(),
+        self.read_only(),
+      )
+      .map_err(Among::Right)?;
+      let encoded_entry_meta = if expire_at.is_some() {
+        encoded_entry_meta.with_expiration()
+      } else {
+        encoded_entry_meta
+      };
+
+      let allocator = self.allocator();
+
+      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+      let is_ondisk = allocator.is_ondisk();
+
+      let buf = allocator.alloc_bytes(encoded_entry_meta.entry_size);
+      let mut cks = self.hasher().build_checksumer();
+
+      match buf {
+        Err(e) => Err(Among::Right(Error::from_insufficient_space(e))),
+        Ok(mut buf) => {
+          unsafe {
+            // We allocate the buffer with the exact size, so it's safe to write to the buffer.
+            let flag = if expire_at.is_some() {
+              Flags::EXPIRING
+            } else {
+              Flags::empty()
+            };
+
+            cks.update(&[flag.bits() | Flags::COMMITTED.bits()]);
+
+            buf.put_u8_unchecked(flag.bits());
             let written = buf.put_u64_varint_unchecked(encoded_entry_meta.packed_kvlen);
             debug_assert_eq!(
               written, encoded_entry_meta.packed_kvlen_size,
@@ -703,30 +1372,41 @@ pub trait Wal<S> {
             );
 
             let mut entry_flag = if !remove {
-              EntryFlags::empty()
+              kind
             } else {
-              EntryFlags::REMOVED
+              kind | EntryFlags::REMOVED
             };
 
-            if let Some(version) = version {
+            if version.is_some() {
               entry_flag |= EntryFlags::VERSIONED;
-              buf.put_u8_unchecked(entry_flag.bits());
+            }
+
+            if txn.is_some() {
+              entry_flag |= EntryFlags::TRANSACTION;
+            }
+
+            buf.put_u8_unchecked(entry_flag.bits());
+            if let Some(version) = version {
               buf.put_u64_le_unchecked(version);
-            } else {
-              buf.put_u8_unchecked(entry_flag.bits());
+            }
+
+            if let Some(txn) = txn {
+              buf.put_u64_le_unchecked(txn);
+            }
+
+            if let Some(expire_at) = expire_at {
+              buf.put_u64_le_unchecked(expire_at);
             }
 
             let ko = encoded_entry_meta.key_offset();
             let ptr = buf.as_mut_ptr().add(ko);
             buf.set_len(encoded_entry_meta.entry_size as usize - VERSION_SIZE);
 
-            let mut key_buf = VacantBuffer::new(
-              encoded_entry_meta.klen as usize,
-              NonNull::new_unchecked(ptr),
-            );
+            let mut key_buf =
+              VacantBuffer::new(encoded_entry_meta.klen, NonNull::new_unchecked(ptr));
             let written = kb.write_once(&mut key_buf).map_err(Among::Left)?;
             debug_assert_eq!(
-              written, encoded_entry_meta.klen as usize,
+              written, encoded_entry_meta.klen,
               "the actual bytes written to the key buffer not equal to the expected size, expected {} but got {}.",
               encoded_entry_meta.klen, written,
             );
@@ -734,13 +1414,13 @@ pub trait Wal<S> {
             if let Some(vb) = vb {
               let vo = encoded_entry_meta.value_offset();
               let mut value_buf = VacantBuffer::new(
-                encoded_entry_meta.vlen as usize,
+                encoded_entry_meta.vlen,
                 NonNull::new_unchecked(buf.as_mut_ptr().add(vo)),
               );
               let written = vb.write_once(&mut value_buf).map_err(Among::Middle)?;
 
               debug_assert_eq!(
-                written, encoded_entry_meta.vlen as usize,
+                written, encoded_entry_meta.vlen,
                 "the actual bytes written to the value buffer not equal to the expected size, expected {} but got {}.",
                 encoded_entry_meta.vlen, written,
               );
@@ -763,25 +1443,17 @@ pub trait Wal<S> {
             }
 
             buf.detach();
-            let ptr = buf.as_ptr().add(encoded_entry_meta.key_offset() as usize);
+            let ptr = buf.as_ptr().add(encoded_entry_meta.key_offset());
             let kp = KeyPointer::new(entry_flag, encoded_entry_meta.klen, ptr);
             let vp = (!remove).then(|| {
               ValuePointer::new(encoded_entry_meta.vlen, ptr.add(encoded_entry_meta.klen))
+                .with_expiration(expire_at)
             });
             Ok((buf.buffer_offset(), kp, vp))
           }
         }
       }
-    };
-
-    res.and_then(|(offset, kp, vp)| {
-      self.insert_pointer(version, kp, vp).map_err(|e| {
-        unsafe {
-          self.allocator().rewind(ArenaPosition::Start(offset as u32));
-        };
-        Among::Right(e)
-      })
-    })
+    }
   }
 
   fn insert_batch<'a, W, B>(
@@ -807,7 +1479,15 @@ pub trait Wal<S> {
       return Err(Among::Right(Error::read_only()));
     }
 
+    self.wait_scanned().map_err(Among::Right)?;
+
+    // Check the guards before any allocation, so a rejected batch leaves nothing in the WAL.
+    if !batch.guards().is_empty() {
+      self.wait_replayed();
+    }
     let opts = self.options();
+    check_batch_guards(self.memtable(), self.range_deletions(), batch.guards())?;
+
     let maximum_key_size = opts.maximum_key_size().to_u32();
     let minimum_value_size = opts.maximum_value_size();
     let start_offset = unsafe {
@@ -953,7 +1633,7 @@ pub trait Wal<S> {
 
     self
       .insert_pointers(batch.iter_mut().map(|e| {
-        let (kp, vp) = e.take_pointer().unwrap();
+        let (kp, vp) = e.pointer().unwrap();
         (e.internal_version(), kp, vp)
       }))
       .map_err(|e| {
@@ -967,6 +1647,213 @@ pub trait Wal<S> {
         Among::Right(e)
       })
   }
+
+  fn insert_prepared_batch<'a>(
+    &'a self,
+    batch: &PreparedBatch<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    if self.read_only() {
+      return Err(Error::read_only());
+    }
+    self.wait_scanned()?;
+
+    // The entries are encoded by the batch, only the size limits of this WAL need to be checked.
+    let opts = self.options();
+    let maximum_key_size = opts.maximum_key_size().to_u32();
+    let maximum_value_size = opts.maximum_value_size();
+    for ent in batch.entries() {
+      if ent.klen > maximum_key_size as usize {
+        return Err(Error::key_too_large(ent.klen as u64, maximum_key_size));
+      }
+
+      if ent.vlen > maximum_value_size as usize {
+        return Err(Error::value_too_large(ent.vlen as u64, maximum_value_size));
+      }
+    }
+
+    let encoded = batch.encoded();
+    let allocator = self.allocator();
+    let remaining = allocator.remaining() as u64;
+    let batch_encoded_size = encoded.len() as u64;
+    if batch_encoded_size > remaining {
+      return Err(Error::insufficient_space(
+        batch_encoded_size,
+        remaining as u32,
+      ));
+    }
+
+    // safe to cast batch_encoded_size to u32 here, we already checked it's less than capacity (less than u32::MAX).
+    let batch_meta = merge_lengths(batch.len() as u32, batch_encoded_size as u32);
+    let batch_meta_size = encoded_u64_varint_len(batch_meta);
+    let total_size =
+      RECORD_FLAG_SIZE as u64 + batch_meta_size as u64 + batch_encoded_size + CHECKSUM_SIZE as u64;
+    if total_size > remaining {
+      return Err(Error::insufficient_space(total_size, remaining as u32));
+    }
+
+    let (start_offset, data_ptr) = unsafe {
+      let mut buf = allocator
+        .alloc_bytes(total_size as u32)
+        .map_err(Error::from_insufficient_space)?;
+
+      buf.put_u8_unchecked(Flags::BATCHING.bits());
+      let size = buf.put_u64_varint_unchecked(batch_meta);
+      debug_assert_eq!(
+        size, batch_meta_size,
+        "the actual encoded u64 varint length ({}) doos not match the length ({}) returned by `dbutils::leb128::encoded_u64_varint_len`, please report bug to https://github.com/al8n/layer0/issues",
+        size, batch_meta_size,
+      );
+      let data_ptr = buf.as_mut_ptr().add(RECORD_FLAG_SIZE + batch_meta_size) as *const u8;
+      buf.put_slice_unchecked(encoded);
+
+      let mut cks = self.hasher().build_checksumer();
+      let committed_flag = Flags::BATCHING | Flags::COMMITTED;
+      cks.update(&[committed_flag.bits()]);
+      cks.update(&buf[1..]);
+      let checksum = cks.digest();
+      buf.put_u64_le_unchecked(checksum);
+
+      // commit the entry
+      buf[0] = committed_flag.bits();
+
+      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+      if self.options().sync() && allocator.is_ondisk() {
+        allocator.flush_header_and_range(Buffer::offset(&buf), buf.capacity())?;
+      }
+      buf.detach();
+      (Buffer::buffer_offset(&buf), data_ptr)
+    };
+
+    self
+      .insert_pointers(batch.entries().iter().map(|ent| {
+        // SAFETY: the pointers point to the encoded entries which were just committed to the WAL.
+        let key_ptr = unsafe { data_ptr.add(ent.key_offset) };
+        let kp = KeyPointer::new(ent.flag, ent.klen, key_ptr);
+        let vp = (!ent.flag.contains(EntryFlags::REMOVED))
+          .then(|| ValuePointer::new(ent.vlen, unsafe { key_ptr.add(ent.klen) }));
+        (ent.version, kp, vp)
+      }))
+      .inspect_err(|_| {
+        // Safety: the writer is single threaded, the memory chunk in buf cannot be accessed by other threads,
+        // so it's safe to rewind the arena.
+        unsafe {
+          allocator.rewind(ArenaPosition::Start(start_offset as u32));
+        }
+      })
+  }
+
+  /// Allocates a record whose key and value are filled later, the record is not committed until
+  /// [`commit_reservation`](Wal::commit_reservation) is called. Returns the offset of the key in the buffer.
+  ///
+  /// The record must be the last one allocated when it is committed, or the records written after it are
+  /// discarded by the replay, because the replay stops at the first uncommitted record.
+  fn reserve(
+    &self,
+    version: Option<u64>,
+    klen: usize,
+    vlen: usize,
+  ) -> Result<(usize, BytesRefMut<'_, Self::Allocator>), Error<Self::Memtable>>
+  where
+    Self::Memtable: BaseTable,
+  {
+    self.wait_scanned()?;
+
+    let meta = check(
+      klen,
+      vlen,
+      version.is_some(),
+      false,
+      self.maximum_key_size().to_u32(),
+      self.maximum_value_size(),
+      self.read_only(),
+    )?;
+
+    let mut buf = self
+      .allocator()
+      .alloc_bytes(meta.entry_size)
+      .map_err(Error::from_insufficient_space)?;
+
+    unsafe {
+      // We allocate the buffer with the exact size, so it's safe to write to the buffer.
+      buf.put_u8_unchecked(Flags::empty().bits());
+      let written = buf.put_u64_varint_unchecked(meta.packed_kvlen);
+      debug_assert_eq!(
+        written, meta.packed_kvlen_size,
+        "the precalculated size should be equal to the written size"
+      );
+
+      if let Some(version) = version {
+        buf.put_u8_unchecked(EntryFlags::VERSIONED.bits());
+        buf.put_u64_le_unchecked(version);
+      } else {
+        buf.put_u8_unchecked(EntryFlags::empty().bits());
+      }
+
+      buf.set_len(meta.checksum_offset());
+    }
+
+    Ok((meta.key_offset(), buf))
+  }
+
+  /// Commits a record allocated by [`reserve`](Wal::reserve), and inserts it into the memtable.
+  fn commit_reservation<'a>(
+    &'a self,
+    version: Option<u64>,
+    key_offset: usize,
+    klen: usize,
+    vlen: usize,
+    mut buf: BytesRefMut<'_, Self::Allocator>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    S: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    let allocator = self.allocator();
+    let (offset, kp, vp) = unsafe {
+      let mut cks = self.hasher().build_checksumer();
+      cks.update(&[Flags::COMMITTED.bits()]);
+      cks.update(&buf[1..]);
+      let checksum = cks.digest();
+      buf.put_u64_le_unchecked(checksum);
+
+      // commit the entry
+      buf[0] |= Flags::COMMITTED.bits();
+
+      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+      if self.options().sync() && allocator.is_ondisk() {
+        allocator.flush_header_and_range(Buffer::offset(&buf), buf.capacity())?;
+      }
+
+      buf.detach();
+      let flag = if version.is_some() {
+        EntryFlags::VERSIONED
+      } else {
+        EntryFlags::empty()
+      };
+      let ptr = buf.as_ptr().add(key_offset);
+      let kp = KeyPointer::new(flag, klen, ptr);
+      let vp = ValuePointer::new(vlen, ptr.add(klen));
+      (Buffer::buffer_offset(&buf), kp, vp)
+    };
+
+    self
+      .insert_pointer(version, kp, Some(vp))
+      .inspect_err(|_| unsafe {
+        allocator.rewind(ArenaPosition::Start(offset as u32));
+      })
+  }
 }
 
 impl<S, T> WalReader<S> for T
@@ -983,6 +1870,11 @@ where
   fn memtable(&self) -> &Self::Memtable {
     T::memtable(self)
   }
+
+  #[inline]
+  fn range_deletions(&self) -> &RangeDeletions {
+    T::range_deletions(self)
+  }
 }
 
 impl<S, T> MultipleVersionWalReader<S> for T
@@ -1000,9 +1892,22 @@ where
   fn memtable(&self) -> &Self::Memtable {
     T::memtable(self)
   }
+
+  #[inline]
+  fn range_deletions(&self) -> &RangeDeletions {
+    T::range_deletions(self)
+  }
+
+  #[inline]
+  fn retention(&self) -> Retention {
+    T::options(self).retention()
+  }
 }
 
 pub trait Constructable: Sized {
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  type Allocator: Allocator<Path = std::sync::Arc<std::path::PathBuf>> + 'static;
+  #[cfg(not(all(feature = "memmap", not(target_family = "wasm"))))]
   type Allocator: Allocator + 'static;
   type Wal: Wal<Self::Checksumer, Allocator = Self::Allocator, Memtable = Self::Memtable> + 'static;
   type Memtable: BaseTable;
@@ -1033,7 +1938,11 @@ pub trait Constructable: Sized {
       cursor += MAGIC_TEXT_SIZE;
       slice[MAGIC_TEXT_SIZE] = <Self::Memtable as BaseTable>::kind() as u8;
       cursor += WAL_KIND_SIZE;
-      slice[cursor..HEADER_SIZE].copy_from_slice(&opts.magic_version().to_le_bytes());
+      slice[cursor..cursor + MAGIC_VERSION_SIZE]
+        .copy_from_slice(&opts.magic_version().to_le_bytes());
+      cursor += MAGIC_VERSION_SIZE;
+      slice[cursor..HEADER_SIZE]
+        .copy_from_slice(&key_comparator_id::<Self::Memtable>().to_le_bytes());
     }
 
     #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
@@ -1043,14 +1952,34 @@ pub trait Constructable: Sized {
       .and_then(|_| {
         Self::Memtable::new(memtable_opts)
           .map(|memtable| {
-            <Self::Wal as Wal<Self::Checksumer>>::construct(arena, memtable, opts, cks)
+            <Self::Wal as Wal<Self::Checksumer>>::construct(
+              arena,
+              memtable,
+              RangeDeletions::new(),
+              MergeOperands::new(),
+              bloom_filter::<Self::Memtable>(&opts),
+              opts,
+              cks,
+              0,
+            )
           })
           .map_err(Error::memtable)
       });
 
     #[cfg(not(all(feature = "memmap", not(target_family = "wasm"))))]
     let this = Self::Memtable::new(memtable_opts)
-      .map(|memtable| <Self::Wal as Wal<Self::Checksumer>>::construct(arena, memtable, opts, cks))
+      .map(|memtable| {
+        <Self::Wal as Wal<Self::Checksumer>>::construct(
+          arena,
+          memtable,
+          RangeDeletions::new(),
+          MergeOperands::new(),
+          bloom_filter::<Self::Memtable>(&opts),
+          opts,
+          cks,
+          0,
+        )
+      })
       .map_err(Error::memtable);
 
     this
@@ -1066,240 +1995,366 @@ pub trait Constructable: Sized {
     checksumer: Self::Checksumer,
   ) -> Result<Self::Wal, Error<Self::Memtable>>
   where
-    Self::Checksumer: BuildChecksumer,
+    Self::Checksumer: BuildChecksumer + Sync,
+    Self::Memtable: Sync,
+    <Self::Memtable as BaseTable>::Error: Send,
     <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
     <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
   {
-    use super::{types::Kind, utils::split_lengths};
-    use dbutils::leb128::decode_u64_varint;
-
-    let slice = arena.reserved_slice();
-    let mut cursor = 0;
-    let magic_text = &slice[0..MAGIC_TEXT_SIZE];
-    if magic_text != MAGIC_TEXT {
-      return Err(Error::magic_text_mismatch());
-    }
-    cursor += MAGIC_TEXT_SIZE;
-    let kind = Kind::try_from(slice[cursor])?;
-    let created_kind = <Self::Memtable as BaseTable>::kind();
-    if kind != created_kind {
-      return Err(Error::wal_kind_mismatch(kind, created_kind));
-    }
-    cursor += WAL_KIND_SIZE;
+    use replay::{check_header, load_index, replay_pending, scan, Replayer};
 
-    let magic_version = u16::from_le_bytes(slice[cursor..HEADER_SIZE].try_into().unwrap());
-    if magic_version != opts.magic_version() {
-      return Err(Error::magic_version_mismatch());
-    }
+    check_header(&arena, &opts)?;
 
     let set = <Self::Wal as Wal<Self::Checksumer>>::Memtable::new(memtable_opts)
       .map_err(Error::memtable)?;
+    let range_deletions = RangeDeletions::new();
+    let merge_operands = MergeOperands::new();
+    let bloom_filter = bloom_filter::<Self::Memtable>(&opts);
+    let threads = opts.replay_threads();
+    let mut replayer = Replayer::new(
+      &set,
+      &range_deletions,
+      &merge_operands,
+      bloom_filter.as_ref(),
+      &opts,
+      key_comparator::<Self::Memtable>(),
+    );
 
     let mut cursor = arena.data_offset();
-    let allocated = arena.allocated();
-    let mut minimum_version = u64::MAX;
-    let mut maximum_version = 0;
-
-    loop {
-      unsafe {
-        // we reached the end of the arena, if we have any remaining, then if means two possibilities:
-        // 1. the remaining is a partial entry, but it does not be persisted to the disk, so following the write-ahead log principle, we should discard it.
-        // 2. our file may be corrupted, so we discard the remaining.
-        if cursor + RECORD_FLAG_SIZE > allocated {
-          if !ro && cursor < allocated {
-            arena.rewind(ArenaPosition::Start(cursor as u32));
-            arena.flush()?;
-          }
-          break;
+    if let Some(index) = load_index(&arena, &opts) {
+      for ent in index.entries {
+        unsafe {
+          replayer.replay(
+            arena.get_pointer(ent.offset),
+            ent.key_len,
+            ent.value_len,
+            ent.expiring,
+          )?;
         }
+      }
+      cursor = index.high_water_mark;
+    }
 
-        let header = arena.get_u8(cursor).unwrap();
-        let flag = Flags::from_bits_retain(header);
-
-        if !flag.contains(Flags::BATCHING) {
-          let (readed, encoded_len) =
-            arena
-              .get_u64_varint(cursor + RECORD_FLAG_SIZE)
-              .map_err(|e| {
-                #[cfg(feature = "tracing")]
-                tracing::error!(err=%e);
-
-                Error::corrupted(e)
-              })?;
-          let (key_len, value_len) = split_lengths(encoded_len);
-          let key_len = key_len as usize;
-          let value_len = value_len as usize;
-          let entry_flag = arena
-            .get_u8(cursor + RECORD_FLAG_SIZE + readed)
-            .map_err(|e| {
-              #[cfg(feature = "tracing")]
-              tracing::error!(err=%e);
-
-              Error::corrupted(e)
-            })?;
-
-          let entry_flag = EntryFlags::from_bits_retain(entry_flag);
-          let version_size = if entry_flag.contains(EntryFlags::VERSIONED) {
-            VERSION_SIZE
-          } else {
-            0
-          };
-          // Same as above, if we reached the end of the arena, we should discard the remaining.
-          let cks_offset =
-            RECORD_FLAG_SIZE + readed + EntryFlags::SIZE + version_size + key_len + value_len;
-          if cks_offset + CHECKSUM_SIZE > allocated {
-            // If the entry is committed, then it means our file is truncated, so we should report corrupted.
-            if flag.contains(Flags::COMMITTED) {
-              return Err(Error::corrupted("file is truncated"));
-            }
+    // The checksums are verified by the worker threads before the records are replayed, if the replay is parallel.
+    let scanned = unsafe {
+      scan(
+        &arena,
+        cursor,
+        &checksumer,
+        (threads <= 1).then_some(&mut replayer),
+      )?
+    };
+    unsafe {
+      replay_pending(
+        &arena,
+        &mut replayer,
+        &scanned.pending,
+        &checksumer,
+        threads,
+      )?;
+    }
+    let next_transaction_id = replayer.finish()?;
 
-            if !ro {
-              arena.rewind(ArenaPosition::Start(cursor as u32));
-              arena.flush()?;
-            }
+    if let Some(cursor) = scanned.truncate {
+      if !ro {
+        unsafe {
+          arena.rewind(ArenaPosition::Start(cursor as u32));
+        }
+        arena.flush()?;
+      }
+    }
 
-            break;
-          }
+    Ok(<Self::Wal as Wal<Self::Checksumer>>::construct(
+      arena,
+      set,
+      range_deletions,
+      merge_operands,
+      bloom_filter,
+      opts,
+      checksumer,
+      next_transaction_id,
+    ))
+  }
 
-          let cks = arena.get_u64_le(cursor + cks_offset).unwrap();
-          if cks != checksumer.checksum_one(arena.get_bytes(cursor, cks_offset)) {
-            return Err(Error::corrupted("checksum mismatch"));
-          }
+  /// Validates the header, then returns the WAL while the boundaries of the records are scanned
+  /// and the memtable is replayed in the background.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  fn replay_lazily<'a>(
+    arena: Self::Allocator,
+    opts: Options,
+    memtable_opts: <Self::Memtable as BaseTable>::Options,
+    ro: bool,
+    checksumer: Self::Checksumer,
+  ) -> Result<Self, Error<Self::Memtable>>
+  where
+    Self::Wal: Send + Sync,
+    Self::Checksumer: BuildChecksumer + Sync,
+    Self::Memtable: Sync + 'static,
+    <Self::Memtable as BaseTable>::Error: Send,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+  {
+    use replay::{check_header, load_index, replay_pending, scan, Replayer};
+    use std::sync::Arc;
 
-          // If the entry is not committed, we should not rewind
-          if !flag.contains(Flags::COMMITTED) {
+    check_header(&arena, &opts)?;
+
+    let set = <Self::Wal as Wal<Self::Checksumer>>::Memtable::new(memtable_opts)
+      .map_err(Error::memtable)?;
+    let bloom_filter = bloom_filter::<Self::Memtable>(&opts);
+    let wal = Arc::new(<Self::Wal as Wal<Self::Checksumer>>::construct(
+      arena,
+      set,
+      RangeDeletions::new(),
+      MergeOperands::new(),
+      bloom_filter,
+      opts,
+      checksumer,
+      0,
+    ));
+    wal.readiness().start();
+
+    let compare = key_comparator::<Self::Memtable>();
+    let replaying = wal.clone();
+    std::thread::Builder::new()
+      .name("orderwal-replay".into())
+      .spawn(move || {
+        let wal = &*replaying;
+        let arena = wal.allocator();
+        let threads = wal.options().replay_threads();
+        let result = (|| unsafe {
+          let index = load_index(arena, wal.options());
+          let cursor = index
+            .as_ref()
+            .map_or(arena.data_offset(), |index| index.high_water_mark);
+
+          // Only the lengths of the records are read by the scan, the writes wait for it,
+          // so the tail is truncated before anything is appended.
+          let scanned = scan::<_, _, Self::Memtable>(arena, cursor, wal.hasher(), None)?;
+          if let Some(cursor) = scanned.truncate {
             if !ro {
               arena.rewind(ArenaPosition::Start(cursor as u32));
               arena.flush()?;
             }
+          }
+          wal.readiness().scanned();
+
+          let mut replayer = Replayer::new(
+            Wal::memtable(wal),
+            Wal::range_deletions(wal),
+            wal.merge_operands(),
+            wal.bloom_filter(),
+            wal.options(),
+            compare,
+          );
 
-            break;
+          for ent in index.into_iter().flat_map(|index| index.entries) {
+            replayer.replay(
+              arena.get_pointer(ent.offset),
+              ent.key_len,
+              ent.value_len,
+              ent.expiring,
+            )?;
+          }
+          replay_pending(
+            arena,
+            &mut replayer,
+            &scanned.pending,
+            wal.hasher(),
+            threads,
+          )?;
+          wal.advance_transaction_id(replayer.finish()?);
+          Ok(())
+        })();
+
+        wal.readiness().complete(result, |(version, kp, vp)| {
+          let t = Wal::memtable(wal);
+          match vp {
+            Some(vp) => t.insert(version, kp, vp),
+            None => t.remove(version, kp),
           }
+          .map_err(Error::memtable)
+        });
+      })?;
 
-          let ptr = arena.get_pointer(cursor + RECORD_FLAG_SIZE + readed);
-          let flag = EntryFlags::from_bits_retain(*ptr);
+    Ok(Self::from_shared_core(wal))
+  }
 
-          let (version, ptr) = if flag.contains(EntryFlags::VERSIONED) {
-            let version_ptr = ptr.add(EntryFlags::SIZE);
-            let version = u64::from_le_bytes(
-              core::slice::from_raw_parts(version_ptr, VERSION_SIZE)
-                .try_into()
-                .unwrap(),
-            );
-            minimum_version = minimum_version.min(version);
-            maximum_version = maximum_version.max(version);
-            (Some(version), version_ptr.add(VERSION_SIZE))
-          } else {
-            (None, ptr.add(EntryFlags::SIZE))
-          };
+  /// Replays the WAL, the memtable is replayed in the background if the lazy replay is enabled.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  fn open<'a>(
+    arena: Self::Allocator,
+    opts: Options,
+    memtable_opts: <Self::Memtable as BaseTable>::Options,
+    ro: bool,
+    checksumer: Self::Checksumer,
+  ) -> Result<Self, Error<Self::Memtable>>
+  where
+    Self::Wal: Send + Sync,
+    Self::Checksumer: BuildChecksumer + Sync,
+    Self::Memtable: Sync + 'static,
+    <Self::Memtable as BaseTable>::Error: Send,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+  {
+    if opts.lazy_replay() {
+      Self::replay_lazily(arena, opts, memtable_opts, ro, checksumer)
+    } else {
+      Self::replay(arena, opts, memtable_opts, ro, checksumer).map(Self::from_core)
+    }
+  }
 
-          let kp = KeyPointer::new(flag, key_len, ptr);
-          if flag.contains(EntryFlags::REMOVED) {
-            set.remove(version, kp).map_err(Error::memtable)?;
-          } else {
-            let vp = ValuePointer::new(value_len, ptr.add(key_len));
-            set.insert(version, kp, vp).map_err(Error::memtable)?;
-          }
+  #[inline]
+  fn from_core(core: Self::Wal) -> Self {
+    Self::from_shared_core(std::sync::Arc::new(core))
+  }
 
-          cursor += cks_offset + CHECKSUM_SIZE;
-        } else {
-          let (readed, encoded_len) =
-            arena
-              .get_u64_varint(cursor + RECORD_FLAG_SIZE)
-              .map_err(|e| {
-                #[cfg(feature = "tracing")]
-                tracing::error!(err=%e);
-
-                Error::corrupted(e)
-              })?;
-
-          let (num_entries, encoded_data_len) = split_lengths(encoded_len);
-          // Same as above, if we reached the end of the arena, we should discard the remaining.
-          let cks_offset = RECORD_FLAG_SIZE + readed + encoded_data_len as usize;
-          let total_size = cks_offset + CHECKSUM_SIZE;
-
-          if total_size > allocated {
-            // If the entry is committed, then it means our file is truncated, so we should report corrupted.
-            if flag.contains(Flags::COMMITTED) {
-              return Err(Error::corrupted("file is truncated"));
-            }
+  fn from_shared_core(core: std::sync::Arc<Self::Wal>) -> Self;
+}
 
-            if !ro {
-              arena.rewind(ArenaPosition::Start(cursor as u32));
-              arena.flush()?;
-            }
+/// An empty key or value, which is used by the tombstones and the transaction markers.
+struct Noop;
 
-            break;
-          }
-          let cks = arena.get_u64_le(cursor + cks_offset).unwrap();
-          let mut batch_data_buf = arena.get_bytes(cursor, cks_offset);
-          if cks != checksumer.checksum_one(batch_data_buf) {
-            return Err(Error::corrupted("checksum mismatch"));
-          }
+impl super::types::BufWriterOnce for Noop {
+  type Error = ();
 
-          let mut sub_cursor = 0;
-          batch_data_buf = &batch_data_buf[RECORD_FLAG_SIZE + readed..];
-          for _ in 0..num_entries {
-            let (kvlen, ent_len) = decode_u64_varint(batch_data_buf).map_err(|e| {
-              #[cfg(feature = "tracing")]
-              tracing::error!(err=%e);
-
-              Error::corrupted(e)
-            })?;
-
-            let (klen, vlen) = split_lengths(ent_len);
-            let klen = klen as usize;
-            let vlen = vlen as usize;
-
-            let ptr = arena.get_pointer(cursor + RECORD_FLAG_SIZE + readed + sub_cursor + kvlen);
-            let flag = EntryFlags::from_bits_retain(*ptr);
-
-            let (version, ptr, ent_len) = if flag.contains(EntryFlags::VERSIONED) {
-              let version_ptr = ptr.add(EntryFlags::SIZE);
-              let version = u64::from_le_bytes(
-                core::slice::from_raw_parts(version_ptr, VERSION_SIZE)
-                  .try_into()
-                  .unwrap(),
-              );
-              minimum_version = minimum_version.min(version);
-              maximum_version = maximum_version.max(version);
-              let ent_len = kvlen + EntryFlags::SIZE + VERSION_SIZE + klen + vlen;
-              (Some(version), version_ptr.add(VERSION_SIZE), ent_len)
-            } else {
-              let ent_len = kvlen + EntryFlags::SIZE + klen + vlen;
-              (None, ptr.add(EntryFlags::SIZE), ent_len)
-            };
+  #[inline(never)]
+  #[cold]
+  fn encoded_len(&self) -> usize {
+    0
+  }
 
-            let kp = KeyPointer::new(flag, klen, ptr);
-            if flag.contains(EntryFlags::REMOVED) {
-              set.remove(version, kp).map_err(Error::memtable)?;
-            } else {
-              let vp = ValuePointer::new(vlen, ptr.add(klen));
-              set.insert(version, kp, vp).map_err(Error::memtable)?;
-            }
+  #[inline(never)]
+  #[cold]
+  fn write_once(self, _: &mut VacantBuffer<'_>) -> Result<usize, Self::Error> {
+    Ok(0)
+  }
+}
 
-            sub_cursor += ent_len;
-            batch_data_buf = &batch_data_buf[ent_len..];
-          }
+/// Converts the error of appending a record with empty key and value.
+#[inline]
+fn noop_err<T: BaseTable>(e: Among<(), (), Error<T>>) -> Error<T> {
+  match e {
+    Among::Right(e) => e,
+    _ => unreachable!("writing an empty key or value never fails"),
+  }
+}
 
-          debug_assert_eq!(
-            encoded_data_len as usize, sub_cursor,
-            "expected encoded batch data size ({}) is not equal to the actual size ({})",
-            encoded_data_len, sub_cursor,
-          );
+/// Moves the cursor by `step` until it reaches an entry which is not removed by the range tombstones.
+#[inline]
+fn skip_deleted<'a, E>(
+  range_deletions: &RangeDeletions,
+  mut ent: E,
+  step: impl Fn(&mut E) -> Option<E>,
+) -> Option<E>
+where
+  E: MemtableEntry<'a>,
+{
+  let coverage = range_deletions.coverage();
+  while coverage.covers_entry(&ent) {
+    ent = step(&mut ent)?;
+  }
+  Some(ent)
+}
 
-          cursor += total_size;
-        }
-      }
+/// Links the merge operand to the latest value of the key which is visible at `version`,
+/// and returns the value pointer which resolves to the merged value.
+///
+/// Only the range tombstones written before the operand are taken into account, so the result does not depend on
+/// whether the later range tombstones have been replayed or not.
+#[inline]
+fn link_merge_operand<M>(
+  memtable: &M,
+  range_deletions: &RangeDeletions,
+  merge_operands: &MergeOperands,
+  operator: &'static dyn MergeOperator,
+  version: Option<u64>,
+  kp: KeyPointer<M::Key, M::Comparator>,
+  vp: ValuePointer<M::Value>,
+) -> ValuePointer<M::Value>
+where
+  M: BaseTable,
+  KeyPointer<M::Key, M::Comparator>: Ord + 'static,
+{
+  let coverage = range_deletions.coverage().written_before(vp.as_ptr());
+  let base = memtable
+    .latest(version, &kp)
+    .filter(|(ver, base)| !coverage.covers_value(version, &kp, *ver, base))
+    .map(|(_, base)| base);
+  merge_operands.insert(kp, vp, base, operator)
+}
+
+fn check_batch_guards<M, K, V>(
+  memtable: &M,
+  range_deletions: &RangeDeletions,
+  guards: &[BatchGuard<K, M>],
+) -> Result<(), Among<K::Error, V, Error<M>>>
+where
+  M: BaseTable,
+  K: BufWriter,
+  KeyPointer<M::Key, M::Comparator>: Ord + 'static,
+{
+  // Guards are checked against the latest values, just like the conditional writes.
+  let version = match M::kind() {
+    Kind::Plain => None,
+    Kind::MultipleVersion => Some(u64::MAX),
+  };
+
+  let coverage = range_deletions.coverage();
+  let mut buf = Vec::new();
+  for (idx, guard) in guards.iter().enumerate() {
+    buf.clear();
+    buf.resize(guard.key.encoded_len(), 0);
+    let len = guard
+      .key
+      .write(&mut VacantBuffer::from(buf.as_mut_slice()))
+      .map_err(Among::Left)?;
+    let kp = KeyPointer::new(EntryFlags::empty(), len, buf.as_ptr());
+    let latest = memtable
+      .latest(version, &kp)
+      .filter(|(ver, vp)| !coverage.covers_value(version, &kp, *ver, vp));
+
+    let holds = match guard.condition {
+      Condition::Absent => latest.is_none(),
+      Condition::Present => latest.is_some(),
+      Condition::Version(expected) => latest.is_some_and(|(ver, _)| ver == expected),
+    };
+    if !holds {
+      return Err(Among::Right(Error::guard_failed(idx)));
     }
+  }
 
-    Ok(<Self::Wal as Wal<Self::Checksumer>>::construct(
-      arena, set, opts, checksumer,
-    ))
+  Ok(())
+}
+
+/// Returns the comparison of the encoded keys of the memtable.
+#[inline]
+fn key_comparator<M: BaseTable>() -> unsafe fn(&[u8], &[u8]) -> cmp::Ordering {
+  <M::Comparator as KeyComparator<M::Key>>::compare
+}
+
+/// Creates the Bloom filter of the WAL.
+///
+/// The filter hashes the encoded keys, which only agrees with the default order, a custom comparator
+/// may treat different encoded keys as equal, so the filter is disabled for it.
+#[inline]
+fn bloom_filter<M: BaseTable>(opts: &Options) -> Option<BloomFilter> {
+  if key_comparator_id::<M>() != 0 {
+    return None;
   }
 
-  fn from_core(core: Self::Wal) -> Self;
+  BloomFilter::new(opts.bloom_filter_bits_per_key())
+}
+
+/// Returns the identity of the comparator of the memtable, which is recorded in the header.
+#[inline]
+pub(crate) const fn key_comparator_id<M: BaseTable>() -> u64 {
+  <M::Comparator as KeyComparator<M::Key>>::ID
 }
 
 #[inline]
@@ -1316,6 +2371,7 @@ const fn check<T: BaseTable>(
   klen: usize,
   vlen: usize,
   versioned: bool,
+  transactional: bool,
   max_key_size: u32,
   max_value_size: u32,
   ro: bool,
@@ -1335,9 +2391,14 @@ const fn check<T: BaseTable>(
     return Err(Error::value_too_large(vlen as u64, max_value_size));
   }
 
-  let encoded_entry_meta = EncodedEntryMeta::new(klen, vlen, versioned);
+  let encoded_entry_meta = EncodedEntryMeta::new(klen, vlen, versioned, transactional);
   if encoded_entry_meta.entry_size == u32::MAX {
     let version_size = if versioned { VERSION_SIZE } else { 0 };
+    let transaction_id_size = if transactional {
+      TRANSACTION_ID_SIZE
+    } else {
+      0
+    };
     return Err(Error::entry_too_large(
       encoded_entry_meta.entry_size as u64,
       min_u64(
@@ -1345,6 +2406,7 @@ const fn check<T: BaseTable>(
           + 10
           + EntryFlags::SIZE as u64
           + version_size as u64
+          + transaction_id_size as u64
           + max_key_size as u64
           + max_value_size as u64,
         u32::MAX as u64,
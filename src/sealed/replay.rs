@@ -0,0 +1,648 @@
+use core::{cmp, hash::BuildHasher};
+use std::{collections::BTreeMap, hash::RandomState, vec::Vec};
+
+use dbutils::{checksum::BuildChecksumer, leb128::decode_u64_varint};
+use rarena_allocator::Allocator;
+
+use crate::{
+  error::Error,
+  memtable::BaseTable,
+  options::{header_size, Options},
+  types::{EntryFlags, Kind, MergeOperator},
+  utils::split_lengths,
+  wal::{index_path, BloomFilter, Index, KeyPointer, MergeOperands, RangeDeletions, ValuePointer},
+  Flags, CHECKSUM_SIZE, EXPIRATION_SIZE, HEADER_SIZE, MAGIC_TEXT, MAGIC_TEXT_SIZE,
+  MAGIC_VERSION_SIZE, RECORD_FLAG_SIZE, TRANSACTION_ID_SIZE, VERSION_SIZE, WAL_KIND_SIZE,
+};
+
+use super::{key_comparator_id, link_merge_operand};
+
+const U64_SIZE: usize = core::mem::size_of::<u64>();
+
+/// An entry in the WAL, which is located by its entry flag.
+#[derive(Clone, Copy)]
+struct RawEntry {
+  ptr: *const u8,
+  key_len: usize,
+  value_len: usize,
+  /// Whether the expiration time follows the transaction id, which is recorded in the record flag.
+  expiring: bool,
+}
+
+// SAFETY: the pointer points to the memory of the WAL, which outlives the replay.
+unsafe impl Send for RawEntry {}
+unsafe impl Sync for RawEntry {}
+
+impl RawEntry {
+  #[inline]
+  unsafe fn flag(&self) -> EntryFlags {
+    EntryFlags::from_bits_retain(*self.ptr)
+  }
+
+  /// Returns the version and the pointer to the key, the transaction id and the expiration time are skipped.
+  #[inline]
+  unsafe fn version(&self) -> (Option<u64>, *const u8) {
+    let flag = self.flag();
+    let ptr = self.ptr.add(EntryFlags::SIZE);
+    let (version, ptr) = if flag.contains(EntryFlags::VERSIONED) {
+      (Some(read_u64(ptr)), ptr.add(VERSION_SIZE))
+    } else {
+      (None, ptr)
+    };
+
+    let ptr = if flag.contains(EntryFlags::TRANSACTION) {
+      ptr.add(TRANSACTION_ID_SIZE)
+    } else {
+      ptr
+    };
+
+    if self.expiring {
+      (version, ptr.add(EXPIRATION_SIZE))
+    } else {
+      (version, ptr)
+    }
+  }
+
+  /// Returns the position which locates the entry for the range tombstones, which is the value, or the key if the entry is removed.
+  #[inline]
+  unsafe fn position(&self) -> *const u8 {
+    let (_, ptr) = self.version();
+    if self.flag().contains(EntryFlags::REMOVED) {
+      ptr
+    } else {
+      ptr.add(self.key_len)
+    }
+  }
+
+  /// Returns the expiration time, which precedes the key.
+  #[inline]
+  unsafe fn expiration(&self) -> Option<u64> {
+    if !self.expiring {
+      return None;
+    }
+
+    let (_, key) = self.version();
+    Some(read_u64(key.sub(EXPIRATION_SIZE)))
+  }
+
+  #[inline]
+  unsafe fn transaction_id(&self) -> u64 {
+    let mut ptr = self.ptr.add(EntryFlags::SIZE);
+    if self.flag().contains(EntryFlags::VERSIONED) {
+      ptr = ptr.add(VERSION_SIZE);
+    }
+    read_u64(ptr)
+  }
+}
+
+/// The state of the memtable which the replayed entries are applied to.
+struct Target<'a, M> {
+  memtable: &'a M,
+  range_deletions: &'a RangeDeletions,
+  merge_operands: &'a MergeOperands,
+  bloom_filter: Option<&'a BloomFilter>,
+  merge_operator: Option<&'static dyn MergeOperator>,
+}
+
+impl<M> Target<'_, M>
+where
+  M: BaseTable,
+  KeyPointer<M::Key, M::Comparator>: Ord + 'static,
+{
+  unsafe fn apply(&self, ent: RawEntry) -> Result<(), Error<M>> {
+    let flag = ent.flag();
+    let (version, ptr) = ent.version();
+    let kp = KeyPointer::new(flag, ent.key_len, ptr);
+    if let Some(filter) = self.bloom_filter {
+      filter.insert(kp.as_slice());
+    }
+
+    let expiration = ent.expiration();
+    if expiration.is_some() {
+      self.range_deletions.mark_expiring();
+    }
+
+    // The entries of the transactions are not merged, even if the flag is set.
+    if flag.contains(EntryFlags::TRANSACTION) {
+      return if flag.contains(EntryFlags::REMOVED) {
+        self.memtable.remove(version, kp)
+      } else {
+        let vp = ValuePointer::new(ent.value_len, ptr.add(ent.key_len));
+        self.memtable.insert(version, kp, vp)
+      }
+      .map_err(Error::memtable);
+    }
+
+    if flag.contains(EntryFlags::MERGE) {
+      let operator = self
+        .merge_operator
+        .ok_or_else(Error::missing_merge_operator)?;
+      let vp = link_merge_operand(
+        self.memtable,
+        self.range_deletions,
+        self.merge_operands,
+        operator,
+        version,
+        kp,
+        ValuePointer::new(ent.value_len, ptr.add(ent.key_len)),
+      );
+      self.memtable.insert(version, kp, vp)
+    } else if flag.contains(EntryFlags::REMOVED) {
+      self.memtable.remove(version, kp)
+    } else {
+      let vp = ValuePointer::new(ent.value_len, ptr.add(ent.key_len)).with_expiration(expiration);
+      self.memtable.insert(version, kp, vp)
+    }
+    .map_err(Error::memtable)
+  }
+}
+
+/// Replays the entries of the WAL to the memtable in the order they are written.
+///
+/// The transactions and the range deletions are always resolved in order. If the replay is parallel,
+/// the memtable writes are deferred, and partitioned by the hash of the key, one partition per worker thread,
+/// so the writes to the same key, including the merge operands, are still applied in order.
+pub(super) struct Replayer<'a, M> {
+  target: Target<'a, M>,
+  compare: unsafe fn(&[u8], &[u8]) -> cmp::Ordering,
+  /// The entries of the transactions whose commit markers are not replayed yet.
+  transactions: BTreeMap<u64, Vec<RawEntry>>,
+  next_transaction_id: u64,
+  /// The memtable writes deferred to the worker threads, partitioned by the hash of the key.
+  deferred: Option<Vec<Vec<RawEntry>>>,
+  state: RandomState,
+}
+
+impl<'a, M> Replayer<'a, M>
+where
+  M: BaseTable + Sync,
+  M::Error: Send,
+  KeyPointer<M::Key, M::Comparator>: Ord + 'static,
+{
+  /// Creates a replayer, the memtable writes are applied with the replay threads of the options if there are more than `1`.
+  pub(super) fn new(
+    memtable: &'a M,
+    range_deletions: &'a RangeDeletions,
+    merge_operands: &'a MergeOperands,
+    bloom_filter: Option<&'a BloomFilter>,
+    opts: &Options,
+    compare: unsafe fn(&[u8], &[u8]) -> cmp::Ordering,
+  ) -> Self {
+    // The writes are partitioned by the hash of the encoded key, which only agrees with the default order,
+    // a custom comparator may treat different encoded keys as equal, so the replay is serial for it.
+    let threads = if key_comparator_id::<M>() == 0 {
+      opts.replay_threads()
+    } else {
+      1
+    };
+    Self {
+      target: Target {
+        memtable,
+        range_deletions,
+        merge_operands,
+        bloom_filter,
+        merge_operator: opts.merge_operator(),
+      },
+      compare,
+      transactions: BTreeMap::new(),
+      next_transaction_id: 0,
+      deferred: (threads > 1).then(|| (0..threads).map(|_| Vec::new()).collect()),
+      state: RandomState::new(),
+    }
+  }
+
+  /// Replays an entry, `ptr` points to its entry flag, `expiring` is whether its record flag contains `Flags::EXPIRING`.
+  ///
+  /// ## Safety
+  /// - `ptr` must point to a committed entry of the WAL, whose key and value are `key_len` and `value_len` bytes.
+  pub(super) unsafe fn replay(
+    &mut self,
+    ptr: *const u8,
+    key_len: usize,
+    value_len: usize,
+    expiring: bool,
+  ) -> Result<(), Error<M>> {
+    let ent = RawEntry {
+      ptr,
+      key_len,
+      value_len,
+      expiring,
+    };
+    let flag = ent.flag();
+
+    if flag.contains(EntryFlags::TRANSACTION) {
+      let txn = ent.transaction_id();
+      self.next_transaction_id = self.next_transaction_id.max(txn + 1);
+      if flag.contains(EntryFlags::TRANSACTION_COMMIT) {
+        let entries = self.transactions.remove(&txn).unwrap_or_default();
+        let (_, marker) = ent.version();
+        self
+          .target
+          .range_deletions
+          .commit(entries.iter().map(|ent| ent.position()), marker);
+        for ent in entries {
+          self.write(ent)?;
+        }
+      } else if flag.contains(EntryFlags::TRANSACTION_ABORT) {
+        self.transactions.remove(&txn);
+      } else {
+        self.transactions.entry(txn).or_default().push(ent);
+      }
+      return Ok(());
+    }
+
+    if flag.contains(EntryFlags::RANGE_DELETION) {
+      let (version, ptr) = ent.version();
+      let vp = ValuePointer::<M::Value>::new(value_len, ptr.add(key_len));
+      self.target.range_deletions.insert(
+        version,
+        KeyPointer::<M::Key, M::Comparator>::new(flag, key_len, ptr),
+        vp,
+        self.compare,
+      );
+      return Ok(());
+    }
+
+    self.write(ent)
+  }
+
+  unsafe fn write(&mut self, ent: RawEntry) -> Result<(), Error<M>> {
+    let Some(deferred) = self.deferred.as_mut() else {
+      return self.target.apply(ent);
+    };
+
+    let (_, ptr) = ent.version();
+    let key = core::slice::from_raw_parts(ptr, ent.key_len);
+    let partition = self.state.hash_one(key) as usize % deferred.len();
+    deferred[partition].push(ent);
+    Ok(())
+  }
+
+  fn apply_deferred(&mut self) -> Result<(), Error<M>> {
+    let Some(deferred) = self.deferred.take() else {
+      return Ok(());
+    };
+
+    let target = &self.target;
+    std::thread::scope(|s| {
+      let workers = deferred
+        .into_iter()
+        .map(|partition| {
+          s.spawn(move || {
+            partition
+              .into_iter()
+              .try_for_each(|ent| unsafe { target.apply(ent) })
+          })
+        })
+        .collect::<Vec<_>>();
+
+      workers
+        .into_iter()
+        .try_for_each(|worker| worker.join().unwrap())
+    })
+  }
+
+  /// Applies the deferred memtable writes, and returns the next transaction id.
+  pub(super) fn finish(mut self) -> Result<u64, Error<M>> {
+    self.apply_deferred()?;
+    Ok(self.next_transaction_id)
+  }
+}
+
+/// A record whose checksum is not verified yet.
+#[derive(Clone, Copy)]
+pub(super) struct PendingRecord {
+  pub(super) offset: usize,
+  pub(super) cks_offset: usize,
+}
+
+/// Verifies the checksums of the records with `threads` worker threads.
+fn verify_checksums<S, M>(
+  memory: &[u8],
+  records: &[PendingRecord],
+  checksumer: &S,
+  threads: usize,
+) -> Result<(), Error<M>>
+where
+  S: BuildChecksumer + Sync,
+  M: BaseTable,
+{
+  let verify = |records: &[PendingRecord]| {
+    records.iter().all(|rec| {
+      let end = rec.offset + rec.cks_offset;
+      let cks = u64::from_le_bytes(memory[end..end + CHECKSUM_SIZE].try_into().unwrap());
+      cks == checksumer.checksum_one(&memory[rec.offset..end])
+    })
+  };
+
+  let chunk_size = records.len().div_ceil(threads.max(1)).max(1);
+  let verified = std::thread::scope(|s| {
+    let workers = records
+      .chunks(chunk_size)
+      .map(|chunk| s.spawn(move || verify(chunk)))
+      .collect::<Vec<_>>();
+    workers.into_iter().all(|worker| worker.join().unwrap())
+  });
+
+  if verified {
+    Ok(())
+  } else {
+    Err(Error::corrupted("checksum mismatch"))
+  }
+}
+
+/// Checks the magic text, the kind, the magic version and the comparator in the header of the WAL.
+pub(super) fn check_header<A, M>(arena: &A, opts: &Options) -> Result<(), Error<M>>
+where
+  A: Allocator,
+  M: BaseTable,
+{
+  let slice = arena.reserved_slice();
+  let mut cursor = 0;
+  let magic_text = &slice[0..MAGIC_TEXT_SIZE];
+  if magic_text != MAGIC_TEXT {
+    return Err(Error::magic_text_mismatch());
+  }
+  cursor += MAGIC_TEXT_SIZE;
+  let kind = Kind::try_from(slice[cursor])?;
+  let created_kind = M::kind();
+  if kind != created_kind {
+    return Err(Error::wal_kind_mismatch(kind, created_kind));
+  }
+  cursor += WAL_KIND_SIZE;
+
+  let magic_version = u16::from_le_bytes(
+    slice[cursor..cursor + MAGIC_VERSION_SIZE]
+      .try_into()
+      .unwrap(),
+  );
+  if magic_version != opts.magic_version() {
+    return Err(Error::magic_version_mismatch());
+  }
+  cursor += MAGIC_VERSION_SIZE;
+
+  // The WALs in the legacy format do not record the comparator, their keys are ordered by the key type.
+  let comparator = if header_size(arena.magic_version()) == HEADER_SIZE {
+    u64::from_le_bytes(slice[cursor..HEADER_SIZE].try_into().unwrap())
+  } else {
+    0
+  };
+  if comparator != key_comparator_id::<M>() {
+    return Err(Error::comparator_mismatch());
+  }
+
+  Ok(())
+}
+
+/// Loads the persisted index of the WAL, if it is enabled and matches the WAL.
+pub(super) fn load_index<A>(arena: &A, opts: &Options) -> Option<Index>
+where
+  A: Allocator<Path = std::sync::Arc<std::path::PathBuf>>,
+{
+  if !opts.persisted_index() {
+    return None;
+  }
+
+  arena
+    .path()
+    .and_then(|path| Index::load(&index_path(path), arena))
+}
+
+/// The records found by [`scan`].
+pub(super) struct Scanned {
+  /// The committed records whose checksums are not verified yet.
+  pub(super) pending: Vec<PendingRecord>,
+  /// The offset where the WAL is truncated, if the tail of the WAL is not committed or persisted.
+  pub(super) truncate: Option<usize>,
+}
+
+/// Walks through the records from `cursor` to the end of the WAL.
+///
+/// If `replayer` is set, the checksums are verified and the records are replayed one by one, otherwise
+/// only the lengths of the records are read, and the committed records are returned to be verified and
+/// replayed later. An uncommitted record is always verified, as the WAL is truncated at it.
+///
+/// ## Safety
+/// - `cursor` must be the offset of a record, or the end of the WAL.
+pub(super) unsafe fn scan<A, S, M>(
+  arena: &A,
+  mut cursor: usize,
+  checksumer: &S,
+  mut replayer: Option<&mut Replayer<'_, M>>,
+) -> Result<Scanned, Error<M>>
+where
+  A: Allocator,
+  S: BuildChecksumer,
+  M: BaseTable + Sync,
+  M::Error: Send,
+  KeyPointer<M::Key, M::Comparator>: Ord + 'static,
+{
+  let allocated = arena.allocated();
+  let mut scanned = Scanned {
+    pending: Vec::new(),
+    truncate: None,
+  };
+
+  loop {
+    // we reached the end of the arena, if we have any remaining, then if means two possibilities:
+    // 1. the remaining is a partial entry, but it does not be persisted to the disk, so following the write-ahead log principle, we should discard it.
+    // 2. our file may be corrupted, so we discard the remaining.
+    if cursor + RECORD_FLAG_SIZE > allocated {
+      if cursor < allocated {
+        scanned.truncate = Some(cursor);
+      }
+      return Ok(scanned);
+    }
+
+    let header = arena.get_u8(cursor).unwrap();
+    let flag = Flags::from_bits_retain(header);
+    let (readed, encoded_len) = arena
+      .get_u64_varint(cursor + RECORD_FLAG_SIZE)
+      .map_err(|e| {
+        #[cfg(feature = "tracing")]
+        tracing::error!(err=%e);
+
+        Error::corrupted(e)
+      })?;
+
+    let cks_offset = if !flag.contains(Flags::BATCHING) {
+      let (key_len, value_len) = split_lengths(encoded_len);
+      let entry_flag = arena
+        .get_u8(cursor + RECORD_FLAG_SIZE + readed)
+        .map_err(|e| {
+          #[cfg(feature = "tracing")]
+          tracing::error!(err=%e);
+
+          Error::corrupted(e)
+        })?;
+
+      let entry_flag = EntryFlags::from_bits_retain(entry_flag);
+      let version_size = if entry_flag.contains(EntryFlags::VERSIONED) {
+        VERSION_SIZE
+      } else {
+        0
+      };
+      let transaction_id_size = if entry_flag.contains(EntryFlags::TRANSACTION) {
+        TRANSACTION_ID_SIZE
+      } else {
+        0
+      };
+      let expiration_size = if flag.contains(Flags::EXPIRING) {
+        EXPIRATION_SIZE
+      } else {
+        0
+      };
+      RECORD_FLAG_SIZE
+        + readed
+        + EntryFlags::SIZE
+        + version_size
+        + transaction_id_size
+        + expiration_size
+        + key_len as usize
+        + value_len as usize
+    } else {
+      let (_, encoded_data_len) = split_lengths(encoded_len);
+      RECORD_FLAG_SIZE + readed + encoded_data_len as usize
+    };
+
+    // Same as above, if we reached the end of the arena, we should discard the remaining.
+    if cks_offset + CHECKSUM_SIZE > allocated {
+      // If the entry is committed, then it means our file is truncated, so we should report corrupted.
+      if flag.contains(Flags::COMMITTED) {
+        return Err(Error::corrupted("file is truncated"));
+      }
+
+      scanned.truncate = Some(cursor);
+      return Ok(scanned);
+    }
+
+    // The batches are always committed, and the uncommitted entry is verified before it is discarded.
+    let committed = flag.contains(Flags::BATCHING) || flag.contains(Flags::COMMITTED);
+    match replayer.as_deref_mut() {
+      None if committed => scanned.pending.push(PendingRecord {
+        offset: cursor,
+        cks_offset,
+      }),
+      replayer => {
+        let cks = arena.get_u64_le(cursor + cks_offset).unwrap();
+        if cks != checksumer.checksum_one(arena.get_bytes(cursor, cks_offset)) {
+          return Err(Error::corrupted("checksum mismatch"));
+        }
+
+        // If the entry is not committed, we should not rewind
+        if !committed {
+          scanned.truncate = Some(cursor);
+          return Ok(scanned);
+        }
+
+        if let Some(replayer) = replayer {
+          replay_record(arena, replayer, cursor)?;
+        }
+      }
+    }
+
+    cursor += cks_offset + CHECKSUM_SIZE;
+  }
+}
+
+/// Verifies the checksums of the pending records with `threads` worker threads, then replays them.
+///
+/// ## Safety
+/// - The records must be the ones returned by [`scan`] on the same WAL.
+pub(super) unsafe fn replay_pending<A, S, M>(
+  arena: &A,
+  replayer: &mut Replayer<'_, M>,
+  records: &[PendingRecord],
+  checksumer: &S,
+  threads: usize,
+) -> Result<(), Error<M>>
+where
+  A: Allocator,
+  S: BuildChecksumer + Sync,
+  M: BaseTable + Sync,
+  M::Error: Send,
+  KeyPointer<M::Key, M::Comparator>: Ord + 'static,
+{
+  if records.is_empty() {
+    return Ok(());
+  }
+
+  verify_checksums(arena.allocated_memory(), records, checksumer, threads)?;
+  records
+    .iter()
+    .try_for_each(|rec| replay_record(arena, replayer, rec.offset))
+}
+
+/// Replays the entries of a record whose checksum is verified, `offset` is the offset of the record.
+unsafe fn replay_record<A, M>(
+  arena: &A,
+  replayer: &mut Replayer<'_, M>,
+  offset: usize,
+) -> Result<(), Error<M>>
+where
+  A: Allocator,
+  M: BaseTable + Sync,
+  M::Error: Send,
+  KeyPointer<M::Key, M::Comparator>: Ord + 'static,
+{
+  let flag = Flags::from_bits_retain(arena.get_u8(offset).unwrap());
+  let (readed, encoded_len) = arena.get_u64_varint(offset + RECORD_FLAG_SIZE).unwrap();
+
+  if !flag.contains(Flags::BATCHING) {
+    let (key_len, value_len) = split_lengths(encoded_len);
+    return replayer.replay(
+      arena.get_pointer(offset + RECORD_FLAG_SIZE + readed),
+      key_len as usize,
+      value_len as usize,
+      flag.contains(Flags::EXPIRING),
+    );
+  }
+
+  let (num_entries, encoded_data_len) = split_lengths(encoded_len);
+  let mut batch_data_buf = arena.get_bytes(
+    offset + RECORD_FLAG_SIZE + readed,
+    encoded_data_len as usize,
+  );
+  let mut sub_cursor = 0;
+  for _ in 0..num_entries {
+    let (kvlen, ent_len) = decode_u64_varint(batch_data_buf).map_err(|e| {
+      #[cfg(feature = "tracing")]
+      tracing::error!(err=%e);
+
+      Error::corrupted(e)
+    })?;
+
+    let (klen, vlen) = split_lengths(ent_len);
+    let klen = klen as usize;
+    let vlen = vlen as usize;
+
+    let ptr = arena.get_pointer(offset + RECORD_FLAG_SIZE + readed + sub_cursor + kvlen);
+    let flag = EntryFlags::from_bits_retain(*ptr);
+    let ent_len = if flag.contains(EntryFlags::VERSIONED) {
+      kvlen + EntryFlags::SIZE + VERSION_SIZE + klen + vlen
+    } else {
+      kvlen + EntryFlags::SIZE + klen + vlen
+    };
+    replayer.replay(ptr, klen, vlen, false)?;
+
+    sub_cursor += ent_len;
+    batch_data_buf = &batch_data_buf[ent_len..];
+  }
+
+  debug_assert_eq!(
+    encoded_data_len as usize, sub_cursor,
+    "expected encoded batch data size ({}) is not equal to the actual size ({})",
+    encoded_data_len, sub_cursor,
+  );
+
+  Ok(())
+}
+
+#[inline]
+unsafe fn read_u64(ptr: *const u8) -> u64 {
+  u64::from_le_bytes(
+    core::slice::from_raw_parts(ptr, U64_SIZE)
+      .try_into()
+      .unwrap(),
+  )
+}
@@ -23,24 +23,40 @@ pub mod base {
   use crate::memtable::linked::Table as BaseLinkedTable;
   use crate::memtable::{
     alternative::Table as BaseAlternativeTable, arena::Table as BaseArenaTable,
+    art::Table as BaseArtTable, btree::Table as BaseBTreeTable, sorted::Table as BaseSortedTable,
   };
+  use crate::types::KeyOrder;
 
   pub use crate::{
-    memtable::arena::TableOptions as ArenaTableOptions,
+    memtable::{arena::TableOptions as ArenaTableOptions, art::LexicographicKey},
     types::base::{Entry, Key, Value},
-    wal::base::{Iter, Keys, RangeKeys, RangeValues, Reader, Writer},
+    wal::{
+      base::{Iter, Keys, RangeByBytes, RangeKeys, RangeValues, Reader, Writer},
+      BloomFilterStats, Reservation, Transaction,
+    },
   };
 
   /// An memory table for [`OrderWal`] or [`OrderWalReader`] based on [`linked::Table`](BaseLinkedTable).
   #[cfg(feature = "std")]
   #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-  pub type LinkedTable<K, V> = BaseLinkedTable<K, V>;
+  pub type LinkedTable<K, V, C = KeyOrder> = BaseLinkedTable<K, V, C>;
 
   /// An memory table for [`OrderWal`] or [`OrderWalReader`] based on [`arena::Table`](BaseArenaTable).
-  pub type ArenaTable<K, V> = BaseArenaTable<K, V>;
+  pub type ArenaTable<K, V, C = KeyOrder> = BaseArenaTable<K, V, C>;
+
+  /// An memory table for [`OrderWal`] or [`OrderWalReader`] based on [`btree::Table`](BaseBTreeTable).
+  pub type BTreeTable<K, V, C = KeyOrder> = BaseBTreeTable<K, V, C>;
+
+  /// An memory table for [`OrderWal`] or [`OrderWalReader`] based on [`art::Table`](BaseArtTable),
+  /// the key type must implement [`LexicographicKey`].
+  pub type ArtTable<K, V> = BaseArtTable<K, V>;
+
+  /// An memory table for [`OrderWal`] or [`OrderWalReader`] based on [`sorted::Table`](BaseSortedTable),
+  /// which suits the [`OrderWalReader`] best.
+  pub type SortedTable<K, V, C = KeyOrder> = BaseSortedTable<K, V, C>;
 
   /// An memory table for [`OrderWal`] or [`OrderWalReader`] based on [`alternative::Table`](BaseAlternativeTable).
-  pub type AlternativeTable<K, V> = BaseAlternativeTable<K, V>;
+  pub type AlternativeTable<K, V, C = KeyOrder> = BaseAlternativeTable<K, V, C>;
 
   /// The default memory table used by [`OrderWal`] or [`OrderWalReader`].
   #[cfg(feature = "std")]
@@ -48,7 +64,7 @@ pub mod base {
 
   /// The default memory table used by [`OrderWal`] or [`OrderWalReader`].
   #[cfg(not(feature = "std"))]
-  pub type DefaultTable<K, V> = ArenaTable<K, V>;
+  pub type DefaultTable<K, V> = BTreeTable<K, V>;
 
   /// A generic ordered write-ahead log implementation for multiple threads environments.
   ///
@@ -83,27 +99,44 @@ pub mod multiple_version {
   use crate::memtable::linked::MultipleVersionTable as BaseLinkedTable;
   use crate::memtable::{
     alternative::MultipleVersionTable as BaseAlternativeTable,
-    arena::MultipleVersionTable as BaseArenaTable,
+    arena::MultipleVersionTable as BaseArenaTable, art::MultipleVersionTable as BaseArtTable,
+    btree::MultipleVersionTable as BaseBTreeTable, sorted::MultipleVersionTable as BaseSortedTable,
   };
+  use crate::types::KeyOrder;
 
   pub use crate::{
-    memtable::arena::TableOptions as ArenaTableOptions,
+    memtable::{arena::TableOptions as ArenaTableOptions, art::LexicographicKey},
     types::multiple_version::{Entry, Key, Value, VersionedEntry},
-    wal::multiple_version::{
-      Iter, IterAll, Keys, RangeAll, RangeKeys, RangeValues, Reader, Writer,
+    wal::{
+      multiple_version::{
+        ChangesBetween, History, Iter, IterAll, Keys, RangeAll, RangeChangesBetween, RangeKeys,
+        RangeValues, Reader, Writer,
+      },
+      BloomFilterStats, Reservation, Transaction,
     },
   };
 
   /// An memory table for multiple version [`OrderWal`] or [`OrderWalReader`] based on [`linked::MultipleVersionTable`](BaseLinkedTable).
   #[cfg(feature = "std")]
   #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
-  pub type LinkedTable<K, V> = BaseLinkedTable<K, V>;
+  pub type LinkedTable<K, V, C = KeyOrder> = BaseLinkedTable<K, V, C>;
 
   /// An memory table for multiple version [`OrderWal`] or [`OrderWalReader`] based on [`arena::MultipleVersionTable`](BaseArenaTable).
-  pub type ArenaTable<K, V> = BaseArenaTable<K, V>;
+  pub type ArenaTable<K, V, C = KeyOrder> = BaseArenaTable<K, V, C>;
+
+  /// An memory table for multiple version [`OrderWal`] or [`OrderWalReader`] based on [`btree::MultipleVersionTable`](BaseBTreeTable).
+  pub type BTreeTable<K, V, C = KeyOrder> = BaseBTreeTable<K, V, C>;
+
+  /// An memory table for multiple version [`OrderWal`] or [`OrderWalReader`] based on [`art::MultipleVersionTable`](BaseArtTable),
+  /// the key type must implement [`LexicographicKey`].
+  pub type ArtTable<K, V> = BaseArtTable<K, V>;
+
+  /// An memory table for multiple version [`OrderWal`] or [`OrderWalReader`] based on [`sorted::MultipleVersionTable`](BaseSortedTable),
+  /// which suits the [`OrderWalReader`] best.
+  pub type SortedTable<K, V, C = KeyOrder> = BaseSortedTable<K, V, C>;
 
   /// An memory table for multiple version [`OrderWal`] or [`OrderWalReader`] based on [`alternative::MultipleVersionTable`](BaseAlternativeTable).
-  pub type AlternativeTable<K, V> = BaseAlternativeTable<K, V>;
+  pub type AlternativeTable<K, V, C = KeyOrder> = BaseAlternativeTable<K, V, C>;
 
   /// The default memory table used by [`OrderWal`] or [`OrderWalReader`].
   #[cfg(feature = "std")]
@@ -111,7 +144,7 @@ pub mod multiple_version {
 
   /// The default memory table used by [`OrderWal`] or [`OrderWalReader`].
   #[cfg(not(feature = "std"))]
-  pub type DefaultTable<K, V> = ArenaTable<K, V>;
+  pub type DefaultTable<K, V> = BTreeTable<K, V>;
 
   /// A multiple versioned generic ordered write-ahead log implementation for multiple threads environments.
   ///
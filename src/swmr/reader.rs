@@ -57,9 +57,7 @@ where
   }
 
   #[inline]
-  fn from_core(core: Self::Wal) -> Self {
-    Self(OrderWal {
-      core: Arc::new(core),
-    })
+  fn from_shared_core(core: Arc<Self::Wal>) -> Self {
+    Self(OrderWal { core })
   }
 }
@@ -8,6 +8,7 @@ use std::{
   vec::Vec,
 };
 
+use crate::types::KeyOrder;
 use base::{AlternativeTable, OrderWal, OrderWalReader};
 use dbutils::{
   equivalent::{Comparable, Equivalent},
@@ -154,11 +155,43 @@ macro_rules! expand_unit_tests {
 type OrderWalAlternativeTable<K, V> = OrderWal<K, V, AlternativeTable<K, V>>;
 type OrderWalReaderAlternativeTable<K, V> = OrderWalReader<K, V, AlternativeTable<K, V>>;
 
+type OrderWalBTreeTable<K, V> = OrderWal<K, V, base::BTreeTable<K, V>>;
+
+type OrderWalSortedTable<K, V> = OrderWal<K, V, base::SortedTable<K, V>>;
+
+type MultipleVersionOrderWalBTreeTable<K, V> =
+  multiple_version::OrderWal<K, V, multiple_version::BTreeTable<K, V>>;
+
+type MultipleVersionOrderWalArtTable<K, V> =
+  multiple_version::OrderWal<K, V, multiple_version::ArtTable<K, V>>;
+
+type MultipleVersionOrderWalSortedTable<K, V> =
+  multiple_version::OrderWal<K, V, multiple_version::SortedTable<K, V>>;
+
 type MultipleVersionOrderWalAlternativeTable<K, V> =
   multiple_version::OrderWal<K, V, multiple_version::AlternativeTable<K, V>>;
 type MultipleVersionOrderWalReaderAlternativeTable<K, V> =
   multiple_version::OrderWalReader<K, V, multiple_version::AlternativeTable<K, V>>;
 
+/// Adds the little-endian `u32` operands to the existing value.
+struct Counter;
+
+impl crate::types::MergeOperator for Counter {
+  fn name(&self) -> &str {
+    "counter"
+  }
+
+  fn full_merge(&self, _: &[u8], existing: Option<&[u8]>, operands: &[&[u8]]) -> Vec<u8> {
+    let decode = |src: &[u8]| u32::from_le_bytes(src.try_into().unwrap());
+    operands
+      .iter()
+      .map(|op| decode(op))
+      .fold(existing.map_or(0, decode), u32::wrapping_add)
+      .to_le_bytes()
+      .to_vec()
+  }
+}
+
 #[doc(hidden)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Person {
@@ -308,14 +341,28 @@ impl<'a> TypeRef<'a> for PersonRef<'a> {
   }
 }
 
-impl PersonRef<'_> {
-  #[cfg(test)]
-  #[allow(dead_code)]
-  fn encode_into_vec(&self) -> Result<Vec<u8>, dbutils::error::InsufficientBuffer> {
-    let mut buf = vec![0; encoded_u64_varint_len(self.id) + self.name.len()];
-    let id_size = encode_u64_varint(self.id, &mut buf)?;
+impl Type for PersonRef<'_> {
+  type Ref<'a> = PersonRef<'a>;
+  type Error = dbutils::error::InsufficientBuffer;
+
+  fn encoded_len(&self) -> usize {
+    encoded_u64_varint_len(self.id) + self.name.len()
+  }
+
+  fn encode(&self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+    let id_size = encode_u64_varint(self.id, buf)?;
     buf[id_size..].copy_from_slice(self.name.as_bytes());
-    Ok(buf)
+    Ok(id_size + self.name.len())
+  }
+
+  #[inline]
+  fn encode_to_buffer(
+    &self,
+    buf: &mut dbutils::buffer::VacantBuffer<'_>,
+  ) -> Result<usize, Self::Error> {
+    let id_size = buf.put_u64_varint(self.id)?;
+    buf.put_slice_unchecked(self.name.as_bytes());
+    Ok(id_size + self.name.len())
   }
 }
 
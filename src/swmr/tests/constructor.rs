@@ -10,7 +10,7 @@ use super::*;
 
 fn zero_reserved<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -25,7 +25,7 @@ where
 
 fn reserved<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -111,3 +111,55 @@ fn reopen_wrong_kind() {
   };
   assert!(matches!(err, crate::error::Error::KindMismatch { .. }));
 }
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn reopen_legacy_header() {
+  use crate::{options::versioned_arena_options, Builder, LEGACY_VERSION};
+  use rarena_allocator::{sync::Arena, Allocator};
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_reopen_legacy_header");
+
+  // A WAL created before the comparator id was recorded in the header.
+  unsafe {
+    let arena = versioned_arena_options(LEGACY_VERSION, 4)
+      .with_capacity(MB)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<Arena, _>(&path)
+      .unwrap();
+    let slice = arena.reserved_slice_mut();
+    slice[..5].copy_from_slice(b"order");
+    slice[5] = crate::types::Kind::Plain as u8;
+    slice[6..8].copy_from_slice(&0u16.to_le_bytes());
+    slice[8..].copy_from_slice(b"al8n");
+    arena.flush().unwrap();
+  }
+
+  let mut wal = unsafe {
+    Builder::new()
+      .with_reserved(4)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, u32>, _>(&path)
+      .unwrap()
+  };
+  unsafe {
+    assert_eq!(wal.reserved_slice(), b"al8n");
+  }
+  for i in 0..10u32 {
+    wal.insert(&i, &i).unwrap();
+  }
+  drop(wal);
+
+  let wal = unsafe {
+    Builder::new()
+      .with_reserved(4)
+      .map::<OrderWalReader<u32, u32>, _>(&path)
+      .unwrap()
+  };
+  assert!(wal.iter().map(|ent| *ent.key()).eq(0..10u32));
+}
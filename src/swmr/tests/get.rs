@@ -2,6 +2,7 @@ use base::OrderWal;
 
 use dbutils::{buffer::VacantBuffer, types::MaybeStructured};
 
+use core::{ops::Bound, time::Duration};
 use std::collections::BTreeMap;
 
 use crate::{
@@ -14,7 +15,7 @@ use super::*;
 
 fn first<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -42,7 +43,7 @@ where
 
 fn last<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -70,7 +71,7 @@ where
 #[allow(clippy::needless_borrows_for_generic_args)]
 fn insert<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -98,7 +99,7 @@ where
 
 fn insert_with_value_builder<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -129,23 +130,20 @@ where
 #[allow(clippy::needless_borrows_for_generic_args)]
 fn insert_with_key_builder<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
   let people = (0..100)
     .map(|_| {
       let p = Person::random();
-      let pvec = p.to_vec();
       let v = std::format!("My name is {}", p.name);
-      unsafe {
-        wal
-          .insert_with_key_builder(
-            KeyBuilder::once(p.encoded_len(), |buf| p.encode_to_buffer(buf)),
-            &v,
-          )
-          .unwrap();
-      }
+      wal
+        .insert_with_key_builder(
+          KeyBuilder::once(p.encoded_len(), |buf| p.encode_to_buffer(buf)),
+          &v,
+        )
+        .unwrap();
       (p, v)
     })
     .collect::<Vec<_>>();
@@ -164,7 +162,7 @@ where
 
 fn insert_with_bytes<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -195,7 +193,7 @@ where
 
 fn insert_with_builders<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a> + std::fmt::Debug,
   M::Error: std::fmt::Debug,
 {
@@ -232,6 +230,141 @@ where
   }
 }
 
+fn bloom_filter<M>(wal: &mut OrderWal<u32, u32, M>)
+where
+  M: Memtable<Key = u32, Value = u32, Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  assert_eq!(wal.bloom_filter_stats().unwrap(), Default::default());
+
+  // More keys than the first layer of the filter is sized for.
+  for i in (0..4000u32).step_by(2) {
+    wal.insert(&i, &i).unwrap();
+  }
+  // Overwrites and removals keep the keys in the filter.
+  wal.insert(&0, &1).unwrap();
+  wal.remove(&2).unwrap();
+
+  let mut misses = 0;
+  for i in 0..4000u32 {
+    let key = i.to_le_bytes();
+    unsafe {
+      let ent = wal.get_by_bytes(&key);
+      assert_eq!(ent.is_some(), i % 2 == 0 && i != 2);
+      assert_eq!(wal.contains_key_by_bytes(&key), i % 2 == 0 && i != 2);
+    }
+    // The typed lookups consult the filter as well.
+    assert_eq!(wal.get(&i).is_some(), i % 2 == 0 && i != 2);
+    assert_eq!(wal.contains_key(&i), i % 2 == 0 && i != 2);
+    if i % 2 == 1 {
+      misses += 4;
+    }
+  }
+
+  let stats = wal.bloom_filter_stats().unwrap();
+  assert_eq!(stats.hits() + stats.misses(), 16000);
+  // 10 bits per key keeps the false positive rate around 1%.
+  assert!(stats.misses() > misses * 9 / 10);
+  assert!(stats.misses() <= misses);
+}
+
+const ALIVE: Duration = Duration::from_secs(3600);
+
+fn ttl<M>(wal: &mut OrderWal<u32, u32, M>)
+where
+  M: Memtable<Key = u32, Value = u32, Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  // The even keys are expired as soon as they are written.
+  for i in 0..12u32 {
+    let ttl = if i % 2 == 0 { Duration::ZERO } else { ALIVE };
+    wal.insert_with_ttl(&i, &i, ttl).unwrap();
+  }
+  wal.insert(&12, &12).unwrap();
+  wal.insert_with_ttl(&13, &13, Duration::ZERO).unwrap();
+
+  let alive = [1u32, 3, 5, 7, 9, 11, 12];
+  for i in 0..14u32 {
+    assert_eq!(
+      wal.get(&i).map(|ent| *ent.value()),
+      alive.contains(&i).then_some(i)
+    );
+    assert_eq!(wal.contains_key(&i), alive.contains(&i));
+  }
+  assert_eq!(wal.len(), alive.len());
+  assert!(wal.iter().map(|ent| *ent.key()).eq(alive));
+  assert!(wal
+    .iter()
+    .rev()
+    .map(|ent| *ent.key())
+    .eq(alive.into_iter().rev()));
+  assert!(wal.range(2..=6).map(|ent| *ent.key()).eq([3, 5]));
+  assert_eq!(wal.first().unwrap().key(), &1);
+  assert_eq!(wal.last().unwrap().key(), &12);
+  assert_eq!(wal.upper_bound(Bound::Included(&8)).unwrap().key(), &7);
+  assert_eq!(wal.lower_bound(Bound::Included(&4)).unwrap().key(), &5);
+
+  // A write without a ttl replaces the expired entry.
+  wal.insert(&0, &100).unwrap();
+  assert_eq!(wal.get(&0).unwrap().value(), &100);
+  wal.insert_with_ttl(&12, &12, Duration::ZERO).unwrap();
+  assert!(wal.get(&12).is_none());
+  assert_eq!(wal.last().unwrap().key(), &11);
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn ttl_reopen() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_ttl_reopen");
+  let open = || unsafe {
+    crate::Builder::new()
+      .with_capacity(MB)
+      .with_create(true)
+      .with_read(true)
+      .with_write(true)
+      .with_persisted_index(true)
+      .map_mut::<OrderWal<u32, u32>, _>(&path)
+      .unwrap()
+  };
+
+  let mut wal = open();
+  for i in 0..100u32 {
+    let ttl = if i % 2 == 0 { Duration::ZERO } else { ALIVE };
+    wal.insert_with_ttl(&i, &i, ttl).unwrap();
+  }
+  wal.persist_index().unwrap();
+  // The records after the index are replayed from the WAL.
+  for i in 100..200u32 {
+    let ttl = if i % 2 == 0 { Duration::ZERO } else { ALIVE };
+    wal.insert_with_ttl(&i, &i, ttl).unwrap();
+  }
+  drop(wal);
+
+  let wal = open();
+  assert_eq!(wal.len(), 100);
+  for i in 0..200u32 {
+    assert_eq!(
+      wal.get(&i).map(|ent| *ent.value()),
+      (i % 2 == 1).then_some(i)
+    );
+  }
+  assert!(wal.iter().map(|ent| *ent.key()).eq((1..200).step_by(2)));
+  drop(wal);
+
+  let wal = unsafe {
+    crate::Builder::new()
+      .map::<OrderWalReader<u32, u32>, _>(&path)
+      .unwrap()
+  };
+  assert_eq!(wal.len(), 100);
+  assert_eq!(wal.first().unwrap().key(), &1);
+  assert_eq!(wal.last().unwrap().key(), &199);
+}
+
 #[cfg(feature = "std")]
 expand_unit_tests!("linked": OrderWalAlternativeTable<Person, String> [TableOptions::Linked]: crate::memtable::alternative::Table<_, _> {
   first,
@@ -252,3 +385,61 @@ expand_unit_tests!("arena": OrderWalAlternativeTable<Person, String> [TableOptio
   insert_with_bytes,
   insert_with_builders,
 });
+
+expand_unit_tests!("arena": OrderWalAlternativeTable<u32, u32> [TableOptions::Arena(Default::default())]: crate::memtable::alternative::Table<_, _> {
+  bloom_filter(crate::Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Arena(Default::default()))
+    .with_bloom_filter_bits_per_key(Some(10))),
+});
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn bloom_filter_reopen() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_bloom_filter_reopen");
+
+  let mut wal = unsafe {
+    crate::Builder::new()
+      .with_capacity(MB)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, u32>, _>(&path)
+      .unwrap()
+  };
+  assert!(wal.bloom_filter_stats().is_none());
+  for i in (0..100u32).step_by(2) {
+    wal.insert(&i, &i).unwrap();
+  }
+  drop(wal);
+
+  let wal = unsafe {
+    crate::Builder::new()
+      .with_bloom_filter_bits_per_key(Some(10))
+      .map::<OrderWalReader<u32, u32>, _>(&path)
+      .unwrap()
+  };
+  for i in 0..100u32 {
+    unsafe {
+      assert_eq!(wal.contains_key_by_bytes(&i.to_le_bytes()), i % 2 == 0);
+    }
+  }
+  let stats = wal.bloom_filter_stats().unwrap();
+  assert_eq!(stats.hits() + stats.misses(), 100);
+  assert!(stats.hits() >= 50);
+}
+
+#[cfg(feature = "std")]
+expand_unit_tests!("linked": OrderWalAlternativeTable<u32, u32> [TableOptions::Linked]: crate::memtable::alternative::Table<_, _> {
+  ttl,
+});
+
+expand_unit_tests!("arena": OrderWalAlternativeTable<u32, u32> [TableOptions::Arena(Default::default())]: crate::memtable::alternative::Table<_, _> {
+  ttl,
+});
+
+expand_unit_tests!("btree": OrderWalBTreeTable<u32, u32> [()]: crate::memtable::btree::Table<_, _> {
+  ttl,
+});
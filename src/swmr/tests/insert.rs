@@ -1,8 +1,10 @@
+use among::Among;
 use base::{Reader, Writer};
 use dbutils::{buffer::VacantBuffer, types::MaybeStructured};
 
 use crate::{
-  batch::BatchEntry,
+  batch::{BatchEntry, BatchGuard, GuardedBatch, PreparedBatch, WriteBatch},
+  error::{BatchError, Error},
   memtable::{
     alternative::{Table, TableOptions},
     Memtable, MemtableEntry,
@@ -16,7 +18,7 @@ use super::*;
 #[cfg(feature = "std")]
 fn concurrent_basic<M>(mut w: OrderWal<u32, [u8; 4], M>)
 where
-  M: Memtable<Key = u32, Value = [u8; 4]> + Send + 'static,
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -47,7 +49,7 @@ where
 #[cfg(feature = "std")]
 fn concurrent_one_key<M>(mut w: OrderWal<u32, [u8; 4], M>)
 where
-  M: Memtable<Key = u32, Value = [u8; 4]> + Send + 'static,
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -71,7 +73,7 @@ where
 
 fn insert_batch<M>(mut wal: OrderWal<Person, String, M>) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: Memtable<Key = Person, Value = String> + Send + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -127,7 +129,7 @@ fn insert_batch_with_key_builder<M>(
   mut wal: OrderWal<Person, String, M>,
 ) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: Memtable<Key = Person, Value = String> + Send + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -186,7 +188,7 @@ fn insert_batch_with_value_builder<M>(
   mut wal: OrderWal<Person, String, M>,
 ) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: Memtable<Key = Person, Value = String> + Send + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -244,7 +246,7 @@ fn insert_batch_with_builders<M>(
   mut wal: OrderWal<Person, String, M>,
 ) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: Memtable<Key = Person, Value = String> + Send + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -301,6 +303,1035 @@ where
   (rp1, output, rp2)
 }
 
+#[allow(clippy::needless_borrows_for_generic_args)]
+fn delete_range<M>(mut wal: OrderWal<u32, [u8; 4], M>) -> Vec<u32>
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  for i in 0..30u32 {
+    wal.insert(&i, &i.to_le_bytes()).unwrap();
+  }
+  wal.insert(&12, &[0; 4]).unwrap();
+
+  wal.delete_range(&10..&20).unwrap();
+  wal.delete_range(&0..&3).unwrap();
+  wal.delete_range(&25..&u32::MAX).unwrap();
+  wal.insert(&15, &15u32.to_le_bytes()).unwrap();
+
+  // The empty and the inverted ranges write nothing.
+  let remaining = wal.remaining();
+  wal.delete_range(&7..&7).unwrap();
+  wal.delete_range(&9..&4).unwrap();
+  assert_eq!(wal.remaining(), remaining);
+
+  let expected = (3..10).chain([15]).chain(20..25).collect::<Vec<_>>();
+  assert_eq!(wal.len(), expected.len());
+  assert!(!wal.is_empty());
+  assert_eq!(
+    wal.iter().map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected
+  );
+  assert_eq!(
+    wal.iter().rev().map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected.iter().rev().copied().collect::<Vec<_>>()
+  );
+  assert_eq!(
+    wal
+      .range(8u32..22)
+      .map(|ent| *ent.key())
+      .collect::<Vec<_>>(),
+    [8, 9, 15, 20, 21]
+  );
+  assert_eq!(wal.keys().count(), expected.len());
+  assert_eq!(wal.values().count(), expected.len());
+
+  assert!(wal.get(&12).is_none());
+  assert!(!wal.contains_key(&19));
+  assert!(wal.contains_key(&20));
+  assert_eq!(wal.get(&15).unwrap().value(), &15u32.to_le_bytes());
+  assert_eq!(*wal.first().unwrap().key(), 3);
+  assert_eq!(*wal.last().unwrap().key(), 24);
+  assert_eq!(
+    *wal
+      .lower_bound(core::ops::Bound::Included(&10))
+      .unwrap()
+      .key(),
+    15
+  );
+  assert_eq!(
+    *wal
+      .upper_bound(core::ops::Bound::Excluded(&15))
+      .unwrap()
+      .key(),
+    9
+  );
+
+  let reader = wal.reader();
+  assert_eq!(
+    reader.iter().map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected
+  );
+  assert!(reader.get(&12).is_none());
+
+  expected
+}
+
+#[allow(clippy::needless_borrows_for_generic_args)]
+fn overlapping_delete_ranges<M>(mut wal: OrderWal<u32, [u8; 4], M>) -> Vec<u32>
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  for i in 0..50u32 {
+    wal.insert(&i, &i.to_le_bytes()).unwrap();
+  }
+
+  wal.delete_range(&10..&30).unwrap();
+  wal.insert(&15, &15u32.to_le_bytes()).unwrap();
+  wal.insert(&25, &25u32.to_le_bytes()).unwrap();
+  // Overlaps the right part of the first range, and removes `25` written after the first range.
+  wal.delete_range(&20..&40).unwrap();
+  // Overlaps the left part of the first range.
+  wal.delete_range(&5..&12).unwrap();
+  // Covers the first range, while `15` is written after it.
+  wal.delete_range(&8..&18).unwrap();
+  wal.delete_range(&35..&35).unwrap();
+  wal.insert(&15, &15u32.to_le_bytes()).unwrap();
+  wal.insert(&22, &22u32.to_le_bytes()).unwrap();
+
+  let expected = (0..5).chain([15, 22]).chain(40..50).collect::<Vec<_>>();
+  assert_eq!(wal.len(), expected.len());
+  assert_eq!(
+    wal.iter().map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected
+  );
+  assert_eq!(
+    wal.iter().rev().map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected.iter().rev().copied().collect::<Vec<_>>()
+  );
+  for i in 0..50u32 {
+    assert_eq!(wal.contains_key(&i), expected.contains(&i));
+  }
+
+  expected
+}
+
+fn upsert<M>(mut wal: OrderWal<u32, [u8; 4], M>) -> Vec<(u32, u32)>
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  let incr = |old: Option<[u8; 4]>| {
+    let n = old.map_or(0, u32::from_le_bytes) + 1;
+    Some(ValueBuilder::new(4, move |buf: &mut VacantBuffer<'_>| {
+      buf.put_slice(&n.to_le_bytes()).map(|_| 4)
+    }))
+  };
+  // Keeps the odd counters, and removes the even ones.
+  let retain_odd = |old: Option<[u8; 4]>| {
+    old
+      .filter(|val| u32::from_le_bytes(*val) % 2 == 1)
+      .map(|val| {
+        ValueBuilder::new(4, move |buf: &mut VacantBuffer<'_>| {
+          buf.put_slice(&val).map(|_| 4)
+        })
+      })
+  };
+
+  let reader = wal.reader();
+  for i in 0..10u32 {
+    for _ in 0..=i {
+      wal.upsert(&i, incr).unwrap();
+    }
+    assert_eq!(reader.get(&i).unwrap().value(), &(i + 1).to_le_bytes());
+  }
+
+  for i in 0..10u32 {
+    wal.upsert(&i, retain_odd).unwrap();
+  }
+  wal.upsert(&100, retain_odd).unwrap();
+
+  let expected = (0..10u32)
+    .step_by(2)
+    .map(|i| (i, i + 1))
+    .collect::<Vec<_>>();
+  assert!(wal.get(&1).is_none());
+  assert!(!wal.contains_key(&100));
+  assert_eq!(
+    reader
+      .iter()
+      .map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value())))
+      .collect::<Vec<_>>(),
+    expected
+  );
+
+  expected
+}
+
+fn insert_returning<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  {
+    let ent = wal.insert_returning(&1, &[1; 4]).unwrap();
+    assert_eq!(*ent.key(), 1);
+    assert_eq!(ent.value(), &[1; 4]);
+    assert_eq!(ent.raw_key(), 1u32.to_le_bytes());
+  }
+
+  let mut batch = vec![
+    BatchEntry::new(MaybeStructured::from(&3), MaybeStructured::from(&[3; 4])),
+    BatchEntry::tombstone(MaybeStructured::from(&1)),
+    BatchEntry::new(MaybeStructured::from(&2), MaybeStructured::from(&[2; 4])),
+  ];
+  let written = wal
+    .insert_batch_returning(&mut batch)
+    .unwrap()
+    .into_iter()
+    .map(|ent| (*ent.key(), ent.value()[0]))
+    .collect::<Vec<_>>();
+  assert_eq!(written, [(3, 3), (2, 2)]);
+  assert!(!wal.contains_key(&1));
+}
+
+fn transaction<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(&0, &[0; 4]).unwrap();
+
+  let mut txn = wal.begin_transaction().unwrap();
+  wal.transaction_insert(&mut txn, &1, &[1; 4]).unwrap();
+  wal.transaction_insert(&mut txn, &2, &[2; 4]).unwrap();
+  wal.transaction_remove(&mut txn, &0).unwrap();
+  assert_eq!(txn.len(), 3);
+
+  // The entries are invisible until the transaction is committed.
+  assert!(wal.contains_key(&0));
+  assert!(!wal.contains_key(&1));
+  assert!(!wal.contains_key(&2));
+
+  let mut aborted = wal.begin_transaction().unwrap();
+  assert_ne!(aborted.id(), txn.id());
+  wal.transaction_insert(&mut aborted, &3, &[3; 4]).unwrap();
+  wal.insert(&4, &[4; 4]).unwrap();
+
+  wal.commit_transaction(txn).unwrap();
+  assert!(!wal.contains_key(&0));
+  assert_eq!(wal.get(&1).unwrap().value(), &[1; 4]);
+  assert_eq!(wal.get(&2).unwrap().value(), &[2; 4]);
+
+  wal.abort_transaction(aborted).unwrap();
+  assert!(!wal.contains_key(&3));
+
+  // The transaction is never committed, so it is discarded when the WAL is reopened.
+  let mut pending = wal.begin_transaction().unwrap();
+  wal.transaction_insert(&mut pending, &5, &[5; 4]).unwrap();
+  assert!(!wal.contains_key(&5));
+}
+
+fn reservation<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + Send + Sync + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  {
+    let mut reservation = wal.reserve(4, 4).unwrap();
+    // The reservation can be filled by another thread.
+    std::thread::scope(|s| {
+      s.spawn(|| {
+        let (key, value) = reservation.key_value_mut();
+        key.copy_from_slice(&1u32.to_le_bytes());
+        value.copy_from_slice(&[1; 4]);
+      });
+    });
+    // SAFETY: the key and value are encoded by `u32` and `[u8; 4]`.
+    unsafe { reservation.commit().unwrap() };
+  }
+  assert_eq!(wal.get(&1).unwrap().value(), &[1; 4]);
+
+  {
+    let reservation = wal.reserve(4, 4).unwrap();
+    // The reservation can be moved to another thread, filled and committed there.
+    std::thread::scope(|s| {
+      s.spawn(move || {
+        let mut reservation = reservation;
+        reservation.key_mut().copy_from_slice(&5u32.to_le_bytes());
+        reservation.value_mut().copy_from_slice(&[5; 4]);
+        // SAFETY: the key and value are encoded by `u32` and `[u8; 4]`.
+        unsafe { reservation.commit().unwrap() };
+      });
+    });
+  }
+  assert_eq!(wal.get(&5).unwrap().value(), &[5; 4]);
+
+  // The space of an aborted or dropped reservation is reclaimed.
+  let remaining = wal.remaining();
+  {
+    let mut reservation = wal.reserve(4, 4).unwrap();
+    reservation.key_mut().copy_from_slice(&2u32.to_le_bytes());
+    reservation.abort();
+  }
+  assert_eq!(wal.remaining(), remaining);
+  {
+    let mut reservation = wal.reserve(4, 4).unwrap();
+    reservation.key_mut().copy_from_slice(&3u32.to_le_bytes());
+  }
+  assert_eq!(wal.remaining(), remaining);
+  assert!(!wal.contains_key(&2));
+  assert!(!wal.contains_key(&3));
+
+  wal.insert(&4, &[4; 4]).unwrap();
+}
+
+fn prepared_batch<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + Send + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(&0, &[0; 4]).unwrap();
+  wal.insert(&1, &[1; 4]).unwrap();
+
+  let mut batch = spawn(|| {
+    let mut batch = PreparedBatch::<M>::new();
+    for i in 2..5u32 {
+      batch.insert(&i, &[i as u8; 4]).unwrap();
+    }
+    batch.remove(&0).unwrap();
+    batch
+  })
+  .join()
+  .unwrap();
+  assert_eq!(batch.len(), 4);
+
+  wal.insert_prepared_batch(&batch).unwrap();
+  assert!(!wal.contains_key(&0));
+  for i in 1..5u32 {
+    assert_eq!(wal.get(&i).unwrap().value(), &[i as u8; 4]);
+  }
+
+  // The batch can be reused after clearing.
+  batch.clear();
+  assert!(batch.is_empty());
+  assert_eq!(batch.encoded_size(), 0);
+  batch.insert(&1, &[11; 4]).unwrap();
+  batch.insert(&5, &[5; 4]).unwrap();
+  let retry = batch.clone();
+  wal.insert_prepared_batch(&batch).unwrap();
+  wal.insert_prepared_batch(&retry).unwrap();
+  assert_eq!(wal.get(&1).unwrap().value(), &[11; 4]);
+  assert_eq!(wal.get(&5).unwrap().value(), &[5; 4]);
+}
+
+fn write_batch<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  for i in 0..6u32 {
+    wal.insert(&i, &[i as u8; 4]).unwrap();
+  }
+
+  // The size limits are checked when the entries are added.
+  let mut batch = WriteBatch::<M>::new().with_maximum_value_size(3);
+  assert!(matches!(
+    batch.insert(&10, &[0; 4]),
+    Err(Among::Right(Error::ValueTooLarge { size: 4, .. }))
+  ));
+  let mut batch = WriteBatch::<M>::new().with_maximum_key_size(crate::KeySize::with(3));
+  assert!(matches!(
+    batch.remove(&10),
+    Err(skl::either::Either::Right(Error::KeyTooLarge {
+      size: 4,
+      ..
+    }))
+  ));
+  assert!(batch.is_empty());
+
+  let mut batch = WriteBatch::new()
+    .with_maximum_key_size(wal.maximum_key_size())
+    .with_maximum_value_size(wal.maximum_value_size());
+
+  batch.insert(&7, &[7; 4]).unwrap();
+  batch.insert(&1, &[0; 4]).unwrap();
+  batch.insert(&1, &[11; 4]).unwrap();
+  batch.remove(&2).unwrap();
+  batch.insert(&3, &[13; 4]).unwrap();
+  batch.remove(&6).unwrap();
+  assert_eq!(batch.len(), 5);
+  assert_eq!(batch.size(), 4 * 5 + 4 * 3);
+
+  // The batch shadows the entries in the WAL.
+  assert_eq!(batch.get(&wal, &0).unwrap(), [0; 4]);
+  assert_eq!(batch.get(&wal, &1).unwrap(), [11; 4]);
+  assert!(batch.get(&wal, &2).is_none());
+  assert_eq!(batch.get(&wal, &7).unwrap(), [7; 4]);
+  assert!(batch.get(&wal, &6).is_none());
+
+  let merged = batch
+    .range(&wal, 1..=7)
+    .map(|(k, v)| (k, v[0]))
+    .collect::<Vec<_>>();
+  assert_eq!(merged, [(1, 11), (3, 13), (4, 4), (5, 5), (7, 7)]);
+  let merged = batch
+    .range::<u32, _, _>(&wal, ..)
+    .map(|(k, _)| k)
+    .collect::<Vec<_>>();
+  assert_eq!(merged, [0, 1, 3, 4, 5, 7]);
+  assert_eq!(
+    batch
+      .range(
+        &wal,
+        (core::ops::Bound::Excluded(3), core::ops::Bound::Excluded(5))
+      )
+      .count(),
+    1
+  );
+
+  wal.insert_batch_with_builders(&mut batch).unwrap();
+  let entries = wal
+    .iter()
+    .map(|ent| (*ent.key(), ent.value()[0]))
+    .collect::<Vec<_>>();
+  assert_eq!(entries, [(0, 0), (1, 11), (3, 13), (4, 4), (5, 5), (7, 7)]);
+}
+
+fn batch_guards<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(&1, &[1; 4]).unwrap();
+  wal.insert(&2, &[2; 4]).unwrap();
+  wal.remove(&2).unwrap();
+  let remaining = wal.remaining();
+
+  // The second guard fails, so nothing is written.
+  let mut batch = GuardedBatch::new(vec![
+    BatchEntry::new(MaybeStructured::from(&3), MaybeStructured::from(&[3; 4])),
+    BatchEntry::tombstone(MaybeStructured::from(&1)),
+  ])
+  .with_guard(BatchGuard::present(MaybeStructured::from(&1)))
+  .with_guard(BatchGuard::present(MaybeStructured::from(&2)));
+  let err = wal.insert_batch(&mut batch).unwrap_err();
+  assert!(matches!(
+    err,
+    Among::Right(Error::Batch(BatchError::GuardFailed(1)))
+  ));
+  assert_eq!(wal.remaining(), remaining);
+  assert!(!wal.contains_key(&3));
+  assert!(wal.contains_key(&1));
+
+  let mut batch = GuardedBatch::new(vec![
+    BatchEntry::new(MaybeStructured::from(&3), MaybeStructured::from(&[3; 4])),
+    BatchEntry::tombstone(MaybeStructured::from(&1)),
+  ])
+  .with_guard(BatchGuard::present(MaybeStructured::from(&1)))
+  .with_guard(BatchGuard::absent(MaybeStructured::from(&2)))
+  .with_guard(BatchGuard::absent(MaybeStructured::from(&3)));
+  wal.insert_batch(&mut batch).unwrap();
+  assert_eq!(wal.get(&3).unwrap().value(), &[3; 4]);
+  assert!(!wal.contains_key(&1));
+
+  let mut batch = GuardedBatch::new(vec![BatchEntry::new(
+    MaybeStructured::from(&3),
+    MaybeStructured::from(&[4; 4]),
+  )])
+  .with_guard(BatchGuard::absent(MaybeStructured::from(&3)));
+  let err = wal.insert_batch(&mut batch).unwrap_err();
+  assert!(matches!(
+    err,
+    Among::Right(Error::Batch(BatchError::GuardFailed(0)))
+  ));
+
+  // The keys in the range deletions are absent.
+  wal.delete_range(&3..&4).unwrap();
+  let mut batch = GuardedBatch::new(vec![BatchEntry::new(
+    MaybeStructured::from(&3),
+    MaybeStructured::from(&[4; 4]),
+  )])
+  .with_guard(BatchGuard::absent(MaybeStructured::from(&3)));
+  wal.insert_batch(&mut batch).unwrap();
+  assert_eq!(wal.get(&3).unwrap().value(), &[4; 4]);
+}
+
+fn compare_and_swap<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  assert!(wal.insert_if_absent(&1, &[1; 4]).unwrap());
+  assert!(!wal.insert_if_absent(&1, &[2; 4]).unwrap());
+  assert_eq!(wal.get(&1).unwrap().value(), &[1; 4]);
+
+  assert!(!wal.compare_and_swap(&1, &[2; 4], &[3; 4]).unwrap());
+  assert_eq!(wal.get(&1).unwrap().value(), &[1; 4]);
+  assert!(wal.compare_and_swap(&1, &[1; 4], &[3; 4]).unwrap());
+  assert_eq!(wal.get(&1).unwrap().value(), &[3; 4]);
+  assert!(!wal.compare_and_swap(&2, &[0; 4], &[3; 4]).unwrap());
+  assert!(!wal.contains_key(&2));
+
+  // The removed keys are absent.
+  wal.remove(&1).unwrap();
+  assert!(!wal.compare_and_swap(&1, &[3; 4], &[4; 4]).unwrap());
+  assert!(wal.insert_if_absent(&1, &[5; 4]).unwrap());
+  wal.delete_range(&0..&2).unwrap();
+  assert!(wal.insert_if_absent(&1, &[6; 4]).unwrap());
+
+  let reader = wal.reader();
+  assert_eq!(reader.get(&1).unwrap().value(), &[6; 4]);
+}
+
+fn merge<M>(wal: &mut OrderWal<u32, [u8; 4], M>)
+where
+  M: Memtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(&1, &10u32.to_le_bytes()).unwrap();
+  for i in 1..=3u32 {
+    wal.merge(&1, &i.to_le_bytes()).unwrap();
+    wal.merge(&2, &i.to_le_bytes()).unwrap();
+  }
+  assert_eq!(wal.len(), 2);
+  assert_eq!(wal.get(&1).unwrap().value(), &16u32.to_le_bytes());
+  assert_eq!(wal.get(&2).unwrap().value(), &6u32.to_le_bytes());
+
+  // The operands are folded over the memoized value.
+  wal.merge(&1, &4u32.to_le_bytes()).unwrap();
+  assert_eq!(wal.get(&1).unwrap().value(), &20u32.to_le_bytes());
+
+  // The values removed by range tombstones are not folded.
+  wal.insert(&3, &100u32.to_le_bytes()).unwrap();
+  wal.delete_range(&3..&4).unwrap();
+  wal.merge(&3, &1u32.to_le_bytes()).unwrap();
+
+  let expected = [(1, 20u32), (2, 6), (3, 1)];
+  assert_eq!(
+    wal
+      .iter()
+      .map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value())))
+      .collect::<Vec<_>>(),
+    expected
+  );
+  let reader = wal.reader();
+  assert_eq!(
+    reader
+      .iter()
+      .rev()
+      .map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value())))
+      .collect::<Vec<_>>(),
+    expected.iter().rev().copied().collect::<Vec<_>>()
+  );
+}
+
+#[cfg(feature = "std")]
+expand_unit_tests!("linked": OrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Linked]: Table<_, _> {
+  merge(Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Linked)
+    .with_merge_operator(Some(&Counter))),
+});
+
+expand_unit_tests!("arena": OrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Arena(Default::default())]: Table<_, _> {
+  merge(Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Arena(Default::default()))
+    .with_merge_operator(Some(&Counter))),
+});
+
+#[test]
+fn delete_range_end_size() {
+  use skl::{either::Either, KeySize};
+
+  let mut wal = Builder::new()
+    .with_capacity(MB)
+    .with_maximum_key_size(KeySize::with(16))
+    .with_maximum_value_size(4)
+    .alloc::<OrderWal<[u8], [u8]>>()
+    .unwrap();
+
+  // The end bound is written as the value, so it is limited by the maximum value size as well.
+  let err = wal
+    .delete_range([0u8; 2].as_slice()..[1u8; 8].as_slice())
+    .unwrap_err();
+  assert!(matches!(
+    err,
+    Either::Right(Error::KeyTooLarge {
+      size: 8,
+      maximum_key_size: 4
+    })
+  ));
+
+  wal
+    .delete_range([0u8; 8].as_slice()..[1u8; 4].as_slice())
+    .unwrap();
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn merge_reopen() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_merge_reopen");
+  let mut wal = unsafe {
+    Builder::new()
+      .with_capacity(MB)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  assert!(matches!(
+    wal.merge(&1, &1u32.to_le_bytes()).unwrap_err(),
+    among::Among::Right(crate::error::Error::MissingMergeOperator)
+  ));
+  drop(wal);
+
+  let mut wal = unsafe {
+    Builder::new()
+      .with_merge_operator(Some(&Counter))
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  wal.insert(&1, &10u32.to_le_bytes()).unwrap();
+  for i in 1..=3u32 {
+    wal.merge(&1, &i.to_le_bytes()).unwrap();
+  }
+  wal.delete_range(&1..&2).unwrap();
+  wal.merge(&1, &4u32.to_le_bytes()).unwrap();
+  wal.merge(&2, &5u32.to_le_bytes()).unwrap();
+  drop(wal);
+
+  let wal = unsafe {
+    Builder::new()
+      .with_merge_operator(Some(&Counter))
+      .map::<OrderWalReader<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  assert_eq!(wal.get(&1).unwrap().value(), &4u32.to_le_bytes());
+  assert_eq!(wal.get(&2).unwrap().value(), &5u32.to_le_bytes());
+
+  let err = unsafe {
+    Builder::new()
+      .map::<OrderWalReader<u32, [u8; 4]>, _>(&path)
+      .unwrap_err()
+  };
+  assert!(matches!(err, crate::error::Error::MissingMergeOperator));
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn transaction_delete_range() {
+  use base::{ArenaTable, ArenaTableOptions};
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_transaction_delete_range");
+  let opts = ArenaTableOptions::new().with_capacity(2048);
+  let open = || unsafe {
+    Builder::<ArenaTable<u32, [u8; 4]>>::new()
+      .with_memtable_options(opts)
+      .map::<OrderWalReader<u32, [u8; 4], ArenaTable<u32, [u8; 4]>>, _>(&path)
+      .unwrap()
+  };
+  let check = |wal: &OrderWalReader<u32, [u8; 4], ArenaTable<u32, [u8; 4]>>| {
+    // The range tombstone is written before the commit marker, so it does not remove the entries of the transaction.
+    for i in 0..200u32 {
+      assert_eq!(wal.get(&i).unwrap().value(), &[1; 4]);
+    }
+    assert!(!wal.contains_key(&200));
+    assert_eq!(wal.get(&1000).unwrap().value(), &[2; 4]);
+    assert_eq!(wal.iter().count(), 201);
+  };
+
+  let mut wal = unsafe {
+    Builder::<ArenaTable<u32, [u8; 4]>>::new()
+      .with_capacity(MB)
+      .with_memtable_options(opts)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, [u8; 4], ArenaTable<u32, [u8; 4]>>, _>(&path)
+      .unwrap()
+  };
+  wal.insert(&200, &[0; 4]).unwrap();
+
+  // Far more entries than the initial capacity of the memtable can hold.
+  let mut txn = wal.begin_transaction().unwrap();
+  for i in 0..200u32 {
+    wal.transaction_insert(&mut txn, &i, &[1; 4]).unwrap();
+  }
+  wal.delete_range(&0..&1000).unwrap();
+  wal.insert(&1000, &[2; 4]).unwrap();
+  wal.commit_transaction(txn).unwrap();
+  check(&wal.reader());
+  drop(wal);
+
+  check(&open());
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn arena_table_grows() {
+  use base::{ArenaTable, ArenaTableOptions};
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_arena_table_grows");
+  let opts = ArenaTableOptions::new().with_capacity(2048);
+
+  let mut wal = unsafe {
+    Builder::<ArenaTable<u32, [u8; 4]>>::new()
+      .with_capacity(MB)
+      .with_memtable_options(opts)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, [u8; 4], ArenaTable<u32, [u8; 4]>>, _>(&path)
+      .unwrap()
+  };
+  wal.insert(&0, &[0; 4]).unwrap();
+  let reader = wal.reader();
+  let first = reader.first().unwrap();
+
+  // Far more entries than the initial capacity can hold.
+  for i in 1..1000u32 {
+    wal.insert(&i, &i.to_le_bytes()).unwrap();
+  }
+  for i in (0..1000u32).step_by(2) {
+    wal.remove(&i).unwrap();
+  }
+
+  // The entries read before growing are still valid.
+  assert_eq!(*first.key(), 0);
+  assert_eq!(first.value(), &[0; 4]);
+  assert_eq!(reader.iter().count(), 500);
+  drop(wal);
+
+  // The replay is not limited by the initial capacity either.
+  let wal = unsafe {
+    Builder::<ArenaTable<u32, [u8; 4]>>::new()
+      .with_memtable_options(opts)
+      .map::<OrderWalReader<u32, [u8; 4], ArenaTable<u32, [u8; 4]>>, _>(&path)
+      .unwrap()
+  };
+  assert_eq!(wal.iter().count(), 500);
+  for i in 0..1000u32 {
+    assert_eq!(wal.contains_key(&i), i % 2 == 1);
+  }
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn sorted_table_reopen() {
+  use base::SortedTable;
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_sorted_table_reopen");
+
+  let mut wal = unsafe {
+    Builder::new()
+      .with_capacity(MB)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  // Insert in a scattered order, then overwrite and remove some of the keys.
+  for i in 0..1000u32 {
+    let k = (i * 7919) % 1000;
+    wal.insert(&k, &k.to_le_bytes()).unwrap();
+  }
+  for i in (0..1000u32).step_by(3) {
+    wal.insert(&i, &(i + 1).to_le_bytes()).unwrap();
+  }
+  for i in (0..1000u32).step_by(5) {
+    wal.remove(&i).unwrap();
+  }
+  wal.insert(&0, &0u32.to_le_bytes()).unwrap();
+  drop(wal);
+
+  let wal = unsafe {
+    Builder::<SortedTable<u32, [u8; 4]>>::new()
+      .map::<OrderWalReader<u32, [u8; 4], SortedTable<u32, [u8; 4]>>, _>(&path)
+      .unwrap()
+  };
+  let expected = |i: u32| match i {
+    0 => Some(0),
+    i if i % 5 == 0 => None,
+    i if i % 3 == 0 => Some(i + 1),
+    i => Some(i),
+  };
+  let keys = (0..1000u32)
+    .filter(|i| expected(*i).is_some())
+    .collect::<Vec<_>>();
+
+  assert_eq!(wal.len(), keys.len());
+  for i in 0..1000u32 {
+    assert_eq!(
+      wal.get(&i).map(|ent| *ent.value()),
+      expected(i).map(u32::to_le_bytes)
+    );
+  }
+  assert!(wal.iter().map(|ent| *ent.key()).eq(keys.iter().copied()));
+  assert!(wal
+    .iter()
+    .rev()
+    .map(|ent| *ent.key())
+    .eq(keys.iter().rev().copied()));
+  assert_eq!(
+    *wal
+      .lower_bound(core::ops::Bound::Excluded(&5))
+      .unwrap()
+      .key(),
+    6
+  );
+  assert_eq!(
+    *wal
+      .upper_bound(core::ops::Bound::Included(&5))
+      .unwrap()
+      .key(),
+    4
+  );
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn persisted_index_reopen() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_persisted_index_reopen");
+  let index = dir.path().join("test_persisted_index_reopen.index");
+  let open = |persisted_index: bool| unsafe {
+    Builder::new()
+      .with_capacity(MB)
+      .with_create(true)
+      .with_read(true)
+      .with_write(true)
+      .with_persisted_index(persisted_index)
+      .map_mut::<OrderWal<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  let expected = |i: u32| match i {
+    1000 => Some([1; 4]),
+    i if i < 100 && i % 2 == 1 => Some(i.to_le_bytes()),
+    _ => None,
+  };
+
+  let mut wal = open(false);
+  for i in 0..100u32 {
+    wal.insert(&i, &i.to_le_bytes()).unwrap();
+  }
+  let mut txn = wal.begin_transaction().unwrap();
+  wal.transaction_insert(&mut txn, &1000, &[1; 4]).unwrap();
+  wal.persist_index().unwrap();
+  assert!(index.exists());
+
+  // The records after the index are replayed, including the commit of the indexed transaction.
+  wal.commit_transaction(txn).unwrap();
+  for i in (0..100u32).step_by(2) {
+    wal.remove(&i).unwrap();
+  }
+  drop(wal);
+
+  let mut wal = open(true);
+  assert_eq!(wal.len(), 51);
+  for i in 0..1001u32 {
+    assert_eq!(wal.get(&i).map(|ent| *ent.value()), expected(i));
+  }
+  wal.insert(&1001, &[2; 4]).unwrap();
+  wal.remove(&1001).unwrap();
+  // The index is brought up to date when the WAL is dropped.
+  drop(wal);
+
+  let wal = unsafe {
+    Builder::new()
+      .with_persisted_index(true)
+      .map::<OrderWalReader<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  assert_eq!(wal.len(), 51);
+  for i in 0..1002u32 {
+    assert_eq!(wal.get(&i).map(|ent| *ent.value()), expected(i));
+  }
+  drop(wal);
+
+  // The index which does not match the WAL is ignored.
+  std::fs::write(&index, b"garbage").unwrap();
+  let wal = open(true);
+  assert_eq!(wal.len(), 51);
+  for i in 0..1002u32 {
+    assert_eq!(wal.get(&i).map(|ent| *ent.value()), expected(i));
+  }
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn parallel_replay() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_parallel_replay");
+  let open_mut = |create_new: bool| unsafe {
+    Builder::new()
+      .with_capacity(4 * MB)
+      .with_create_new(create_new)
+      .with_read(true)
+      .with_write(true)
+      .with_merge_operator(Some(&Counter))
+      .map_mut::<OrderWal<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  let open = |threads: usize| unsafe {
+    Builder::new()
+      .with_merge_operator(Some(&Counter))
+      .with_replay_threads(threads)
+      .map::<OrderWalReader<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  let entries = |wal: &OrderWalReader<u32, [u8; 4]>| {
+    wal
+      .iter()
+      .map(|ent| (*ent.key(), *ent.value()))
+      .collect::<Vec<_>>()
+  };
+
+  let mut wal = open_mut(true);
+  // Every key is overwritten several times, the last write must win.
+  for round in 0..4u32 {
+    for i in 0..2000u32 {
+      wal.insert(&i, &(i + round).to_le_bytes()).unwrap();
+    }
+  }
+  for i in (0..2000u32).step_by(3) {
+    wal.remove(&i).unwrap();
+  }
+  let mut txn = wal.begin_transaction().unwrap();
+  wal.transaction_insert(&mut txn, &3, &[3; 4]).unwrap();
+  wal.transaction_remove(&mut txn, &4).unwrap();
+  wal.insert(&4, &[4; 4]).unwrap();
+  wal.commit_transaction(txn).unwrap();
+  wal.delete_range(&100..&200).unwrap();
+  wal.insert(&150, &[1; 4]).unwrap();
+  drop(wal);
+
+  let expected = entries(&open(1));
+  assert_eq!(expected.len(), 1267);
+  assert_eq!(expected[2], (3, [3; 4]));
+  assert_eq!(expected[3], (5, 8u32.to_le_bytes()));
+  assert_eq!(entries(&open(4)), expected);
+
+  // The merge operands are replayed by the worker threads too.
+  let mut wal = open_mut(false);
+  wal.insert(&5000, &10u32.to_le_bytes()).unwrap();
+  wal.delete_range(&5000..&5001).unwrap();
+  wal.merge(&5000, &1u32.to_le_bytes()).unwrap();
+  wal.insert(&5001, &[5; 4]).unwrap();
+  wal.remove(&1).unwrap();
+  drop(wal);
+
+  let wal = open(4);
+  assert_eq!(entries(&wal), entries(&open(1)));
+  assert_eq!(wal.get(&5000).unwrap().value(), &1u32.to_le_bytes());
+  assert!(!wal.contains_key(&1));
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn lazy_replay() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_lazy_replay");
+  let open_mut = |create_new: bool| unsafe {
+    Builder::new()
+      .with_capacity(4 * MB)
+      .with_create_new(create_new)
+      .with_read(true)
+      .with_write(true)
+      .with_lazy_replay(!create_new)
+      .with_replay_threads(2)
+      .map_mut::<OrderWal<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  let open = |lazy: bool| unsafe {
+    Builder::new()
+      .with_lazy_replay(lazy)
+      .map::<OrderWalReader<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  let entries = |wal: &OrderWalReader<u32, [u8; 4]>| {
+    wal
+      .iter()
+      .map(|ent| (*ent.key(), *ent.value()))
+      .collect::<Vec<_>>()
+  };
+
+  let mut wal = open_mut(true);
+  for i in 0..2000u32 {
+    wal.insert(&i, &i.to_le_bytes()).unwrap();
+  }
+  for i in (0..2000u32).step_by(3) {
+    wal.remove(&i).unwrap();
+  }
+  let mut txn = wal.begin_transaction().unwrap();
+  wal.transaction_insert(&mut txn, &3, &[3; 4]).unwrap();
+  wal.commit_transaction(txn).unwrap();
+  drop(wal);
+
+  // The reads do not block, they see all the entries once the replay completes.
+  let expected = entries(&open(false));
+  assert_eq!(expected.len(), 1334);
+  let lazy = open(true);
+  assert!(entries(&lazy).len() <= 2000);
+  lazy.wait_ready().unwrap();
+  assert!(lazy.is_ready());
+  assert_eq!(entries(&lazy), expected);
+
+  // The writes appended while replaying are applied after the replayed ones.
+  let mut wal = open_mut(false);
+  wal.insert(&1, &[9; 4]).unwrap();
+  wal.remove(&2).unwrap();
+  wal.insert(&5000, &[5; 4]).unwrap();
+  wal.wait_ready().unwrap();
+  assert!(wal.is_ready());
+  assert_eq!(wal.get(&1).unwrap().value(), &[9; 4]);
+  assert!(!wal.contains_key(&2));
+  assert_eq!(wal.get(&3).unwrap().value(), &[3; 4]);
+  assert_eq!(wal.get(&5000).unwrap().value(), &[5; 4]);
+  assert_eq!(wal.len(), 1334 + 1 - 1);
+
+  // The transaction ids in the WAL are never reused.
+  let mut txn = wal.begin_transaction().unwrap();
+  wal.transaction_insert(&mut txn, &6000, &[6; 4]).unwrap();
+  wal.commit_transaction(txn).unwrap();
+  drop(wal);
+
+  let wal = open(false);
+  let lazy = open(true);
+  lazy.wait_ready().unwrap();
+  assert_eq!(entries(&lazy), entries(&wal));
+  assert_eq!(wal.get(&5000).unwrap().value(), &[5; 4]);
+  assert_eq!(wal.get(&6000).unwrap().value(), &[6; 4]);
+}
+
 #[cfg(feature = "std")]
 expand_unit_tests!(
   move "linked": OrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Linked]: Table<_, _> {
@@ -449,3 +1480,185 @@ expand_unit_tests!(
     }
   }
 );
+
+#[cfg(feature = "std")]
+expand_unit_tests!(
+  move "linked": OrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Linked]: Table<_, _> {
+    insert_returning |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(2, 2), (3, 3)]);
+    },
+    reservation |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(1, 1), (4, 4), (5, 5)]);
+    },
+    transaction |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(1, 1), (2, 2), (4, 4)]);
+    },
+    prepared_batch |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(1, 11), (2, 2), (3, 3), (4, 4), (5, 5)]);
+    },
+    write_batch |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(0, 0), (1, 11), (3, 13), (4, 4), (5, 5), (7, 7)]);
+    },
+    batch_guards |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert!(!wal.contains_key(&1));
+      assert!(!wal.contains_key(&2));
+      assert_eq!(wal.get(&3).unwrap().value(), &[4; 4]);
+    },
+    compare_and_swap |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(&1).unwrap().value(), &[6; 4]);
+      assert!(!wal.contains_key(&2));
+    },
+    upsert |p, expected: Vec<(u32, u32)>| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(
+        wal.iter().map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value()))).collect::<Vec<_>>(),
+        expected
+      );
+    },
+    delete_range |p, expected: Vec<u32>| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.iter().map(|ent| *ent.key()).collect::<Vec<_>>(), expected);
+      assert!(wal.get(&12).is_none());
+      assert_eq!(wal.get(&15).unwrap().value(), &15u32.to_le_bytes());
+    },
+    overlapping_delete_ranges |p, expected: Vec<u32>| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.iter().map(|ent| *ent.key()).collect::<Vec<_>>(), expected);
+    },
+  }
+);
+
+expand_unit_tests!(
+  move "arena": OrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Arena(Default::default())]: Table<_, _> {
+    insert_returning |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(2, 2), (3, 3)]);
+    },
+    reservation |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(1, 1), (4, 4), (5, 5)]);
+    },
+    transaction |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(1, 1), (2, 2), (4, 4)]);
+    },
+    prepared_batch |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(1, 11), (2, 2), (3, 3), (4, 4), (5, 5)]);
+    },
+    write_batch |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      let entries = wal.iter().map(|ent| (*ent.key(), ent.value()[0])).collect::<Vec<_>>();
+      assert_eq!(entries, [(0, 0), (1, 11), (3, 13), (4, 4), (5, 5), (7, 7)]);
+    },
+    batch_guards |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert!(!wal.contains_key(&1));
+      assert!(!wal.contains_key(&2));
+      assert_eq!(wal.get(&3).unwrap().value(), &[4; 4]);
+    },
+    compare_and_swap |p, _res| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(&1).unwrap().value(), &[6; 4]);
+      assert!(!wal.contains_key(&2));
+    },
+    upsert |p, expected: Vec<(u32, u32)>| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(
+        wal.iter().map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value()))).collect::<Vec<_>>(),
+        expected
+      );
+    },
+    delete_range |p, expected: Vec<u32>| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.iter().map(|ent| *ent.key()).collect::<Vec<_>>(), expected);
+      assert!(wal.get(&12).is_none());
+      assert_eq!(wal.get(&15).unwrap().value(), &15u32.to_le_bytes());
+    },
+    overlapping_delete_ranges |p, expected: Vec<u32>| {
+      let wal = unsafe { Builder::new().map::<OrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.iter().map(|ent| *ent.key()).collect::<Vec<_>>(), expected);
+    },
+  }
+);
+
+#[test]
+fn sorted_table_interleaved() {
+  use base::SortedTable;
+  use std::collections::BTreeMap;
+
+  let mut wal = Builder::<SortedTable<u32, [u8; 4]>>::new()
+    .with_capacity(4 * MB)
+    .alloc::<OrderWal<u32, [u8; 4], SortedTable<u32, [u8; 4]>>>()
+    .unwrap();
+  let mut expected = BTreeMap::new();
+
+  // Each write is followed by the reads, so the updates are merged into the array many times.
+  for i in 0..3000u32 {
+    let k = (i * 7919) % 500;
+    if i % 7 == 0 {
+      wal.remove(&k).unwrap();
+      expected.remove(&k);
+    } else {
+      wal.insert(&k, &i.to_le_bytes()).unwrap();
+      expected.insert(k, i.to_le_bytes());
+    }
+
+    assert_eq!(
+      wal.get(&k).map(|ent| *ent.value()),
+      expected.get(&k).copied()
+    );
+    if i % 100 == 0 {
+      assert_eq!(wal.len(), expected.len());
+      assert!(wal
+        .iter()
+        .map(|ent| (*ent.key(), *ent.value()))
+        .eq(expected.iter().map(|(k, v)| (*k, *v))));
+      assert!(wal
+        .iter()
+        .rev()
+        .map(|ent| *ent.key())
+        .eq(expected.keys().rev().copied()));
+      assert!(wal
+        .range(100u32..200)
+        .map(|ent| *ent.key())
+        .eq(expected.range(100u32..200).map(|(k, _)| *k)));
+    }
+  }
+}
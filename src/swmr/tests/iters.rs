@@ -3,16 +3,19 @@ use std::collections::BTreeMap;
 
 use base::{OrderWal, Reader, Writer};
 
-use crate::memtable::{
-  alternative::{Table, TableOptions},
-  Memtable, MemtableEntry,
+use crate::{
+  memtable::{
+    alternative::{Table, TableOptions},
+    Memtable, MemtableEntry,
+  },
+  types::Comparator,
 };
 
 use super::*;
 
 fn iter<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -86,7 +89,7 @@ where
 
 fn bounds<M>(wal: &mut OrderWal<u32, u32, M>)
 where
-  M: Memtable<Key = u32, Value = u32> + 'static,
+  M: Memtable<Key = u32, Value = u32, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -226,7 +229,7 @@ where
 
 fn range<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: Memtable<Key = Person, Value = String> + 'static,
+  M: Memtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -305,7 +308,7 @@ where
 
 fn entry_iter<M>(wal: &mut OrderWal<u32, u32, M>)
 where
-  M: Memtable<Key = u32, Value = u32> + 'static,
+  M: Memtable<Key = u32, Value = u32, Comparator = KeyOrder> + 'static,
   for<'a> M::Item<'a>: MemtableEntry<'a> + std::fmt::Debug,
   M::Error: std::fmt::Debug,
 {
@@ -376,7 +379,292 @@ where
   }
 }
 
-#[cfg(feature = "std")]
+fn prefixed_keys<M>(wal: &mut OrderWal<str, str, M>)
+where
+  M: Memtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  fn check<M>(wal: &OrderWal<str, str, M>, model: &BTreeMap<String, String>)
+  where
+    M: Memtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+    for<'a> M::Item<'a>: MemtableEntry<'a>,
+    M::Error: std::fmt::Debug,
+  {
+    let model_range = |start: Bound<&str>, end: Bound<&str>| {
+      model
+        .range::<str, _>((start, end))
+        .map(|(k, _)| k.as_bytes().to_vec())
+    };
+
+    assert_eq!(wal.len(), model.len());
+    for (k, v) in model {
+      assert_eq!(wal.get(k.as_str()).unwrap().raw_value(), v.as_bytes());
+    }
+    assert!(wal
+      .iter()
+      .map(|ent| ent.raw_key().to_vec())
+      .eq(model.keys().map(|k| k.as_bytes().to_vec())));
+    assert!(wal
+      .iter()
+      .rev()
+      .map(|ent| ent.raw_key().to_vec())
+      .eq(model.keys().rev().map(|k| k.as_bytes().to_vec())));
+
+    let probes = [
+      "",
+      "https://example.com",
+      "https://example.com/",
+      "https://example.com/A",
+      "https://example.com/A/",
+      "https://example.com/A/1",
+      "https://example.com/~/9",
+      "https://example.org",
+      "z",
+    ];
+    for probe in probes {
+      assert_eq!(
+        wal.get(probe).map(|ent| ent.raw_key().to_vec()),
+        model
+          .get_key_value(probe)
+          .map(|(k, _)| k.as_bytes().to_vec())
+      );
+      assert_eq!(wal.contains_key(probe), model.contains_key(probe));
+      assert_eq!(
+        wal
+          .lower_bound(Bound::Included(probe))
+          .map(|ent| ent.raw_key().to_vec()),
+        model_range(Bound::Included(probe), Bound::Unbounded).next()
+      );
+      assert_eq!(
+        wal
+          .lower_bound(Bound::Excluded(probe))
+          .map(|ent| ent.raw_key().to_vec()),
+        model_range(Bound::Excluded(probe), Bound::Unbounded).next()
+      );
+      assert_eq!(
+        wal
+          .upper_bound(Bound::Included(probe))
+          .map(|ent| ent.raw_key().to_vec()),
+        model_range(Bound::Unbounded, Bound::Included(probe)).next_back()
+      );
+      assert_eq!(
+        wal
+          .upper_bound(Bound::Excluded(probe))
+          .map(|ent| ent.raw_key().to_vec()),
+        model_range(Bound::Unbounded, Bound::Excluded(probe)).next_back()
+      );
+    }
+
+    for (start, end) in probes.iter().zip(probes.iter().skip(2)) {
+      let (start, end) = (Bound::Included(*start), *end);
+      assert!(wal
+        .range::<str, _>((start, Bound::Excluded(end)))
+        .map(|ent| ent.raw_key().to_vec())
+        .eq(model_range(start, Bound::Excluded(end))));
+      let end = Bound::Included(end);
+      assert!(wal
+        .range::<str, _>((start, end))
+        .rev()
+        .map(|ent| ent.raw_key().to_vec())
+        .eq(model_range(start, end).rev()));
+    }
+  }
+
+  // The keys share long prefixes, some of them are the prefixes of the others,
+  // and there are more than 48 different bytes after the common prefix.
+  let mut model = BTreeMap::new();
+  model.insert("https://example.com".to_string(), "root".to_string());
+  for c in (b' '..=b'~').map(char::from) {
+    model.insert(std::format!("https://example.com/{c}"), c.to_string());
+    for i in 0..3 {
+      model.insert(std::format!("https://example.com/{c}/{i}"), i.to_string());
+    }
+  }
+  for (k, v) in &model {
+    wal.insert(k.as_str(), v.as_str()).unwrap();
+  }
+  check(wal, &model);
+
+  let keys = model.keys().cloned().collect::<Vec<_>>();
+  for k in keys.iter().step_by(3) {
+    wal.remove(k.as_str()).unwrap();
+    model.remove(k);
+  }
+  check(wal, &model);
+
+  // Removes almost all the keys, so the nodes shrink and the paths are compressed again.
+  for k in keys.iter().filter(|k| !k.ends_with('1')) {
+    wal.remove(k.as_str()).unwrap();
+    model.remove(k);
+  }
+  check(wal, &model);
+
+  let rest = model
+    .keys()
+    .filter(|k| !k.starts_with("https://example.com/a"))
+    .cloned()
+    .collect::<Vec<_>>();
+  for k in rest {
+    wal.remove(k.as_str()).unwrap();
+    model.remove(&k);
+  }
+  assert_eq!(model.len(), 1);
+  check(wal, &model);
+}
+
+/// Orders the `u32` keys, which are encoded in little-endian, in descending order.
+struct Descending;
+
+impl Comparator for Descending {
+  const NAME: &'static str = "descending";
+
+  fn compare(a: &[u8], b: &[u8]) -> core::cmp::Ordering {
+    u32::from_le_bytes(b.try_into().unwrap()).cmp(&u32::from_le_bytes(a.try_into().unwrap()))
+  }
+}
+
+/// Orders the `u32` keys, which are encoded in little-endian, in ascending order.
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+struct Ascending;
+
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+impl Comparator for Ascending {
+  const NAME: &'static str = "ascending";
+
+  fn compare(a: &[u8], b: &[u8]) -> core::cmp::Ordering {
+    u32::from_le_bytes(a.try_into().unwrap()).cmp(&u32::from_le_bytes(b.try_into().unwrap()))
+  }
+}
+
+fn comparator<M>(wal: &mut OrderWal<u32, u32, M>)
+where
+  M: Memtable<Key = u32, Value = u32, Comparator = Descending> + 'static,
+  for<'a> M::Item<'a>: MemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  for i in 0..100u32 {
+    wal.insert(&i, &i).unwrap();
+  }
+
+  assert!(wal.iter().map(|ent| *ent.key()).eq((0..100u32).rev()));
+  assert!(wal.iter().rev().map(|ent| *ent.key()).eq(0..100u32));
+
+  let key = |k: u32| k.to_le_bytes();
+  unsafe {
+    assert_eq!(wal.get_by_bytes(&key(50)).unwrap().value(), &50);
+    assert!(wal.get_by_bytes(&key(100)).is_none());
+    assert!(wal.contains_key_by_bytes(&key(50)));
+    assert!(!wal.contains_key_by_bytes(&key(100)));
+
+    let lower = wal
+      .lower_bound_by_bytes(Bound::Excluded(key(50).as_ref()))
+      .unwrap();
+    assert_eq!(lower.key(), &49);
+    let lower = wal
+      .lower_bound_by_bytes(Bound::Included(key(50).as_ref()))
+      .unwrap();
+    assert_eq!(lower.key(), &50);
+    let upper = wal
+      .upper_bound_by_bytes(Bound::Excluded(key(50).as_ref()))
+      .unwrap();
+    assert_eq!(upper.key(), &51);
+    let upper = wal
+      .upper_bound_by_bytes(Bound::Included(key(50).as_ref()))
+      .unwrap();
+    assert_eq!(upper.key(), &50);
+
+    // The bounds follow the comparator, so the range starts from the greater key.
+    let (start, end) = (key(60), key(50));
+    let range = wal.range_by_bytes::<(Bound<&[u8]>, Bound<&[u8]>)>((
+      Bound::Included(start.as_ref()),
+      Bound::Excluded(end.as_ref()),
+    ));
+    assert!(range.map(|ent| *ent.key()).eq((51..=60u32).rev()));
+    let range = wal.range_by_bytes::<(Bound<&[u8]>, Bound<&[u8]>)>((
+      Bound::Included(start.as_ref()),
+      Bound::Excluded(end.as_ref()),
+    ));
+    assert!(range.rev().map(|ent| *ent.key()).eq(51..=60u32));
+  }
+
+  // The range follows the comparator, so it removes the keys in `(70, 80]`.
+  wal.delete_range(&80u32..&70u32).unwrap();
+  unsafe {
+    assert!(wal.get_by_bytes(&key(80)).is_none());
+    assert!(wal.get_by_bytes(&key(71)).is_none());
+    assert!(!wal.contains_key_by_bytes(&key(75)));
+    assert!(wal.get_by_bytes(&key(70)).is_some());
+    assert!(wal.get_by_bytes(&key(81)).is_some());
+
+    let (start, end) = (key(85), key(65));
+    let range = wal.range_by_bytes::<(Bound<&[u8]>, Bound<&[u8]>)>((
+      Bound::Included(start.as_ref()),
+      Bound::Included(end.as_ref()),
+    ));
+    assert!(range
+      .map(|ent| *ent.key())
+      .eq((65..=85u32).rev().filter(|i| !(71..=80).contains(i))));
+  }
+  assert_eq!(wal.iter().count(), 90);
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn comparator_reopen() {
+  use crate::memtable::btree::Table as BTreeTable;
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_comparator_reopen");
+
+  let mut wal = unsafe {
+    crate::Builder::new()
+      .with_capacity(MB)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, u32, BTreeTable<u32, u32, Descending>>, _>(&path)
+      .unwrap()
+  };
+  for i in 0..100u32 {
+    wal.insert(&i, &i).unwrap();
+  }
+  wal.delete_range(&80u32..&70u32).unwrap();
+  drop(wal);
+
+  // The Bloom filter and the parallel replay hash the encoded keys, so they are disabled for the comparator.
+  let wal = unsafe {
+    crate::Builder::new()
+      .with_bloom_filter_bits_per_key(Some(10))
+      .with_replay_threads(4)
+      .map::<OrderWalReader<u32, u32, BTreeTable<u32, u32, Descending>>, _>(&path)
+      .unwrap()
+  };
+  assert!(wal.bloom_filter_stats().is_none());
+  assert!(wal
+    .iter()
+    .map(|ent| *ent.key())
+    .eq((0..100u32).rev().filter(|i| !(71..=80).contains(i))));
+  drop(wal);
+
+  let err = unsafe {
+    crate::Builder::new()
+      .map::<OrderWalReader<u32, u32, BTreeTable<u32, u32>>, _>(&path)
+      .err()
+      .unwrap()
+  };
+  assert!(matches!(err, crate::error::Error::IO(e) if e.kind() == std::io::ErrorKind::InvalidData));
+
+  let err = unsafe {
+    crate::Builder::new()
+      .map::<OrderWalReader<u32, u32, BTreeTable<u32, u32, Ascending>>, _>(&path)
+      .err()
+      .unwrap()
+  };
+  assert!(matches!(err, crate::error::Error::IO(e) if e.kind() == std::io::ErrorKind::InvalidData));
+}
+
 expand_unit_tests!("linked": OrderWalAlternativeTable<u32, u32> [TableOptions::Linked]: Table<_, _> {
   bounds,
   entry_iter,
@@ -397,3 +685,52 @@ expand_unit_tests!("arena": OrderWalAlternativeTable<Person, String> [TableOptio
   range,
   iter,
 });
+
+expand_unit_tests!("btree": OrderWalBTreeTable<u32, u32> [()]: crate::memtable::btree::Table<_, _> {
+  bounds,
+  entry_iter,
+});
+
+expand_unit_tests!("btree": OrderWalBTreeTable<Person, String> [()]: crate::memtable::btree::Table<_, _> {
+  range,
+  iter,
+});
+
+expand_unit_tests!("art": OrderWal<str, str, crate::memtable::art::Table<str, str>> [()]: crate::memtable::art::Table<_, _> {
+  prefixed_keys,
+});
+
+expand_unit_tests!("btree": OrderWalBTreeTable<str, str> [()]: crate::memtable::btree::Table<_, _> {
+  prefixed_keys,
+});
+
+expand_unit_tests!("sorted": OrderWalSortedTable<u32, u32> [()]: crate::memtable::sorted::Table<_, _> {
+  bounds,
+  entry_iter,
+});
+
+expand_unit_tests!("sorted": OrderWalSortedTable<Person, String> [()]: crate::memtable::sorted::Table<_, _> {
+  range,
+  iter,
+});
+
+expand_unit_tests!("sorted": OrderWalSortedTable<str, str> [()]: crate::memtable::sorted::Table<_, _> {
+  prefixed_keys,
+});
+
+#[cfg(feature = "std")]
+expand_unit_tests!("linked": OrderWal<u32, u32, Table<u32, u32, Descending>> [TableOptions::Linked]: Table<_, _, _> {
+  comparator,
+});
+
+expand_unit_tests!("arena": OrderWal<u32, u32, Table<u32, u32, Descending>> [TableOptions::Arena(Default::default())]: Table<_, _, _> {
+  comparator,
+});
+
+expand_unit_tests!("btree": OrderWal<u32, u32, crate::memtable::btree::Table<u32, u32, Descending>> [()]: crate::memtable::btree::Table<_, _, _> {
+  comparator,
+});
+
+expand_unit_tests!("sorted": OrderWal<u32, u32, crate::memtable::sorted::Table<u32, u32, Descending>> [()]: crate::memtable::sorted::Table<_, _, _> {
+  comparator,
+});
@@ -10,7 +10,7 @@ use super::*;
 
 fn zero_reserved<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -25,7 +25,7 @@ where
 
 fn reserved<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -1,4 +1,4 @@
-use core::ops::Bound;
+use core::{ops::Bound, time::Duration};
 
 use crate::{
   memtable::{
@@ -20,6 +20,7 @@ expand_unit_tests!("linked": MultipleVersionOrderWalAlternativeTable<str, str> [
   ge,
   le,
   lt,
+  ttl,
 });
 
 expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
@@ -28,6 +29,62 @@ expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<str, str> [T
   ge,
   le,
   lt,
+  ttl,
+});
+
+expand_unit_tests!("btree": MultipleVersionOrderWalBTreeTable<str, str> [()]: crate::memtable::btree::MultipleVersionTable<_, _> {
+  mvcc,
+  gt,
+  ge,
+  le,
+  lt,
+  ttl,
+});
+
+expand_unit_tests!("art": MultipleVersionOrderWalArtTable<str, str> [()]: crate::memtable::art::MultipleVersionTable<_, _> {
+  mvcc,
+  gt,
+  ge,
+  le,
+  lt,
+  ttl,
+});
+
+expand_unit_tests!("sorted": MultipleVersionOrderWalSortedTable<str, str> [()]: crate::memtable::sorted::MultipleVersionTable<_, _> {
+  mvcc,
+  gt,
+  ge,
+  le,
+  lt,
+  ttl,
+});
+
+expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
+  bloom_filter(crate::Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Arena(Default::default()))
+    .with_bloom_filter_bits_per_key(Some(10))),
+});
+
+#[cfg(feature = "std")]
+expand_unit_tests!("linked": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Linked]: MultipleVersionTable<_, _> {
+  as_of,
+});
+
+#[cfg(feature = "std")]
+expand_unit_tests!("linked": MultipleVersionOrderWalAlternativeTable<String, String> [TableOptions::Linked]: MultipleVersionTable<_, _> {
+  export_snapshot,
+});
+
+expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<String, String> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
+  export_snapshot(crate::Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(crate::memtable::arena::TableOptions::new().with_capacity(MB).into())),
+});
+
+#[cfg(feature = "std")]
+expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
+  as_of,
 });
 
 #[cfg(feature = "std")]
@@ -49,7 +106,7 @@ expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<Person, Stri
 
 fn mvcc<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
 where
-  M: MultipleVersionMemtable<Key = str, Value = str> + 'static,
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -124,9 +181,258 @@ where
   assert!(wal.get(5, "d").is_none());
 }
 
+#[cfg(feature = "std")]
+fn as_of<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  use crate::types::{HlcTimestamp, HybridLogicalClock};
+  use std::time::{Duration, UNIX_EPOCH};
+
+  let at = |millis: u64| UNIX_EPOCH + Duration::from_millis(millis);
+
+  wal
+    .insert(HlcTimestamp::new(1000, 0).version(), "a", "a1")
+    .unwrap();
+  wal
+    .insert(HlcTimestamp::new(1000, 1).version(), "a", "a2")
+    .unwrap();
+  wal
+    .insert(HlcTimestamp::new(1500, 0).version(), "b", "b1")
+    .unwrap();
+  wal
+    .insert(HlcTimestamp::new(2000, 0).version(), "a", "a3")
+    .unwrap();
+  wal
+    .remove(HlcTimestamp::new(3000, 0).version(), "a")
+    .unwrap();
+
+  assert!(wal.get_as_of(at(999), "a").is_none());
+  assert!(!wal.contains_key_as_of(at(999), "a"));
+
+  // all the events happened in the same millisecond are visible.
+  let ent = wal.get_as_of(at(1000), "a").unwrap();
+  assert_eq!(ent.value(), "a2");
+  assert_eq!(ent.timestamp(), HlcTimestamp::new(1000, 1));
+  assert_eq!(ent.timestamp().system_time(), at(1000));
+
+  assert_eq!(wal.get_as_of(at(2999), "a").unwrap().value(), "a3");
+  assert!(wal.contains_key_as_of(at(2999), "a"));
+
+  assert!(wal.get_as_of(at(3000), "a").is_none());
+  let ent = wal.get_versioned_as_of(at(3000), "a").unwrap();
+  assert!(ent.value().is_none());
+  assert_eq!(ent.timestamp().physical(), 3000);
+
+  let keys = wal
+    .iter_as_of(at(1500))
+    .map(|ent| (ent.key().to_string(), ent.value().to_string()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    keys,
+    [
+      ("a".to_string(), "a2".to_string()),
+      ("b".to_string(), "b1".to_string())
+    ]
+  );
+
+  let keys = wal
+    .range_as_of(at(3000), "a"..)
+    .map(|ent| ent.key().to_string())
+    .collect::<Vec<_>>();
+  assert_eq!(keys, ["b"]);
+
+  // the clock never goes back, even though the recorded versions are in the future.
+  let clock = HybridLogicalClock::with_last(HlcTimestamp::from_version(u64::MAX - 1));
+  assert_eq!(clock.now().version(), u64::MAX);
+
+  let clock = HybridLogicalClock::with_last(wal.maximum_version().into());
+  let now = clock.now();
+  assert!(now.version() > wal.maximum_version());
+  assert!(clock.now() > now);
+  assert!(
+    clock.update(HlcTimestamp::new(now.physical() + 10_000, 0))
+      > HlcTimestamp::new(now.physical() + 10_000, 0)
+  );
+}
+
+fn export_snapshot<M>(wal: &mut multiple_version::OrderWal<String, String, M>)
+where
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  use crate::base::{ArenaTable, OrderWal, Reader as _};
+
+  for i in 0..1200usize {
+    wal.insert(1, &format!("{i:05}"), &format!("a{i}")).unwrap();
+  }
+  for i in (0..1200usize).step_by(2) {
+    wal.insert(2, &format!("{i:05}"), &format!("b{i}")).unwrap();
+  }
+  for i in (0..1200usize).step_by(3) {
+    wal.remove(3, &format!("{i:05}")).unwrap();
+  }
+
+  let new = || {
+    crate::Builder::new()
+      .with_capacity(MB)
+      .with_memtable_options(crate::memtable::arena::TableOptions::new().with_capacity(MB))
+      .alloc::<OrderWal<String, String, ArenaTable<String, String>>>()
+  };
+
+  let snapshot = wal.export_snapshot(0, new).unwrap();
+  assert!(snapshot.is_empty());
+
+  let snapshot = wal.export_snapshot(2, new).unwrap();
+  assert_eq!(snapshot.len(), 1200);
+  for (i, ent) in snapshot.iter().enumerate() {
+    assert_eq!(ent.key(), format!("{i:05}").as_str());
+    if i % 2 == 0 {
+      assert_eq!(ent.value(), format!("b{i}").as_str());
+    } else {
+      assert_eq!(ent.value(), format!("a{i}").as_str());
+    }
+  }
+
+  let snapshot = wal.export_snapshot(3, new).unwrap();
+  assert_eq!(snapshot.len(), 1200 - 1200usize.div_ceil(3));
+  assert!(snapshot.get("00000").is_none());
+  assert_eq!(snapshot.get("00001").unwrap().value(), "a1");
+  assert_eq!(snapshot.get("00002").unwrap().value(), "b2");
+  assert!(snapshot
+    .iter()
+    .zip(wal.iter(3))
+    .all(|(a, b)| a.key() == b.key() && a.value() == b.value()));
+
+  // not enough space in the new WAL.
+  let err = wal
+    .export_snapshot(3, || {
+      crate::Builder::new()
+        .with_capacity(1024)
+        .alloc::<OrderWal<String, String, ArenaTable<String, String>>>()
+    })
+    .unwrap_err();
+  assert!(matches!(err, crate::error::Error::InsufficientSpace(_)));
+}
+
+const ALIVE: Duration = Duration::from_secs(3600);
+
+fn ttl<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  wal.insert(1, "a", "a1").unwrap();
+  wal.insert_with_ttl(2, "a", "a2", ALIVE).unwrap();
+  wal.insert_with_ttl(3, "a", "a3", Duration::ZERO).unwrap();
+  wal.insert_with_ttl(1, "b", "b1", Duration::ZERO).unwrap();
+  wal.insert(2, "c", "c2").unwrap();
+
+  assert_eq!(wal.get(1, "a").unwrap().value(), "a1");
+  assert_eq!(wal.get(2, "a").unwrap().value(), "a2");
+  // The expired version hides the key, the older versions are not visible through it.
+  assert!(wal.get(3, "a").is_none());
+  assert!(!wal.contains_key(3, "a"));
+  assert!(wal.get_versioned(3, "a").is_none());
+  assert!(wal.get(3, "b").is_none());
+  assert!(!wal.contains_key(1, "b"));
+
+  assert!(wal.iter(2).map(|ent| ent.key().to_string()).eq(["a", "c"]));
+  assert!(wal.iter(3).map(|ent| ent.key().to_string()).eq(["c"]));
+  assert!(wal.iter(3).rev().map(|ent| ent.key().to_string()).eq(["c"]));
+  assert_eq!(wal.first(3).unwrap().key(), "c");
+  assert_eq!(wal.last(2).unwrap().key(), "c");
+  assert_eq!(wal.lower_bound(3, Bound::Included("a")).unwrap().key(), "c");
+  assert!(wal.upper_bound(3, Bound::Excluded("c")).is_none());
+
+  // A write without a ttl replaces the expired version.
+  wal.insert(4, "a", "a4").unwrap();
+  assert_eq!(wal.get(4, "a").unwrap().value(), "a4");
+  assert!(wal.get(3, "a").is_none());
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn ttl_reopen() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_multiple_version_ttl_reopen");
+  let open = || unsafe {
+    crate::Builder::new()
+      .with_capacity(MB)
+      .with_create(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<multiple_version::OrderWal<u32, u32>, _>(&path)
+      .unwrap()
+  };
+
+  let mut wal = open();
+  for i in 0..100u32 {
+    let ttl = if i % 2 == 0 { Duration::ZERO } else { ALIVE };
+    wal.insert_with_ttl(1, &i, &i, ttl).unwrap();
+  }
+  drop(wal);
+
+  let wal = open();
+  for i in 0..100u32 {
+    assert_eq!(
+      wal.get(1, &i).map(|ent| *ent.value()),
+      (i % 2 == 1).then_some(i)
+    );
+  }
+  assert!(wal.iter(1).map(|ent| *ent.key()).eq((1..100).step_by(2)));
+}
+
+fn bloom_filter<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  wal.insert(1, "a", "a1").unwrap();
+  wal.remove(2, "a").unwrap();
+
+  unsafe {
+    assert_eq!(wal.get_by_bytes(1, b"a").unwrap().value(), "a1");
+    assert!(wal.get_by_bytes(2, b"a").is_none());
+    assert!(wal
+      .get_versioned_by_bytes(2, b"a")
+      .unwrap()
+      .value()
+      .is_none());
+    assert!(wal.contains_key_versioned_by_bytes(2, b"a"));
+
+    assert!(wal.get_by_bytes(2, b"b").is_none());
+    assert!(wal.get_versioned_by_bytes(2, b"b").is_none());
+    assert!(!wal.contains_key_by_bytes(2, b"b"));
+    assert!(!wal.contains_key_versioned_by_bytes(2, b"b"));
+  }
+
+  // The typed lookups consult the filter as well.
+  assert_eq!(wal.get(1, "a").unwrap().value(), "a1");
+  assert!(wal.contains_key_versioned(2, "a"));
+  assert!(wal.get(2, "b").is_none());
+  assert!(wal.get_versioned(2, "b").is_none());
+  assert!(!wal.contains_key(2, "b"));
+  assert!(!wal.contains_key_versioned(2, "b"));
+
+  let stats = wal.bloom_filter_stats().unwrap();
+  assert_eq!(stats.hits(), 6);
+  assert_eq!(stats.misses(), 8);
+}
+
 fn gt<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
 where
-  M: MultipleVersionMemtable<Key = str, Value = str> + 'static,
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -232,7 +538,7 @@ where
 
 fn ge<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
 where
-  M: MultipleVersionMemtable<Key = str, Value = str> + 'static,
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -338,7 +644,7 @@ where
 
 fn le<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
 where
-  M: MultipleVersionMemtable<Key = str, Value = str> + 'static,
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -466,7 +772,7 @@ where
 
 fn lt<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
 where
-  M: MultipleVersionMemtable<Key = str, Value = str> + 'static,
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -569,7 +875,7 @@ where
 #[allow(clippy::needless_borrows_for_generic_args)]
 fn insert<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -591,7 +897,7 @@ where
 
 fn insert_with_value_builder<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -620,7 +926,7 @@ where
 
 fn insert_with_key_builder<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -647,7 +953,7 @@ where
 
 fn insert_with_bytes<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -677,7 +983,7 @@ where
 
 fn insert_with_builders<M>(wal: &mut OrderWal<Person, String, M>)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
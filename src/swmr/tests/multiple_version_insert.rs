@@ -1,8 +1,10 @@
+use among::Among;
 use dbutils::{buffer::VacantBuffer, types::MaybeStructured};
 use multiple_version::{Reader, Writer};
 
 use crate::{
-  batch::BatchEntry,
+  batch::{BatchEntry, BatchGuard, GuardedBatch, PreparedBatch},
+  error::{BatchError, Error},
   memtable::{
     alternative::{MultipleVersionTable, TableOptions},
     MultipleVersionMemtable, VersionedMemtableEntry,
@@ -16,7 +18,7 @@ use super::*;
 #[cfg(feature = "std")]
 fn concurrent_basic<M>(mut w: OrderWal<u32, [u8; 4], M>)
 where
-  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4]> + Send + 'static,
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -47,7 +49,7 @@ where
 #[cfg(feature = "std")]
 fn concurrent_one_key<M>(mut w: OrderWal<u32, [u8; 4], M>)
 where
-  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4]> + Send + 'static,
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -71,7 +73,7 @@ where
 
 fn insert_batch<M>(mut wal: OrderWal<Person, String, M>) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + Send + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -128,7 +130,7 @@ fn insert_batch_with_key_builder<M>(
   mut wal: OrderWal<Person, String, M>,
 ) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + Send + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -188,7 +190,7 @@ fn insert_batch_with_value_builder<M>(
   mut wal: OrderWal<Person, String, M>,
 ) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + Send + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -247,7 +249,7 @@ fn insert_batch_with_builders<M>(
   mut wal: OrderWal<Person, String, M>,
 ) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + Send + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -309,7 +311,7 @@ fn insert_batch_with_tombstone<M>(
   mut wal: OrderWal<Person, String, M>,
 ) -> (Person, Vec<(Person, String)>, Person)
 where
-  M: MultipleVersionMemtable<Key = Person, Value = String> + Send + 'static,
+  M: MultipleVersionMemtable<Key = Person, Value = String, Comparator = KeyOrder> + Send + 'static,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
   M::Error: std::fmt::Debug,
 {
@@ -371,6 +373,528 @@ where
   (rp1, output, rp2)
 }
 
+#[allow(clippy::needless_borrows_for_generic_args)]
+fn delete_range<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  for i in 0..30u32 {
+    wal.insert(1, &i, &i.to_le_bytes()).unwrap();
+  }
+  wal.insert(2, &11, &[2; 4]).unwrap();
+
+  wal.delete_range(3, &10..&20).unwrap();
+  // inserted after the range tombstone at the same version
+  wal.insert(3, &15, &[3; 4]).unwrap();
+  // inserted after the range tombstone but at a lower version
+  wal.insert(2, &17, &[2; 4]).unwrap();
+  wal.insert(4, &16, &[4; 4]).unwrap();
+
+  // the range tombstone is not visible at the lower versions
+  assert_eq!(wal.get(2, &11).unwrap().value(), &[2; 4]);
+  assert_eq!(wal.get(2, &17).unwrap().value(), &[2; 4]);
+  assert!(wal.contains_key(2, &10));
+  assert_eq!(wal.iter(2).count(), 30);
+
+  assert!(wal.get(3, &11).is_none());
+  assert!(wal.get_versioned(3, &12).is_none());
+  assert!(wal.get(3, &17).is_none());
+  assert!(!wal.contains_key(3, &10));
+  assert!(!wal.contains_key_versioned(3, &19));
+  assert_eq!(wal.get(3, &15).unwrap().value(), &[3; 4]);
+  assert!(wal.get(3, &16).is_none());
+  assert_eq!(wal.get(4, &16).unwrap().value(), &[4; 4]);
+
+  let expected = (0..10).chain([15]).chain(20..30).collect::<Vec<_>>();
+  assert_eq!(
+    wal.iter(3).map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected
+  );
+  assert_eq!(
+    wal.iter(3).rev().map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected.iter().rev().copied().collect::<Vec<_>>()
+  );
+  assert_eq!(
+    wal.iter(4).map(|ent| *ent.key()).collect::<Vec<_>>(),
+    (0..10).chain([15, 16]).chain(20..30).collect::<Vec<_>>()
+  );
+  assert_eq!(
+    wal
+      .range(3, 8u32..22)
+      .map(|ent| *ent.key())
+      .collect::<Vec<_>>(),
+    [8, 9, 15, 20, 21]
+  );
+  assert_eq!(
+    wal
+      .iter_all_versions(3)
+      .map(|ent| (*ent.key(), ent.version()))
+      .collect::<Vec<_>>(),
+    (0..10)
+      .map(|i| (i, 1))
+      .chain([(15, 3)])
+      .chain((20..30).map(|i| (i, 1)))
+      .collect::<Vec<_>>()
+  );
+
+  assert_eq!(
+    *wal
+      .lower_bound(3, core::ops::Bound::Included(&10))
+      .unwrap()
+      .key(),
+    15
+  );
+  assert_eq!(
+    *wal
+      .upper_bound(3, core::ops::Bound::Excluded(&15))
+      .unwrap()
+      .key(),
+    9
+  );
+  assert_eq!(
+    *wal
+      .upper_bound_versioned(3, core::ops::Bound::Excluded(&20))
+      .unwrap()
+      .key(),
+    15
+  );
+
+  let reader = wal.reader();
+  assert_eq!(
+    reader.iter(3).map(|ent| *ent.key()).collect::<Vec<_>>(),
+    expected
+  );
+}
+
+fn upsert<M>(mut wal: OrderWal<u32, [u8; 4], M>) -> Vec<(u32, u32)>
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  let incr = |old: Option<[u8; 4]>| {
+    let n = old.map_or(0, u32::from_le_bytes) + 1;
+    Some(ValueBuilder::new(4, move |buf: &mut VacantBuffer<'_>| {
+      buf.put_slice(&n.to_le_bytes()).map(|_| 4)
+    }))
+  };
+  // Keeps the odd counters, and removes the even ones.
+  let retain_odd = |old: Option<[u8; 4]>| {
+    old
+      .filter(|val| u32::from_le_bytes(*val) % 2 == 1)
+      .map(|val| {
+        ValueBuilder::new(4, move |buf: &mut VacantBuffer<'_>| {
+          buf.put_slice(&val).map(|_| 4)
+        })
+      })
+  };
+
+  for version in 1..=3 {
+    for i in 0..5u32 {
+      wal.upsert(version, &i, incr).unwrap();
+    }
+  }
+  wal.upsert(4, &0, incr).unwrap();
+  for i in 0..5u32 {
+    wal.upsert(5, &i, retain_odd).unwrap();
+  }
+
+  assert_eq!(wal.get(1, &1).unwrap().value(), &1u32.to_le_bytes());
+  assert_eq!(wal.get(2, &1).unwrap().value(), &2u32.to_le_bytes());
+  assert_eq!(wal.get(4, &0).unwrap().value(), &4u32.to_le_bytes());
+  assert!(wal.get(5, &0).is_none());
+
+  let expected = (1..5u32).map(|i| (i, 3)).collect::<Vec<_>>();
+  let reader = wal.reader();
+  assert_eq!(
+    reader
+      .iter(5)
+      .map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value())))
+      .collect::<Vec<_>>(),
+    expected
+  );
+
+  expected
+}
+
+fn insert_returning<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(3, &1, &[3; 4]).unwrap();
+
+  // The entry is the one just written, even if there is a newer version.
+  {
+    let ent = wal.insert_returning(2, &1, &[2; 4]).unwrap();
+    assert_eq!(*ent.key(), 1);
+    assert_eq!(ent.value(), &[2; 4]);
+    assert_eq!(ent.version(), 2);
+  }
+
+  let mut batch = vec![
+    BatchEntry::with_version(4, MaybeStructured::from(&1), MaybeStructured::from(&[4; 4])),
+    BatchEntry::tombstone_with_version(5, MaybeStructured::from(&1)),
+    BatchEntry::with_version(1, MaybeStructured::from(&2), MaybeStructured::from(&[1; 4])),
+  ];
+  let written = wal
+    .insert_batch_returning(&mut batch)
+    .unwrap()
+    .into_iter()
+    .map(|ent| (*ent.key(), ent.version(), ent.value()[0]))
+    .collect::<Vec<_>>();
+  assert_eq!(written, [(1, 4, 4), (2, 1, 1)]);
+  assert!(wal.get(5, &1).is_none());
+}
+
+fn transaction<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(1, &0, &[0; 4]).unwrap();
+
+  let mut txn = wal.begin_transaction().unwrap();
+  wal.transaction_insert(&mut txn, 2, &1, &[1; 4]).unwrap();
+  wal.transaction_remove(&mut txn, 2, &0).unwrap();
+  assert_eq!(txn.len(), 2);
+
+  // The entries are invisible until the transaction is committed.
+  assert!(wal.contains_key(2, &0));
+  assert!(!wal.contains_key(2, &1));
+
+  let mut aborted = wal.begin_transaction().unwrap();
+  wal
+    .transaction_insert(&mut aborted, 3, &2, &[2; 4])
+    .unwrap();
+
+  wal.commit_transaction(txn).unwrap();
+  assert!(wal.contains_key(1, &0));
+  assert!(!wal.contains_key(2, &0));
+  assert_eq!(wal.get(2, &1).unwrap().value(), &[1; 4]);
+
+  wal.abort_transaction(aborted).unwrap();
+  assert!(!wal.contains_key(3, &2));
+
+  // The transaction is never committed, so it is discarded when the WAL is reopened.
+  let mut pending = wal.begin_transaction().unwrap();
+  wal
+    .transaction_insert(&mut pending, 4, &3, &[3; 4])
+    .unwrap();
+  assert!(!wal.contains_key(4, &3));
+}
+
+fn reservation<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  {
+    let mut reservation = wal.reserve(2, 4, 4).unwrap();
+    assert_eq!(reservation.version(), Some(2));
+    reservation.key_mut().copy_from_slice(&1u32.to_le_bytes());
+    reservation.value_mut().copy_from_slice(&[2; 4]);
+    // SAFETY: the key and value are encoded by `u32` and `[u8; 4]`.
+    unsafe { reservation.commit().unwrap() };
+  }
+  assert!(wal.get(1, &1).is_none());
+  assert_eq!(wal.get(2, &1).unwrap().value(), &[2; 4]);
+
+  let remaining = wal.remaining();
+  {
+    let mut reservation = wal.reserve(3, 4, 4).unwrap();
+    reservation.key_mut().copy_from_slice(&1u32.to_le_bytes());
+  }
+  assert_eq!(wal.remaining(), remaining);
+  assert_eq!(wal.get(3, &1).unwrap().value(), &[2; 4]);
+}
+
+fn prepared_batch<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(1, &1, &[1; 4]).unwrap();
+
+  let mut batch = PreparedBatch::new();
+  batch.insert_with_version(2, &1, &[2; 4]).unwrap();
+  batch.insert_with_version(3, &2, &[3; 4]).unwrap();
+  batch.remove_with_version(4, &1).unwrap();
+  wal.insert_prepared_batch(&batch).unwrap();
+
+  assert_eq!(wal.get(1, &1).unwrap().value(), &[1; 4]);
+  assert_eq!(wal.get(3, &1).unwrap().value(), &[2; 4]);
+  assert!(wal.get(4, &1).is_none());
+  assert!(wal.get(2, &2).is_none());
+  assert_eq!(wal.get(3, &2).unwrap().value(), &[3; 4]);
+  assert_eq!(wal.maximum_version(), 4);
+}
+
+fn batch_guards<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(1, &1, &[1; 4]).unwrap();
+  wal.insert(3, &1, &[3; 4]).unwrap();
+  wal.insert(2, &2, &[2; 4]).unwrap();
+  wal.remove(4, &2).unwrap();
+
+  // The guards are checked against the latest versions.
+  let mut batch = GuardedBatch::new(vec![
+    BatchEntry::with_version(5, MaybeStructured::from(&1), MaybeStructured::from(&[5; 4])),
+    BatchEntry::with_version(5, MaybeStructured::from(&2), MaybeStructured::from(&[5; 4])),
+  ])
+  .with_guard(BatchGuard::at_version(MaybeStructured::from(&1), 1))
+  .with_guard(BatchGuard::absent(MaybeStructured::from(&2)));
+  let err = wal.insert_batch(&mut batch).unwrap_err();
+  assert!(matches!(
+    err,
+    Among::Right(Error::Batch(BatchError::GuardFailed(0)))
+  ));
+  assert_eq!(wal.get(5, &1).unwrap().version(), 3);
+  assert!(wal.get(5, &2).is_none());
+
+  let mut batch = GuardedBatch::new(vec![
+    BatchEntry::with_version(5, MaybeStructured::from(&1), MaybeStructured::from(&[5; 4])),
+    BatchEntry::with_version(5, MaybeStructured::from(&2), MaybeStructured::from(&[5; 4])),
+  ])
+  .with_guard(BatchGuard::at_version(MaybeStructured::from(&1), 3))
+  .with_guard(BatchGuard::absent(MaybeStructured::from(&2)))
+  .with_guard(BatchGuard::at_version(MaybeStructured::from(&2), 4));
+  let err = wal.insert_batch(&mut batch).unwrap_err();
+  assert!(matches!(
+    err,
+    Among::Right(Error::Batch(BatchError::GuardFailed(2)))
+  ));
+
+  let mut batch = GuardedBatch::new(vec![
+    BatchEntry::with_version(5, MaybeStructured::from(&1), MaybeStructured::from(&[5; 4])),
+    BatchEntry::with_version(5, MaybeStructured::from(&2), MaybeStructured::from(&[5; 4])),
+  ])
+  .with_guard(BatchGuard::at_version(MaybeStructured::from(&1), 3))
+  .with_guard(BatchGuard::absent(MaybeStructured::from(&2)));
+  wal.insert_batch(&mut batch).unwrap();
+  assert_eq!(wal.get(5, &1).unwrap().value(), &[5; 4]);
+  assert_eq!(wal.get(5, &2).unwrap().value(), &[5; 4]);
+  assert_eq!(wal.get(3, &1).unwrap().value(), &[3; 4]);
+}
+
+fn compare_and_swap<M>(mut wal: OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  assert!(!wal.compare_and_swap(1, &1, &[0; 4], &[1; 4]).unwrap());
+  assert!(!wal.contains_key(1, &1));
+
+  wal.insert(1, &1, &[1; 4]).unwrap();
+  assert!(!wal.compare_and_swap(2, &1, &[2; 4], &[3; 4]).unwrap());
+  assert!(wal.get(2, &1).unwrap().version() == 1);
+  assert!(wal.compare_and_swap(2, &1, &[1; 4], &[2; 4]).unwrap());
+  assert_eq!(wal.get(1, &1).unwrap().value(), &[1; 4]);
+  assert_eq!(wal.get(2, &1).unwrap().value(), &[2; 4]);
+
+  // The value is compared with the latest version, even if it is newer than the version to write.
+  wal.insert(5, &1, &[5; 4]).unwrap();
+  assert!(!wal.compare_and_swap(3, &1, &[2; 4], &[3; 4]).unwrap());
+  assert!(wal.compare_and_swap(6, &1, &[5; 4], &[6; 4]).unwrap());
+
+  wal.remove(7, &1).unwrap();
+  assert!(!wal.compare_and_swap(8, &1, &[6; 4], &[8; 4]).unwrap());
+
+  let reader = wal.reader();
+  assert_eq!(reader.get(6, &1).unwrap().value(), &[6; 4]);
+  assert!(reader.get(8, &1).is_none());
+}
+
+fn merge<M>(wal: &mut OrderWal<u32, [u8; 4], M>)
+where
+  M: MultipleVersionMemtable<Key = u32, Value = [u8; 4], Comparator = KeyOrder> + 'static,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a>,
+  M::Error: std::fmt::Debug,
+{
+  wal.insert(1, &1, &10u32.to_le_bytes()).unwrap();
+  wal.merge(2, &1, &1u32.to_le_bytes()).unwrap();
+  wal.merge(3, &1, &2u32.to_le_bytes()).unwrap();
+
+  assert_eq!(wal.get(1, &1).unwrap().value(), &10u32.to_le_bytes());
+  assert_eq!(wal.get(2, &1).unwrap().value(), &11u32.to_le_bytes());
+  assert_eq!(wal.get(3, &1).unwrap().value(), &13u32.to_le_bytes());
+  assert_eq!(wal.get(4, &1).unwrap().value(), &13u32.to_le_bytes());
+
+  // The entries inserted later with a lower version are not folded.
+  wal.merge(2, &2, &5u32.to_le_bytes()).unwrap();
+  wal.insert(1, &2, &100u32.to_le_bytes()).unwrap();
+  assert_eq!(wal.get(1, &2).unwrap().value(), &100u32.to_le_bytes());
+  assert_eq!(wal.get(2, &2).unwrap().value(), &5u32.to_le_bytes());
+
+  // The removed values are not folded.
+  wal.remove(4, &1).unwrap();
+  wal.merge(5, &1, &7u32.to_le_bytes()).unwrap();
+  assert!(wal.get(4, &1).is_none());
+
+  assert_eq!(
+    wal
+      .iter(5)
+      .map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value())))
+      .collect::<Vec<_>>(),
+    [(1, 7), (2, 5)]
+  );
+  assert_eq!(
+    wal
+      .iter_all_versions(5)
+      .filter(|ent| *ent.key() == 1)
+      .map(|ent| ent.value().map(|v| u32::from_le_bytes(*v)))
+      .collect::<Vec<_>>(),
+    [Some(7), None, Some(13), Some(11), Some(10)]
+  );
+  let reader = wal.reader();
+  assert_eq!(reader.get(3, &1).unwrap().value(), &13u32.to_le_bytes());
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn parallel_replay_merge() {
+  use multiple_version::{OrderWal, OrderWalReader};
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir
+    .path()
+    .join("test_multiple_version_parallel_replay_merge");
+  let mut wal = unsafe {
+    Builder::new()
+      .with_capacity(4 * MB)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .with_merge_operator(Some(&Counter))
+      .map_mut::<OrderWal<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  for i in 0..1000u32 {
+    wal.insert(1, &i, &i.to_le_bytes()).unwrap();
+    wal.merge(3, &i, &1u32.to_le_bytes()).unwrap();
+  }
+  // The range tombstone is written after the merge operands, but with a lower version,
+  // the operands were linked to the values before it.
+  wal.delete_range(2, &0..&500).unwrap();
+  for i in (0..1000u32).step_by(7) {
+    wal.merge(4, &i, &2u32.to_le_bytes()).unwrap();
+  }
+  drop(wal);
+
+  let open = |threads: usize| unsafe {
+    Builder::new()
+      .with_merge_operator(Some(&Counter))
+      .with_replay_threads(threads)
+      .map::<OrderWalReader<u32, [u8; 4]>, _>(&path)
+      .unwrap()
+  };
+  let entries = |wal: &OrderWalReader<u32, [u8; 4]>| {
+    wal
+      .iter_all_versions(4)
+      .map(|ent| (*ent.key(), ent.version(), ent.value().copied()))
+      .collect::<Vec<_>>()
+  };
+
+  let expected = entries(&open(1));
+  let wal = open(4);
+  assert_eq!(entries(&wal), expected);
+  for i in 0..1000u32 {
+    let merged = i + 1 + if i % 7 == 0 { 2 } else { 0 };
+    assert_eq!(wal.get(4, &i).unwrap().value(), &merged.to_le_bytes());
+    assert_eq!(wal.get(3, &i).unwrap().value(), &(i + 1).to_le_bytes());
+    assert_eq!(wal.get(2, &i).is_some(), i >= 500);
+  }
+}
+
+#[test]
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+#[cfg_attr(miri, ignore)]
+fn arena_table_grows() {
+  use multiple_version::{ArenaTable, ArenaTableOptions, OrderWal, OrderWalReader};
+
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("test_multiple_version_arena_table_grows");
+  let opts = ArenaTableOptions::new().with_capacity(2048);
+
+  let mut wal = unsafe {
+    Builder::<ArenaTable<u32, [u8; 4]>>::new()
+      .with_capacity(MB)
+      .with_memtable_options(opts)
+      .with_create_new(true)
+      .with_read(true)
+      .with_write(true)
+      .map_mut::<OrderWal<u32, [u8; 4], ArenaTable<u32, [u8; 4]>>, _>(&path)
+      .unwrap()
+  };
+  wal.insert(0, &0, &[0; 4]).unwrap();
+  let reader = wal.reader();
+  let first = reader.first(0).unwrap();
+
+  // Far more entries than the initial capacity can hold, all the versions and tombstones are kept when growing.
+  for i in 0..1000u32 {
+    wal.insert(1, &i, &i.to_le_bytes()).unwrap();
+    if i % 2 == 0 {
+      wal.remove(2, &i).unwrap();
+    }
+  }
+
+  // The entries read before growing are still valid.
+  assert_eq!(*first.key(), 0);
+  assert_eq!(first.value(), &[0; 4]);
+  drop(wal);
+
+  // The replay is not limited by the initial capacity either.
+  let wal = unsafe {
+    Builder::<ArenaTable<u32, [u8; 4]>>::new()
+      .with_memtable_options(opts)
+      .map::<OrderWalReader<u32, [u8; 4], ArenaTable<u32, [u8; 4]>>, _>(&path)
+      .unwrap()
+  };
+  assert_eq!(wal.get(0, &0).unwrap().value(), &[0; 4]);
+  assert_eq!(wal.iter(1).count(), 1000);
+  assert_eq!(wal.iter(2).count(), 500);
+  for i in 0..1000u32 {
+    assert_eq!(wal.get(1, &i).unwrap().value(), &i.to_le_bytes());
+    assert_eq!(wal.contains_key(2, &i), i % 2 == 1);
+  }
+}
+
+#[cfg(feature = "std")]
+expand_unit_tests!("linked": MultipleVersionOrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Linked]: MultipleVersionTable<_, _> {
+  merge(Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Linked)
+    .with_merge_operator(Some(&Counter))),
+});
+
+expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
+  merge(Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Arena(Default::default()))
+    .with_merge_operator(Some(&Counter))),
+});
+
 #[cfg(feature = "std")]
 expand_unit_tests!(
   move "linked": MultipleVersionOrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Linked]: MultipleVersionTable<_, _> {
@@ -547,3 +1071,197 @@ expand_unit_tests!(
     }
   }
 );
+
+#[cfg(feature = "std")]
+expand_unit_tests!(
+  move "linked": MultipleVersionOrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Linked]: MultipleVersionTable<_, _> {
+    insert_returning |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(2, &1).unwrap().value(), &[2; 4]);
+      assert_eq!(wal.get(4, &1).unwrap().value(), &[4; 4]);
+      assert!(wal.get(5, &1).is_none());
+    },
+    reservation |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert!(wal.get(1, &1).is_none());
+      assert_eq!(wal.get(3, &1).unwrap().value(), &[2; 4]);
+    },
+    transaction |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert!(wal.contains_key(1, &0));
+      assert!(!wal.contains_key(2, &0));
+      assert_eq!(wal.get(2, &1).unwrap().value(), &[1; 4]);
+      assert!(!wal.contains_key(3, &2));
+      assert!(!wal.contains_key(4, &3));
+    },
+    prepared_batch |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(3, &1).unwrap().value(), &[2; 4]);
+      assert!(wal.get(4, &1).is_none());
+      assert_eq!(wal.get(3, &2).unwrap().value(), &[3; 4]);
+    },
+    batch_guards |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(5, &1).unwrap().value(), &[5; 4]);
+      assert_eq!(wal.get(5, &2).unwrap().value(), &[5; 4]);
+      assert!(wal.get(4, &2).is_none());
+    },
+    compare_and_swap |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(2, &1).unwrap().value(), &[2; 4]);
+      assert_eq!(wal.get(6, &1).unwrap().value(), &[6; 4]);
+      assert!(wal.get(8, &1).is_none());
+    },
+    upsert |p, expected: Vec<(u32, u32)>| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(
+        wal.iter(5).map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value()))).collect::<Vec<_>>(),
+        expected
+      );
+      assert_eq!(wal.get(4, &0).unwrap().value(), &4u32.to_le_bytes());
+    },
+    delete_range |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(2, &11).unwrap().value(), &[2; 4]);
+      assert!(wal.get(3, &11).is_none());
+      assert!(wal.get(3, &17).is_none());
+      assert_eq!(wal.get(3, &15).unwrap().value(), &[3; 4]);
+      assert_eq!(wal.iter(3).count(), 21);
+    },
+  }
+);
+
+expand_unit_tests!(
+  move "arena": MultipleVersionOrderWalAlternativeTable<u32, [u8; 4]> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
+    insert_returning |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(2, &1).unwrap().value(), &[2; 4]);
+      assert_eq!(wal.get(4, &1).unwrap().value(), &[4; 4]);
+      assert!(wal.get(5, &1).is_none());
+    },
+    reservation |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert!(wal.get(1, &1).is_none());
+      assert_eq!(wal.get(3, &1).unwrap().value(), &[2; 4]);
+    },
+    transaction |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert!(wal.contains_key(1, &0));
+      assert!(!wal.contains_key(2, &0));
+      assert_eq!(wal.get(2, &1).unwrap().value(), &[1; 4]);
+      assert!(!wal.contains_key(3, &2));
+      assert!(!wal.contains_key(4, &3));
+    },
+    prepared_batch |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(3, &1).unwrap().value(), &[2; 4]);
+      assert!(wal.get(4, &1).is_none());
+      assert_eq!(wal.get(3, &2).unwrap().value(), &[3; 4]);
+    },
+    batch_guards |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(5, &1).unwrap().value(), &[5; 4]);
+      assert_eq!(wal.get(5, &2).unwrap().value(), &[5; 4]);
+      assert!(wal.get(4, &2).is_none());
+    },
+    compare_and_swap |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(2, &1).unwrap().value(), &[2; 4]);
+      assert_eq!(wal.get(6, &1).unwrap().value(), &[6; 4]);
+      assert!(wal.get(8, &1).is_none());
+    },
+    upsert |p, expected: Vec<(u32, u32)>| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(
+        wal.iter(5).map(|ent| (*ent.key(), u32::from_le_bytes(*ent.value()))).collect::<Vec<_>>(),
+        expected
+      );
+      assert_eq!(wal.get(4, &0).unwrap().value(), &4u32.to_le_bytes());
+    },
+    delete_range |p, _res| {
+      let wal = unsafe { Builder::new().map::<MultipleVersionOrderWalReaderAlternativeTable<u32, [u8; 4]>, _>(p).unwrap() };
+
+      assert_eq!(wal.get(2, &11).unwrap().value(), &[2; 4]);
+      assert!(wal.get(3, &11).is_none());
+      assert!(wal.get(3, &17).is_none());
+      assert_eq!(wal.get(3, &15).unwrap().value(), &[3; 4]);
+      assert_eq!(wal.iter(3).count(), 21);
+    },
+  }
+);
+
+#[test]
+fn sorted_table_interleaved() {
+  use multiple_version::SortedTable;
+  use std::collections::BTreeMap;
+
+  let mut wal = Builder::<SortedTable<u32, [u8; 4]>>::new()
+    .with_capacity(4 * MB)
+    .alloc::<OrderWal<u32, [u8; 4], SortedTable<u32, [u8; 4]>>>()
+    .unwrap();
+  let mut expected = BTreeMap::new();
+  let latest = |expected: &BTreeMap<(u32, u64), Option<[u8; 4]>>, k: u32, version: u64| {
+    expected
+      .range((k, 0)..=(k, version))
+      .next_back()
+      .and_then(|(_, v)| *v)
+  };
+
+  // Each write is followed by the reads, so the updates are merged into the array many times.
+  for i in 0..3000u32 {
+    let k = (i * 7919) % 500;
+    let version = i as u64 + 1;
+    if i % 7 == 0 {
+      wal.remove(version, &k).unwrap();
+      expected.insert((k, version), None);
+    } else {
+      wal.insert(version, &k, &i.to_le_bytes()).unwrap();
+      expected.insert((k, version), Some(i.to_le_bytes()));
+    }
+
+    assert_eq!(
+      wal.get(version, &k).map(|ent| *ent.value()),
+      latest(&expected, k, version)
+    );
+    assert_eq!(
+      wal.get(version / 2, &k).map(|ent| *ent.value()),
+      latest(&expected, k, version / 2)
+    );
+    if i % 100 == 0 {
+      let keys = (0..500u32)
+        .filter_map(|k| latest(&expected, k, version).map(|v| (k, v)))
+        .collect::<Vec<_>>();
+      assert!(wal
+        .iter(version)
+        .map(|ent| (*ent.key(), *ent.value()))
+        .eq(keys.iter().copied()));
+      assert!(wal
+        .iter(version)
+        .rev()
+        .map(|ent| *ent.key())
+        .eq(keys.iter().rev().map(|(k, _)| *k)));
+      assert_eq!(
+        wal.iter_all_versions(version).count(),
+        expected
+          .range(..)
+          .filter(|((_, v), _)| *v <= version)
+          .count()
+      );
+    }
+  }
+}
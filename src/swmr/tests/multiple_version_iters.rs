@@ -11,10 +11,14 @@ use super::*;
 #[cfg(feature = "std")]
 expand_unit_tests!("linked": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Linked]: MultipleVersionTable<_, _> {
   iter_all_versions_mvcc,
+  changes_between,
+  history,
 });
 
 expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
   iter_all_versions_mvcc,
+  changes_between,
+  history,
 });
 
 #[cfg(feature = "std")]
@@ -52,6 +56,107 @@ expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<String, Stri
   iter_all_versions_prev_by_versioned_entry(arena_builder!()),
 });
 
+#[cfg(feature = "std")]
+expand_unit_tests!("linked": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Linked]: MultipleVersionTable<_, _> {
+  retention_maximum_versions(crate::Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Linked)
+    .with_maximum_versions_per_key(Some(2))),
+  retention_window(crate::Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Linked)
+    .with_version_window(Some(1))),
+});
+
+expand_unit_tests!("arena": MultipleVersionOrderWalAlternativeTable<str, str> [TableOptions::Arena(Default::default())]: MultipleVersionTable<_, _> {
+  retention_maximum_versions(crate::Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Arena(Default::default()))
+    .with_maximum_versions_per_key(Some(2))),
+  retention_window(crate::Builder::new()
+    .with_capacity(MB)
+    .with_memtable_options(TableOptions::Arena(Default::default()))
+    .with_version_window(Some(1))),
+});
+
+expand_unit_tests!("btree": MultipleVersionOrderWalBTreeTable<str, str> [()]: crate::memtable::btree::MultipleVersionTable<_, _> {
+  retention_maximum_versions(crate::Builder::new()
+    .with_capacity(MB)
+    .with_maximum_versions_per_key(Some(2))),
+  retention_window(crate::Builder::new()
+    .with_capacity(MB)
+    .with_version_window(Some(1))),
+});
+
+expand_unit_tests!("art": MultipleVersionOrderWalArtTable<str, str> [()]: crate::memtable::art::MultipleVersionTable<_, _> {
+  retention_maximum_versions(crate::Builder::new()
+    .with_capacity(MB)
+    .with_maximum_versions_per_key(Some(2))),
+  retention_window(crate::Builder::new()
+    .with_capacity(MB)
+    .with_version_window(Some(1))),
+});
+
+expand_unit_tests!("sorted": MultipleVersionOrderWalSortedTable<str, str> [()]: crate::memtable::sorted::MultipleVersionTable<_, _> {
+  retention_maximum_versions(crate::Builder::new()
+    .with_capacity(MB)
+    .with_maximum_versions_per_key(Some(2))),
+  retention_window(crate::Builder::new()
+    .with_capacity(MB)
+    .with_version_window(Some(1))),
+});
+
+expand_unit_tests!("btree": MultipleVersionOrderWalBTreeTable<str, str> [()]: crate::memtable::btree::MultipleVersionTable<_, _> {
+  iter_all_versions_mvcc,
+  changes_between,
+  history,
+});
+
+expand_unit_tests!("art": MultipleVersionOrderWalArtTable<str, str> [()]: crate::memtable::art::MultipleVersionTable<_, _> {
+  iter_all_versions_mvcc,
+  changes_between,
+  history,
+});
+
+expand_unit_tests!("sorted": MultipleVersionOrderWalSortedTable<str, str> [()]: crate::memtable::sorted::MultipleVersionTable<_, _> {
+  iter_all_versions_mvcc,
+  changes_between,
+  history,
+});
+
+expand_unit_tests!("btree": MultipleVersionOrderWalBTreeTable<String, String> [()]: crate::memtable::btree::MultipleVersionTable<_, _> {
+  iter_next,
+  iter_all_versions_next_by_entry,
+  iter_all_versions_next_by_versioned_entry,
+  range_next,
+  iter_prev,
+  range_prev,
+  iter_all_versions_prev_by_entry,
+  iter_all_versions_prev_by_versioned_entry,
+});
+
+expand_unit_tests!("art": MultipleVersionOrderWalArtTable<String, String> [()]: crate::memtable::art::MultipleVersionTable<_, _> {
+  iter_next,
+  iter_all_versions_next_by_entry,
+  iter_all_versions_next_by_versioned_entry,
+  range_next,
+  iter_prev,
+  range_prev,
+  iter_all_versions_prev_by_entry,
+  iter_all_versions_prev_by_versioned_entry,
+});
+
+expand_unit_tests!("sorted": MultipleVersionOrderWalSortedTable<String, String> [()]: crate::memtable::sorted::MultipleVersionTable<_, _> {
+  iter_next,
+  iter_all_versions_next_by_entry,
+  iter_all_versions_next_by_versioned_entry,
+  range_next,
+  iter_prev,
+  range_prev,
+  iter_all_versions_prev_by_entry,
+  iter_all_versions_prev_by_versioned_entry,
+});
+
 fn make_int_key(i: usize) -> String {
   ::std::format!("{:05}", i)
 }
@@ -62,7 +167,7 @@ fn make_value(i: usize) -> String {
 
 fn iter_all_versions_mvcc<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
 where
-  M: MultipleVersionMemtable<Key = str, Value = str> + 'static,
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -132,9 +237,139 @@ where
   assert_eq!(lower_bound.value().unwrap(), "c1");
 }
 
+fn changes_between<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  wal.insert(1, "a", "a1").unwrap();
+  wal.insert(2, "a", "a2").unwrap();
+  wal.insert(4, "a", "a4").unwrap();
+  wal.insert(1, "b", "b1").unwrap();
+  wal.insert(1, "c", "c1").unwrap();
+  wal.remove(3, "c").unwrap();
+  wal.insert(5, "d", "d5").unwrap();
+
+  let changes = wal
+    .changes_between(1, 3)
+    .map(|ent| {
+      (
+        ent.key().to_string(),
+        ent.value().map(|v| v.to_string()),
+        ent.version(),
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    changes,
+    [
+      ("a".to_string(), Some("a2".to_string()), 2),
+      ("c".to_string(), None, 3),
+    ]
+  );
+
+  let changes = wal
+    .changes_between(1, 3)
+    .rev()
+    .map(|ent| (ent.key().to_string(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(changes, [("c".to_string(), 3), ("a".to_string(), 2)]);
+
+  let changes = wal
+    .changes_between(2, 5)
+    .map(|ent| (ent.key().to_string(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    changes,
+    [
+      ("a".to_string(), 4),
+      ("c".to_string(), 3),
+      ("d".to_string(), 5),
+    ]
+  );
+
+  // mix forward and backward iteration
+  {
+    let mut iter = wal.changes_between(0, 5);
+    assert_eq!(iter.next().unwrap().key(), "a");
+    assert_eq!(iter.next_back().unwrap().key(), "d");
+    assert_eq!(iter.next_back().unwrap().key(), "c");
+    let ent = iter.next().unwrap();
+    assert_eq!(ent.key(), "b");
+    assert_eq!(ent.version(), 1);
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+  }
+
+  assert_eq!(wal.changes_between(5, 10).count(), 0);
+  assert_eq!(wal.changes_between(3, 3).count(), 0);
+  assert_eq!(wal.changes_between(3, 1).count(), 0);
+
+  let changes = wal
+    .range_changes_between(0, 3, "b"..)
+    .map(|ent| (ent.key().to_string(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(changes, [("b".to_string(), 1), ("c".to_string(), 3)]);
+
+  let changes = wal
+    .range_changes_between(1, 5, .."c")
+    .rev()
+    .map(|ent| (ent.key().to_string(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(changes, [("a".to_string(), 4)]);
+
+  // The keys removed by a range tombstone are removed at the version of the tombstone,
+  // even if they are written before `from`.
+  wal.delete_range(6, "a".."c").unwrap();
+  let changes = wal
+    .changes_between(5, 6)
+    .map(|ent| (ent.key().to_string(), ent.value().is_some(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    changes,
+    [("a".to_string(), false, 6), ("b".to_string(), false, 6)]
+  );
+  assert_eq!(wal.changes_between(6, 10).count(), 0);
+
+  wal.insert(7, "b", "b7").unwrap();
+  let changes = wal
+    .changes_between(0, 7)
+    .map(|ent| (ent.key().to_string(), ent.value().is_some(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    changes,
+    [
+      ("a".to_string(), false, 6),
+      ("b".to_string(), true, 7),
+      ("c".to_string(), false, 3),
+      ("d".to_string(), true, 5)
+    ]
+  );
+  let changes = wal
+    .changes_between(5, 7)
+    .rev()
+    .map(|ent| (ent.key().to_string(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(changes, [("b".to_string(), 7), ("a".to_string(), 6)]);
+  let changes = wal
+    .range_changes_between(0, 6, "a"..="c")
+    .rev()
+    .map(|ent| (ent.key().to_string(), ent.version()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    changes,
+    [
+      ("c".to_string(), 3),
+      ("b".to_string(), 6),
+      ("a".to_string(), 6)
+    ]
+  );
+}
+
 fn iter_next<M>(wal: &mut multiple_version::OrderWal<String, String, M>)
 where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -193,7 +428,7 @@ where
 
 fn iter_all_versions_next_by_entry<M>(wal: &mut multiple_version::OrderWal<String, String, M>)
 where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -243,7 +478,7 @@ where
 fn iter_all_versions_next_by_versioned_entry<M>(
   wal: &mut multiple_version::OrderWal<String, String, M>,
 ) where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
   for<'a> M::VersionedItem<'a>: std::fmt::Debug,
@@ -292,7 +527,7 @@ fn iter_all_versions_next_by_versioned_entry<M>(
 
 fn range_next<M>(wal: &mut multiple_version::OrderWal<String, String, M>)
 where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -349,7 +584,7 @@ where
 
 fn iter_prev<M>(wal: &mut multiple_version::OrderWal<String, String, M>)
 where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -400,7 +635,7 @@ where
 
 fn iter_all_versions_prev_by_entry<M>(wal: &mut multiple_version::OrderWal<String, String, M>)
 where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -447,7 +682,7 @@ where
 fn iter_all_versions_prev_by_versioned_entry<M>(
   wal: &mut multiple_version::OrderWal<String, String, M>,
 ) where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
   for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
@@ -495,7 +730,7 @@ fn iter_all_versions_prev_by_versioned_entry<M>(
 
 fn range_prev<M>(wal: &mut multiple_version::OrderWal<String, String, M>)
 where
-  M: MultipleVersionMemtable<Key = String, Value = String> + 'static,
+  M: MultipleVersionMemtable<Key = String, Value = String, Comparator = KeyOrder> + 'static,
   M::Error: std::fmt::Debug,
   for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
 {
@@ -554,3 +789,238 @@ where
   }
   assert_eq!(i, 49);
 }
+
+fn collect_all_versions<M>(
+  wal: &multiple_version::OrderWal<str, str, M>,
+  version: u64,
+  rev: bool,
+) -> Vec<(String, Option<String>, u64)>
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  let iter = wal.iter_all_versions(version);
+  if rev {
+    iter
+      .rev()
+      .map(|ent| {
+        (
+          ent.key().to_string(),
+          ent.value().map(|v| v.to_string()),
+          ent.version(),
+        )
+      })
+      .collect()
+  } else {
+    iter
+      .map(|ent| {
+        (
+          ent.key().to_string(),
+          ent.value().map(|v| v.to_string()),
+          ent.version(),
+        )
+      })
+      .collect()
+  }
+}
+
+fn retention_populate<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  wal.insert(1, "a", "a1").unwrap();
+  wal.insert(2, "a", "a2").unwrap();
+  wal.insert(3, "a", "a3").unwrap();
+  wal.insert(1, "b", "b1").unwrap();
+  wal.insert(2, "c", "c2").unwrap();
+  wal.remove(3, "c").unwrap();
+}
+
+fn retention_maximum_versions<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  retention_populate(wal);
+
+  let expected = [
+    ("a", Some("a3"), 3),
+    ("a", Some("a2"), 2),
+    ("b", Some("b1"), 1),
+    ("c", None, 3),
+    ("c", Some("c2"), 2),
+  ]
+  .into_iter()
+  .map(|(k, v, ver)| (k.to_string(), v.map(|v: &str| v.to_string()), ver))
+  .collect::<Vec<_>>();
+
+  assert_eq!(collect_all_versions(wal, 3, false), expected);
+  let mut rev = expected.clone();
+  rev.reverse();
+  assert_eq!(collect_all_versions(wal, 3, true), rev);
+
+  // The limit is counted from the latest version of the WAL, so the pruned versions
+  // are hidden from the reads at the older versions too.
+  let expected = [
+    ("a", Some("a2"), 2),
+    ("b", Some("b1"), 1),
+    ("c", Some("c2"), 2),
+  ]
+  .into_iter()
+  .map(|(k, v, ver)| (k.to_string(), v.map(|v: &str| v.to_string()), ver))
+  .collect::<Vec<_>>();
+  assert_eq!(collect_all_versions(wal, 2, false), expected);
+  let mut rev = expected.clone();
+  rev.reverse();
+  assert_eq!(collect_all_versions(wal, 2, true), rev);
+
+  let range = wal
+    .range_all_versions(3, ..="a")
+    .rev()
+    .map(|ent| ent.version())
+    .collect::<Vec<_>>();
+  assert_eq!(range, [2, 3]);
+
+  assert_eq!(wal.iter(3).count(), 2);
+  assert_eq!(wal.get(2, "a").unwrap().value(), "a2");
+  assert!(wal.get(1, "a").is_none());
+  assert!(wal.get_versioned(1, "a").is_none());
+  assert!(!wal.contains_key(1, "a"));
+  assert_eq!(wal.iter(1).count(), 1);
+  assert_eq!(wal.history("a").count(), 2);
+
+  // New versions push the old ones out.
+  wal.insert(4, "b", "b4").unwrap();
+  wal.insert(5, "b", "b5").unwrap();
+  assert!(wal.get(1, "b").is_none());
+  assert_eq!(wal.get(4, "b").unwrap().value(), "b4");
+  assert_eq!(wal.iter(1).count(), 0);
+}
+
+fn retention_window<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  retention_populate(wal);
+
+  let expected = [
+    ("a", Some("a3"), 3),
+    ("a", Some("a2"), 2),
+    ("b", Some("b1"), 1),
+    ("c", None, 3),
+    ("c", Some("c2"), 2),
+  ]
+  .into_iter()
+  .map(|(k, v, ver)| (k.to_string(), v.map(|v: &str| v.to_string()), ver))
+  .collect::<Vec<_>>();
+
+  assert_eq!(collect_all_versions(wal, 3, false), expected);
+  let mut rev = expected.clone();
+  rev.reverse();
+  assert_eq!(collect_all_versions(wal, 3, true), rev);
+
+  assert!(wal.get(1, "a").is_none());
+  assert_eq!(wal.get(2, "a").unwrap().value(), "a2");
+
+  // The window is counted from the latest version of the WAL, once it moves forward,
+  // only the latest version of each key is older than the window.
+  wal.insert(10, "d", "d10").unwrap();
+  let expected = [
+    ("a", Some("a3"), 3),
+    ("b", Some("b1"), 1),
+    ("c", None, 3),
+    ("d", Some("d10"), 10),
+  ]
+  .into_iter()
+  .map(|(k, v, ver)| (k.to_string(), v.map(|v: &str| v.to_string()), ver))
+  .collect::<Vec<_>>();
+  assert_eq!(collect_all_versions(wal, 10, false), expected);
+  let mut rev = expected.clone();
+  rev.reverse();
+  assert_eq!(collect_all_versions(wal, 10, true), rev);
+
+  let range = wal
+    .range_all_versions(10, "b"..)
+    .map(|ent| ent.version())
+    .collect::<Vec<_>>();
+  assert_eq!(range, [1, 3, 10]);
+
+  assert!(wal.get(2, "a").is_none());
+  assert!(wal.get_versioned(2, "c").is_none());
+  assert_eq!(wal.get(1, "b").unwrap().value(), "b1");
+  assert_eq!(
+    wal.first(2).map(|ent| ent.key().to_string()),
+    Some("b".to_string())
+  );
+}
+
+fn history<M>(wal: &mut multiple_version::OrderWal<str, str, M>)
+where
+  M: MultipleVersionMemtable<Key = str, Value = str, Comparator = KeyOrder> + 'static,
+  M::Error: std::fmt::Debug,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+  for<'a> M::VersionedItem<'a>: VersionedMemtableEntry<'a> + std::fmt::Debug,
+{
+  wal.insert(1, "a", "a1").unwrap();
+  wal.insert(2, "b", "b2").unwrap();
+  wal.insert(3, "a", "a3").unwrap();
+  wal.remove(4, "a").unwrap();
+  wal.insert(5, "aa", "aa5").unwrap();
+  wal.insert(6, "a", "a6").unwrap();
+
+  let versions = wal
+    .history("a")
+    .map(|ent| (ent.version(), ent.value().map(|v| v.to_string())))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    versions,
+    [
+      (6, Some("a6".to_string())),
+      (4, None),
+      (3, Some("a3".to_string())),
+      (1, Some("a1".to_string())),
+    ]
+  );
+  assert!(wal.history("a").all(|ent| ent.key() == "a"));
+
+  let versions = wal
+    .history("a")
+    .rev()
+    .map(|ent| ent.version())
+    .collect::<Vec<_>>();
+  assert_eq!(versions, [1, 3, 4, 6]);
+
+  let versions = wal
+    .history("b")
+    .map(|ent| ent.version())
+    .collect::<Vec<_>>();
+  assert_eq!(versions, [2]);
+  assert_eq!(wal.history("z").count(), 0);
+
+  let versions =
+    |iter: multiple_version::History<'_, str, M>| iter.map(|ent| ent.version()).collect::<Vec<_>>();
+  assert_eq!(versions(wal.history_range("a", 2..=4)), [4, 3]);
+  assert_eq!(versions(wal.history_range("a", 3..)), [6, 4, 3]);
+  assert_eq!(versions(wal.history_range("a", ..3)), [1]);
+  assert_eq!(versions(wal.history_range("a", ..0)), []);
+  assert_eq!(
+    versions(wal.history_range("a", (Bound::Included(4), Bound::Included(3)))),
+    []
+  );
+
+  let mut iter = wal.history_range("a", 2..);
+  assert_eq!(iter.next().unwrap().version(), 6);
+  assert_eq!(iter.next_back().unwrap().version(), 3);
+  assert_eq!(iter.next().unwrap().version(), 4);
+  assert!(iter.next_back().is_none());
+  assert!(iter.next().is_none());
+}
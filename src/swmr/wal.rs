@@ -1,8 +1,16 @@
-use core::marker::PhantomData;
+use core::{
+  marker::PhantomData,
+  sync::atomic::{AtomicU64, Ordering},
+};
 
 use rarena_allocator::sync::Arena;
 
-use crate::{memtable::BaseTable, sealed::Wal, Options};
+use crate::{
+  memtable::BaseTable,
+  sealed::Wal,
+  wal::{BloomFilter, MergeOperands, RangeDeletions},
+  Options,
+};
 
 pub struct OrderCore<K, V, M, S>
 where
@@ -11,8 +19,14 @@ where
 {
   pub(super) arena: Arena,
   pub(super) map: M,
+  pub(super) range_deletions: RangeDeletions,
+  pub(super) merge_operands: MergeOperands,
+  pub(super) bloom_filter: Option<BloomFilter>,
   pub(super) opts: Options,
   pub(super) cks: S,
+  pub(super) next_transaction_id: AtomicU64,
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  pub(super) readiness: crate::wal::Readiness<K, V>,
   pub(super) _m: PhantomData<(fn() -> K, fn() -> V)>,
 }
 
@@ -25,11 +39,36 @@ where
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
     f.debug_struct("OrderCore")
       .field("arena", &self.arena)
+      .field("bloom_filter", &self.bloom_filter)
       .field("options", &self.opts)
       .finish()
   }
 }
 
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+impl<K, V, M, S> Drop for OrderCore<K, V, M, S>
+where
+  K: ?Sized,
+  V: ?Sized,
+{
+  fn drop(&mut self) {
+    use rarena_allocator::Allocator;
+
+    if !self.opts.persisted_index() || self.arena.read_only() {
+      return;
+    }
+
+    if let Some(path) = self.arena.path() {
+      // The index only speeds up the next open, the WAL is replayed in full if it is missing.
+      if let Err(e) = crate::wal::Index::persist(&crate::wal::index_path(path), &self.arena) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(err=%e, "failed to persist the index of the WAL");
+        let _ = e;
+      }
+    }
+  }
+}
+
 impl<K, V, M, S> Wal<S> for OrderCore<K, V, M, S>
 where
   K: ?Sized,
@@ -50,16 +89,63 @@ where
   }
 
   #[inline]
-  fn construct(arena: Self::Allocator, set: Self::Memtable, opts: Options, checksumer: S) -> Self {
+  fn construct(
+    arena: Self::Allocator,
+    set: Self::Memtable,
+    range_deletions: RangeDeletions,
+    merge_operands: MergeOperands,
+    bloom_filter: Option<BloomFilter>,
+    opts: Options,
+    checksumer: S,
+    next_transaction_id: u64,
+  ) -> Self {
     Self {
       arena,
       map: set,
+      range_deletions,
+      merge_operands,
+      bloom_filter,
       opts,
       cks: checksumer,
+      next_transaction_id: AtomicU64::new(next_transaction_id),
+      #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+      readiness: crate::wal::Readiness::new(),
       _m: PhantomData,
     }
   }
 
+  #[inline]
+  fn next_transaction_id(&self) -> u64 {
+    self.next_transaction_id.fetch_add(1, Ordering::Relaxed)
+  }
+
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[inline]
+  fn advance_transaction_id(&self, next: u64) {
+    self.next_transaction_id.fetch_max(next, Ordering::Relaxed);
+  }
+
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[inline]
+  fn readiness(&self) -> &crate::wal::Readiness<K, V> {
+    &self.readiness
+  }
+
+  #[inline]
+  fn range_deletions(&self) -> &RangeDeletions {
+    &self.range_deletions
+  }
+
+  #[inline]
+  fn merge_operands(&self) -> &MergeOperands {
+    &self.merge_operands
+  }
+
+  #[inline]
+  fn bloom_filter(&self) -> Option<&BloomFilter> {
+    self.bloom_filter.as_ref()
+  }
+
   #[inline]
   fn options(&self) -> &Options {
     &self.opts
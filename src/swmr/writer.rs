@@ -56,10 +56,8 @@ where
   }
 
   #[inline]
-  fn from_core(core: Self::Wal) -> Self {
-    Self {
-      core: Arc::new(core),
-    }
+  fn from_shared_core(core: Arc<Self::Wal>) -> Self {
+    Self { core }
   }
 }
 
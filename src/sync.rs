@@ -0,0 +1,118 @@
+use core::{
+  cell::UnsafeCell,
+  ops::{Deref, DerefMut},
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The lock is held by a writer.
+const WRITER: usize = 1 << (usize::BITS - 1);
+/// A writer is waiting for the lock, the new readers wait until it is released.
+const WAITING: usize = 1 << (usize::BITS - 2);
+/// The number of the readers holding the lock.
+const READERS: usize = !(WRITER | WAITING);
+
+/// A spin based reader-writer lock, which prefers the writers.
+///
+/// It is shared by the memtables and the range deletions, whose critical sections are short lookups,
+/// and there is only one writer, so spinning is cheaper than parking, and it does not require `std`.
+/// Once a writer is waiting, the new readers wait for it, so a stream of the readers cannot starve it.
+pub(crate) struct RwLock<T> {
+  state: AtomicUsize,
+  data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for RwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+  #[inline]
+  pub(crate) const fn new(data: T) -> Self {
+    Self {
+      state: AtomicUsize::new(0),
+      data: UnsafeCell::new(data),
+    }
+  }
+
+  #[inline]
+  pub(crate) fn read(&self) -> ReadGuard<'_, T> {
+    loop {
+      let state = self.state.load(Ordering::Relaxed);
+      if state & !READERS == 0
+        && self
+          .state
+          .compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Relaxed)
+          .is_ok()
+      {
+        return ReadGuard(self);
+      }
+      core::hint::spin_loop();
+    }
+  }
+
+  #[inline]
+  pub(crate) fn write(&self) -> WriteGuard<'_, T> {
+    loop {
+      let state = self.state.load(Ordering::Relaxed);
+      if state & !WAITING == 0 {
+        if self
+          .state
+          .compare_exchange_weak(state, WRITER, Ordering::Acquire, Ordering::Relaxed)
+          .is_ok()
+        {
+          return WriteGuard(self);
+        }
+      } else if state & WAITING == 0 {
+        // Blocks the new readers, the lock is acquired once the current readers release it.
+        self.state.fetch_or(WAITING, Ordering::Relaxed);
+      }
+      core::hint::spin_loop();
+    }
+  }
+}
+
+pub(crate) struct ReadGuard<'a, T>(&'a RwLock<T>);
+
+impl<T> Deref for ReadGuard<'_, T> {
+  type Target = T;
+
+  #[inline]
+  fn deref(&self) -> &T {
+    // SAFETY: no writer can hold the lock while the guard is alive.
+    unsafe { &*self.0.data.get() }
+  }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+  #[inline]
+  fn drop(&mut self) {
+    self.0.state.fetch_sub(1, Ordering::Release);
+  }
+}
+
+pub(crate) struct WriteGuard<'a, T>(&'a RwLock<T>);
+
+impl<T> Deref for WriteGuard<'_, T> {
+  type Target = T;
+
+  #[inline]
+  fn deref(&self) -> &T {
+    // SAFETY: the guard holds the lock exclusively.
+    unsafe { &*self.0.data.get() }
+  }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T> {
+  #[inline]
+  fn deref_mut(&mut self) -> &mut T {
+    // SAFETY: the guard holds the lock exclusively.
+    unsafe { &mut *self.0.data.get() }
+  }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+  #[inline]
+  fn drop(&mut self) {
+    // Keeps the other writers waiting ahead of the readers.
+    self.0.state.fetch_and(!WRITER, Ordering::Release);
+  }
+}
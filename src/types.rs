@@ -4,11 +4,23 @@ pub use dbutils::{
   types::*,
 };
 
-use crate::{utils::merge_lengths, CHECKSUM_SIZE, RECORD_FLAG_SIZE, VERSION_SIZE};
+use crate::{
+  utils::merge_lengths, CHECKSUM_SIZE, EXPIRATION_SIZE, RECORD_FLAG_SIZE, TRANSACTION_ID_SIZE,
+  VERSION_SIZE,
+};
 
 pub(crate) mod base;
 pub(crate) mod multiple_version;
 
+mod hlc;
+pub use hlc::*;
+
+mod merge;
+pub use merge::*;
+
+mod comparator;
+pub use comparator::*;
+
 const ENTRY_FLAGS_SIZE: usize = core::mem::size_of::<EntryFlags>();
 
 /// The kind of the Write-Ahead Log.
@@ -50,6 +62,16 @@ bitflags::bitflags! {
     const POINTER = 0b00000010;
     /// Third bit: 1 indicates the entry contains a version
     const VERSIONED = 0b00000100;
+    /// Fourth bit: 1 indicates the entry is a range tombstone, the key is the start bound and the value is the end bound
+    const RANGE_DELETION = 0b00001000;
+    /// Fifth bit: 1 indicates the value is a merge operand, which is folded over the previous value of the key
+    const MERGE = 0b00010000;
+    /// Sixth bit: 1 indicates the entry belongs to a transaction, the transaction id follows the version
+    const TRANSACTION = 0b00100000;
+    /// Seventh bit: 1 indicates the entry is the commit marker of a transaction
+    const TRANSACTION_COMMIT = 0b01000000;
+    /// Eighth bit: 1 indicates the entry is the abort marker of a transaction
+    const TRANSACTION_ABORT = 0b10000000;
   }
 }
 
@@ -65,21 +87,34 @@ pub(crate) struct EncodedEntryMeta {
   pub(crate) klen: usize,
   pub(crate) vlen: usize,
   pub(crate) versioned: bool,
+  pub(crate) transactional: bool,
+  expiring: bool,
   batch: bool,
 }
 
 impl EncodedEntryMeta {
   #[inline]
-  pub(crate) const fn new(key_len: usize, value_len: usize, versioned: bool) -> Self {
+  pub(crate) const fn new(
+    key_len: usize,
+    value_len: usize,
+    versioned: bool,
+    transactional: bool,
+  ) -> Self {
     // Cast to u32 is safe, because we already checked those values before calling this function.
 
     let len = merge_lengths(key_len as u32, value_len as u32);
     let len_size = encoded_u64_varint_len(len);
     let version_size = if versioned { VERSION_SIZE } else { 0 };
+    let transaction_id_size = if transactional {
+      TRANSACTION_ID_SIZE
+    } else {
+      0
+    };
     let elen = RECORD_FLAG_SIZE as u32
       + len_size as u32
       + ENTRY_FLAGS_SIZE as u32
       + version_size as u32
+      + transaction_id_size as u32
       + key_len as u32
       + value_len as u32
       + CHECKSUM_SIZE as u32;
@@ -92,9 +127,21 @@ impl EncodedEntryMeta {
       klen: key_len,
       vlen: value_len,
       versioned,
+      transactional,
+      expiring: false,
     }
   }
 
+  /// Reserves the expiration time in the entry, which follows the transaction id.
+  #[inline]
+  pub(crate) const fn with_expiration(mut self) -> Self {
+    if !self.expiring {
+      self.expiring = true;
+      self.entry_size += EXPIRATION_SIZE as u32;
+    }
+    self
+  }
+
   #[inline]
   pub(crate) const fn batch(key_len: usize, value_len: usize, versioned: bool) -> Self {
     // Cast to u32 is safe, because we already checked those values before calling this function.
@@ -115,6 +162,8 @@ impl EncodedEntryMeta {
       klen: key_len,
       vlen: value_len,
       versioned,
+      transactional: false,
+      expiring: false,
       batch: true,
     }
   }
@@ -128,6 +177,8 @@ impl EncodedEntryMeta {
       klen: 0,
       vlen: 0,
       versioned,
+      transactional: false,
+      expiring: false,
       batch: true,
     }
   }
@@ -147,7 +198,7 @@ impl EncodedEntryMeta {
   }
 
   #[inline]
-  pub(crate) const fn key_offset(&self) -> usize {
+  pub(crate) const fn transaction_id_offset(&self) -> usize {
     if self.versioned {
       self.version_offset() + VERSION_SIZE
     } else {
@@ -155,6 +206,24 @@ impl EncodedEntryMeta {
     }
   }
 
+  #[inline]
+  pub(crate) const fn expiration_offset(&self) -> usize {
+    if self.transactional {
+      self.transaction_id_offset() + TRANSACTION_ID_SIZE
+    } else {
+      self.transaction_id_offset()
+    }
+  }
+
+  #[inline]
+  pub(crate) const fn key_offset(&self) -> usize {
+    if self.expiring {
+      self.expiration_offset() + EXPIRATION_SIZE
+    } else {
+      self.expiration_offset()
+    }
+  }
+
   #[inline]
   pub(crate) const fn value_offset(&self) -> usize {
     self.key_offset() + self.klen
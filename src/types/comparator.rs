@@ -0,0 +1,148 @@
+use core::cmp;
+
+use dbutils::types::{KeyRef, Type};
+
+/// A comparator which orders the encoded keys of a WAL, instead of the ordering of the key type.
+///
+/// The comparator is the last type parameter of the memtables, e.g. `linked::Table<K, V, C>`, and the
+/// identity of the comparator, which is derived from its [`NAME`](Comparator::NAME), is recorded in the
+/// header of the WAL when the WAL is created. The WAL can only be opened with the comparator of the same
+/// name, so the name must be changed whenever the ordering is changed.
+///
+/// The keys which are equal by the comparator are the same key, e.g. a case-insensitive comparator makes
+/// `b"a"` and `b"A"` the same key. The Bloom filter and the parallel replay hash the encoded keys, so they
+/// are disabled for the WALs ordered by a comparator.
+///
+/// The typed lookups, e.g. `get` and `range`, compare the queries by the ordering of the key type, so they
+/// are only available for the memtables ordered by [`KeyOrder`]. Use the lookups by the encoded keys, e.g.
+/// `get_by_bytes` and `range_by_bytes`, with a comparator.
+///
+/// ## Example
+///
+/// ```rust
+/// use orderwal::{
+///   base::{BTreeTable, OrderWal, Reader, Writer},
+///   types::Comparator,
+///   Builder,
+/// };
+///
+/// struct Reverse;
+///
+/// impl Comparator for Reverse {
+///   const NAME: &'static str = "reverse";
+///
+///   fn compare(a: &[u8], b: &[u8]) -> core::cmp::Ordering {
+///     b.cmp(a)
+///   }
+/// }
+///
+/// let mut wal = Builder::new()
+///   .with_capacity(1024)
+///   .alloc::<OrderWal<[u8], [u8], BTreeTable<[u8], [u8], Reverse>>>()
+///   .unwrap();
+/// wal.insert(b"a".as_slice(), b"1".as_slice()).unwrap();
+/// wal.insert(b"b".as_slice(), b"2".as_slice()).unwrap();
+///
+/// assert_eq!(wal.first().unwrap().key(), b"b");
+/// assert_eq!(unsafe { wal.get_by_bytes(b"a") }.unwrap().value(), b"1");
+/// ```
+///
+/// The typed lookups do not compile with a comparator:
+///
+/// ```rust,compile_fail
+/// # use orderwal::{base::{BTreeTable, OrderWal, Reader}, types::Comparator, Builder};
+/// # struct Reverse;
+/// # impl Comparator for Reverse {
+/// #   const NAME: &'static str = "reverse";
+/// #   fn compare(a: &[u8], b: &[u8]) -> core::cmp::Ordering { b.cmp(a) }
+/// # }
+/// let wal = Builder::new()
+///   .with_capacity(1024)
+///   .alloc::<OrderWal<[u8], [u8], BTreeTable<[u8], [u8], Reverse>>>()
+///   .unwrap();
+/// wal.get(b"a".as_slice());
+/// ```
+pub trait Comparator: 'static {
+  /// The name of the comparator, which identifies the ordering of the WAL.
+  const NAME: &'static str;
+
+  /// Compares two encoded keys.
+  fn compare(a: &[u8], b: &[u8]) -> cmp::Ordering;
+}
+
+/// The default comparator of the memtables, which orders the keys by the key type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct KeyOrder;
+
+/// Compares the encoded keys stored in the memtables, by the ordering of the key type or a [`Comparator`].
+pub trait KeyComparator<K: ?Sized>: 'static {
+  /// The identity recorded in the header of the WAL, `0` for the ordering of the key type.
+  const ID: u64;
+
+  /// Compares two encoded keys.
+  ///
+  /// ## Safety
+  /// - `a` and `b` must be the bytes encoded by the key type.
+  unsafe fn compare(a: &[u8], b: &[u8]) -> cmp::Ordering;
+
+  /// Returns `true` if two encoded keys are the same key.
+  ///
+  /// ## Safety
+  /// - `a` and `b` must be the bytes encoded by the key type.
+  unsafe fn equivalent(a: &[u8], b: &[u8]) -> bool;
+}
+
+impl<K> KeyComparator<K> for KeyOrder
+where
+  K: Type + ?Sized,
+  for<'a> K::Ref<'a>: KeyRef<'a, K>,
+{
+  const ID: u64 = 0;
+
+  #[inline]
+  unsafe fn compare(a: &[u8], b: &[u8]) -> cmp::Ordering {
+    <K::Ref<'_> as KeyRef<K>>::compare_binary(a, b)
+  }
+
+  #[inline]
+  unsafe fn equivalent(a: &[u8], b: &[u8]) -> bool {
+    a == b
+  }
+}
+
+impl<K, C> KeyComparator<K> for C
+where
+  K: ?Sized,
+  C: Comparator,
+{
+  const ID: u64 = id(C::NAME);
+
+  #[inline]
+  unsafe fn compare(a: &[u8], b: &[u8]) -> cmp::Ordering {
+    C::compare(a, b)
+  }
+
+  #[inline]
+  unsafe fn equivalent(a: &[u8], b: &[u8]) -> bool {
+    C::compare(a, b).is_eq()
+  }
+}
+
+/// Returns the identity of the comparator, which is never `0`, the WALs ordered by the key type record `0`.
+const fn id(name: &str) -> u64 {
+  // FNV-1a, which is stable across the builds and the platforms.
+  let name = name.as_bytes();
+  let mut h = 0xcbf29ce484222325u64;
+  let mut i = 0;
+  while i < name.len() {
+    h ^= name[i] as u64;
+    h = h.wrapping_mul(0x100000001b3);
+    i += 1;
+  }
+
+  if h == 0 {
+    1
+  } else {
+    h
+  }
+}
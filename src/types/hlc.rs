@@ -0,0 +1,215 @@
+#[cfg(feature = "std")]
+use core::{
+  sync::atomic::{AtomicU64, Ordering},
+  time::Duration,
+};
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOGICAL_BITS: u32 = 16;
+const LOGICAL_MASK: u64 = (1 << LOGICAL_BITS) - 1;
+const MAXIMUM_PHYSICAL: u64 = u64::MAX >> LOGICAL_BITS;
+
+/// A hybrid logical clock timestamp, which can be used as the version of the multiple version WALs.
+///
+/// The high 48 bits are the physical time in milliseconds since the UNIX epoch,
+/// and the low 16 bits are a logical counter, which orders the events happened in the same millisecond.
+/// Hence, the timestamps are ordered in the same way as the versions they are encoded to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct HlcTimestamp(u64);
+
+impl HlcTimestamp {
+  /// The minimum timestamp.
+  pub const MIN: Self = Self(0);
+
+  /// The maximum timestamp.
+  pub const MAX: Self = Self(u64::MAX);
+
+  /// Creates a new timestamp from the physical time (in milliseconds since the UNIX epoch) and the logical counter.
+  ///
+  /// The physical time is saturated to 48 bits.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::types::HlcTimestamp;
+  ///
+  /// let ts = HlcTimestamp::new(1_000, 7);
+  /// assert_eq!(ts.physical(), 1_000);
+  /// assert_eq!(ts.logical(), 7);
+  /// assert_eq!(HlcTimestamp::from_version(ts.version()), ts);
+  /// ```
+  #[inline]
+  pub const fn new(physical: u64, logical: u16) -> Self {
+    let physical = if physical > MAXIMUM_PHYSICAL {
+      MAXIMUM_PHYSICAL
+    } else {
+      physical
+    };
+    Self((physical << LOGICAL_BITS) | logical as u64)
+  }
+
+  /// Creates a timestamp from a version of the multiple version WALs.
+  #[inline]
+  pub const fn from_version(version: u64) -> Self {
+    Self(version)
+  }
+
+  /// Returns the version of the multiple version WALs encoded by this timestamp.
+  #[inline]
+  pub const fn version(&self) -> u64 {
+    self.0
+  }
+
+  /// Returns the physical time in milliseconds since the UNIX epoch.
+  #[inline]
+  pub const fn physical(&self) -> u64 {
+    self.0 >> LOGICAL_BITS
+  }
+
+  /// Returns the logical counter.
+  #[inline]
+  pub const fn logical(&self) -> u16 {
+    (self.0 & LOGICAL_MASK) as u16
+  }
+
+  /// Returns the smallest timestamp at the given time, the time is truncated to milliseconds.
+  ///
+  /// The time before the UNIX epoch is treated as the UNIX epoch.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  pub fn earliest_at(time: SystemTime) -> Self {
+    Self::new(millis_since_epoch(time), 0)
+  }
+
+  /// Returns the largest timestamp at the given time, the time is truncated to milliseconds.
+  ///
+  /// This is the version to query with when we want to see the WAL as of the given time,
+  /// all the events happened in the same millisecond are included.
+  ///
+  /// The time before the UNIX epoch is treated as the UNIX epoch.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::types::HlcTimestamp;
+  /// use std::time::{Duration, UNIX_EPOCH};
+  ///
+  /// let ts = HlcTimestamp::latest_at(UNIX_EPOCH + Duration::from_millis(1_000));
+  /// assert_eq!(ts.physical(), 1_000);
+  /// assert_eq!(ts.logical(), u16::MAX);
+  /// ```
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  pub fn latest_at(time: SystemTime) -> Self {
+    Self::new(millis_since_epoch(time), u16::MAX)
+  }
+
+  /// Returns the physical time of the timestamp.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  pub fn system_time(&self) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(self.physical())
+  }
+}
+
+impl From<u64> for HlcTimestamp {
+  #[inline]
+  fn from(version: u64) -> Self {
+    Self::from_version(version)
+  }
+}
+
+impl From<HlcTimestamp> for u64 {
+  #[inline]
+  fn from(ts: HlcTimestamp) -> Self {
+    ts.version()
+  }
+}
+
+/// A hybrid logical clock, which generates strictly increasing [`HlcTimestamp`]s close to the wall-clock time.
+///
+/// Use the versions generated by the clock when writing to the multiple version WALs,
+/// then the time-travel queries (e.g. `get_as_of`, `iter_as_of`) can be used to read the WALs as of a wall-clock time.
+///
+/// ## Example
+///
+/// ```rust
+/// use orderwal::types::HybridLogicalClock;
+///
+/// let clock = HybridLogicalClock::new();
+/// let a = clock.now();
+/// let b = clock.now();
+/// assert!(a < b);
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Default)]
+pub struct HybridLogicalClock {
+  last: AtomicU64,
+}
+
+#[cfg(feature = "std")]
+impl HybridLogicalClock {
+  /// Creates a new clock.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      last: AtomicU64::new(0),
+    }
+  }
+
+  /// Creates a new clock which never generates a timestamp less than or equal to `last`.
+  ///
+  /// This is useful when reopening a WAL, e.g. `HybridLogicalClock::with_last(wal.maximum_version().into())`.
+  #[inline]
+  pub const fn with_last(last: HlcTimestamp) -> Self {
+    Self {
+      last: AtomicU64::new(last.version()),
+    }
+  }
+
+  /// Returns the last timestamp generated or observed by the clock.
+  #[inline]
+  pub fn last(&self) -> HlcTimestamp {
+    HlcTimestamp(self.last.load(Ordering::Acquire))
+  }
+
+  /// Generates a new timestamp, which is greater than all the timestamps generated or observed before.
+  #[inline]
+  pub fn now(&self) -> HlcTimestamp {
+    let physical = HlcTimestamp::earliest_at(SystemTime::now()).version();
+    let mut last = self.last.load(Ordering::Acquire);
+    loop {
+      let next = physical.max(last.saturating_add(1));
+      match self
+        .last
+        .compare_exchange_weak(last, next, Ordering::AcqRel, Ordering::Acquire)
+      {
+        Ok(_) => return HlcTimestamp(next),
+        Err(current) => last = current,
+      }
+    }
+  }
+
+  /// Observes a timestamp from another node, and then generates a new timestamp
+  /// which is greater than both the observed one and all the timestamps generated or observed before.
+  #[inline]
+  pub fn update(&self, observed: HlcTimestamp) -> HlcTimestamp {
+    self.last.fetch_max(observed.version(), Ordering::AcqRel);
+    self.now()
+  }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn millis_since_epoch(time: SystemTime) -> u64 {
+  time
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis().min(u64::MAX as u128) as u64)
+    .unwrap_or(0)
+}
@@ -0,0 +1,45 @@
+use std::vec::Vec;
+
+/// A merge operator, which folds the merge operands of a key over its existing value.
+///
+/// The operands are appended by `merge` instead of the full values, and are folded lazily when the value is read.
+/// The operator works on the encoded keys and values, and must be deterministic, because the same operands
+/// are folded again when the WAL is reopened.
+///
+/// ## Example
+///
+/// ```rust
+/// use orderwal::types::MergeOperator;
+///
+/// /// Adds the little-endian `u64` operands to the existing value.
+/// struct Counter;
+///
+/// impl MergeOperator for Counter {
+///   fn name(&self) -> &str {
+///     "counter"
+///   }
+///
+///   fn full_merge(&self, _: &[u8], existing: Option<&[u8]>, operands: &[&[u8]]) -> Vec<u8> {
+///     let decode = |src: &[u8]| u64::from_le_bytes(src.try_into().unwrap());
+///     let base = existing.map_or(0, decode);
+///     operands.iter().map(|op| decode(op)).fold(base, u64::wrapping_add).to_le_bytes().to_vec()
+///   }
+/// }
+///
+/// assert_eq!(Counter.full_merge(b"k", Some(&1u64.to_le_bytes()), &[&2u64.to_le_bytes()]), 3u64.to_le_bytes());
+/// ```
+pub trait MergeOperator: Send + Sync {
+  /// Returns the name of the merge operator.
+  fn name(&self) -> &str;
+
+  /// Folds the operands over the existing value of the key, and returns the encoded merged value.
+  ///
+  /// `existing` is `None` if the key does not have a value, and the operands are ordered from the oldest to the newest.
+  fn full_merge(&self, key: &[u8], existing: Option<&[u8]>, operands: &[&[u8]]) -> Vec<u8>;
+}
+
+impl core::fmt::Debug for dyn MergeOperator {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_tuple("MergeOperator").field(&self.name()).finish()
+  }
+}
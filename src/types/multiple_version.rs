@@ -1,7 +1,7 @@
 use dbutils::types::{KeyRef, Type};
 use skl::LazyRef;
 
-use crate::memtable::VersionedMemtableEntry;
+use crate::{memtable::VersionedMemtableEntry, types::HlcTimestamp};
 
 /// The reference to an entry in the generic WALs.
 pub struct Entry<'a, E>
@@ -119,6 +119,14 @@ where
     self.version
   }
 
+  /// Returns the version of the entry as a hybrid logical clock timestamp.
+  ///
+  /// This is only meaningful when the versions are generated by a [`HybridLogicalClock`](crate::types::HybridLogicalClock).
+  #[inline]
+  pub const fn timestamp(&self) -> HlcTimestamp {
+    HlcTimestamp::from_version(self.version)
+  }
+
   /// Returns the key of the entry.
   #[inline]
   pub fn key(&self) -> &<E::Key as Type>::Ref<'a> {
@@ -244,6 +252,14 @@ where
     self.version
   }
 
+  /// Returns the version of the entry as a hybrid logical clock timestamp.
+  ///
+  /// This is only meaningful when the versions are generated by a [`HybridLogicalClock`](crate::types::HybridLogicalClock).
+  #[inline]
+  pub const fn timestamp(&self) -> HlcTimestamp {
+    HlcTimestamp::from_version(self.version)
+  }
+
   /// Returns the key of the entry.
   #[inline]
   pub fn key(&self) -> &<E::Key as Type>::Ref<'a> {
@@ -364,6 +380,14 @@ where
     self.version
   }
 
+  /// Returns the version of the entry as a hybrid logical clock timestamp.
+  ///
+  /// This is only meaningful when the versions are generated by a [`HybridLogicalClock`](crate::types::HybridLogicalClock).
+  #[inline]
+  pub const fn timestamp(&self) -> HlcTimestamp {
+    HlcTimestamp::from_version(self.version)
+  }
+
   /// Returns the value of the entry.
   #[inline]
   pub fn value(&self) -> &<E::Value as Type>::Ref<'a> {
@@ -445,6 +469,18 @@ where
       }
     }
   }
+
+  /// Creates an entry of a change, which is `ent` itself, or the removal of its key at the version of
+  /// the range tombstone which removes it.
+  #[inline]
+  pub(crate) fn changed(ent: E, removed: Option<u64>, query_version: u64) -> Self {
+    let mut this = Self::with_version(ent, query_version);
+    if let Some(version) = removed {
+      this.value = None;
+      this.version = version;
+    }
+    this
+  }
 }
 
 impl<'a, E> VersionedEntry<'a, E>
@@ -490,6 +526,14 @@ where
     self.version
   }
 
+  /// Returns the version of the entry as a hybrid logical clock timestamp.
+  ///
+  /// This is only meaningful when the versions are generated by a [`HybridLogicalClock`](crate::types::HybridLogicalClock).
+  #[inline]
+  pub const fn timestamp(&self) -> HlcTimestamp {
+    HlcTimestamp::from_version(self.version)
+  }
+
   /// Returns the key of the entry.
   #[inline]
   pub fn key(&self) -> &<E::Key as Type>::Ref<'a> {
@@ -1,4 +1,8 @@
+use std::borrow::Cow;
+
 pub use dbutils::leb128;
+use dbutils::types::{MaybeStructured, Type};
+use skl::either::Either;
 
 /// Merge two `u32` into a `u64`.
 ///
@@ -18,3 +22,46 @@ pub(crate) const fn merge_lengths(a: u32, b: u32) -> u64 {
 pub(crate) const fn split_lengths(len: u64) -> (u32, u32) {
   ((len >> 32) as u32, len as u32)
 }
+
+/// Returns the milliseconds since the UNIX epoch, which the expiration time of the entries is measured in.
+#[inline]
+#[cfg(feature = "std")]
+pub(crate) fn unix_millis() -> u64 {
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Returns the encoded bytes of a [`MaybeStructured`], the structured data is encoded into a new buffer.
+#[inline]
+pub(crate) fn encoded_bytes<'a, T>(data: &MaybeStructured<'a, T>) -> Result<Cow<'a, [u8]>, T::Error>
+where
+  T: Type + ?Sized,
+{
+  match data.data() {
+    Either::Left(val) => val.encode_into_vec().map(Cow::Owned),
+    Either::Right(raw) => Ok(Cow::Borrowed(raw)),
+  }
+}
+
+/// Calls `f` with the encoded bytes of a query, the query is encoded on the stack if it is short.
+#[inline]
+pub(crate) fn with_encoded<Q, R>(key: &Q, f: impl FnOnce(&[u8]) -> R) -> Result<R, Q::Error>
+where
+  Q: Type + ?Sized,
+{
+  const INLINE: usize = 64;
+
+  if let Some(raw) = key.as_encoded() {
+    return Ok(f(raw));
+  }
+
+  let len = key.encoded_len();
+  if len <= INLINE {
+    let mut buf = [0; INLINE];
+    let written = key.encode(&mut buf[..len])?;
+    return Ok(f(&buf[..written]));
+  }
+
+  key.encode_into_vec().map(|buf| f(&buf))
+}
@@ -7,3 +7,31 @@ pub(crate) use query::*;
 
 mod pointer;
 pub use pointer::*;
+
+mod range_deletion;
+pub use range_deletion::*;
+
+mod retention;
+pub(crate) use retention::*;
+
+mod merge;
+pub use merge::MergeOperands;
+
+mod transaction;
+pub use transaction::Transaction;
+
+mod bloom;
+pub use bloom::{BloomFilter, BloomFilterStats};
+
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+mod index;
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+pub(crate) use index::{index_path, Index};
+
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+mod readiness;
+#[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+pub use readiness::Readiness;
+
+mod reservation;
+pub use reservation::Reservation;
@@ -5,23 +5,26 @@ use dbutils::{
   buffer::VacantBuffer,
   checksum::BuildChecksumer,
   equivalent::Comparable,
-  types::{KeyRef, MaybeStructured, Type},
+  types::{KeyRef, MaybeStructured, Type, TypeRef},
 };
 #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
 use rarena_allocator::Allocator;
 use ref_cast::RefCast;
 use skl::{either::Either, KeySize};
+use std::vec::Vec;
 
 use crate::{
-  batch::Batch,
+  batch::{Batch, PreparedBatch},
   error::Error,
   memtable::{BaseTable, Memtable, MemtableEntry},
   sealed::{Constructable, Wal, WalReader},
-  types::{base::Entry, BufWriter, KeyBuilder, ValueBuilder},
+  types::{base::Entry, BufWriter, KeyBuilder, KeyOrder, ValueBuilder},
+  utils::{encoded_bytes, with_encoded},
+  wal::{BloomFilter, BloomFilterStats, Reservation, Transaction},
   Options,
 };
 
-use super::{Query, QueryRange, Slice};
+use super::{Query, QueryRange, Slice, SliceRange};
 
 mod iter;
 pub use iter::*;
@@ -46,6 +49,32 @@ pub trait Reader: Constructable {
     self.as_wal().path()
   }
 
+  /// Returns `true` if the memtable is replayed, it is `false` until the replay in the background completes.
+  ///
+  /// The reads do not wait for the replay, before it completes they only see the entries replayed so far.
+  ///
+  /// See [`Builder::with_lazy_replay`](crate::Builder::with_lazy_replay) for more details.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  #[inline]
+  fn is_ready(&self) -> bool {
+    self.as_wal().readiness().is_ready()
+  }
+
+  /// Blocks until the memtable is replayed, returns immediately if the WAL is not replayed in the background.
+  ///
+  /// If the replay in the background failed, the memtable only contains part of the entries, and the error is
+  /// returned by the first call, the later calls return a corrupted error.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  #[inline]
+  fn wait_ready(&self) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Memtable: 'static,
+  {
+    self.as_wal().readiness().wait_ready()
+  }
+
   /// Returns the number of entries in the WAL.
   #[inline]
   fn len(&self) -> usize
@@ -82,6 +111,14 @@ pub trait Reader: Constructable {
     self.as_wal().maximum_value_size()
   }
 
+  /// Returns the statistics of the Bloom filter, or `None` if the filter is disabled.
+  ///
+  /// See [`Builder::with_bloom_filter_bits_per_key`](crate::Builder::with_bloom_filter_bits_per_key) for more details.
+  #[inline]
+  fn bloom_filter_stats(&self) -> Option<BloomFilterStats> {
+    self.as_wal().bloom_filter().map(BloomFilter::stats)
+  }
+
   /// Returns the remaining capacity of the WAL.
   #[inline]
   fn remaining(&self) -> u32 {
@@ -115,7 +152,10 @@ pub trait Reader: Constructable {
     <Self::Memtable as BaseTable>::Value: Type,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
-    Iter::new(BaseIter::new(self.as_wal().iter()))
+    Iter::new(BaseIter::new(
+      self.as_wal().iter(),
+      Wal::range_deletions(self.as_wal()).coverage(),
+    ))
   }
 
   /// Returns an iterator over a subset of entries in the WAL.
@@ -127,12 +167,40 @@ pub trait Reader: Constructable {
   where
     R: RangeBounds<Q>,
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
+    Self::Memtable: Memtable<Comparator = KeyOrder>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+  {
+    Range::new(BaseIter::new(
+      self.as_wal().range(QueryRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+    ))
+  }
+
+  /// Returns an iterator over a subset of entries in the WAL, whose bounds are the encoded keys.
+  ///
+  /// The bounds are compared by the comparator of the memtable, so it is the range lookup for the memtables
+  /// ordered by a [`Comparator`](crate::types::Comparator).
+  ///
+  /// ## Safety
+  /// - The keys in the bounds must be valid to construct to `K::Ref` without remaining.
+  #[inline]
+  unsafe fn range_by_bytes<'a, R>(
+    &'a self,
+    range: R,
+  ) -> RangeByBytes<'a, R, <Self::Wal as Wal<Self::Checksumer>>::Memtable>
+  where
+    R: RangeBounds<[u8]> + 'a,
     Self::Memtable: Memtable,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
   {
-    Range::new(BaseIter::new(self.as_wal().range(QueryRange::new(range))))
+    RangeByBytes::new(BaseIter::new(
+      self.as_wal().range(SliceRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+    ))
   }
 
   /// Returns an iterator over the keys in the WAL.
@@ -150,7 +218,10 @@ pub trait Reader: Constructable {
     <Self::Memtable as BaseTable>::Value: Type,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
-    Keys::new(BaseIter::new(self.as_wal().iter()))
+    Keys::new(BaseIter::new(
+      self.as_wal().iter(),
+      Wal::range_deletions(self.as_wal()).coverage(),
+    ))
   }
 
   /// Returns an iterator over a subset of keys in the WAL.
@@ -162,15 +233,15 @@ pub trait Reader: Constructable {
   where
     R: RangeBounds<Q>,
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: Memtable,
+    Self::Memtable: Memtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
   {
-    RangeKeys::new(BaseIter::new(WalReader::range(
-      self.as_wal(),
-      QueryRange::new(range),
-    )))
+    RangeKeys::new(BaseIter::new(
+      WalReader::range(self.as_wal(), QueryRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+    ))
   }
 
   /// Returns an iterator over the values in the WAL.
@@ -188,7 +259,10 @@ pub trait Reader: Constructable {
     <Self::Memtable as BaseTable>::Value: Type,
     for<'a> <Self::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
   {
-    Values::new(BaseIter::new(self.as_wal().iter()))
+    Values::new(BaseIter::new(
+      self.as_wal().iter(),
+      Wal::range_deletions(self.as_wal()).coverage(),
+    ))
   }
 
   /// Returns an iterator over a subset of values in the WAL.
@@ -200,12 +274,15 @@ pub trait Reader: Constructable {
   where
     R: RangeBounds<Q> + 'a,
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: Memtable,
+    Self::Memtable: Memtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
   {
-    RangeValues::new(BaseIter::new(self.as_wal().range(QueryRange::new(range))))
+    RangeValues::new(BaseIter::new(
+      self.as_wal().range(QueryRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+    ))
   }
 
   /// Returns the first key-value pair in the map. The key in this pair is the minimum key in the wal.
@@ -237,18 +314,29 @@ pub trait Reader: Constructable {
   }
 
   /// Returns `true` if the key exists in the WAL.
+  ///
+  /// The query is encoded and looked up by its bytes, so it must share the reference type with the key type,
+  /// e.g. `str` for `String`, and the memtables which can locate a key by its bytes do not compare the keys.
+  ///
+  /// Same as [`contains_key_by_bytes`](Reader::contains_key_by_bytes), the Bloom filter is consulted first.
   #[inline]
   fn contains_key<'a, Q>(&'a self, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: Memtable,
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: Memtable<Comparator = KeyOrder>,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
     <Self::Memtable as BaseTable>::Key: Ord + Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
       KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type,
   {
-    self.as_wal().contains_key(Query::<_, Q>::ref_cast(key))
+    // SAFETY: the query shares the reference type with the key type, so its encoding is a valid key.
+    with_encoded(key, |raw| unsafe {
+      Reader::contains_key_by_bytes(self, raw)
+    })
+    .unwrap_or(false)
   }
 
   /// Returns `true` if the key exists in the WAL.
@@ -265,27 +353,30 @@ pub trait Reader: Constructable {
       KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type,
   {
-    self
-      .as_wal()
-      .contains_key(Slice::<<Self::Memtable as BaseTable>::Key>::ref_cast(key))
+    self.as_wal().may_contain_key(key) && self.as_wal().contains_key_by_bytes(key)
   }
 
   /// Gets the value associated with the key.
+  ///
+  /// Same as [`contains_key`](Reader::contains_key), the query is looked up by its encoded bytes,
+  /// and the Bloom filter is consulted first.
   #[inline]
   fn get<'a, Q>(&'a self, key: &Q) -> Option<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>
   where
-    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: Memtable,
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: Memtable<Comparator = KeyOrder>,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
     <Self::Memtable as BaseTable>::Key: Ord + Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
       KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type,
   {
-    self
-      .as_wal()
-      .get(Query::<_, Q>::ref_cast(key))
-      .map(Entry::new)
+    // SAFETY: the query shares the reference type with the key type, so its encoding is a valid key.
+    with_encoded(key, |raw| unsafe { Reader::get_by_bytes(self, raw) })
+      .ok()
+      .flatten()
   }
 
   /// Gets the value associated with the key.
@@ -305,10 +396,11 @@ pub trait Reader: Constructable {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type,
   {
-    self
-      .as_wal()
-      .get(Slice::<<Self::Memtable as BaseTable>::Key>::ref_cast(key))
-      .map(Entry::new)
+    if !self.as_wal().may_contain_key(key) {
+      return None;
+    }
+
+    self.as_wal().get_by_bytes(key).map(Entry::new)
   }
 
   /// Returns a value associated to the highest element whose key is below the given bound.
@@ -320,7 +412,7 @@ pub trait Reader: Constructable {
   ) -> Option<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>
   where
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: Memtable,
+    Self::Memtable: Memtable<Comparator = KeyOrder>,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
     <Self::Memtable as BaseTable>::Key: Ord + Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -353,7 +445,7 @@ pub trait Reader: Constructable {
   {
     self
       .as_wal()
-      .upper_bound(bound.map(Slice::<<Self::Memtable as BaseTable>::Key>::ref_cast))
+      .upper_bound(bound.map(Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast))
       .map(Entry::new)
   }
 
@@ -366,7 +458,7 @@ pub trait Reader: Constructable {
   ) -> Option<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>
   where
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: Memtable,
+    Self::Memtable: Memtable<Comparator = KeyOrder>,
     for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
     <Self::Memtable as BaseTable>::Key: Ord + Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -399,7 +491,7 @@ pub trait Reader: Constructable {
   {
     self
       .as_wal()
-      .lower_bound(bound.map(Slice::<<Self::Memtable as BaseTable>::Key>::ref_cast))
+      .lower_bound(bound.map(Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast))
       .map(Entry::new)
   }
 }
@@ -454,6 +546,19 @@ where
     self.as_wal().flush_async()
   }
 
+  /// Writes the index of the WAL to `<path of the WAL>.index`, so that reopening the WAL with
+  /// [`Options::with_persisted_index`](crate::Options::with_persisted_index) only replays the records
+  /// written after this call.
+  ///
+  /// The index is also written when the WAL is dropped if the option is enabled, this method is
+  /// for the checkpoints. It is a no-op if the WAL is not backed by a file.
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  fn persist_index(&self) -> Result<(), Error<Self::Memtable>> {
+    self.as_wal().persist_index()
+  }
+
   /// Returns the read-only view for the WAL.
   fn reader(&self) -> Self::Reader;
 
@@ -549,6 +654,222 @@ where
     self.as_wal().insert(None, key.into(), value.into())
   }
 
+  /// Inserts a key-value pair into the WAL, which expires after `ttl`.
+  ///
+  /// The expiration time is recorded in the entry by the system clock, in milliseconds. The reads treat
+  /// the expired entry as absent, but it is only filtered out: there is no compaction or garbage collection
+  /// of the expired entries, so it still occupies the space of the WAL and the memtable, until the live
+  /// key-value pairs are rewritten into a new WAL.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use core::time::Duration;
+  /// use orderwal::{base::{OrderWal, Reader, Writer}, Builder};
+  ///
+  /// let mut wal = Builder::new()
+  ///   .with_capacity(1024)
+  ///   .alloc::<OrderWal<str, str>>()
+  ///   .unwrap();
+  ///
+  /// wal.insert_with_ttl("session", "alive", Duration::from_secs(60)).unwrap();
+  /// wal.insert_with_ttl("expired", "gone", Duration::ZERO).unwrap();
+  /// assert_eq!(wal.get("session").unwrap().value(), "alive");
+  /// assert!(wal.get("expired").is_none());
+  /// ```
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  fn insert_with_ttl<'a>(
+    &'a mut self,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    value: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+    ttl: core::time::Duration,
+  ) -> Result<
+    (),
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    let ttl = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+    // `0` means the entry never expires.
+    let expire_at = crate::utils::unix_millis().saturating_add(ttl).max(1);
+    self
+      .as_wal()
+      .update(None, key.into(), Some(value.into()), Some(expire_at))
+      .map(|_| ())
+  }
+
+  /// Inserts a key-value pair into the WAL, returns the entry which was just written.
+  ///
+  /// The entry references the bytes in the WAL, so nothing is copied or decoded eagerly.
+  #[inline]
+  fn insert_returning<'a>(
+    &'a mut self,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    value: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>,
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: Memtable,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    let wal = self.as_wal();
+    let kp = wal.update(None, key.into(), Some(value.into()), None)?;
+    // The writes appended while replaying are only inserted into the memtable after the replay.
+    wal.wait_replayed();
+    Ok(Entry::new(
+      WalReader::get(wal, &kp).expect("the entry was just inserted into the memtable"),
+    ))
+  }
+
+  /// Inserts a key-value pair into the WAL if the key does not exist, returns `true` if the key-value pair is inserted.
+  ///
+  /// There is only one writer, so no other write can happen between the check and the insertion.
+  #[inline]
+  fn insert_if_absent<'a>(
+    &'a mut self,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    value: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    bool,
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    let key = key.into();
+    let raw = encoded_bytes(&key).map_err(Among::Left)?;
+
+    let wal = self.as_wal();
+    // The check must see the whole memtable, so it waits for the replay in the background.
+    wal.wait_replayed();
+    if wal.contains_key(Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast(&raw)) {
+      return Ok(false);
+    }
+
+    wal.insert(None, key, value.into()).map(|_| true)
+  }
+
+  /// Replaces the value of the key by `new` if the current value is `expected`, returns `true` if the value is replaced.
+  ///
+  /// The values are compared by their encoded bytes, and nothing is written if the key does not exist.
+  /// There is only one writer, so no other write can happen between the comparison and the insertion.
+  #[inline]
+  fn compare_and_swap<'a>(
+    &'a mut self,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    expected: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+    new: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    bool,
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    let key = key.into();
+    let raw = encoded_bytes(&key).map_err(Among::Left)?;
+    let expected = encoded_bytes(&expected.into()).map_err(Among::Middle)?;
+
+    let wal = self.as_wal();
+    // The check must see the whole memtable, so it waits for the replay in the background.
+    wal.wait_replayed();
+    match wal.get(Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast(&raw)) {
+      Some(ent) if ent.value().as_slice() == expected.as_ref() => {
+        wal.insert(None, key, new.into()).map(|_| true)
+      }
+      _ => Ok(false),
+    }
+  }
+
+  /// Reads the current value of the key and replaces it by the value built by `f` in one call.
+  ///
+  /// The closure receives the current value of the key, and returns the builder of the new value,
+  /// or `None` to remove the key. There is only one writer, so no other write can happen between
+  /// the read and the write, while the readers see either the old value or the new value.
+  #[inline]
+  fn upsert<'a, F, VB, E>(
+    &'a mut self,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    f: F,
+  ) -> Result<
+    (),
+    Among<<<Self::Memtable as BaseTable>::Key as Type>::Error, E, Error<Self::Memtable>>,
+  >
+  where
+    F: FnOnce(
+      Option<<<Self::Memtable as BaseTable>::Value as Type>::Ref<'_>>,
+    ) -> Option<ValueBuilder<VB>>,
+    VB: FnOnce(&mut VacantBuffer<'_>) -> Result<usize, E>,
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    let key = key.into();
+    let raw = encoded_bytes(&key).map_err(Among::Left)?;
+
+    let wal = self.as_wal();
+    // The check must see the whole memtable, so it waits for the replay in the background.
+    wal.wait_replayed();
+    let old = wal
+      .get(Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast(&raw))
+      .map(|ent| {
+        // SAFETY: the value is encoded by the value type.
+        unsafe { TypeRef::from_slice(ent.value().as_slice()) }
+      });
+
+    match f(old) {
+      Some(vb) => wal.insert(None, key, vb),
+      None => wal.remove(None, key).map_err(|e| match e {
+        Either::Left(e) => Among::Left(e),
+        Either::Right(e) => Among::Right(e),
+      }),
+    }
+  }
+
   /// Removes a key-value pair from the WAL. This method
   /// allows the caller to build the key in place.
   #[inline]
@@ -584,6 +905,64 @@ where
     self.as_wal().remove(None, key.into())
   }
 
+  /// Removes all the key-value pairs whose keys are in `start..end` from the WAL.
+  ///
+  /// Only one range tombstone record is appended, the key-value pairs inserted after it are not affected.
+  ///
+  /// If `start` is not less than `end`, the range is empty and nothing is written.
+  ///
+  /// The removed key-value pairs are hidden from the reads immediately, but they still occupy the space of the WAL,
+  /// which is reclaimed only when the remaining key-value pairs are rewritten into a new WAL.
+  ///
+  /// The end bound is written as the value of the range tombstone record, so it must fit in both
+  /// the maximum key size and the maximum value size, otherwise [`Error::KeyTooLarge`] is returned.
+  #[inline]
+  fn delete_range<'a>(
+    &'a mut self,
+    range: core::ops::Range<impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>>,
+  ) -> Result<(), Either<<<Self::Memtable as BaseTable>::Key as Type>::Error, Error<Self::Memtable>>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    self
+      .as_wal()
+      .delete_range(None, range.start.into(), range.end.into())
+  }
+
+  /// Appends a merge operand of the key to the WAL, instead of the full value.
+  ///
+  /// The operands are folded over the current value of the key by the merge operator
+  /// set by [`Options::with_merge_operator`](crate::Options::with_merge_operator) lazily, when the value is read.
+  /// Returns [`Error::MissingMergeOperator`] if the merge operator is not set.
+  #[inline]
+  fn merge<'a>(
+    &'a mut self,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    operand: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    (),
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    self.as_wal().merge(None, key.into(), operand.into())
+  }
+
   /// Inserts a batch of key-value pairs into the WAL.
   #[inline]
   fn insert_batch<'a, B>(
@@ -684,4 +1063,191 @@ where
   {
     self.as_wal().insert_batch::<Self, _>(batch)
   }
+
+  /// Inserts a batch of key-value pairs into the WAL, returns the entries which were just written in the order of the batch.
+  ///
+  /// The tombstones in the batch are skipped, and if a key is written more than once in the batch,
+  /// all of its entries are the last written one.
+  fn insert_batch_returning<'a, B>(
+    &'a mut self,
+    batch: &mut B,
+  ) -> Result<
+    Vec<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>,
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    B: Batch<
+      Self::Memtable,
+      Key = MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>,
+      Value = MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>,
+    >,
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: Memtable,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: MemtableEntry<'b>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    let wal = self.as_wal();
+    wal.insert_batch::<Self, _>(batch)?;
+    // The writes appended while replaying are only inserted into the memtable after the replay.
+    wal.wait_replayed();
+    Ok(
+      batch
+        .iter_mut()
+        .filter_map(|ent| match ent.pointer() {
+          Some((kp, Some(_))) => Some(Entry::new(
+            WalReader::get(wal, &kp).expect("the entry was just inserted into the memtable"),
+          )),
+          _ => None,
+        })
+        .collect(),
+    )
+  }
+
+  /// Inserts a prepared batch into the WAL, the entries are copied without encoding them again.
+  #[inline]
+  fn insert_prepared_batch<'a>(
+    &'a mut self,
+    batch: &PreparedBatch<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    self.as_wal().insert_prepared_batch(batch)
+  }
+
+  /// Starts a long-running transaction, the key-value pairs written in it are invisible until
+  /// the transaction is committed by [`commit_transaction`](Writer::commit_transaction).
+  ///
+  /// The key-value pairs of a transaction which is never committed are discarded when the WAL is reopened.
+  ///
+  /// The transaction holds a pointer to each of its entries in memory until it is committed,
+  /// see [`Transaction`] for the limit this puts on its size.
+  #[inline]
+  fn begin_transaction(&mut self) -> Result<Transaction<Self::Memtable>, Error<Self::Memtable>>
+  where
+    Self::Memtable: BaseTable,
+  {
+    self.as_wal().begin_transaction()
+  }
+
+  /// Inserts a key-value pair into the WAL as a part of the transaction.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn transaction_insert<'a>(
+    &'a mut self,
+    txn: &mut Transaction<Self::Memtable>,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    value: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    (),
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    self
+      .as_wal()
+      .transaction_update(txn, None, key.into(), Some(value.into()))
+  }
+
+  /// Removes a key-value pair from the WAL as a part of the transaction.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn transaction_remove<'a>(
+    &'a mut self,
+    txn: &mut Transaction<Self::Memtable>,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+  ) -> Result<(), Either<<<Self::Memtable as BaseTable>::Key as Type>::Error, Error<Self::Memtable>>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    self
+      .as_wal()
+      .transaction_update::<_, MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>(
+        txn,
+        None,
+        key.into(),
+        None,
+      )
+      .map_err(Among::into_left_right)
+  }
+
+  /// Commits the transaction, all the key-value pairs written in it become visible at once.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn commit_transaction<'a>(
+    &'a mut self,
+    txn: Transaction<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    self.as_wal().commit_transaction(txn)
+  }
+
+  /// Aborts the transaction, all the key-value pairs written in it are discarded.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn abort_transaction(
+    &mut self,
+    txn: Transaction<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: BaseTable,
+  {
+    self.as_wal().abort_transaction(txn)
+  }
+
+  /// Reserves a record in the WAL, whose key and value are filled in place later,
+  /// e.g. when the value is read from a stream.
+  ///
+  /// The record is invisible until [`Reservation::commit`] is called, and no other record can be written before
+  /// the reservation is committed or aborted.
+  #[inline]
+  fn reserve(
+    &mut self,
+    key_len: usize,
+    value_len: usize,
+  ) -> Result<Reservation<'_, Self>, Error<Self::Memtable>>
+  where
+    Self::Memtable: BaseTable,
+  {
+    Reservation::new(self.as_wal(), None, key_len, value_len)
+  }
 }
@@ -2,13 +2,16 @@ use core::{iter::FusedIterator, marker::PhantomData, ops::RangeBounds};
 
 use crate::{
   memtable::{BaseEntry, Memtable, MemtableEntry},
-  types::base::{Entry, Key, Value},
-  wal::{KeyPointer, ValuePointer},
+  types::{
+    base::{Entry, Key, Value},
+    KeyOrder,
+  },
+  wal::{Coverage, KeyPointer, ValuePointer},
 };
 
 use dbutils::{equivalent::Comparable, types::Type};
 
-use super::{Query, QueryRange};
+use super::{Query, QueryRange, Slice, SliceRange};
 
 /// Iterator over the entries in the WAL.
 pub struct BaseIter<'a, I, M>
@@ -17,20 +20,22 @@ where
   for<'b> M::Item<'b>: MemtableEntry<'b>,
 {
   iter: I,
-  head: Option<(KeyPointer<M::Key>, ValuePointer<M::Value>)>,
-  tail: Option<(KeyPointer<M::Key>, ValuePointer<M::Value>)>,
+  coverage: Coverage<'a>,
+  head: Option<(KeyPointer<M::Key, M::Comparator>, ValuePointer<M::Value>)>,
+  tail: Option<(KeyPointer<M::Key, M::Comparator>, ValuePointer<M::Value>)>,
   _m: PhantomData<&'a ()>,
 }
 
-impl<I, M> BaseIter<'_, I, M>
+impl<'a, I, M> BaseIter<'a, I, M>
 where
   M: Memtable,
   for<'b> M::Item<'b>: MemtableEntry<'b>,
 {
   #[inline]
-  pub(super) fn new(iter: I) -> Self {
+  pub(super) fn new(iter: I, coverage: Coverage<'a>) -> Self {
     Self {
       iter,
+      coverage,
       head: None,
       tail: None,
       _m: PhantomData,
@@ -48,9 +53,13 @@ where
 
   #[inline]
   fn next(&mut self) -> Option<Self::Item> {
-    self.iter.next().inspect(|ent| {
-      self.head = Some((ent.key(), ent.value()));
-    })
+    loop {
+      let ent = self.iter.next()?;
+      if !self.coverage.covers_entry(&ent) {
+        self.head = Some((ent.key(), ent.value()));
+        return Some(ent);
+      }
+    }
   }
 }
 
@@ -62,9 +71,13 @@ where
 {
   #[inline]
   fn next_back(&mut self) -> Option<Self::Item> {
-    self.iter.next_back().inspect(|ent| {
-      self.tail = Some((ent.key(), ent.value()));
-    })
+    loop {
+      let ent = self.iter.next_back()?;
+      if !self.coverage.covers_entry(&ent) {
+        self.tail = Some((ent.key(), ent.value()));
+        return Some(ent);
+      }
+    }
   }
 }
 
@@ -255,7 +268,7 @@ pub struct Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: MemtableEntry<'b>,
 {
@@ -266,7 +279,7 @@ impl<'a, R, Q, B> Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: MemtableEntry<'b>,
 {
@@ -282,7 +295,7 @@ impl<'a, R, Q, B> Iterator for Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>: Iterator<Item = B::Item<'a>>,
@@ -300,7 +313,7 @@ impl<'a, R, Q, B> DoubleEndedIterator for Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -317,7 +330,7 @@ impl<'a, R, Q, B> FusedIterator for Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -331,7 +344,7 @@ pub struct RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: MemtableEntry<'b>,
 {
@@ -342,7 +355,7 @@ impl<'a, R, Q, B> RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: MemtableEntry<'b>,
 {
@@ -358,7 +371,7 @@ impl<'a, R, Q, B> Iterator for RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>: Iterator<Item = B::Item<'a>>,
   for<'b> B::Item<'b>: MemtableEntry<'b>,
@@ -375,7 +388,7 @@ impl<'a, R, Q, B> DoubleEndedIterator for RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
     DoubleEndedIterator<Item = B::Item<'a>>,
@@ -391,7 +404,7 @@ impl<'a, R, Q, B> FusedIterator for RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
     FusedIterator<Item = B::Item<'a>>,
@@ -404,7 +417,7 @@ pub struct RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: MemtableEntry<'b>,
 {
@@ -415,7 +428,7 @@ impl<'a, R, Q, B> RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: MemtableEntry<'b>,
 {
@@ -431,7 +444,7 @@ impl<'a, R, Q, B> Iterator for RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>: Iterator<Item = B::Item<'a>>,
@@ -449,7 +462,7 @@ impl<'a, R, Q, B> DoubleEndedIterator for RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -466,7 +479,7 @@ impl<'a, R, Q, B> FusedIterator for RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: Memtable + 'a,
+  B: Memtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -474,3 +487,65 @@ where
   for<'b> B::Item<'b>: MemtableEntry<'b>,
 {
 }
+
+/// An iterator over a subset of the entries in the WAL, whose bounds are the encoded keys.
+pub struct RangeByBytes<'a, R, B>
+where
+  R: RangeBounds<[u8]> + 'a,
+  B: Memtable + 'a,
+  for<'b> B::Item<'b>: MemtableEntry<'b>,
+{
+  iter: BaseIter<
+    'a,
+    B::Range<'a, Slice<'a, B::Key, B::Comparator>, SliceRange<'a, B::Key, B::Comparator, R>>,
+    B,
+  >,
+}
+
+impl<'a, R, B> RangeByBytes<'a, R, B>
+where
+  R: RangeBounds<[u8]> + 'a,
+  B: Memtable + 'a,
+  for<'b> B::Item<'b>: MemtableEntry<'b>,
+{
+  #[inline]
+  pub(super) fn new(
+    iter: BaseIter<
+      'a,
+      B::Range<'a, Slice<'a, B::Key, B::Comparator>, SliceRange<'a, B::Key, B::Comparator, R>>,
+      B,
+    >,
+  ) -> Self {
+    Self { iter }
+  }
+}
+
+impl<'a, R, B> Iterator for RangeByBytes<'a, R, B>
+where
+  R: RangeBounds<[u8]> + 'a,
+  B: Memtable + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  for<'b> B::Item<'b>: MemtableEntry<'b>,
+{
+  type Item = Entry<'a, B::Item<'a>>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    self.iter.next().map(Entry::new)
+  }
+}
+
+impl<'a, R, B> DoubleEndedIterator for RangeByBytes<'a, R, B>
+where
+  R: RangeBounds<[u8]> + 'a,
+  B: Memtable + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  for<'b> B::Item<'b>: MemtableEntry<'b>,
+{
+  #[inline]
+  fn next_back(&mut self) -> Option<Self::Item> {
+    self.iter.next_back().map(Entry::new)
+  }
+}
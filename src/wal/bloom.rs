@@ -0,0 +1,212 @@
+use core::{
+  ptr,
+  sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering},
+};
+use std::boxed::Box;
+
+/// The number of keys the first layer of the filter is sized for.
+const INITIAL_KEYS: usize = 1024;
+
+/// The statistics of the Bloom filter of a WAL.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BloomFilterStats {
+  hits: u64,
+  misses: u64,
+}
+
+impl BloomFilterStats {
+  /// Returns the number of the lookups the filter reported that the key may exist,
+  /// so the memtable was looked up.
+  #[inline]
+  pub const fn hits(&self) -> u64 {
+    self.hits
+  }
+
+  /// Returns the number of the lookups the filter reported that the key does not exist,
+  /// so the memtable was not looked up.
+  #[inline]
+  pub const fn misses(&self) -> u64 {
+    self.misses
+  }
+}
+
+struct Layer {
+  bits: Box<[AtomicU64]>,
+  /// The number of keys the layer is sized for.
+  capacity: usize,
+  len: AtomicUsize,
+  next: AtomicPtr<Layer>,
+}
+
+impl Layer {
+  fn new(capacity: usize, bits_per_key: u32) -> Self {
+    let words = (capacity * bits_per_key as usize).div_ceil(64).max(1);
+    Self {
+      bits: (0..words).map(|_| AtomicU64::new(0)).collect(),
+      capacity,
+      len: AtomicUsize::new(0),
+      next: AtomicPtr::new(ptr::null_mut()),
+    }
+  }
+
+  #[inline]
+  fn num_bits(&self) -> u64 {
+    self.bits.len() as u64 * 64
+  }
+
+  fn insert(&self, mut h: u64, delta: u64, probes: u32) {
+    let num_bits = self.num_bits();
+    for _ in 0..probes {
+      let bit = h % num_bits;
+      self.bits[(bit / 64) as usize].fetch_or(1 << (bit % 64), Ordering::Relaxed);
+      h = h.wrapping_add(delta);
+    }
+    self.len.fetch_add(1, Ordering::Relaxed);
+  }
+
+  fn may_contain(&self, mut h: u64, delta: u64, probes: u32) -> bool {
+    let num_bits = self.num_bits();
+    for _ in 0..probes {
+      let bit = h % num_bits;
+      if self.bits[(bit / 64) as usize].load(Ordering::Relaxed) & (1 << (bit % 64)) == 0 {
+        return false;
+      }
+      h = h.wrapping_add(delta);
+    }
+    true
+  }
+}
+
+/// A Bloom filter over the encoded keys written to the memtable.
+///
+/// The number of keys is unknown when the WAL is opened, so the filter starts small and
+/// chains a layer twice as large when the last one is full, the false positive rate of
+/// each layer stays at the configured bits per key.
+pub struct BloomFilter {
+  head: Box<Layer>,
+  bits_per_key: u32,
+  probes: u32,
+  hits: AtomicU64,
+  misses: AtomicU64,
+}
+
+impl core::fmt::Debug for BloomFilter {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("BloomFilter")
+      .field("bits_per_key", &self.bits_per_key)
+      .field("stats", &self.stats())
+      .finish()
+  }
+}
+
+impl Drop for BloomFilter {
+  fn drop(&mut self) {
+    let mut next = *self.head.next.get_mut();
+    while !next.is_null() {
+      // SAFETY: the layers are allocated by `Box::into_raw` in `insert`, and only freed here.
+      let mut layer = unsafe { Box::from_raw(next) };
+      next = *layer.next.get_mut();
+    }
+  }
+}
+
+impl BloomFilter {
+  /// Creates a new filter, returns `None` if `bits_per_key` is `None` or `0`.
+  pub(crate) fn new(bits_per_key: Option<u32>) -> Option<Self> {
+    let bits_per_key = bits_per_key.filter(|bits| *bits > 0)?;
+    // ln(2) * bits per key minimizes the false positive rate.
+    let probes = (bits_per_key * 69 / 100).clamp(1, 30);
+    Some(Self {
+      head: Box::new(Layer::new(INITIAL_KEYS, bits_per_key)),
+      bits_per_key,
+      probes,
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+    })
+  }
+
+  /// Adds an encoded key to the filter.
+  pub(crate) fn insert(&self, key: &[u8]) {
+    let (h, delta) = hash(key);
+    // The same key is written again by the updates and the new versions, do not let it fill the layers.
+    if self.probe(h, delta) {
+      return;
+    }
+
+    let mut layer: &Layer = &self.head;
+    loop {
+      let next = layer.next.load(Ordering::Acquire);
+      if !next.is_null() {
+        // SAFETY: the layers are never freed before the filter is dropped.
+        layer = unsafe { &*next };
+        continue;
+      }
+
+      if layer.len.load(Ordering::Relaxed) < layer.capacity {
+        layer.insert(h, delta, self.probes);
+        return;
+      }
+
+      let new = Box::into_raw(Box::new(Layer::new(layer.capacity * 2, self.bits_per_key)));
+      let published =
+        layer
+          .next
+          .compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire);
+      if published.is_err() {
+        // SAFETY: the layer is not published, so it is still owned here.
+        drop(unsafe { Box::from_raw(new) });
+      }
+    }
+  }
+
+  /// Returns `false` if the encoded key is definitely not in the filter, and records the result.
+  pub(crate) fn may_contain(&self, key: &[u8]) -> bool {
+    let (h, delta) = hash(key);
+    if self.probe(h, delta) {
+      self.hits.fetch_add(1, Ordering::Relaxed);
+      true
+    } else {
+      self.misses.fetch_add(1, Ordering::Relaxed);
+      false
+    }
+  }
+
+  fn probe(&self, h: u64, delta: u64) -> bool {
+    let mut layer: *const Layer = &*self.head;
+    while !layer.is_null() {
+      // SAFETY: the layers are never freed before the filter is dropped.
+      let l = unsafe { &*layer };
+      if l.may_contain(h, delta, self.probes) {
+        return true;
+      }
+      layer = l.next.load(Ordering::Acquire);
+    }
+    false
+  }
+
+  /// Returns the statistics of the filter.
+  #[inline]
+  pub(crate) fn stats(&self) -> BloomFilterStats {
+    BloomFilterStats {
+      hits: self.hits.load(Ordering::Relaxed),
+      misses: self.misses.load(Ordering::Relaxed),
+    }
+  }
+}
+
+/// Hashes the key, returns the first probe and the distance between the probes.
+#[inline]
+fn hash(key: &[u8]) -> (u64, u64) {
+  // FNV-1a, followed by the finalizer of MurmurHash3 to spread the bits.
+  let mut h = 0xcbf29ce484222325u64;
+  for b in key {
+    h ^= *b as u64;
+    h = h.wrapping_mul(0x100000001b3);
+  }
+  h ^= h >> 33;
+  h = h.wrapping_mul(0xff51afd7ed558ccd);
+  h ^= h >> 33;
+  h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+  h ^= h >> 33;
+  (h, h.rotate_right(17) | 1)
+}
@@ -0,0 +1,291 @@
+use std::{
+  fs,
+  io::{self, Write},
+  path::{Path, PathBuf},
+  vec::Vec,
+};
+
+use dbutils::{
+  checksum::{BuildChecksumer, Crc32},
+  leb128::decode_u64_varint,
+};
+use rarena_allocator::Allocator;
+
+use crate::{
+  options::header_size, types::EntryFlags, utils::split_lengths, Flags, CHECKSUM_SIZE,
+  EXPIRATION_SIZE, RECORD_FLAG_SIZE, TRANSACTION_ID_SIZE, VERSION_SIZE,
+};
+
+const INDEX_MAGIC_TEXT: [u8; 5] = *b"owidx";
+const INDEX_MAGIC_TEXT_SIZE: usize = INDEX_MAGIC_TEXT.len();
+const U64_SIZE: usize = core::mem::size_of::<u64>();
+const U32_SIZE: usize = core::mem::size_of::<u32>();
+/// magic text + high water mark + checksum of the last indexed record + number of entries,
+/// the header of the WAL follows the magic text.
+const INDEX_HEADER_SIZE: usize = INDEX_MAGIC_TEXT_SIZE + U64_SIZE * 3;
+/// offset of the entry flag + key length + value length + whether the entry expires
+const INDEX_ENTRY_SIZE: usize = U64_SIZE + U32_SIZE * 2 + 1;
+
+/// Returns the path of the index of a WAL file, which is `<path of the WAL>.index`.
+pub(crate) fn index_path(path: &Path) -> PathBuf {
+  let mut path = path.as_os_str().to_os_string();
+  path.push(".index");
+  path.into()
+}
+
+/// An entry of the WAL, which is located by the offset of its entry flag.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct IndexEntry {
+  pub(crate) offset: usize,
+  pub(crate) key_len: usize,
+  pub(crate) value_len: usize,
+  /// Whether the record flag of the entry contains `Flags::EXPIRING`.
+  pub(crate) expiring: bool,
+}
+
+/// The offsets of the entries of the records before the high water mark, in the order they are written.
+///
+/// Replaying the indexed entries only touches the keys, the values and the checksums of the
+/// records are not read again.
+#[derive(Debug)]
+pub(crate) struct Index {
+  pub(crate) high_water_mark: usize,
+  pub(crate) entries: Vec<IndexEntry>,
+}
+
+impl Index {
+  /// Loads the index of the WAL, returns `None` if the index does not exist, or it does not match the WAL.
+  pub(crate) fn load<A: Allocator>(path: &Path, arena: &A) -> Option<Self> {
+    let buf = match fs::read(path) {
+      Ok(buf) => buf,
+      Err(e) => {
+        #[cfg(feature = "tracing")]
+        if e.kind() != io::ErrorKind::NotFound {
+          tracing::warn!(err=%e, "failed to read the index of the WAL");
+        }
+        let _ = e;
+        return None;
+      }
+    };
+
+    let index = Self::decode(&buf, arena);
+    #[cfg(feature = "tracing")]
+    if index.is_none() {
+      tracing::warn!("the index does not match the WAL, replay the whole WAL");
+    }
+    index
+  }
+
+  fn decode<A: Allocator>(buf: &[u8], arena: &A) -> Option<Self> {
+    let header_size = header_size(arena.magic_version());
+    if buf.len() < INDEX_HEADER_SIZE + header_size + CHECKSUM_SIZE {
+      return None;
+    }
+
+    let (data, cks) = buf.split_at(buf.len() - CHECKSUM_SIZE);
+    if u64::from_le_bytes(cks.try_into().unwrap()) != Crc32::new().checksum_one(data) {
+      return None;
+    }
+
+    let mut cursor = 0;
+    if data[..INDEX_MAGIC_TEXT_SIZE] != INDEX_MAGIC_TEXT {
+      return None;
+    }
+    cursor += INDEX_MAGIC_TEXT_SIZE;
+    if data[cursor..cursor + header_size] != arena.reserved_slice()[..header_size] {
+      return None;
+    }
+    cursor += header_size;
+
+    let high_water_mark = read_u64(data, &mut cursor) as usize;
+    let tail_checksum = read_u64(data, &mut cursor);
+    let num_entries = read_u64(data, &mut cursor) as usize;
+    if data.len() - cursor != num_entries.checked_mul(INDEX_ENTRY_SIZE)? {
+      return None;
+    }
+
+    // The records before the high water mark must be the ones which are indexed.
+    if high_water_mark < arena.data_offset()
+      || high_water_mark > arena.allocated()
+      || tail_checksum != last_checksum(arena, high_water_mark)
+    {
+      return None;
+    }
+
+    let mut entries = Vec::with_capacity(num_entries);
+    for _ in 0..num_entries {
+      let offset = read_u64(data, &mut cursor) as usize;
+      let key_len = read_u32(data, &mut cursor) as usize;
+      let value_len = read_u32(data, &mut cursor) as usize;
+      let expiring = match data[cursor] {
+        0 => false,
+        1 => true,
+        _ => return None,
+      };
+      cursor += 1;
+      if offset < arena.data_offset() || offset + key_len + value_len >= high_water_mark {
+        return None;
+      }
+
+      entries.push(IndexEntry {
+        offset,
+        key_len,
+        value_len,
+        expiring,
+      });
+    }
+
+    Some(Self {
+      high_water_mark,
+      entries,
+    })
+  }
+
+  /// Indexes the records of the WAL, and writes the index to the path.
+  ///
+  /// The existing index at the path is reused if it matches the WAL, so only the records
+  /// after it are scanned.
+  pub(crate) fn persist<A: Allocator>(path: &Path, arena: &A) -> io::Result<()> {
+    let mut index = Self::load(path, arena).unwrap_or_else(|| Self {
+      high_water_mark: arena.data_offset(),
+      entries: Vec::new(),
+    });
+    index.scan(arena);
+
+    let header_size = header_size(arena.magic_version());
+    let mut buf = Vec::with_capacity(
+      INDEX_HEADER_SIZE + header_size + index.entries.len() * INDEX_ENTRY_SIZE + CHECKSUM_SIZE,
+    );
+    buf.extend_from_slice(&INDEX_MAGIC_TEXT);
+    buf.extend_from_slice(&arena.reserved_slice()[..header_size]);
+    buf.extend_from_slice(&(index.high_water_mark as u64).to_le_bytes());
+    buf.extend_from_slice(&last_checksum(arena, index.high_water_mark).to_le_bytes());
+    buf.extend_from_slice(&(index.entries.len() as u64).to_le_bytes());
+    for ent in &index.entries {
+      buf.extend_from_slice(&(ent.offset as u64).to_le_bytes());
+      buf.extend_from_slice(&(ent.key_len as u32).to_le_bytes());
+      buf.extend_from_slice(&(ent.value_len as u32).to_le_bytes());
+      buf.push(ent.expiring as u8);
+    }
+    let cks = Crc32::new().checksum_one(&buf);
+    buf.extend_from_slice(&cks.to_le_bytes());
+
+    // Write to a temporary file first, so a crash never leaves a partial index behind.
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(&buf)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, path)
+  }
+
+  /// Indexes the committed records after the high water mark, and moves the high water mark after them.
+  fn scan<A: Allocator>(&mut self, arena: &A) {
+    let memory = arena.allocated_memory();
+    let mut cursor = self.high_water_mark;
+
+    while cursor + RECORD_FLAG_SIZE <= memory.len() {
+      let flag = Flags::from_bits_retain(memory[cursor]);
+      // The reserved records are the last ones, and they are not committed yet.
+      if !flag.contains(Flags::COMMITTED) {
+        break;
+      }
+
+      let Ok((readed, encoded_len)) = decode_u64_varint(&memory[cursor + RECORD_FLAG_SIZE..])
+      else {
+        break;
+      };
+      let body = cursor + RECORD_FLAG_SIZE + readed;
+
+      let end = if !flag.contains(Flags::BATCHING) {
+        let (key_len, value_len) = split_lengths(encoded_len);
+        let (key_len, value_len) = (key_len as usize, value_len as usize);
+        let Some(entry_flag) = memory.get(body).map(|f| EntryFlags::from_bits_retain(*f)) else {
+          break;
+        };
+        let mut len = EntryFlags::SIZE + key_len + value_len;
+        if entry_flag.contains(EntryFlags::VERSIONED) {
+          len += VERSION_SIZE;
+        }
+        if entry_flag.contains(EntryFlags::TRANSACTION) {
+          len += TRANSACTION_ID_SIZE;
+        }
+        let expiring = flag.contains(Flags::EXPIRING);
+        if expiring {
+          len += EXPIRATION_SIZE;
+        }
+
+        let end = body + len + CHECKSUM_SIZE;
+        if end > memory.len() {
+          break;
+        }
+
+        self.entries.push(IndexEntry {
+          offset: body,
+          key_len,
+          value_len,
+          expiring,
+        });
+        end
+      } else {
+        let (num_entries, encoded_data_len) = split_lengths(encoded_len);
+        let end = body + encoded_data_len as usize + CHECKSUM_SIZE;
+        if end > memory.len() {
+          break;
+        }
+
+        let mut sub_cursor = 0;
+        for _ in 0..num_entries {
+          let Ok((kvlen, ent_len)) = decode_u64_varint(&memory[body + sub_cursor..]) else {
+            return;
+          };
+          let (key_len, value_len) = split_lengths(ent_len);
+          let (key_len, value_len) = (key_len as usize, value_len as usize);
+          let offset = body + sub_cursor + kvlen;
+          let entry_flag = EntryFlags::from_bits_retain(memory[offset]);
+          let mut len = kvlen + EntryFlags::SIZE + key_len + value_len;
+          if entry_flag.contains(EntryFlags::VERSIONED) {
+            len += VERSION_SIZE;
+          }
+
+          self.entries.push(IndexEntry {
+            offset,
+            key_len,
+            value_len,
+            expiring: false,
+          });
+          sub_cursor += len;
+        }
+        end
+      };
+
+      cursor = end;
+      self.high_water_mark = cursor;
+    }
+  }
+}
+
+/// Returns the checksum of the record which ends at the offset, or `0` if there is no record before the offset.
+#[inline]
+fn last_checksum<A: Allocator>(arena: &A, end: usize) -> u64 {
+  if end < arena.data_offset() + CHECKSUM_SIZE {
+    return 0;
+  }
+
+  arena.get_u64_le(end - CHECKSUM_SIZE).unwrap_or(0)
+}
+
+#[inline]
+fn read_u64(buf: &[u8], cursor: &mut usize) -> u64 {
+  let val = u64::from_le_bytes(buf[*cursor..*cursor + U64_SIZE].try_into().unwrap());
+  *cursor += U64_SIZE;
+  val
+}
+
+#[inline]
+fn read_u32(buf: &[u8], cursor: &mut usize) -> u32 {
+  let val = u32::from_le_bytes(buf[*cursor..*cursor + U32_SIZE].try_into().unwrap());
+  *cursor += U32_SIZE;
+  val
+}
@@ -0,0 +1,193 @@
+use core::{
+  ptr, slice,
+  sync::atomic::{AtomicPtr, Ordering},
+};
+
+use std::{boxed::Box, vec, vec::Vec};
+
+use crate::types::MergeOperator;
+
+use super::{KeyPointer, ValuePointer};
+
+/// A merge operand, which is folded over the value of the key when it was written.
+pub(crate) struct MergeOperand {
+  key: *const u8,
+  key_len: usize,
+  operand: *const u8,
+  operand_len: usize,
+  base: Option<ValuePointer<()>>,
+  operator: &'static dyn MergeOperator,
+  merged: AtomicPtr<Vec<u8>>,
+  next: *mut MergeOperand,
+}
+
+impl MergeOperand {
+  #[inline]
+  fn key(&self) -> &[u8] {
+    // SAFETY: the merge operands never outlive the WAL, and the keys are never moved in the WAL.
+    unsafe { bytes(self.key, self.key_len) }
+  }
+
+  #[inline]
+  fn operand(&self) -> &[u8] {
+    // SAFETY: the merge operands never outlive the WAL, and the values are never moved in the WAL.
+    unsafe { bytes(self.operand, self.operand_len) }
+  }
+
+  #[inline]
+  fn merged(&self) -> Option<&[u8]> {
+    // SAFETY: the merged value is only freed when the merge operand is dropped.
+    unsafe { self.merged.load(Ordering::Acquire).as_ref() }.map(Vec::as_slice)
+  }
+
+  /// Returns the merged value, the operands are folded at the first time the value is read.
+  pub(crate) fn resolve(&self) -> &[u8] {
+    if let Some(merged) = self.merged() {
+      return merged;
+    }
+
+    // Walk back to the nearest plain or already merged value, so a long chain of operands does not recurse.
+    let mut operands = vec![self.operand()];
+    let mut cursor = self.base;
+    let existing = loop {
+      let Some(vp) = cursor else {
+        break None;
+      };
+
+      match vp.merge_operand() {
+        None => break Some(vp.as_slice()),
+        Some(op) => match op.merged() {
+          Some(merged) => break Some(merged),
+          None => {
+            operands.push(op.operand());
+            cursor = op.base;
+          }
+        },
+      }
+    };
+    operands.reverse();
+
+    let merged = Box::into_raw(Box::new(self.operator.full_merge(
+      self.key(),
+      existing,
+      &operands,
+    )));
+    match self
+      .merged
+      .compare_exchange(ptr::null_mut(), merged, Ordering::AcqRel, Ordering::Acquire)
+    {
+      // SAFETY: the merged value is published and only freed when the merge operand is dropped.
+      Ok(_) => unsafe { (*merged).as_slice() },
+      Err(current) => {
+        // SAFETY: another reader has merged the value first, and ours is never published.
+        unsafe {
+          drop(Box::from_raw(merged));
+          (*current).as_slice()
+        }
+      }
+    }
+  }
+}
+
+/// An append-only set of the merge operands in a WAL.
+///
+/// The value pointers of the merge operands point to the nodes in this set, which are only freed when the WAL is dropped.
+#[doc(hidden)]
+pub struct MergeOperands {
+  head: AtomicPtr<MergeOperand>,
+}
+
+// SAFETY: the pointers point to the memory of the WAL, which is immutable after the entries are committed,
+// and the merged values are published atomically.
+unsafe impl Send for MergeOperands {}
+unsafe impl Sync for MergeOperands {}
+
+impl Default for MergeOperands {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl core::fmt::Debug for MergeOperands {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let mut list = f.debug_list();
+    let mut cursor = self.head.load(Ordering::Acquire);
+    // SAFETY: the nodes are never freed until the set is dropped.
+    while let Some(node) = unsafe { cursor.as_ref() } {
+      list.entry(&(node.key(), node.operand()));
+      cursor = node.next;
+    }
+    list.finish()
+  }
+}
+
+impl MergeOperands {
+  /// Creates an empty set.
+  #[inline]
+  pub(crate) const fn new() -> Self {
+    Self {
+      head: AtomicPtr::new(ptr::null_mut()),
+    }
+  }
+
+  /// Adds a merge operand, which is folded over `base`, and returns the value pointer of the merged value.
+  pub(crate) fn insert<K: ?Sized, C, V: ?Sized>(
+    &self,
+    key: KeyPointer<K, C>,
+    operand: ValuePointer<V>,
+    base: Option<ValuePointer<V>>,
+    operator: &'static dyn MergeOperator,
+  ) -> ValuePointer<V> {
+    let key_bytes = key.as_slice();
+    let operand_bytes = operand.as_slice();
+    let node = Box::into_raw(Box::new(MergeOperand {
+      key: key.as_ptr(),
+      key_len: key_bytes.len(),
+      operand: operand.as_ptr(),
+      operand_len: operand_bytes.len(),
+      base: base.map(ValuePointer::cast),
+      operator,
+      merged: AtomicPtr::new(ptr::null_mut()),
+      next: ptr::null_mut(),
+    }));
+
+    let mut head = self.head.load(Ordering::Acquire);
+    loop {
+      // SAFETY: the node is not published yet.
+      unsafe { (*node).next = head };
+      match self
+        .head
+        .compare_exchange_weak(head, node, Ordering::AcqRel, Ordering::Acquire)
+      {
+        Ok(_) => return operand.with_merge_operand(node),
+        Err(current) => head = current,
+      }
+    }
+  }
+}
+
+impl Drop for MergeOperands {
+  fn drop(&mut self) {
+    let mut cursor = *self.head.get_mut();
+    while !cursor.is_null() {
+      // SAFETY: the nodes are allocated by `Box::into_raw` and only freed here.
+      let node = unsafe { Box::from_raw(cursor) };
+      let merged = node.merged.load(Ordering::Acquire);
+      if !merged.is_null() {
+        // SAFETY: the merged values are allocated by `Box::into_raw` and only freed here.
+        drop(unsafe { Box::from_raw(merged) });
+      }
+      cursor = node.next;
+    }
+  }
+}
+
+#[inline]
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+  if len == 0 {
+    return &[];
+  }
+
+  slice::from_raw_parts(ptr, len)
+}
@@ -5,26 +5,34 @@ use dbutils::{
   buffer::VacantBuffer,
   checksum::BuildChecksumer,
   equivalent::Comparable,
-  types::{KeyRef, MaybeStructured, Type},
+  types::{KeyRef, MaybeStructured, Type, TypeRef},
 };
 #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
 use rarena_allocator::Allocator;
 use ref_cast::RefCast;
 use skl::{either::Either, KeySize};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+use std::vec::Vec;
+
+#[cfg(feature = "std")]
+use crate::types::HlcTimestamp;
 
 use crate::{
-  batch::Batch,
+  batch::{Batch, BatchEntry, PreparedBatch},
   error::Error,
-  memtable::{BaseTable, MultipleVersionMemtable, VersionedMemtableEntry},
+  memtable::{BaseTable, Memtable, MemtableEntry, MultipleVersionMemtable, VersionedMemtableEntry},
   sealed::{Constructable, MultipleVersionWalReader, Wal},
   types::{
     multiple_version::{Entry, VersionedEntry},
-    BufWriter, KeyBuilder, ValueBuilder,
+    BufWriter, KeyBuilder, KeyOrder, ValueBuilder,
   },
+  utils::{encoded_bytes, with_encoded},
+  wal::{BloomFilter, BloomFilterStats, Reservation, Transaction},
   Options,
 };
 
-use super::{Query, QueryRange, Slice};
+use super::{base, Query, QueryRange, Slice};
 
 mod iter;
 pub use iter::*;
@@ -49,6 +57,32 @@ pub trait Reader: Constructable {
     self.as_wal().path()
   }
 
+  /// Returns `true` if the memtable is replayed, it is `false` until the replay in the background completes.
+  ///
+  /// The reads do not wait for the replay, before it completes they only see the entries replayed so far.
+  ///
+  /// See [`Builder::with_lazy_replay`](crate::Builder::with_lazy_replay) for more details.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  #[inline]
+  fn is_ready(&self) -> bool {
+    self.as_wal().readiness().is_ready()
+  }
+
+  /// Blocks until the memtable is replayed, returns immediately if the WAL is not replayed in the background.
+  ///
+  /// If the replay in the background failed, the memtable only contains part of the entries, and the error is
+  /// returned by the first call, the later calls return a corrupted error.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  #[inline]
+  fn wait_ready(&self) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Memtable: 'static,
+  {
+    self.as_wal().readiness().wait_ready()
+  }
+
   /// Returns the maximum key size allowed in the WAL.
   #[inline]
   fn maximum_key_size(&self) -> KeySize {
@@ -61,6 +95,14 @@ pub trait Reader: Constructable {
     self.as_wal().maximum_value_size()
   }
 
+  /// Returns the statistics of the Bloom filter, or `None` if the filter is disabled.
+  ///
+  /// See [`Builder::with_bloom_filter_bits_per_key`](crate::Builder::with_bloom_filter_bits_per_key) for more details.
+  #[inline]
+  fn bloom_filter_stats(&self) -> Option<BloomFilterStats> {
+    self.as_wal().bloom_filter().map(BloomFilter::stats)
+  }
+
   /// Returns the maximum version in the WAL.
   #[inline]
   fn maximum_version(&self) -> u64
@@ -127,7 +169,12 @@ pub trait Reader: Constructable {
       KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
     <Self::Memtable as BaseTable>::Value: Type,
   {
-    Iter::new(BaseIter::new(version, self.as_wal().iter(version)))
+    Iter::new(BaseIter::new(
+      version,
+      self.as_wal().iter(version),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
+    ))
   }
 
   /// Returns an iterator over the entries (all versions) in the WAL.
@@ -151,6 +198,8 @@ pub trait Reader: Constructable {
     IterAll::new(MultipleVersionBaseIter::new(
       version,
       self.as_wal().iter_all_versions(version),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
     ))
   }
 
@@ -164,7 +213,7 @@ pub trait Reader: Constructable {
   where
     R: RangeBounds<Q>,
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -176,6 +225,8 @@ pub trait Reader: Constructable {
     Range::new(BaseIter::new(
       version,
       self.as_wal().range(version, QueryRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
     ))
   }
 
@@ -189,7 +240,7 @@ pub trait Reader: Constructable {
   where
     R: RangeBounds<Q> + 'a,
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -203,9 +254,155 @@ pub trait Reader: Constructable {
       self
         .as_wal()
         .range_all_versions(version, QueryRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
+    ))
+  }
+
+  /// Returns an iterator over the entries which were changed between version `from` (exclusive) and version `to` (inclusive).
+  ///
+  /// For each key, only the newest entry whose version is in `(from, to]` is yielded, and the entry may be marked as removed.
+  /// A key removed by a range tombstone whose version is in `(from, to]` is yielded as removed at the version of the tombstone,
+  /// even if it is not written after `from`. Other keys whose newest version (less than or equal to `to`) is not greater
+  /// than `from` are skipped.
+  #[inline]
+  fn changes_between(&self, from: u64, to: u64) -> ChangesBetween<'_, Self::Memtable>
+  where
+    Self::Memtable: MultipleVersionMemtable + 'static,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+  {
+    let wal = self.as_wal();
+    ChangesBetween::new(ChangesBetweenBase::new(
+      Wal::memtable(wal),
+      ..,
+      from,
+      to,
+      Wal::range_deletions(wal).coverage(),
+      MultipleVersionWalReader::pruning(wal),
+      !may_contain_changes(Wal::memtable(wal), from, to),
+    ))
+  }
+
+  /// Returns an iterator over the entries which were changed between version `from` (exclusive) and version `to` (inclusive)
+  /// in a subset of the WAL.
+  ///
+  /// For each key, only the newest entry whose version is in `(from, to]` is yielded, and the entry may be marked as removed.
+  /// A key removed by a range tombstone whose version is in `(from, to]` is yielded as removed at the version of the tombstone,
+  /// even if it is not written after `from`. Other keys whose newest version (less than or equal to `to`) is not greater
+  /// than `from` are skipped.
+  #[inline]
+  fn range_changes_between<'a, Q, R>(
+    &'a self,
+    from: u64,
+    to: u64,
+    range: R,
+  ) -> RangeChangesBetween<'a, R, Q, <Self::Wal as Wal<Self::Checksumer>>::Memtable>
+  where
+    R: RangeBounds<Q> + 'a,
+    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let wal = self.as_wal();
+    RangeChangesBetween::new(ChangesBetweenBase::new(
+      Wal::memtable(wal),
+      QueryRange::new(range),
+      from,
+      to,
+      Wal::range_deletions(wal).coverage(),
+      MultipleVersionWalReader::pruning(wal),
+      !may_contain_changes(Wal::memtable(wal), from, to),
     ))
   }
 
+  /// Returns an iterator over all the versions of the key in the WAL, from the newest to the oldest.
+  ///
+  /// The entries marked as removed are also yielded.
+  #[inline]
+  fn history<'a, Q>(
+    &'a self,
+    key: &'a Q,
+  ) -> History<'a, Q, <Self::Wal as Wal<Self::Checksumer>>::Memtable>
+  where
+    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder> + 'static,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.history_range(key, ..)
+  }
+
+  /// Returns an iterator over the versions of the key in the WAL which are in the given version range,
+  /// from the newest to the oldest.
+  ///
+  /// The entries marked as removed are also yielded.
+  #[inline]
+  fn history_range<'a, Q, V>(
+    &'a self,
+    key: &'a Q,
+    versions: V,
+  ) -> History<'a, Q, <Self::Wal as Wal<Self::Checksumer>>::Memtable>
+  where
+    V: RangeBounds<u64>,
+    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder> + 'static,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let upper = match versions.end_bound() {
+      Bound::Included(&version) => Some(version),
+      Bound::Excluded(&version) => version.checked_sub(1),
+      Bound::Unbounded => Some(self.maximum_version()),
+    };
+    let lower = versions.start_bound().cloned();
+    let (version, exhausted) = match (upper, lower) {
+      (None, _) => (0, true),
+      (Some(upper), Bound::Included(lower)) => (upper, lower > upper),
+      (Some(upper), Bound::Excluded(lower)) => (upper, lower >= upper),
+      (Some(upper), Bound::Unbounded) => (upper, false),
+    };
+
+    let wal = self.as_wal();
+    let pruning = MultipleVersionWalReader::pruning(wal);
+    let bounds = (Bound::Included(key), Bound::Included(key));
+    History::new(
+      MultipleVersionBaseIter::new(
+        version,
+        wal.range_all_versions(version, QueryRange::new(bounds)),
+        Wal::range_deletions(wal).coverage(),
+        pruning,
+      ),
+      MultipleVersionBaseIter::new(
+        version,
+        wal.range_all_versions(version, QueryRange::new(bounds)),
+        Wal::range_deletions(wal).coverage(),
+        pruning,
+      ),
+      lower,
+      exhausted,
+    )
+  }
+
   /// Returns an iterator over the keys in the WAL.
   #[inline]
   fn keys(
@@ -226,7 +423,12 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    Keys::new(BaseIter::new(version, self.as_wal().iter(version)))
+    Keys::new(BaseIter::new(
+      version,
+      self.as_wal().iter(version),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
+    ))
   }
 
   /// Returns an iterator over a subset of keys in the WAL.
@@ -239,7 +441,7 @@ pub trait Reader: Constructable {
   where
     R: RangeBounds<Q> + 'a,
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -251,6 +453,8 @@ pub trait Reader: Constructable {
     RangeKeys::new(BaseIter::new(
       version,
       self.as_wal().range(version, QueryRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
     ))
   }
 
@@ -274,7 +478,12 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    Values::new(BaseIter::new(version, self.as_wal().iter(version)))
+    Values::new(BaseIter::new(
+      version,
+      self.as_wal().iter(version),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
+    ))
   }
 
   /// Returns an iterator over a subset of values in the WAL.
@@ -287,7 +496,7 @@ pub trait Reader: Constructable {
   where
     R: RangeBounds<Q> + 'a,
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -299,6 +508,8 @@ pub trait Reader: Constructable {
     RangeValues::new(BaseIter::new(
       version,
       self.as_wal().range(version, QueryRange::new(range)),
+      Wal::range_deletions(self.as_wal()).coverage(),
+      MultipleVersionWalReader::pruning(self.as_wal()),
     ))
   }
 
@@ -389,11 +600,18 @@ pub trait Reader: Constructable {
   }
 
   /// Returns `true` if the key exists in the WAL.
+  ///
+  /// The query is encoded and looked up by its bytes, so it must share the reference type with the key type,
+  /// e.g. `str` for `String`, and the memtables which can locate a key by its bytes do not compare the keys.
+  ///
+  /// Same as [`contains_key_by_bytes`](Reader::contains_key_by_bytes), the Bloom filter is consulted first.
   #[inline]
   fn contains_key<'a, Q>(&'a self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -402,9 +620,11 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    self
-      .as_wal()
-      .contains_key(version, Query::<_, Q>::ref_cast(key))
+    // SAFETY: the query shares the reference type with the key type, so its encoding is a valid key.
+    with_encoded(key, |raw| unsafe {
+      Reader::contains_key_by_bytes(self, version, raw)
+    })
+    .unwrap_or(false)
   }
 
   /// Returns `true` if the key exists in the WAL.
@@ -413,8 +633,10 @@ pub trait Reader: Constructable {
   #[inline]
   fn contains_key_versioned<'a, Q>(&'a self, version: u64, key: &Q) -> bool
   where
-    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -423,9 +645,11 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    self
-      .as_wal()
-      .contains_key_versioned(version, Query::<_, Q>::ref_cast(key))
+    // SAFETY: the query shares the reference type with the key type, so its encoding is a valid key.
+    with_encoded(key, |raw| unsafe {
+      Reader::contains_key_versioned_by_bytes(self, version, raw)
+    })
+    .unwrap_or(false)
   }
 
   /// Returns `true` if the key exists in the WAL.
@@ -444,7 +668,7 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    self.as_wal().contains_key(version, Slice::ref_cast(key))
+    self.as_wal().may_contain_key(key) && self.as_wal().contains_key_by_bytes(version, key)
   }
 
   /// Returns `true` if the key exists in the WAL.
@@ -465,12 +689,14 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    self
-      .as_wal()
-      .contains_key_versioned(version, Slice::ref_cast(key))
+    self.as_wal().may_contain_key(key)
+      && self.as_wal().contains_key_versioned_by_bytes(version, key)
   }
 
   /// Gets the value associated with the key.
+  ///
+  /// Same as [`contains_key`](Reader::contains_key), the query is looked up by its encoded bytes,
+  /// and the Bloom filter is consulted first.
   #[inline]
   fn get<'a, Q>(
     &'a self,
@@ -478,8 +704,10 @@ pub trait Reader: Constructable {
     key: &Q,
   ) -> Option<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>
   where
-    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -488,10 +716,12 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    self
-      .as_wal()
-      .get(version, Query::<_, Q>::ref_cast(key))
-      .map(|ent| Entry::with_version(ent, version))
+    // SAFETY: the query shares the reference type with the key type, so its encoding is a valid key.
+    with_encoded(key, |raw| unsafe {
+      Reader::get_by_bytes(self, version, raw)
+    })
+    .ok()
+    .flatten()
   }
 
   /// Gets the value associated with the key.
@@ -505,8 +735,10 @@ pub trait Reader: Constructable {
     key: &Q,
   ) -> Option<VersionedEntry<'a, <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>>>
   where
-    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -515,10 +747,12 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    self
-      .as_wal()
-      .get_versioned(version, Query::<_, Q>::ref_cast(key))
-      .map(|ent| VersionedEntry::with_version(ent, version))
+    // SAFETY: the query shares the reference type with the key type, so its encoding is a valid key.
+    with_encoded(key, |raw| unsafe {
+      Reader::get_versioned_by_bytes(self, version, raw)
+    })
+    .ok()
+    .flatten()
   }
 
   /// Gets the value associated with the key.
@@ -541,9 +775,13 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
+    if !self.as_wal().may_contain_key(key) {
+      return None;
+    }
+
     self
       .as_wal()
-      .get(version, Slice::ref_cast(key))
+      .get_by_bytes(version, key)
       .map(|ent| Entry::with_version(ent, version))
   }
 
@@ -570,9 +808,13 @@ pub trait Reader: Constructable {
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
+    if !self.as_wal().may_contain_key(key) {
+      return None;
+    }
+
     self
       .as_wal()
-      .get_versioned(version, Slice::ref_cast(key))
+      .get_versioned_by_bytes(version, key)
       .map(|ent| VersionedEntry::with_version(ent, version))
   }
 
@@ -586,7 +828,7 @@ pub trait Reader: Constructable {
   ) -> Option<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>
   where
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -613,7 +855,7 @@ pub trait Reader: Constructable {
   ) -> Option<VersionedEntry<'a, <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>>>
   where
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -694,7 +936,7 @@ pub trait Reader: Constructable {
   ) -> Option<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>
   where
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -722,7 +964,7 @@ pub trait Reader: Constructable {
   ) -> Option<VersionedEntry<'a, <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>>>
   where
     Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
-    Self::Memtable: MultipleVersionMemtable,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
     <Self::Memtable as BaseTable>::Key: Type + Ord,
     <Self::Memtable as BaseTable>::Value: Type,
     for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
@@ -792,10 +1034,208 @@ pub trait Reader: Constructable {
       .as_wal()
       .lower_bound_versioned(
         version,
-        bound.map(Slice::<<Self::Memtable as BaseTable>::Key>::ref_cast),
+        bound.map(Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast),
       )
       .map(|ent| VersionedEntry::with_version(ent, version))
   }
+
+  /// Returns an iterator over the entries in the WAL as of the given wall-clock time.
+  ///
+  /// The time is translated to the largest [`HlcTimestamp`] in the same millisecond, so this method
+  /// is only meaningful when the versions are generated by a [`HybridLogicalClock`](crate::types::HybridLogicalClock).
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  fn iter_as_of(
+    &self,
+    time: SystemTime,
+  ) -> Iter<
+    '_,
+    <<Self::Wal as Wal<Self::Checksumer>>::Memtable as BaseTable>::Iterator<'_>,
+    Self::Memtable,
+  >
+  where
+    Self::Memtable: MultipleVersionMemtable + 'static,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+    for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type,
+  {
+    self.iter(HlcTimestamp::latest_at(time).version())
+  }
+
+  /// Returns an iterator over a subset of entries in the WAL as of the given wall-clock time.
+  ///
+  /// See [`iter_as_of`](Reader::iter_as_of) for how the time is translated to a version.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  fn range_as_of<'a, Q, R>(
+    &'a self,
+    time: SystemTime,
+    range: R,
+  ) -> Range<'a, R, Q, <Self::Wal as Wal<Self::Checksumer>>::Memtable>
+  where
+    R: RangeBounds<Q>,
+    Q: ?Sized + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.range(HlcTimestamp::latest_at(time).version(), range)
+  }
+
+  /// Returns `true` if the key exists in the WAL as of the given wall-clock time.
+  ///
+  /// See [`iter_as_of`](Reader::iter_as_of) for how the time is translated to a version.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  fn contains_key_as_of<'a, Q>(&'a self, time: SystemTime, key: &Q) -> bool
+  where
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.contains_key(HlcTimestamp::latest_at(time).version(), key)
+  }
+
+  /// Gets the value associated with the key as of the given wall-clock time.
+  ///
+  /// See [`iter_as_of`](Reader::iter_as_of) for how the time is translated to a version.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  fn get_as_of<'a, Q>(
+    &'a self,
+    time: SystemTime,
+    key: &Q,
+  ) -> Option<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>
+  where
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.get(HlcTimestamp::latest_at(time).version(), key)
+  }
+
+  /// Gets the value associated with the key as of the given wall-clock time.
+  ///
+  /// Compared to [`get_as_of`](Reader::get_as_of), this method returns a versioned item, which means that the returned item
+  /// may already be marked as removed.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  fn get_versioned_as_of<'a, Q>(
+    &'a self,
+    time: SystemTime,
+    key: &Q,
+  ) -> Option<VersionedEntry<'a, <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'a>>>
+  where
+    Q: ?Sized
+      + Comparable<<<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>>
+      + for<'b> Type<Ref<'b> = <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>>,
+    Self::Memtable: MultipleVersionMemtable<Comparator = KeyOrder>,
+    <Self::Memtable as BaseTable>::Key: Type + Ord,
+    <Self::Memtable as BaseTable>::Value: Type,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.get_versioned(HlcTimestamp::latest_at(time).version(), key)
+  }
+
+  /// Exports the visible state of the WAL at the given version into a new plain WAL.
+  ///
+  /// The new WAL is constructed by `builder`, then for each key, only the latest value
+  /// (less than or equal to `version`) is written, and the removed keys are skipped. The entries are
+  /// written by batch insertions, so the new WAL contains no history and no tombstones.
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use orderwal::{base, multiple_version::{OrderWal, Reader, Writer}, Builder};
+  ///
+  /// let mut wal = Builder::new()
+  ///   .with_capacity(1024)
+  ///   .alloc::<OrderWal<str, str>>()
+  ///   .unwrap();
+  /// wal.insert(1, "a", "a1").unwrap();
+  /// wal.insert(2, "a", "a2").unwrap();
+  ///
+  /// let snapshot = wal
+  ///   .export_snapshot(1, || Builder::new().with_capacity(1024).alloc::<base::OrderWal<str, str>>())
+  ///   .unwrap();
+  ///
+  /// use orderwal::base::Reader as _;
+  /// assert_eq!(snapshot.get("a").unwrap().value(), "a1");
+  /// ```
+  fn export_snapshot<W, F>(&self, version: u64, builder: F) -> Result<W, Error<W::Memtable>>
+  where
+    F: FnOnce() -> Result<W, Error<W::Memtable>>,
+    W: base::Writer,
+    W::Reader: base::Reader<Memtable = W::Memtable>,
+    W::Checksumer: BuildChecksumer,
+    W::Memtable: Memtable<
+      Key = <Self::Memtable as BaseTable>::Key,
+      Value = <Self::Memtable as BaseTable>::Value,
+    >,
+    for<'a> <W::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
+    Self::Memtable: MultipleVersionMemtable + 'static,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    for<'a> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+      KeyRef<'a, <Self::Memtable as BaseTable>::Key>,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'a> <Self::Memtable as BaseTable>::Item<'a>: VersionedMemtableEntry<'a>,
+  {
+    // The snapshot must contain all the entries, not only the ones replayed so far.
+    self.as_wal().wait_replayed();
+
+    let mut snapshot = builder()?;
+    let mut batch = std::vec::Vec::new();
+    let mut batch_size = 0;
+
+    for ent in self.iter(version) {
+      let (key, value) = (ent.raw_key(), ent.raw_value());
+      batch_size += key.len() + value.len();
+      batch.push(BatchEntry::new(key, value));
+
+      if batch.len() >= EXPORT_BATCH_ENTRIES || batch_size >= EXPORT_BATCH_SIZE {
+        write_snapshot_batch(&mut snapshot, &mut batch)?;
+        batch_size = 0;
+      }
+    }
+
+    if !batch.is_empty() {
+      write_snapshot_batch(&mut snapshot, &mut batch)?;
+    }
+
+    Ok(snapshot)
+  }
 }
 
 impl<T> Reader for T
@@ -847,6 +1287,19 @@ where
     self.as_wal().flush_async()
   }
 
+  /// Writes the index of the WAL to `<path of the WAL>.index`, so that reopening the WAL with
+  /// [`Options::with_persisted_index`](crate::Options::with_persisted_index) only replays the records
+  /// written after this call.
+  ///
+  /// The index is also written when the WAL is dropped if the option is enabled, this method is
+  /// for the checkpoints. It is a no-op if the WAL is not backed by a file.
+  #[inline]
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "memmap", not(target_family = "wasm")))))]
+  fn persist_index(&self) -> Result<(), Error<Self::Memtable>> {
+    self.as_wal().persist_index()
+  }
+
   /// Returns the read-only view for the WAL.
   fn reader(&self) -> Self::Reader;
 
@@ -960,14 +1413,49 @@ where
       .insert(Some(version), key.into(), value.into())
   }
 
-  /// Removes a key-value pair from the WAL. This method
-  /// allows the caller to build the key in place.
+  /// Inserts a key-value pair into the WAL, which expires after `ttl`.
+  ///
+  /// The expiration time is recorded in the entry by the system clock, in milliseconds. Once expired,
+  /// the reads at any version treat the entry as absent, and the older versions of the key are not
+  /// visible through it. The expired entry is only filtered out by the reads: there is no compaction or
+  /// garbage collection of the expired entries, so it still occupies the space of the WAL and the memtable,
+  /// until the live entries are rewritten into a new WAL, e.g. by [`export_snapshot`](Reader::export_snapshot).
+  ///
+  /// ## Example
+  ///
+  /// ```rust
+  /// use core::time::Duration;
+  /// use orderwal::{multiple_version::{OrderWal, Reader, Writer}, Builder};
+  ///
+  /// let mut wal = Builder::new()
+  ///   .with_capacity(1024)
+  ///   .alloc::<OrderWal<str, str>>()
+  ///   .unwrap();
+  ///
+  /// wal.insert(1, "session", "old").unwrap();
+  /// wal.insert_with_ttl(2, "session", "alive", Duration::from_secs(60)).unwrap();
+  /// wal.insert_with_ttl(3, "session", "gone", Duration::ZERO).unwrap();
+  /// assert_eq!(wal.get(1, "session").unwrap().value(), "old");
+  /// assert_eq!(wal.get(2, "session").unwrap().value(), "alive");
+  /// assert!(wal.get(3, "session").is_none());
+  /// ```
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
   #[inline]
-  fn remove_with_builder<KE>(
-    &mut self,
+  fn insert_with_ttl<'a>(
+    &'a mut self,
     version: u64,
-    kb: KeyBuilder<impl FnOnce(&mut VacantBuffer<'_>) -> Result<usize, KE>>,
-  ) -> Result<(), Either<KE, Error<Self::Memtable>>>
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    value: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+    ttl: core::time::Duration,
+  ) -> Result<
+    (),
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
   where
     Self::Checksumer: BuildChecksumer,
     Self::Memtable: MultipleVersionMemtable,
@@ -979,7 +1467,188 @@ where
     for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
       VersionedMemtableEntry<'b>,
   {
-    self.as_wal().remove(Some(version), kb)
+    let ttl = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+    // `0` means the entry never expires.
+    let expire_at = crate::utils::unix_millis().saturating_add(ttl).max(1);
+    self
+      .as_wal()
+      .update(
+        Some(version),
+        key.into(),
+        Some(value.into()),
+        Some(expire_at),
+      )
+      .map(|_| ())
+  }
+
+  /// Inserts a key-value pair into the WAL, returns the entry which was just written.
+  ///
+  /// The entry references the bytes in the WAL, so nothing is copied or decoded eagerly.
+  #[inline]
+  fn insert_returning<'a>(
+    &'a mut self,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    value: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>,
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let wal = self.as_wal();
+    let kp = wal.update(Some(version), key.into(), Some(value.into()), None)?;
+    // The writes appended while replaying are only inserted into the memtable after the replay.
+    wal.wait_replayed();
+    Ok(Entry::with_version(
+      MultipleVersionWalReader::get(wal, version, &kp)
+        .expect("the entry was just inserted into the memtable"),
+      version,
+    ))
+  }
+
+  /// Writes `new` at the given version if the value of the key visible at the latest version is `expected`,
+  /// returns `true` if the value is written.
+  ///
+  /// The values are compared by their encoded bytes, and nothing is written if the key does not exist
+  /// or is removed at the latest version. There is only one writer, so no other write can happen between
+  /// the comparison and the insertion.
+  #[inline]
+  fn compare_and_swap<'a>(
+    &'a mut self,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    expected: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+    new: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    bool,
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let key = key.into();
+    let raw = encoded_bytes(&key).map_err(Among::Left)?;
+    let expected = encoded_bytes(&expected.into()).map_err(Among::Middle)?;
+
+    let wal = self.as_wal();
+    // The check must see the whole memtable, so it waits for the replay in the background.
+    wal.wait_replayed();
+    let current = wal
+      .get(
+        u64::MAX,
+        Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast(&raw),
+      )
+      .and_then(|ent| ent.value());
+    match current {
+      Some(vp) if vp.as_slice() == expected.as_ref() => {
+        wal.insert(Some(version), key, new.into()).map(|_| true)
+      }
+      _ => Ok(false),
+    }
+  }
+
+  /// Reads the latest value of the key and writes the value built by `f` at the given version in one call.
+  ///
+  /// The closure receives the value of the key visible at the latest version, and returns the builder of the new value,
+  /// or `None` to remove the key. There is only one writer, so no other write can happen between
+  /// the read and the write, while the readers see either the old value or the new value.
+  #[inline]
+  fn upsert<'a, F, VB, E>(
+    &'a mut self,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    f: F,
+  ) -> Result<
+    (),
+    Among<<<Self::Memtable as BaseTable>::Key as Type>::Error, E, Error<Self::Memtable>>,
+  >
+  where
+    F: FnOnce(
+      Option<<<Self::Memtable as BaseTable>::Value as Type>::Ref<'_>>,
+    ) -> Option<ValueBuilder<VB>>,
+    VB: FnOnce(&mut VacantBuffer<'_>) -> Result<usize, E>,
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let key = key.into();
+    let raw = encoded_bytes(&key).map_err(Among::Left)?;
+
+    let wal = self.as_wal();
+    // The check must see the whole memtable, so it waits for the replay in the background.
+    wal.wait_replayed();
+    let old = wal
+      .get(
+        u64::MAX,
+        Slice::<_, <Self::Memtable as BaseTable>::Comparator>::ref_cast(&raw),
+      )
+      .and_then(|ent| ent.value())
+      .map(|vp| {
+        // SAFETY: the value is encoded by the value type.
+        unsafe { TypeRef::from_slice(vp.as_slice()) }
+      });
+
+    match f(old) {
+      Some(vb) => wal.insert(Some(version), key, vb),
+      None => wal.remove(Some(version), key).map_err(|e| match e {
+        Either::Left(e) => Among::Left(e),
+        Either::Right(e) => Among::Right(e),
+      }),
+    }
+  }
+
+  /// Removes a key-value pair from the WAL. This method
+  /// allows the caller to build the key in place.
+  #[inline]
+  fn remove_with_builder<KE>(
+    &mut self,
+    version: u64,
+    kb: KeyBuilder<impl FnOnce(&mut VacantBuffer<'_>) -> Result<usize, KE>>,
+  ) -> Result<(), Either<KE, Error<Self::Memtable>>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.as_wal().remove(Some(version), kb)
   }
 
   /// Removes a key-value pair from the WAL.
@@ -1003,6 +1672,77 @@ where
     self.as_wal().remove(Some(version), key.into())
   }
 
+  /// Removes all the entries whose keys are in `start..end` at the given version.
+  ///
+  /// The entries in the range are hidden from the reads whose query version is greater than or equal to `version`,
+  /// if their versions are less than `version`, or equal to `version` but inserted before the range tombstone.
+  /// The reads at a lower version still see the entries.
+  ///
+  /// If `start` is not less than `end`, the range is empty and nothing is written.
+  ///
+  /// The removed entries still occupy the space of the WAL, which is reclaimed only when the remaining entries
+  /// are rewritten into a new WAL, e.g. by [`export_snapshot`](Reader::export_snapshot).
+  ///
+  /// The end bound is written as the value of the range tombstone record, so it must fit in both
+  /// the maximum key size and the maximum value size, otherwise [`Error::KeyTooLarge`] is returned.
+  #[inline]
+  fn delete_range<'a>(
+    &'a mut self,
+    version: u64,
+    range: core::ops::Range<impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>>,
+  ) -> Result<(), Either<<<Self::Memtable as BaseTable>::Key as Type>::Error, Error<Self::Memtable>>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self
+      .as_wal()
+      .delete_range(Some(version), range.start.into(), range.end.into())
+  }
+
+  /// Appends a merge operand of the key at the given version to the WAL, instead of the full value.
+  ///
+  /// The operands are folded over the value of the key visible at `version` when the operand is appended,
+  /// by the merge operator set by [`Options::with_merge_operator`](crate::Options::with_merge_operator) lazily,
+  /// when the value is read. The entries inserted later with a lower version are not folded.
+  /// Returns [`Error::MissingMergeOperator`] if the merge operator is not set.
+  #[inline]
+  fn merge<'a>(
+    &'a mut self,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    operand: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    (),
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self
+      .as_wal()
+      .merge(Some(version), key.into(), operand.into())
+  }
+
   /// Inserts a batch of key-value pairs into the WAL.
   #[inline]
   fn insert_batch<'a, B>(
@@ -1115,4 +1855,279 @@ where
   {
     self.as_wal().insert_batch::<Self, _>(batch)
   }
+
+  /// Inserts a batch of key-value pairs into the WAL, returns the entries which were just written in the order of the batch.
+  ///
+  /// The tombstones in the batch are skipped, and if a key is written more than once at the same version in the batch,
+  /// all of its entries are the last written one.
+  fn insert_batch_returning<'a, B>(
+    &'a mut self,
+    batch: &mut B,
+  ) -> Result<
+    Vec<Entry<'a, <Self::Memtable as BaseTable>::Item<'a>>>,
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    B: Batch<
+      Self::Memtable,
+      Key = MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>,
+      Value = MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>,
+    >,
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    let wal = self.as_wal();
+    wal.insert_batch::<Self, _>(batch)?;
+    // The writes appended while replaying are only inserted into the memtable after the replay.
+    wal.wait_replayed();
+    Ok(
+      batch
+        .iter_mut()
+        .filter_map(|ent| match (ent.internal_version(), ent.pointer()) {
+          (Some(version), Some((kp, Some(_)))) => Some(Entry::with_version(
+            MultipleVersionWalReader::get(wal, version, &kp)
+              .expect("the entry was just inserted into the memtable"),
+            version,
+          )),
+          _ => None,
+        })
+        .collect(),
+    )
+  }
+
+  /// Inserts a prepared batch into the WAL, the entries are copied without encoding them again.
+  #[inline]
+  fn insert_prepared_batch(
+    &mut self,
+    batch: &PreparedBatch<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.as_wal().insert_prepared_batch(batch)
+  }
+
+  /// Starts a long-running transaction, the entries written in it are invisible until
+  /// the transaction is committed by [`commit_transaction`](Writer::commit_transaction).
+  ///
+  /// The entries of a transaction which is never committed are discarded when the WAL is reopened.
+  ///
+  /// The transaction holds a pointer to each of its entries in memory until it is committed,
+  /// see [`Transaction`] for the limit this puts on its size.
+  #[inline]
+  fn begin_transaction(&mut self) -> Result<Transaction<Self::Memtable>, Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.as_wal().begin_transaction()
+  }
+
+  /// Inserts a key-value pair at the given version into the WAL as a part of the transaction.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn transaction_insert<'a>(
+    &'a mut self,
+    txn: &mut Transaction<Self::Memtable>,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+    value: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>,
+  ) -> Result<
+    (),
+    Among<
+      <<Self::Memtable as BaseTable>::Key as Type>::Error,
+      <<Self::Memtable as BaseTable>::Value as Type>::Error,
+      Error<Self::Memtable>,
+    >,
+  >
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self
+      .as_wal()
+      .transaction_update(txn, Some(version), key.into(), Some(value.into()))
+  }
+
+  /// Removes a key-value pair at the given version from the WAL as a part of the transaction.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn transaction_remove<'a>(
+    &'a mut self,
+    txn: &mut Transaction<Self::Memtable>,
+    version: u64,
+    key: impl Into<MaybeStructured<'a, <Self::Memtable as BaseTable>::Key>>,
+  ) -> Result<(), Either<<<Self::Memtable as BaseTable>::Key as Type>::Error, Error<Self::Memtable>>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self
+      .as_wal()
+      .transaction_update::<_, MaybeStructured<'a, <Self::Memtable as BaseTable>::Value>>(
+        txn,
+        Some(version),
+        key.into(),
+        None,
+      )
+      .map_err(Among::into_left_right)
+  }
+
+  /// Commits the transaction, all the entries written in it become visible at once.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn commit_transaction(
+    &mut self,
+    txn: Transaction<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.as_wal().commit_transaction(txn)
+  }
+
+  /// Aborts the transaction, all the entries written in it are discarded.
+  ///
+  /// ## Panics
+  /// - If the transaction is not started by this WAL.
+  #[inline]
+  fn abort_transaction(
+    &mut self,
+    txn: Transaction<Self::Memtable>,
+  ) -> Result<(), Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    self.as_wal().abort_transaction(txn)
+  }
+
+  /// Reserves a record at the given version in the WAL, whose key and value are filled in place later,
+  /// e.g. when the value is read from a stream.
+  ///
+  /// The record is invisible until [`Reservation::commit`] is called, and no other record can be written before
+  /// the reservation is committed or aborted.
+  #[inline]
+  fn reserve(
+    &mut self,
+    version: u64,
+    key_len: usize,
+    value_len: usize,
+  ) -> Result<Reservation<'_, Self>, Error<Self::Memtable>>
+  where
+    Self::Checksumer: BuildChecksumer,
+    Self::Memtable: MultipleVersionMemtable,
+    <Self::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <Self::Memtable as BaseTable>::Value: Type + 'static,
+    for<'b> <<Self::Memtable as BaseTable>::Key as Type>::Ref<'b>:
+      KeyRef<'b, <Self::Memtable as BaseTable>::Key>,
+    for<'b> <Self::Memtable as BaseTable>::Item<'b>: VersionedMemtableEntry<'b>,
+    for<'b> <Self::Memtable as MultipleVersionMemtable>::VersionedItem<'b>:
+      VersionedMemtableEntry<'b>,
+  {
+    Reservation::new(self.as_wal(), Some(version), key_len, value_len)
+  }
+}
+
+/// Returns `false` if the memtable cannot contain any entry whose version is in `(from, to]`.
+/// The maximum number of entries in a batch written by [`Reader::export_snapshot`].
+const EXPORT_BATCH_ENTRIES: usize = 1024;
+
+/// The maximum number of key and value bytes in a batch written by [`Reader::export_snapshot`].
+const EXPORT_BATCH_SIZE: usize = 4 * 1024 * 1024;
+
+#[inline]
+fn write_snapshot_batch<W>(
+  snapshot: &mut W,
+  batch: &mut std::vec::Vec<BatchEntry<&[u8], &[u8], W::Memtable>>,
+) -> Result<(), Error<W::Memtable>>
+where
+  W: base::Writer,
+  W::Reader: base::Reader<Memtable = W::Memtable>,
+  W::Checksumer: BuildChecksumer,
+  W::Memtable: Memtable,
+  <W::Memtable as BaseTable>::Key: Type + Ord + 'static,
+  for<'a> <<W::Memtable as BaseTable>::Key as Type>::Ref<'a>:
+    KeyRef<'a, <W::Memtable as BaseTable>::Key>,
+  <W::Memtable as BaseTable>::Value: Type + 'static,
+  for<'a> <W::Memtable as BaseTable>::Item<'a>: MemtableEntry<'a>,
+{
+  let res = snapshot
+    .insert_batch_with_builders(batch)
+    .map_err(|e| match e {
+      Among::Left(e) | Among::Middle(e) => Error::InsufficientSpace(e),
+      Among::Right(e) => e,
+    });
+  batch.clear();
+  res
+}
+
+#[inline]
+fn may_contain_changes<M>(memtable: &M, from: u64, to: u64) -> bool
+where
+  M: MultipleVersionMemtable,
+  for<'a> M::Item<'a>: VersionedMemtableEntry<'a>,
+{
+  from < to && memtable.maximum_version() > from && memtable.minimum_version() <= to
 }
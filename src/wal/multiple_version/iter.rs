@@ -1,11 +1,21 @@
-use core::{iter::FusedIterator, marker::PhantomData, ops::RangeBounds};
+use core::{
+  iter::FusedIterator,
+  marker::PhantomData,
+  ops::{Bound, RangeBounds, RangeFull},
+};
 
 use dbutils::{equivalent::Comparable, types::Type};
 
 use crate::{
-  memtable::{BaseEntry, MultipleVersionMemtable, VersionedMemtableEntry},
-  types::multiple_version::{Entry, Key, Value, VersionedEntry},
-  wal::{KeyPointer, ValuePointer},
+  memtable::{
+    btree::{above_start, below_end},
+    BaseEntry, MultipleVersionMemtable, VersionedMemtableEntry,
+  },
+  types::{
+    multiple_version::{Entry, Key, Value, VersionedEntry},
+    KeyOrder,
+  },
+  wal::{Coverage, KeyPointer, Pruning, ValuePointer},
 };
 
 use super::{Query, QueryRange};
@@ -13,14 +23,16 @@ use super::{Query, QueryRange};
 /// Iterator over the entries in the WAL.
 pub struct BaseIter<'a, I, M>
 where
-  M: MultipleVersionMemtable + 'a,
+  M: MultipleVersionMemtable,
   for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
 {
   iter: I,
   version: u64,
-  head: Option<(KeyPointer<M::Key>, ValuePointer<M::Value>)>,
-  tail: Option<(KeyPointer<M::Key>, ValuePointer<M::Value>)>,
+  coverage: Coverage<'a>,
+  pruning: Pruning<'a, M>,
+  head: Option<(KeyPointer<M::Key, M::Comparator>, ValuePointer<M::Value>)>,
+  tail: Option<(KeyPointer<M::Key, M::Comparator>, ValuePointer<M::Value>)>,
   _m: PhantomData<&'a ()>,
 }
 
@@ -31,10 +43,17 @@ where
   for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
 {
   #[inline]
-  pub(super) fn new(version: u64, iter: I) -> Self {
+  pub(super) fn new(
+    version: u64,
+    iter: I,
+    coverage: Coverage<'a>,
+    pruning: Pruning<'a, M>,
+  ) -> Self {
     Self {
       version,
       iter,
+      coverage,
+      pruning,
       head: None,
       tail: None,
       _m: PhantomData,
@@ -48,6 +67,19 @@ where
   }
 }
 
+impl<'a, I, M> BaseIter<'a, I, M>
+where
+  M: MultipleVersionMemtable + 'a,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  /// Returns `true` if the entry is neither removed by a range tombstone nor pruned by the retention policy.
+  #[inline]
+  fn is_visible(&self, ent: &M::Item<'a>) -> bool {
+    !self.coverage.covers_versioned_entry(self.version, ent) && !self.pruning.prunes(ent)
+  }
+}
+
 impl<'a, I, M> Iterator for BaseIter<'a, I, M>
 where
   M: MultipleVersionMemtable + 'a,
@@ -59,9 +91,13 @@ where
 
   #[inline]
   fn next(&mut self) -> Option<Self::Item> {
-    self.iter.next().inspect(|ent| {
-      self.head = Some((ent.key(), ent.value().unwrap()));
-    })
+    loop {
+      let ent = self.iter.next()?;
+      if self.is_visible(&ent) {
+        self.head = Some((ent.key(), ent.value().unwrap()));
+        return Some(ent);
+      }
+    }
   }
 }
 
@@ -74,9 +110,13 @@ where
 {
   #[inline]
   fn next_back(&mut self) -> Option<Self::Item> {
-    self.iter.next_back().inspect(|ent| {
-      self.tail = Some((ent.key(), ent.value().unwrap()));
-    })
+    loop {
+      let ent = self.iter.next_back()?;
+      if self.is_visible(&ent) {
+        self.tail = Some((ent.key(), ent.value().unwrap()));
+        return Some(ent);
+      }
+    }
   }
 }
 
@@ -92,14 +132,26 @@ where
 /// Iterator over the entries in the WAL.
 pub struct MultipleVersionBaseIter<'a, I, M>
 where
-  M: MultipleVersionMemtable + 'a,
+  M: MultipleVersionMemtable,
   for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
 {
   iter: I,
   version: u64,
-  head: Option<(KeyPointer<M::Key>, Option<ValuePointer<M::Value>>)>,
-  tail: Option<(KeyPointer<M::Key>, Option<ValuePointer<M::Value>>)>,
+  coverage: Coverage<'a>,
+  pruning: Pruning<'a, M>,
+  // The floors of the last keys seen from both ends, the entries of the same key are adjacent,
+  // so each floor is computed only once per key and direction.
+  head_floor: Option<(KeyPointer<M::Key, M::Comparator>, u64)>,
+  tail_floor: Option<(KeyPointer<M::Key, M::Comparator>, u64)>,
+  head: Option<(
+    KeyPointer<M::Key, M::Comparator>,
+    Option<ValuePointer<M::Value>>,
+  )>,
+  tail: Option<(
+    KeyPointer<M::Key, M::Comparator>,
+    Option<ValuePointer<M::Value>>,
+  )>,
   _m: PhantomData<&'a ()>,
 }
 
@@ -110,10 +162,19 @@ where
   for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
 {
   #[inline]
-  pub(super) fn new(version: u64, iter: I) -> Self {
+  pub(super) fn new(
+    version: u64,
+    iter: I,
+    coverage: Coverage<'a>,
+    pruning: Pruning<'a, M>,
+  ) -> Self {
     Self {
       version,
       iter,
+      coverage,
+      pruning,
+      head_floor: None,
+      tail_floor: None,
       head: None,
       tail: None,
       _m: PhantomData,
@@ -127,9 +188,42 @@ where
   }
 }
 
+impl<'a, I, M> MultipleVersionBaseIter<'a, I, M>
+where
+  M: MultipleVersionMemtable + 'a,
+  M::Key: Type,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  /// Returns `true` if the entry is neither removed by a range tombstone nor pruned by the retention policy.
+  #[inline]
+  fn is_visible(&mut self, ent: &M::VersionedItem<'a>, back: bool) -> bool {
+    if self.coverage.covers_versioned_entry(self.version, ent) {
+      return false;
+    }
+
+    if self.pruning.is_empty() {
+      return true;
+    }
+
+    let cached = if back {
+      &mut self.tail_floor
+    } else {
+      &mut self.head_floor
+    };
+    let key = ent.key();
+    let floor = match cached {
+      Some((last, floor)) if *last == key => *floor,
+      _ => cached.insert((key, self.pruning.floor(&key))).1,
+    };
+    ent.version() >= floor
+  }
+}
+
 impl<'a, I, M> Iterator for MultipleVersionBaseIter<'a, I, M>
 where
   M: MultipleVersionMemtable + 'a,
+  M::Key: Type,
   for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
   I: Iterator<Item = M::VersionedItem<'a>>,
@@ -138,30 +232,40 @@ where
 
   #[inline]
   fn next(&mut self) -> Option<Self::Item> {
-    self.iter.next().inspect(|ent| {
-      self.head = Some((ent.key(), ent.value()));
-    })
+    loop {
+      let ent = self.iter.next()?;
+      if self.is_visible(&ent, false) {
+        self.head = Some((ent.key(), ent.value()));
+        return Some(ent);
+      }
+    }
   }
 }
 
 impl<'a, I, M> DoubleEndedIterator for MultipleVersionBaseIter<'a, I, M>
 where
   M: MultipleVersionMemtable + 'a,
+  M::Key: Type,
   for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
   I: DoubleEndedIterator<Item = M::VersionedItem<'a>>,
 {
   #[inline]
   fn next_back(&mut self) -> Option<Self::Item> {
-    self.iter.next_back().inspect(|ent| {
-      self.tail = Some((ent.key(), ent.value()));
-    })
+    loop {
+      let ent = self.iter.next_back()?;
+      if self.is_visible(&ent, true) {
+        self.tail = Some((ent.key(), ent.value()));
+        return Some(ent);
+      }
+    }
   }
 }
 
 impl<'a, I, M> FusedIterator for MultipleVersionBaseIter<'a, I, M>
 where
   M: MultipleVersionMemtable + 'a,
+  M::Key: Type,
   for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
   I: FusedIterator<Item = M::VersionedItem<'a>>,
@@ -410,7 +514,7 @@ pub struct Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable,
+  B: MultipleVersionMemtable<Comparator = KeyOrder>,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -423,7 +527,7 @@ impl<'a, R, Q, B> Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -449,7 +553,7 @@ impl<'a, R, Q, B> Iterator for Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>: Iterator<Item = B::Item<'a>>,
@@ -471,7 +575,7 @@ impl<'a, R, Q, B> DoubleEndedIterator for Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -492,7 +596,7 @@ impl<'a, R, Q, B> FusedIterator for Range<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -507,7 +611,7 @@ pub struct RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable,
+  B: MultipleVersionMemtable<Comparator = KeyOrder>,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -520,7 +624,7 @@ impl<'a, R, Q, B> RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -546,7 +650,7 @@ impl<'a, R, Q, B> Iterator for RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>: Iterator<Item = B::Item<'a>>,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
@@ -567,7 +671,7 @@ impl<'a, R, Q, B> DoubleEndedIterator for RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
     DoubleEndedIterator<Item = B::Item<'a>>,
@@ -587,7 +691,7 @@ impl<'a, R, Q, B> FusedIterator for RangeKeys<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
     FusedIterator<Item = B::Item<'a>>,
@@ -601,7 +705,7 @@ pub struct RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable,
+  B: MultipleVersionMemtable<Comparator = KeyOrder>,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -614,7 +718,7 @@ impl<'a, R, Q, B> RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -640,7 +744,7 @@ impl<'a, R, Q, B> Iterator for RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>: Iterator<Item = B::Item<'a>>,
@@ -662,7 +766,7 @@ impl<'a, R, Q, B> DoubleEndedIterator for RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -683,7 +787,7 @@ impl<'a, R, Q, B> FusedIterator for RangeValues<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::Range<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -779,7 +883,7 @@ pub struct RangeAll<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable,
+  B: MultipleVersionMemtable<Comparator = KeyOrder>,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -796,7 +900,7 @@ impl<'a, R, Q, B> RangeAll<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
@@ -826,7 +930,7 @@ impl<'a, R, Q, B> Iterator for RangeAll<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -849,7 +953,7 @@ impl<'a, R, Q, B> DoubleEndedIterator for RangeAll<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -870,7 +974,7 @@ impl<'a, R, Q, B> FusedIterator for RangeAll<'a, R, Q, B>
 where
   R: RangeBounds<Q> + 'a,
   Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
-  B: MultipleVersionMemtable + 'a,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
   B::Key: Type + Ord,
   B::Value: Type,
   B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>>:
@@ -879,3 +983,581 @@ where
   for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
 {
 }
+
+/// Iterator which only yields the newest entry of each key whose version is in `(from, to]`, along with the version
+/// of the range tombstone in `(from, to]` which removes the key, if any.
+///
+/// The iterator seeks the memtable key by key, and only walks the versions of a key until the first visible one,
+/// or the first one which is not newer than `from`, so the older versions are never visited.
+pub struct ChangesBetweenBase<'a, Q, R, M>
+where
+  Q: ?Sized,
+  M: MultipleVersionMemtable,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  memtable: &'a M,
+  range: R,
+  version: u64,
+  from: u64,
+  coverage: Coverage<'a>,
+  pruning: Pruning<'a, M>,
+  exhausted: bool,
+  // The last keys visited from both ends.
+  head: Option<KeyPointer<M::Key, M::Comparator>>,
+  tail: Option<KeyPointer<M::Key, M::Comparator>>,
+  _q: PhantomData<&'a Q>,
+}
+
+impl<'a, Q, R, M> ChangesBetweenBase<'a, Q, R, M>
+where
+  Q: ?Sized,
+  M: MultipleVersionMemtable + 'a,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  /// Creates a new iterator over the changes of the keys in the range in `(from, version]`.
+  ///
+  /// If `exhausted` is `true`, the caller already knows that no entry is written in the range between the versions,
+  /// then the iterator will not touch the underlying memtable at all, unless a range tombstone may be written between them.
+  #[inline]
+  pub(super) fn new(
+    memtable: &'a M,
+    range: R,
+    from: u64,
+    version: u64,
+    coverage: Coverage<'a>,
+    pruning: Pruning<'a, M>,
+    exhausted: bool,
+  ) -> Self {
+    Self {
+      memtable,
+      range,
+      version,
+      from,
+      coverage,
+      pruning,
+      exhausted: (exhausted && !coverage.may_remove_after(from)) || version <= from,
+      head: None,
+      tail: None,
+      _q: PhantomData,
+    }
+  }
+
+  /// Returns the lower bound (exclusive) of the versions yielded by the iterator.
+  #[inline]
+  pub(super) const fn from(&self) -> u64 {
+    self.from
+  }
+
+  /// Returns the upper bound (inclusive) of the versions yielded by the iterator.
+  #[inline]
+  pub(super) const fn version(&self) -> u64 {
+    self.version
+  }
+
+  /// Returns the newest visible entry of the key of `ent`, which must be the newest version of the key,
+  /// if its version is in `(from, version]`.
+  ///
+  /// If the key is removed by a range tombstone, the version of the tombstone is returned along with `ent`
+  /// if it is in `(from, version]`, even if the key is not written after `from`.
+  ///
+  /// The range tombstones and the retention policy remove a version along with all the older ones, so
+  /// the walk stops at the first version which is not newer than `from`.
+  fn newest_change(
+    &self,
+    mut ent: M::VersionedItem<'a>,
+  ) -> Option<(M::VersionedItem<'a>, Option<u64>)> {
+    let key = ent.key();
+    let floor = self.pruning.floor(&key);
+    if ent.version() < floor {
+      return None;
+    }
+
+    if let Some(removed) = self.coverage.removed_by(self.version, &ent) {
+      return (removed > self.from).then_some((ent, Some(removed)));
+    }
+
+    // Only the expired versions are left to skip.
+    loop {
+      if ent.version() <= self.from || ent.version() < floor {
+        return None;
+      }
+
+      if !self.coverage.covers_versioned_entry(self.version, &ent) {
+        return Some((ent, None));
+      }
+
+      ent = ent.next().filter(|next| next.key() == key)?;
+    }
+  }
+}
+
+impl<'a, Q, R, M> Iterator for ChangesBetweenBase<'a, Q, R, M>
+where
+  Q: ?Sized + Comparable<KeyPointer<M::Key, M::Comparator>>,
+  R: RangeBounds<Q>,
+  M: MultipleVersionMemtable + 'a,
+  M::Key: Type,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  type Item = (M::VersionedItem<'a>, Option<u64>);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    while !self.exhausted {
+      // The first entry of a key is its newest version.
+      let ent = match &self.head {
+        Some(key) => self
+          .memtable
+          .lower_bound_versioned(self.version, Bound::Excluded(key)),
+        None => self
+          .memtable
+          .lower_bound_versioned(self.version, self.range.start_bound()),
+      };
+
+      let key = match ent.as_ref().map(|ent| ent.key()) {
+        Some(key)
+          if below_end(self.range.end_bound(), &key)
+            && self.tail.as_ref().map_or(true, |tail| key < *tail) =>
+        {
+          key
+        }
+        _ => {
+          self.exhausted = true;
+          return None;
+        }
+      };
+
+      self.head = Some(key);
+      if let Some(ent) = ent.and_then(|ent| self.newest_change(ent)) {
+        return Some(ent);
+      }
+    }
+
+    None
+  }
+}
+
+impl<'a, Q, R, M> DoubleEndedIterator for ChangesBetweenBase<'a, Q, R, M>
+where
+  Q: ?Sized + Comparable<KeyPointer<M::Key, M::Comparator>>,
+  R: RangeBounds<Q>,
+  M: MultipleVersionMemtable + 'a,
+  M::Key: Type,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  fn next_back(&mut self) -> Option<Self::Item> {
+    while !self.exhausted {
+      let ent = match &self.tail {
+        Some(key) => self
+          .memtable
+          .upper_bound_versioned(self.version, Bound::Excluded(key)),
+        None => self
+          .memtable
+          .upper_bound_versioned(self.version, self.range.end_bound()),
+      };
+
+      let key = match ent.map(|ent| ent.key()) {
+        Some(key)
+          if above_start(self.range.start_bound(), &key)
+            && self.head.as_ref().map_or(true, |head| key > *head) =>
+        {
+          key
+        }
+        _ => {
+          self.exhausted = true;
+          return None;
+        }
+      };
+
+      // The upper bound may be any version of the key, so the newest one is looked up again.
+      self.tail = Some(key);
+      if let Some(ent) = self
+        .memtable
+        .get_versioned(self.version, &key)
+        .and_then(|ent| self.newest_change(ent))
+      {
+        return Some(ent);
+      }
+    }
+
+    None
+  }
+}
+
+impl<'a, Q, R, M> FusedIterator for ChangesBetweenBase<'a, Q, R, M>
+where
+  Q: ?Sized + Comparable<KeyPointer<M::Key, M::Comparator>>,
+  R: RangeBounds<Q>,
+  M: MultipleVersionMemtable + 'a,
+  M::Key: Type,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+}
+
+/// Iterator over the entries which were changed between two versions in the WAL.
+///
+/// For each key, only the newest entry whose version is in `(from, to]` is yielded,
+/// the entry may be marked as removed. The keys removed by a range tombstone whose version is in `(from, to]`
+/// are yielded as removed at the version of the tombstone.
+pub struct ChangesBetween<'a, M>
+where
+  M: MultipleVersionMemtable,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  iter: ChangesBetweenBase<'a, KeyPointer<M::Key, M::Comparator>, RangeFull, M>,
+  from: u64,
+  version: u64,
+}
+
+impl<'a, M> ChangesBetween<'a, M>
+where
+  M: MultipleVersionMemtable + 'a,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  #[inline]
+  pub(super) fn new(
+    iter: ChangesBetweenBase<'a, KeyPointer<M::Key, M::Comparator>, RangeFull, M>,
+  ) -> Self {
+    Self {
+      from: iter.from(),
+      version: iter.version(),
+      iter,
+    }
+  }
+
+  /// Returns the lower bound (exclusive) of the versions of the entries in the iterator.
+  #[inline]
+  pub const fn from(&self) -> u64 {
+    self.from
+  }
+
+  /// Returns the query version (the upper bound, inclusive) of the entries in the iterator.
+  #[inline]
+  pub const fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<'a, M> Iterator for ChangesBetween<'a, M>
+where
+  M: MultipleVersionMemtable + 'a,
+  M::Key: Type + Ord,
+  M::Value: Type,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  type Item = VersionedEntry<'a, M::VersionedItem<'a>>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    self
+      .iter
+      .next()
+      .map(|(ent, removed)| VersionedEntry::changed(ent, removed, self.version))
+  }
+}
+
+impl<'a, M> DoubleEndedIterator for ChangesBetween<'a, M>
+where
+  M: MultipleVersionMemtable + 'a,
+  M::Key: Type + Ord,
+  M::Value: Type,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  #[inline]
+  fn next_back(&mut self) -> Option<Self::Item> {
+    self
+      .iter
+      .next_back()
+      .map(|(ent, removed)| VersionedEntry::changed(ent, removed, self.version))
+  }
+}
+
+impl<'a, M> FusedIterator for ChangesBetween<'a, M>
+where
+  M: MultipleVersionMemtable + 'a,
+  M::Key: Type + Ord,
+  M::Value: Type,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+}
+
+/// An iterator over the entries which were changed between two versions in a subset of the WAL.
+///
+/// For each key, only the newest entry whose version is in `(from, to]` is yielded,
+/// the entry may be marked as removed. The keys removed by a range tombstone whose version is in `(from, to]`
+/// are yielded as removed at the version of the tombstone.
+pub struct RangeChangesBetween<'a, R, Q, B>
+where
+  R: RangeBounds<Q> + 'a,
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder>,
+  B::Key: Type + Ord,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  iter: ChangesBetweenBase<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>, B>,
+  from: u64,
+  version: u64,
+}
+
+impl<'a, R, Q, B> RangeChangesBetween<'a, R, Q, B>
+where
+  R: RangeBounds<Q> + 'a,
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  #[inline]
+  pub(super) fn new(
+    iter: ChangesBetweenBase<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, R>, B>,
+  ) -> Self {
+    Self {
+      from: iter.from(),
+      version: iter.version(),
+      iter,
+    }
+  }
+
+  /// Returns the lower bound (exclusive) of the versions of the entries in the iterator.
+  #[inline]
+  pub const fn from(&self) -> u64 {
+    self.from
+  }
+
+  /// Returns the query version (the upper bound, inclusive) of the entries in the iterator.
+  #[inline]
+  pub const fn version(&self) -> u64 {
+    self.version
+  }
+}
+
+impl<'a, R, Q, B> Iterator for RangeChangesBetween<'a, R, Q, B>
+where
+  R: RangeBounds<Q> + 'a,
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  type Item = VersionedEntry<'a, B::VersionedItem<'a>>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    self
+      .iter
+      .next()
+      .map(|(ent, removed)| VersionedEntry::changed(ent, removed, self.version))
+  }
+}
+
+impl<'a, R, Q, B> DoubleEndedIterator for RangeChangesBetween<'a, R, Q, B>
+where
+  R: RangeBounds<Q> + 'a,
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  #[inline]
+  fn next_back(&mut self) -> Option<Self::Item> {
+    self
+      .iter
+      .next_back()
+      .map(|(ent, removed)| VersionedEntry::changed(ent, removed, self.version))
+  }
+}
+
+impl<'a, R, Q, B> FusedIterator for RangeChangesBetween<'a, R, Q, B>
+where
+  R: RangeBounds<Q> + 'a,
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+}
+
+/// An iterator over all the versions of a single key in the WAL, from the newest to the oldest.
+pub struct History<'a, Q, B>
+where
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder>,
+  B::Key: Type + Ord,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  // All the entries yielded belong to the same key, the underlying iterators cannot tell whether
+  // the two ends have met or not by comparing keys, so we use two iterators and track the versions ourselves.
+  front: MultipleVersionBaseIter<
+    'a,
+    B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, (Bound<&'a Q>, Bound<&'a Q>)>>,
+    B,
+  >,
+  back: MultipleVersionBaseIter<
+    'a,
+    B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, (Bound<&'a Q>, Bound<&'a Q>)>>,
+    B,
+  >,
+  lower: Bound<u64>,
+  version: u64,
+  head: Option<u64>,
+  tail: Option<u64>,
+  exhausted: bool,
+}
+
+impl<'a, Q, B> History<'a, Q, B>
+where
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  /// Creates a new iterator over the versions of the key in `[lower, front.version()]`.
+  ///
+  /// `front` and `back` must iterate over the same key with the same query version.
+  /// If `exhausted` is `true`, the version range is empty and the iterator will not touch the underlying memtable at all.
+  #[inline]
+  pub(super) fn new(
+    front: MultipleVersionBaseIter<
+      'a,
+      B::RangeAll<
+        'a,
+        Query<'a, B::Key, Q>,
+        QueryRange<'a, B::Key, Q, (Bound<&'a Q>, Bound<&'a Q>)>,
+      >,
+      B,
+    >,
+    back: MultipleVersionBaseIter<
+      'a,
+      B::RangeAll<
+        'a,
+        Query<'a, B::Key, Q>,
+        QueryRange<'a, B::Key, Q, (Bound<&'a Q>, Bound<&'a Q>)>,
+      >,
+      B,
+    >,
+    lower: Bound<u64>,
+    exhausted: bool,
+  ) -> Self {
+    Self {
+      version: front.version(),
+      front,
+      back,
+      lower,
+      head: None,
+      tail: None,
+      exhausted,
+    }
+  }
+
+  /// Returns the query version (the newest version) of the entries in the iterator.
+  #[inline]
+  pub const fn version(&self) -> u64 {
+    self.version
+  }
+
+  #[inline]
+  fn is_too_old(&self, version: u64) -> bool {
+    match self.lower {
+      Bound::Included(lower) => version < lower,
+      Bound::Excluded(lower) => version <= lower,
+      Bound::Unbounded => false,
+    }
+  }
+}
+
+impl<'a, Q, B> Iterator for History<'a, Q, B>
+where
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, (Bound<&'a Q>, Bound<&'a Q>)>>:
+    Iterator<Item = B::VersionedItem<'a>>,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  type Item = VersionedEntry<'a, B::VersionedItem<'a>>;
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.exhausted {
+      return None;
+    }
+
+    // versions are yielded from the newest to the oldest, once we meet a version which is too old
+    // or has already been yielded from the back, all the remaining versions are done.
+    match self.front.next() {
+      Some(ent)
+        if !self.is_too_old(ent.version())
+          && self.tail.map_or(true, |tail| ent.version() > tail) =>
+      {
+        self.head = Some(ent.version());
+        Some(VersionedEntry::with_version(ent, self.version))
+      }
+      _ => {
+        self.exhausted = true;
+        None
+      }
+    }
+  }
+}
+
+impl<'a, Q, B> DoubleEndedIterator for History<'a, Q, B>
+where
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, (Bound<&'a Q>, Bound<&'a Q>)>>:
+    DoubleEndedIterator<Item = B::VersionedItem<'a>>,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  #[inline]
+  fn next_back(&mut self) -> Option<Self::Item> {
+    while !self.exhausted {
+      match self.back.next_back() {
+        Some(ent) if self.is_too_old(ent.version()) => continue,
+        Some(ent) if self.head.map_or(true, |head| ent.version() < head) => {
+          self.tail = Some(ent.version());
+          return Some(VersionedEntry::with_version(ent, self.version));
+        }
+        _ => self.exhausted = true,
+      }
+    }
+
+    None
+  }
+}
+
+impl<'a, Q, B> FusedIterator for History<'a, Q, B>
+where
+  Q: ?Sized + Comparable<<B::Key as Type>::Ref<'a>>,
+  B: MultipleVersionMemtable<Comparator = KeyOrder> + 'a,
+  B::Key: Type + Ord,
+  B::Value: Type,
+  B::RangeAll<'a, Query<'a, B::Key, Q>, QueryRange<'a, B::Key, Q, (Bound<&'a Q>, Bound<&'a Q>)>>:
+    Iterator<Item = B::VersionedItem<'a>>,
+  for<'b> B::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> B::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+}
@@ -6,14 +6,20 @@ use dbutils::{
   types::{KeyRef, Type, TypeRef},
 };
 
-use crate::types::EntryFlags;
+use crate::types::{EntryFlags, KeyComparator};
+
+use super::merge::MergeOperand;
 
 const PTR_SIZE: usize = mem::size_of::<usize>();
 const U32_SIZE: usize = mem::size_of::<u32>();
+const U64_SIZE: usize = mem::size_of::<u64>();
 
 pub struct ValuePointer<V: ?Sized> {
   ptr: *const u8,
   len: usize,
+  merge: *const MergeOperand,
+  /// The expiration time in milliseconds since the UNIX epoch, `0` if the value never expires.
+  expire_at: u64,
   _m: PhantomData<V>,
 }
 
@@ -39,17 +45,27 @@ impl<V: ?Sized> Clone for ValuePointer<V> {
 impl<V: ?Sized> Copy for ValuePointer<V> {}
 
 impl<V: ?Sized> ValuePointer<V> {
+  /// The encoded length of the value pointers which expire, which is the longest one.
+  pub(crate) const MAX_ENCODED_LEN: usize = PTR_SIZE + U32_SIZE + PTR_SIZE + U64_SIZE;
+
   #[inline]
   pub(crate) fn new(len: usize, ptr: *const u8) -> Self {
     Self {
       ptr,
       len,
+      merge: core::ptr::null(),
+      expire_at: 0,
       _m: PhantomData,
     }
   }
 
+  /// Returns the value, the merge operands are folded over the previous value if the value is merged.
   #[inline]
   pub(crate) fn as_slice<'a>(&self) -> &'a [u8] {
+    if let Some(op) = self.merge_operand() {
+      return op.resolve();
+    }
+
     if self.len == 0 {
       return &[];
     }
@@ -57,6 +73,52 @@ impl<V: ?Sized> ValuePointer<V> {
     // SAFETY: `ptr` is a valid pointer to `len` bytes.
     unsafe { slice::from_raw_parts(self.ptr, self.len) }
   }
+
+  #[inline]
+  pub(crate) const fn as_ptr(&self) -> *const u8 {
+    self.ptr
+  }
+
+  #[inline]
+  pub(crate) fn merge_operand<'a>(&self) -> Option<&'a MergeOperand> {
+    // SAFETY: the merge operands are never freed until the WAL is dropped.
+    unsafe { self.merge.as_ref() }
+  }
+
+  #[inline]
+  pub(crate) fn with_merge_operand(mut self, op: *const MergeOperand) -> Self {
+    self.merge = op;
+    self
+  }
+
+  #[inline]
+  pub(crate) fn with_expiration(mut self, expire_at: Option<u64>) -> Self {
+    self.expire_at = expire_at.unwrap_or(0);
+    self
+  }
+
+  /// Returns `true` if the value expires.
+  #[inline]
+  pub(crate) const fn is_expiring(&self) -> bool {
+    self.expire_at != 0
+  }
+
+  /// Returns `true` if the expiration time of the value has passed at `now`, which is the milliseconds since the UNIX epoch.
+  #[inline]
+  pub(crate) const fn is_expired_at(&self, now: u64) -> bool {
+    self.is_expiring() && self.expire_at <= now
+  }
+
+  #[inline]
+  pub(crate) fn cast<U: ?Sized>(self) -> ValuePointer<U> {
+    ValuePointer {
+      ptr: self.ptr,
+      len: self.len,
+      merge: self.merge,
+      expire_at: self.expire_at,
+      _m: PhantomData,
+    }
+  }
 }
 
 impl<V> Type for ValuePointer<V>
@@ -70,7 +132,15 @@ where
   #[inline]
   fn encoded_len(&self) -> usize {
     const SIZE: usize = PTR_SIZE + U32_SIZE;
-    SIZE
+    // The pointer to the merge operand is only encoded for the merged values, or the values which
+    // expire, the expiration time follows it.
+    if self.is_expiring() {
+      SIZE + PTR_SIZE + U64_SIZE
+    } else if self.merge.is_null() {
+      SIZE
+    } else {
+      SIZE + PTR_SIZE
+    }
   }
 
   #[inline]
@@ -78,38 +148,65 @@ where
     // Safe to cast to u32 here, because the key and value length are guaranteed to be less than or equal to u32::MAX.
     let val_len = self.len as u32;
     let ptr = self.ptr as usize;
+    let merge = self.merge as usize;
 
     buf.set_len(self.encoded_len());
     buf[0..PTR_SIZE].copy_from_slice(&ptr.to_le_bytes());
 
-    buf[PTR_SIZE..PTR_SIZE + U32_SIZE].copy_from_slice(&val_len.to_le_bytes());
+    let mut offset = PTR_SIZE;
+    buf[offset..offset + U32_SIZE].copy_from_slice(&val_len.to_le_bytes());
+    offset += U32_SIZE;
+    if !self.merge.is_null() || self.is_expiring() {
+      buf[offset..offset + PTR_SIZE].copy_from_slice(&merge.to_le_bytes());
+      offset += PTR_SIZE;
+    }
+    if self.is_expiring() {
+      buf[offset..offset + U64_SIZE].copy_from_slice(&self.expire_at.to_le_bytes());
+      offset += U64_SIZE;
+    }
 
-    Ok(PTR_SIZE + U32_SIZE)
+    Ok(offset)
   }
 }
 
 impl<'a, V: ?Sized> TypeRef<'a> for ValuePointer<V> {
   unsafe fn from_slice(src: &'a [u8]) -> Self {
     let ptr = usize_to_addr(usize::from_le_bytes((&src[..PTR_SIZE]).try_into().unwrap()));
-    let len =
-      u32::from_le_bytes((&src[PTR_SIZE..PTR_SIZE + U32_SIZE]).try_into().unwrap()) as usize;
+    let mut offset = PTR_SIZE;
+    let len = u32::from_le_bytes((&src[offset..offset + U32_SIZE]).try_into().unwrap()) as usize;
+    offset += U32_SIZE;
+    let vp = Self::new(len, ptr);
+    if src.len() < offset + PTR_SIZE {
+      return vp;
+    }
+
+    let merge = usize_to_addr(usize::from_le_bytes(
+      (&src[offset..offset + PTR_SIZE]).try_into().unwrap(),
+    ));
+    offset += PTR_SIZE;
+    let vp = vp.with_merge_operand(merge);
+    if src.len() < offset + U64_SIZE {
+      return vp;
+    }
 
-    Self::new(len, ptr)
+    vp.with_expiration(Some(u64::from_le_bytes(
+      (&src[offset..offset + U64_SIZE]).try_into().unwrap(),
+    )))
   }
 }
 
 #[doc(hidden)]
-pub struct KeyPointer<K: ?Sized> {
+pub struct KeyPointer<K: ?Sized, C> {
   flag: EntryFlags,
   ptr: *const u8,
   len: usize,
-  _m: PhantomData<K>,
+  _m: PhantomData<(fn() -> C, K)>,
 }
 
-unsafe impl<K: ?Sized> Send for KeyPointer<K> {}
-unsafe impl<K: ?Sized> Sync for KeyPointer<K> {}
+unsafe impl<K: ?Sized, C> Send for KeyPointer<K, C> {}
+unsafe impl<K: ?Sized, C> Sync for KeyPointer<K, C> {}
 
-impl<K: ?Sized> core::fmt::Debug for KeyPointer<K> {
+impl<K: ?Sized, C> core::fmt::Debug for KeyPointer<K, C> {
   fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
     f.debug_struct("KeyPointer")
       .field("ptr", &self.ptr)
@@ -119,16 +216,19 @@ impl<K: ?Sized> core::fmt::Debug for KeyPointer<K> {
   }
 }
 
-impl<K: ?Sized> Clone for KeyPointer<K> {
+impl<K: ?Sized, C> Clone for KeyPointer<K, C> {
   #[inline]
   fn clone(&self) -> Self {
     *self
   }
 }
 
-impl<K: ?Sized> Copy for KeyPointer<K> {}
+impl<K: ?Sized, C> Copy for KeyPointer<K, C> {}
+
+impl<K: ?Sized, C> KeyPointer<K, C> {
+  /// The encoded length of the key pointers.
+  pub(crate) const ENCODED_LEN: usize = PTR_SIZE + U32_SIZE + mem::size_of::<EntryFlags>();
 
-impl<K: ?Sized> KeyPointer<K> {
   #[inline]
   pub(crate) fn new(flag: EntryFlags, len: usize, ptr: *const u8) -> Self {
     Self {
@@ -139,6 +239,18 @@ impl<K: ?Sized> KeyPointer<K> {
     }
   }
 
+  /// Compares the encoded key with the key, by the comparator of the memtable.
+  ///
+  /// ## Safety
+  /// - `key` must be the bytes encoded by the key type.
+  #[inline]
+  pub(crate) unsafe fn compare_encoded(&self, key: &[u8]) -> cmp::Ordering
+  where
+    C: KeyComparator<K>,
+  {
+    C::compare(key, self.as_slice())
+  }
+
   #[inline]
   pub(crate) fn as_slice<'a>(&self) -> &'a [u8] {
     if self.len == 0 {
@@ -148,38 +260,48 @@ impl<K: ?Sized> KeyPointer<K> {
     // SAFETY: `ptr` is a valid pointer to `len` bytes.
     unsafe { slice::from_raw_parts(self.ptr, self.len) }
   }
+
+  #[inline]
+  pub(crate) const fn as_ptr(&self) -> *const u8 {
+    self.ptr
+  }
+
+  /// Returns the same key pointer ordered by another comparator.
+  #[cfg(all(feature = "memmap", not(target_family = "wasm")))]
+  #[inline]
+  pub(crate) const fn cast<D>(self) -> KeyPointer<K, D> {
+    KeyPointer {
+      flag: self.flag,
+      ptr: self.ptr,
+      len: self.len,
+      _m: PhantomData,
+    }
+  }
 }
 
-impl<K: Type + ?Sized> PartialEq for KeyPointer<K> {
+impl<K: ?Sized, C: KeyComparator<K>> PartialEq for KeyPointer<K, C> {
   fn eq(&self, other: &Self) -> bool {
-    self.as_slice() == other.as_slice()
+    // SAFETY: WALs guarantee that the self and other must be the same as the result returned by `<K as Type>::encode`.
+    unsafe { C::equivalent(self.as_slice(), other.as_slice()) }
   }
 }
 
-impl<K: Type + ?Sized> Eq for KeyPointer<K> {}
+impl<K: ?Sized, C: KeyComparator<K>> Eq for KeyPointer<K, C> {}
 
-impl<'a, K> PartialOrd for KeyPointer<K>
-where
-  K: Type + Ord + ?Sized,
-  K::Ref<'a>: KeyRef<'a, K>,
-{
+impl<K: ?Sized, C: KeyComparator<K>> PartialOrd for KeyPointer<K, C> {
   fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
     Some(self.cmp(other))
   }
 }
 
-impl<'a, K> Ord for KeyPointer<K>
-where
-  K: Type + Ord + ?Sized,
-  K::Ref<'a>: KeyRef<'a, K>,
-{
+impl<K: ?Sized, C: KeyComparator<K>> Ord for KeyPointer<K, C> {
   fn cmp(&self, other: &Self) -> cmp::Ordering {
     // SAFETY: WALs guarantee that the self and other must be the same as the result returned by `<K as Type>::encode`.
-    unsafe { <K::Ref<'_> as KeyRef<K>>::compare_binary(self.as_slice(), other.as_slice()) }
+    unsafe { C::compare(self.as_slice(), other.as_slice()) }
   }
 }
 
-impl<K> Type for KeyPointer<K>
+impl<K, C> Type for KeyPointer<K, C>
 where
   K: ?Sized,
 {
@@ -189,8 +311,7 @@ where
 
   #[inline]
   fn encoded_len(&self) -> usize {
-    const SIZE: usize = PTR_SIZE + U32_SIZE + mem::size_of::<EntryFlags>();
-    SIZE
+    Self::ENCODED_LEN
   }
 
   #[inline]
@@ -211,7 +332,7 @@ where
   }
 }
 
-impl<'a, K: ?Sized> TypeRef<'a> for KeyPointer<K> {
+impl<'a, K: ?Sized, C> TypeRef<'a> for KeyPointer<K, C> {
   unsafe fn from_slice(src: &'a [u8]) -> Self {
     let ptr = usize_to_addr(usize::from_le_bytes((&src[..PTR_SIZE]).try_into().unwrap()));
     let mut offset = PTR_SIZE;
@@ -219,15 +340,14 @@ impl<'a, K: ?Sized> TypeRef<'a> for KeyPointer<K> {
     offset += 1;
     let key_len =
       u32::from_le_bytes((&src[offset..offset + U32_SIZE]).try_into().unwrap()) as usize;
-
     Self::new(flag, key_len, ptr)
   }
 }
 
-impl<'a, K> KeyRef<'a, Self> for KeyPointer<K>
+impl<K, C> KeyRef<'_, Self> for KeyPointer<K, C>
 where
-  K: Type + Ord + ?Sized,
-  K::Ref<'a>: KeyRef<'a, K>,
+  K: ?Sized,
+  C: KeyComparator<K>,
 {
   #[inline]
   fn compare<Q>(&self, a: &Q) -> cmp::Ordering
@@ -239,7 +359,8 @@ where
 
   #[inline]
   unsafe fn compare_binary(a: &[u8], b: &[u8]) -> cmp::Ordering {
-    <K::Ref<'_> as KeyRef<K>>::compare_binary(a, b)
+    // The bytes are the encoded pointers, so the keys are compared by the pointers.
+    <Self as TypeRef<'_>>::from_slice(a).cmp(&<Self as TypeRef<'_>>::from_slice(b))
   }
 }
 
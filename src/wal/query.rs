@@ -6,36 +6,72 @@ use core::{
 
 use dbutils::{
   equivalent::{Comparable, Equivalent},
-  types::{KeyRef, Type, TypeRef},
+  types::{Type, TypeRef},
 };
 use ref_cast::RefCast;
 
+use crate::types::{KeyComparator, KeyOrder};
+
 use super::KeyPointer;
 
 #[derive(ref_cast::RefCast)]
 #[repr(transparent)]
-pub struct Slice<'a, K: ?Sized> {
-  _k: PhantomData<&'a K>,
+pub struct Slice<'a, K: ?Sized, C> {
+  _k: PhantomData<(&'a K, fn() -> C)>,
   data: [u8],
 }
 
-impl<'a, K> Equivalent<KeyPointer<K>> for Slice<'a, K>
+impl<K, C> Equivalent<KeyPointer<K, C>> for Slice<'_, K, C>
 where
-  K: Type + ?Sized,
-  K::Ref<'a>: KeyRef<'a, K>,
+  K: ?Sized,
+  C: KeyComparator<K>,
 {
-  fn equivalent(&self, key: &KeyPointer<K>) -> bool {
-    self.data.eq(key.as_slice())
+  fn equivalent(&self, key: &KeyPointer<K, C>) -> bool {
+    // SAFETY: the slice is the encoded key.
+    unsafe { key.compare_encoded(&self.data).is_eq() }
   }
 }
 
-impl<'a, K> Comparable<KeyPointer<K>> for Slice<'a, K>
+impl<K, C> Comparable<KeyPointer<K, C>> for Slice<'_, K, C>
 where
-  K: Type + ?Sized,
-  K::Ref<'a>: KeyRef<'a, K>,
+  K: ?Sized,
+  C: KeyComparator<K>,
+{
+  fn compare(&self, p: &KeyPointer<K, C>) -> cmp::Ordering {
+    // SAFETY: the slice is the encoded key.
+    unsafe { p.compare_encoded(&self.data) }
+  }
+}
+
+pub struct SliceRange<'a, K: ?Sized, C, R>
+where
+  R: RangeBounds<[u8]>,
+{
+  r: R,
+  _k: PhantomData<(&'a K, fn() -> C)>,
+}
+
+impl<K: ?Sized, C, R> SliceRange<'_, K, C, R>
+where
+  R: RangeBounds<[u8]>,
+{
+  #[inline]
+  pub(super) const fn new(r: R) -> Self {
+    Self { r, _k: PhantomData }
+  }
+}
+
+impl<'a, K: ?Sized, C, R> RangeBounds<Slice<'a, K, C>> for SliceRange<'a, K, C, R>
+where
+  R: RangeBounds<[u8]>,
 {
-  fn compare(&self, p: &KeyPointer<K>) -> cmp::Ordering {
-    unsafe { <K::Ref<'_> as KeyRef<K>>::compare_binary(&self.data, p.as_slice()) }
+  #[inline]
+  fn start_bound(&self) -> Bound<&Slice<'a, K, C>> {
+    self.r.start_bound().map(RefCast::ref_cast)
+  }
+
+  fn end_bound(&self) -> Bound<&Slice<'a, K, C>> {
+    self.r.end_bound().map(RefCast::ref_cast)
   }
 }
 
@@ -82,25 +118,25 @@ where
   key: Q,
 }
 
-impl<'a, K, Q> Equivalent<KeyPointer<K>> for Query<'a, K, Q>
+impl<'a, K, Q> Equivalent<KeyPointer<K, KeyOrder>> for Query<'a, K, Q>
 where
   K: Type + ?Sized,
   Q: ?Sized + Equivalent<K::Ref<'a>>,
 {
   #[inline]
-  fn equivalent(&self, p: &KeyPointer<K>) -> bool {
+  fn equivalent(&self, p: &KeyPointer<K, KeyOrder>) -> bool {
     let kr = unsafe { <K::Ref<'_> as TypeRef<'_>>::from_slice(p.as_slice()) };
     Equivalent::equivalent(&self.key, &kr)
   }
 }
 
-impl<'a, K, Q> Comparable<KeyPointer<K>> for Query<'a, K, Q>
+impl<'a, K, Q> Comparable<KeyPointer<K, KeyOrder>> for Query<'a, K, Q>
 where
   K: Type + ?Sized,
   Q: ?Sized + Comparable<K::Ref<'a>>,
 {
   #[inline]
-  fn compare(&self, p: &KeyPointer<K>) -> cmp::Ordering {
+  fn compare(&self, p: &KeyPointer<K, KeyOrder>) -> cmp::Ordering {
     let kr = unsafe { <K::Ref<'_> as TypeRef<'_>>::from_slice(p.as_slice()) };
     Comparable::compare(&self.key, &kr)
   }
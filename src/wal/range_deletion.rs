@@ -0,0 +1,462 @@
+use core::{
+  cmp, slice,
+  sync::atomic::{AtomicBool, Ordering},
+};
+
+use std::{collections::BTreeMap, vec, vec::Vec};
+
+use crate::{
+  memtable::{MemtableEntry, VersionedMemtableEntry},
+  sync::RwLock,
+};
+
+use super::{KeyPointer, ValuePointer};
+
+/// A bound of the fragments, which points to an encoded key in the WAL.
+#[derive(Clone, Copy)]
+struct Endpoint {
+  ptr: *const u8,
+  len: usize,
+  compare: unsafe fn(&[u8], &[u8]) -> cmp::Ordering,
+}
+
+impl Endpoint {
+  #[inline]
+  fn as_slice(&self) -> &[u8] {
+    if self.len == 0 {
+      return &[];
+    }
+
+    // SAFETY: the range tombstones never outlive the WAL, and the keys are never moved in the WAL.
+    unsafe { slice::from_raw_parts(self.ptr, self.len) }
+  }
+}
+
+impl PartialEq for Endpoint {
+  #[inline]
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other).is_eq()
+  }
+}
+
+impl Eq for Endpoint {}
+
+impl PartialOrd for Endpoint {
+  #[inline]
+  fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Endpoint {
+  #[inline]
+  fn cmp(&self, other: &Self) -> cmp::Ordering {
+    // SAFETY: all the bounds are encoded by the same key type, and compared by the same comparator.
+    unsafe { (self.compare)(self.as_slice(), other.as_slice()) }
+  }
+}
+
+/// A range tombstone, which removes the entries written before it.
+///
+/// The WAL is append-only, so an entry is written before the range tombstone if and only if it is stored at
+/// a lower address in the WAL.
+#[derive(Clone, Copy)]
+struct Tombstone {
+  version: u64,
+  /// The start bound of the range tombstone in the WAL, which is the position of the range tombstone.
+  position: *const u8,
+}
+
+impl Tombstone {
+  /// Returns `true` if the entry at `position` with the given `version` is removed by this range tombstone.
+  #[inline]
+  fn covers(&self, query_version: u64, version: u64, position: *const u8) -> bool {
+    self.version <= query_version && (version, position) < (self.version, self.position)
+  }
+}
+
+/// A part of the keys, which is covered by the same range tombstones.
+struct Fragment {
+  /// The exclusive end bound, the inclusive start bound is the key of the fragment in the map.
+  end: Endpoint,
+  tombstones: Vec<Tombstone>,
+}
+
+/// The range tombstones, which are split into the non-overlapping fragments sorted by the start bound.
+struct Fragments {
+  map: BTreeMap<Endpoint, Fragment>,
+  compare: Option<unsafe fn(&[u8], &[u8]) -> cmp::Ordering>,
+  /// The position of the last range tombstone.
+  last: usize,
+  /// The maximum version of the range tombstones.
+  maximum_version: u64,
+  /// The positions of the committed entries of the transactions which were open when a range tombstone was written,
+  /// mapped to the positions of their commit markers.
+  committed: BTreeMap<usize, usize>,
+}
+
+impl Fragments {
+  fn insert(&mut self, start: Endpoint, end: Endpoint, tombstone: Tombstone) {
+    self.compare = Some(start.compare);
+    self.last = self.last.max(tombstone.position as usize);
+    self.maximum_version = self.maximum_version.max(tombstone.version);
+    // An empty range covers no key.
+    if start >= end {
+      return;
+    }
+
+    self.split(start);
+    self.split(end);
+
+    // The fragments in the range are covered by the tombstone, and the gaps between them become new fragments.
+    let mut cursor = start;
+    let mut gaps = Vec::new();
+    for (fragment_start, fragment) in self.map.range_mut(start..end) {
+      if cursor < *fragment_start {
+        gaps.push((cursor, *fragment_start));
+      }
+      fragment.tombstones.push(tombstone);
+      cursor = fragment.end;
+    }
+    if cursor < end {
+      gaps.push((cursor, end));
+    }
+
+    for (start, end) in gaps {
+      self.map.insert(
+        start,
+        Fragment {
+          end,
+          tombstones: vec![tombstone],
+        },
+      );
+    }
+  }
+
+  /// Splits the fragment which contains `at` into two, so that `at` becomes a start bound.
+  fn split(&mut self, at: Endpoint) {
+    let Some((_, fragment)) = self.map.range_mut(..at).next_back() else {
+      return;
+    };
+
+    if at >= fragment.end {
+      return;
+    }
+
+    let right = Fragment {
+      end: fragment.end,
+      tombstones: fragment.tombstones.clone(),
+    };
+    fragment.end = at;
+    self.map.insert(at, right);
+  }
+
+  /// Returns the fragment which contains the key.
+  fn get(&self, key: &[u8]) -> Option<&Fragment> {
+    let probe = Endpoint {
+      ptr: key.as_ptr(),
+      len: key.len(),
+      compare: self.compare?,
+    };
+    self
+      .map
+      .range(..=probe)
+      .next_back()
+      .map(|(_, fragment)| fragment)
+      .filter(|fragment| probe < fragment.end)
+  }
+}
+
+/// An append-only set of the range tombstones in a WAL.
+///
+/// The set is written by the single writer and read by the readers concurrently, the range tombstones
+/// are only added and never removed until the WAL is dropped.
+///
+/// The expired entries are removed from the reads by the set as well, so the set also tracks whether
+/// any entry which expires was written, apart from the range tombstones, see `Coverage`.
+#[doc(hidden)]
+pub struct RangeDeletions {
+  fragments: RwLock<Fragments>,
+  /// Whether any range tombstone was added, so the reads skip the lock if there is none.
+  tombstones: AtomicBool,
+  expiring: AtomicBool,
+}
+
+// SAFETY: the pointers point to the memory of the WAL, which is immutable after the entries are committed.
+unsafe impl Send for RangeDeletions {}
+unsafe impl Sync for RangeDeletions {}
+
+impl Default for RangeDeletions {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl core::fmt::Debug for RangeDeletions {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let fragments = self.fragments.read();
+    f.debug_list()
+      .entries(fragments.map.iter().map(|(start, fragment)| {
+        (
+          start.as_slice(),
+          fragment.end.as_slice(),
+          fragment.tombstones.len(),
+        )
+      }))
+      .finish()
+  }
+}
+
+impl RangeDeletions {
+  /// Creates an empty set.
+  #[inline]
+  pub(crate) const fn new() -> Self {
+    Self {
+      fragments: RwLock::new(Fragments {
+        map: BTreeMap::new(),
+        compare: None,
+        last: 0,
+        maximum_version: 0,
+        committed: BTreeMap::new(),
+      }),
+      tombstones: AtomicBool::new(false),
+      expiring: AtomicBool::new(false),
+    }
+  }
+
+  /// Returns the coverage of a read, see [`Coverage`] for more details.
+  #[inline]
+  pub(crate) fn coverage(&self) -> Coverage<'_> {
+    Coverage {
+      range_deletions: self.tombstones.load(Ordering::Acquire).then_some(self),
+      #[cfg(feature = "std")]
+      now: if self.expiring.load(Ordering::Acquire) {
+        crate::utils::unix_millis()
+      } else {
+        0
+      },
+      // There is no clock without `std`, and the entries which expire can only be written with `std`.
+      #[cfg(not(feature = "std"))]
+      now: 0,
+      written_before: usize::MAX,
+    }
+  }
+
+  /// Records that an entry which expires was written, it must be called before the entry is visible to the readers.
+  #[inline]
+  pub(crate) fn mark_expiring(&self) {
+    self.expiring.store(true, Ordering::Release);
+  }
+
+  /// Adds a range tombstone, whose start bound is stored in the key and end bound is stored in the value of a record.
+  ///
+  /// The range tombstone is split along the existing fragments, so a lookup finds the fragment of a key
+  /// in logarithmic time, then only checks the range tombstones which overlap at the key.
+  #[inline]
+  pub(crate) fn insert<K: ?Sized, C, V: ?Sized>(
+    &self,
+    version: Option<u64>,
+    start: KeyPointer<K, C>,
+    end: ValuePointer<V>,
+    compare: unsafe fn(&[u8], &[u8]) -> cmp::Ordering,
+  ) {
+    let start_bytes = start.as_slice();
+    let end_bytes = end.as_slice();
+    let tombstone = Tombstone {
+      version: version.unwrap_or(0),
+      position: start.as_ptr(),
+    };
+    self.fragments.write().insert(
+      Endpoint {
+        ptr: start.as_ptr(),
+        len: start_bytes.len(),
+        compare,
+      },
+      Endpoint {
+        ptr: end.as_ptr(),
+        len: end_bytes.len(),
+        compare,
+      },
+      tombstone,
+    );
+    self.tombstones.store(true, Ordering::Release);
+  }
+
+  /// Moves the entries of a committed transaction, which are located at `positions`, to the position of
+  /// its commit marker, so a range tombstone written after an entry but before the commit marker does not remove it.
+  ///
+  /// Only the entries written before the last range tombstone are recorded, so the transactions which
+  /// do not overlap a range tombstone cost nothing.
+  pub(crate) fn commit(&self, positions: impl Iterator<Item = *const u8>, marker: *const u8) {
+    if !self.tombstones.load(Ordering::Acquire) {
+      return;
+    }
+
+    let mut fragments = self.fragments.write();
+    let last = fragments.last;
+    for position in positions.filter(|position| (*position as usize) < last) {
+      fragments
+        .committed
+        .insert(position as usize, marker as usize);
+    }
+  }
+
+  /// Returns the version of the newest range tombstone which removes the entry.
+  fn removed_by(
+    &self,
+    query_version: u64,
+    key: &[u8],
+    version: u64,
+    position: *const u8,
+    written_before: usize,
+  ) -> Option<u64> {
+    let fragments = self.fragments.read();
+    // The committed entries of the transactions are positioned at their commit markers.
+    let position = fragments
+      .committed
+      .get(&(position as usize))
+      .map_or(position, |marker| *marker as *const u8);
+    fragments.get(key).and_then(|fragment| {
+      fragment
+        .tombstones
+        .iter()
+        .filter(|tombstone| {
+          (tombstone.position as usize) < written_before
+            && tombstone.covers(query_version, version, position)
+        })
+        .map(|tombstone| tombstone.version)
+        .max()
+    })
+  }
+}
+
+/// The entries removed from a read, which are the ones covered by the range tombstones and the expired ones.
+///
+/// The range tombstones and the expiring entries are tracked separately, so a read only pays for what
+/// was written to the WAL, and the clock is read once when the coverage is created, so all the entries
+/// of one read are judged by the same time.
+#[derive(Clone, Copy)]
+pub(crate) struct Coverage<'a> {
+  /// The range tombstones, `None` if there is no range tombstone.
+  range_deletions: Option<&'a RangeDeletions>,
+  /// The milliseconds since the UNIX epoch, `0` if no entry expires.
+  now: u64,
+  /// Only the range tombstones stored below this address are taken into account.
+  written_before: usize,
+}
+
+impl Coverage<'_> {
+  /// Ignores the range tombstones written at or after `position`, which is used to resolve an entry
+  /// against the range tombstones as they were when the entry was written.
+  #[inline]
+  pub(crate) fn written_before(mut self, position: *const u8) -> Self {
+    self.written_before = position as usize;
+    self
+  }
+
+  /// Returns `true` if no entry is removed, i.e. there is no range tombstone, and no entry which expires.
+  #[inline]
+  pub(crate) const fn is_empty(&self) -> bool {
+    self.range_deletions.is_none() && self.now == 0
+  }
+
+  /// Returns `true` if there may be a range tombstone whose version is greater than `version`.
+  #[inline]
+  pub(crate) fn may_remove_after(&self, version: u64) -> bool {
+    self
+      .range_deletions
+      .is_some_and(|range_deletions| range_deletions.fragments.read().maximum_version > version)
+  }
+
+  /// Returns `true` if the entry is removed by a range tombstone, or it is expired.
+  #[inline]
+  pub(crate) fn covers_entry<'a, E>(&self, ent: &E) -> bool
+  where
+    E: MemtableEntry<'a>,
+  {
+    if self.is_empty() {
+      return false;
+    }
+
+    // Use the value to locate the entry, because the key of an overwritten entry may still point to the old record.
+    let vp = ent.value();
+    self.is_expired(&vp) || self.covers(u64::MAX, ent.key().as_slice(), 0, vp.as_ptr())
+  }
+
+  /// Returns `true` if the versioned entry is removed by a range tombstone visible at the query version, or it is expired.
+  #[inline]
+  pub(crate) fn covers_versioned_entry<'a, E>(&self, query_version: u64, ent: &E) -> bool
+  where
+    E: VersionedMemtableEntry<'a>,
+  {
+    if self.is_empty() {
+      return false;
+    }
+
+    let kp = ent.key();
+    let vp = ent.value();
+    if vp.is_some_and(|vp| self.is_expired(&vp)) {
+      return true;
+    }
+
+    let position = vp.map_or_else(|| kp.as_ptr(), |vp| vp.as_ptr());
+    self.covers(query_version, kp.as_slice(), ent.version(), position)
+  }
+
+  /// Returns the version of the newest range tombstone visible at the query version which removes the versioned entry,
+  /// the expired entries are not taken into account.
+  #[inline]
+  pub(crate) fn removed_by<'a, E>(&self, query_version: u64, ent: &E) -> Option<u64>
+  where
+    E: VersionedMemtableEntry<'a>,
+  {
+    let range_deletions = self.range_deletions?;
+    let kp = ent.key();
+    let position = ent.value().map_or_else(|| kp.as_ptr(), |vp| vp.as_ptr());
+    range_deletions.removed_by(
+      query_version,
+      kp.as_slice(),
+      ent.version(),
+      position,
+      self.written_before,
+    )
+  }
+
+  /// Returns `true` if the value of the key written with `version` is removed by a range tombstone visible at the query version,
+  /// or it is expired.
+  #[inline]
+  pub(crate) fn covers_value<K: ?Sized, C, V: ?Sized>(
+    &self,
+    query_version: Option<u64>,
+    key: &KeyPointer<K, C>,
+    version: u64,
+    value: &ValuePointer<V>,
+  ) -> bool {
+    if self.is_empty() {
+      return false;
+    }
+
+    self.is_expired(value)
+      || self.covers(
+        query_version.unwrap_or(u64::MAX),
+        key.as_slice(),
+        version,
+        value.as_ptr(),
+      )
+  }
+
+  #[inline]
+  fn is_expired<V: ?Sized>(&self, value: &ValuePointer<V>) -> bool {
+    self.now != 0 && value.is_expired_at(self.now)
+  }
+
+  #[inline]
+  fn covers(&self, query_version: u64, key: &[u8], version: u64, position: *const u8) -> bool {
+    self.range_deletions.is_some_and(|range_deletions| {
+      range_deletions
+        .removed_by(query_version, key, version, position, self.written_before)
+        .is_some()
+    })
+  }
+}
@@ -0,0 +1,215 @@
+use core::{
+  any::Any,
+  sync::atomic::{AtomicBool, Ordering},
+};
+use std::{
+  boxed::Box,
+  sync::{Condvar, Mutex, MutexGuard},
+  vec::Vec,
+};
+
+use crate::{error::Error, memtable::BaseTable};
+
+use super::{KeyPointer, ValuePointer};
+
+type Write<K, V, C> = (Option<u64>, KeyPointer<K, C>, Option<ValuePointer<V>>);
+
+struct State<K: ?Sized, V: ?Sized> {
+  /// The boundaries of the records are being scanned, nothing can be appended before the tail is truncated.
+  scanning: bool,
+  replaying: bool,
+  failed: bool,
+  /// The error of the replay, which is the `Error` of the memtable, it is taken by the first `wait_ready`.
+  error: Option<Box<dyn Any + Send>>,
+  /// The writes appended while replaying, they are applied in order after the replay completes.
+  /// The comparators of the keys are erased, so the readiness does not depend on the memtable.
+  writes: Vec<Write<K, V, ()>>,
+}
+
+/// Tracks the replay of a WAL which is running in the background.
+pub struct Readiness<K: ?Sized, V: ?Sized> {
+  ready: AtomicBool,
+  state: Mutex<State<K, V>>,
+  cond: Condvar,
+}
+
+impl<K: ?Sized, V: ?Sized> core::fmt::Debug for Readiness<K, V> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Readiness")
+      .field("ready", &self.is_ready())
+      .finish()
+  }
+}
+
+impl<K: ?Sized, V: ?Sized> Readiness<K, V> {
+  /// Creates a state of the WAL which is ready.
+  #[inline]
+  pub(crate) const fn new() -> Self {
+    Self {
+      ready: AtomicBool::new(true),
+      state: Mutex::new(State {
+        scanning: false,
+        replaying: false,
+        failed: false,
+        error: None,
+        writes: Vec::new(),
+      }),
+      cond: Condvar::new(),
+    }
+  }
+
+  /// Marks the WAL as replaying, it must be called before the WAL is shared.
+  #[inline]
+  pub(crate) fn start(&self) {
+    let mut state = self.lock();
+    state.scanning = true;
+    state.replaying = true;
+    self.ready.store(false, Ordering::Release);
+  }
+
+  /// Marks the boundaries of the records as scanned, so the writes can be appended after the replayed records.
+  #[inline]
+  pub(crate) fn scanned(&self) {
+    self.lock().scanning = false;
+    self.cond.notify_all();
+  }
+
+  /// Blocks until the boundaries of the records are scanned, returns an error if the replay failed.
+  pub(crate) fn wait_scanned<M>(&self) -> Result<(), Error<M>>
+  where
+    M: BaseTable<Key = K, Value = V>,
+  {
+    if self.is_ready() {
+      return Ok(());
+    }
+
+    let mut state = self.lock();
+    while state.scanning {
+      state = self
+        .cond
+        .wait(state)
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+
+    if state.failed {
+      Err(failed())
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Returns `true` if the replay is completed successfully.
+  #[inline]
+  pub(crate) fn is_ready(&self) -> bool {
+    self.ready.load(Ordering::Acquire)
+  }
+
+  /// Blocks until the replay is completed, successfully or not.
+  #[inline]
+  pub(crate) fn wait(&self) {
+    if self.is_ready() {
+      return;
+    }
+
+    let mut state = self.lock();
+    while state.replaying {
+      state = self
+        .cond
+        .wait(state)
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    }
+  }
+
+  /// Blocks until the replay is completed, returns the error if the replay failed.
+  pub(crate) fn wait_ready<M>(&self) -> Result<(), Error<M>>
+  where
+    M: BaseTable<Key = K, Value = V> + 'static,
+  {
+    self.wait();
+    let mut state = self.lock();
+    if !state.failed {
+      return Ok(());
+    }
+
+    Err(
+      state
+        .error
+        .take()
+        .and_then(|e| e.downcast::<Error<M>>().ok())
+        .map(|e| *e)
+        .unwrap_or_else(failed),
+    )
+  }
+
+  /// Queues a write appended while replaying, returns the write back if the replay is completed.
+  #[allow(clippy::type_complexity)]
+  pub(crate) fn defer<M>(
+    &self,
+    write: Write<K, V, M::Comparator>,
+  ) -> Result<Option<Write<K, V, M::Comparator>>, Error<M>>
+  where
+    M: BaseTable<Key = K, Value = V>,
+  {
+    if self.is_ready() {
+      return Ok(Some(write));
+    }
+
+    let mut state = self.lock();
+    if state.failed {
+      return Err(failed());
+    }
+
+    if state.replaying {
+      let (version, kp, vp) = write;
+      state.writes.push((version, kp.cast(), vp));
+      Ok(None)
+    } else {
+      Ok(Some(write))
+    }
+  }
+
+  /// Completes the replay, the writes appended while replaying are applied by `apply` in order if the replay succeeded.
+  pub(crate) fn complete<M>(
+    &self,
+    result: Result<(), Error<M>>,
+    mut apply: impl FnMut(Write<K, V, M::Comparator>) -> Result<(), Error<M>>,
+  ) where
+    M: BaseTable<Key = K, Value = V> + 'static,
+    M::Error: Send,
+  {
+    let mut state = self.lock();
+    let result = result.and_then(|_| {
+      core::mem::take(&mut state.writes)
+        .into_iter()
+        .try_for_each(|(version, kp, vp)| apply((version, kp.cast(), vp)))
+    });
+
+    state.scanning = false;
+    state.replaying = false;
+    match result {
+      Ok(()) => self.ready.store(true, Ordering::Release),
+      Err(e) => {
+        #[cfg(feature = "tracing")]
+        tracing::error!("failed to replay the WAL in the background");
+
+        state.failed = true;
+        state.error = Some(Box::new(e));
+      }
+    }
+    drop(state);
+    self.cond.notify_all();
+  }
+
+  #[inline]
+  fn lock(&self) -> MutexGuard<'_, State<K, V>> {
+    self
+      .state
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+#[inline]
+fn failed<M: BaseTable>() -> Error<M> {
+  Error::corrupted("the WAL failed to replay in the background")
+}
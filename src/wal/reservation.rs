@@ -0,0 +1,119 @@
+use dbutils::{
+  checksum::BuildChecksumer,
+  types::{KeyRef, Type},
+};
+use rarena_allocator::BytesRefMut;
+
+use crate::{error::Error, memtable::BaseTable, sealed::Constructable, sealed::Wal};
+
+/// A record reserved in the WAL by `reserve` of the writer, whose key and value are filled in place.
+///
+/// The record is not visible until it is committed by [`commit`](Reservation::commit), which is `unsafe`
+/// because the filled bytes are not validated. Dropping a reservation without committing it has the same effect
+/// as [`abort`](Reservation::abort). If the process crashes before the reservation is committed, the record is
+/// discarded when the WAL is replayed.
+///
+/// The reservation holds the writer exclusively, so the reserved record is always the last one in the WAL.
+/// It can be moved to another thread, e.g. to fill it from a stream there, and committed on that thread.
+pub struct Reservation<'a, W: Constructable> {
+  wal: &'a W::Wal,
+  buf: BytesRefMut<'a, W::Allocator>,
+  version: Option<u64>,
+  key_offset: usize,
+  klen: usize,
+  vlen: usize,
+}
+
+// SAFETY: the reservation holds the writer exclusively, so no other record is written while it is alive,
+// and its buffer is only accessed through it. The WAL and the allocator are already shared with the readers
+// on the other threads, so the reservation can be filled, committed or aborted on another thread if they are `Sync`.
+unsafe impl<W> Send for Reservation<'_, W>
+where
+  W: Constructable,
+  W::Wal: Sync,
+  W::Allocator: Sync,
+{
+}
+
+impl<W: Constructable> core::fmt::Debug for Reservation<'_, W> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Reservation")
+      .field("version", &self.version)
+      .field("key_len", &self.klen)
+      .field("value_len", &self.vlen)
+      .finish()
+  }
+}
+
+impl<'a, W: Constructable> Reservation<'a, W> {
+  #[inline]
+  pub(crate) fn new(
+    wal: &'a W::Wal,
+    version: Option<u64>,
+    klen: usize,
+    vlen: usize,
+  ) -> Result<Self, Error<W::Memtable>> {
+    let (key_offset, buf) = wal.reserve(version, klen, vlen)?;
+    Ok(Self {
+      wal,
+      buf,
+      version,
+      key_offset,
+      klen,
+      vlen,
+    })
+  }
+
+  /// Returns the version of the reserved record.
+  #[inline]
+  pub const fn version(&self) -> Option<u64> {
+    self.version
+  }
+
+  /// Returns the key buffer, which must be filled with the encoded key before committing.
+  #[inline]
+  pub fn key_mut(&mut self) -> &mut [u8] {
+    &mut self.buf[self.key_offset..self.key_offset + self.klen]
+  }
+
+  /// Returns the value buffer, which must be filled with the encoded value before committing.
+  #[inline]
+  pub fn value_mut(&mut self) -> &mut [u8] {
+    let offset = self.key_offset + self.klen;
+    &mut self.buf[offset..offset + self.vlen]
+  }
+
+  /// Returns the key buffer and the value buffer at the same time.
+  #[inline]
+  pub fn key_value_mut(&mut self) -> (&mut [u8], &mut [u8]) {
+    let (key, value) =
+      self.buf[self.key_offset..self.key_offset + self.klen + self.vlen].split_at_mut(self.klen);
+    (key, value)
+  }
+
+  /// Commits the reserved record, and makes it visible.
+  ///
+  /// ## Safety
+  /// - The bytes in the key buffer must be valid to construct to `K::Ref` without remaining.
+  /// - The bytes in the value buffer must be valid to construct to `V::Ref` without remaining.
+  #[inline]
+  pub unsafe fn commit(self) -> Result<(), Error<W::Memtable>>
+  where
+    W::Checksumer: BuildChecksumer,
+    <W::Memtable as BaseTable>::Key: Type + Ord + 'static,
+    <<W::Memtable as BaseTable>::Key as Type>::Ref<'a>: KeyRef<'a, <W::Memtable as BaseTable>::Key>,
+    <W::Memtable as BaseTable>::Value: Type + 'static,
+  {
+    self.wal.commit_reservation(
+      self.version,
+      self.key_offset,
+      self.klen,
+      self.vlen,
+      self.buf,
+    )
+  }
+
+  /// Aborts the reserved record, the space of the record is reclaimed.
+  #[inline]
+  pub fn abort(self) {}
+}
@@ -0,0 +1,140 @@
+use crate::memtable::{BaseEntry, MultipleVersionMemtable, VersionedMemtableEntry};
+
+use super::KeyPointer;
+
+/// The retention policy of the versions of the multiple version WALs.
+///
+/// Only the version based policies are supported, the number of versions per key and a window of versions.
+/// The policy is applied by the reads, the pruned versions are never removed from the WAL or the memtable.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Retention {
+  maximum_versions: Option<u32>,
+  window: Option<u64>,
+}
+
+impl Retention {
+  #[inline]
+  pub(crate) const fn new(maximum_versions: Option<u32>, window: Option<u64>) -> Self {
+    Self {
+      maximum_versions,
+      window,
+    }
+  }
+
+  /// Returns `true` if all versions are retained.
+  #[inline]
+  pub(crate) const fn is_unlimited(&self) -> bool {
+    self.maximum_versions.is_none() && self.window.is_none()
+  }
+
+  /// Returns `true` if the entry with `version`, which has `newer` newer versions of the same key,
+  /// is retained when the latest version of the WAL is `latest`.
+  #[inline]
+  const fn retains(&self, latest: u64, newer: usize, version: u64) -> bool {
+    // The latest version of a key is always retained.
+    if newer == 0 {
+      return true;
+    }
+
+    if let Some(window) = self.window {
+      if latest.saturating_sub(version) > window {
+        return false;
+      }
+    }
+
+    match self.maximum_versions {
+      Some(max) => newer < max as usize,
+      None => true,
+    }
+  }
+}
+
+/// The retention policy anchored at the latest version of a memtable.
+///
+/// Pruning is monotonic, if a version of a key is pruned, all the older versions of the key are pruned too,
+/// so each key has a floor, the versions below the floor are hidden from all the readers.
+pub(crate) struct Pruning<'a, M> {
+  memtable: Option<&'a M>,
+  retention: Retention,
+  latest: u64,
+}
+
+impl<M> Clone for Pruning<'_, M> {
+  #[inline]
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<M> Copy for Pruning<'_, M> {}
+
+impl<'a, M> Pruning<'a, M>
+where
+  M: MultipleVersionMemtable,
+  for<'b> M::Item<'b>: VersionedMemtableEntry<'b>,
+  for<'b> M::VersionedItem<'b>: VersionedMemtableEntry<'b>,
+{
+  /// Anchors the retention policy at the current latest version of the memtable.
+  #[inline]
+  pub(crate) fn new(memtable: &'a M, retention: Retention) -> Self {
+    if retention.is_unlimited() {
+      return Self {
+        memtable: None,
+        retention,
+        latest: 0,
+      };
+    }
+
+    Self {
+      memtable: Some(memtable),
+      retention,
+      latest: memtable.maximum_version(),
+    }
+  }
+
+  /// Returns `true` if no version is pruned.
+  #[inline]
+  pub(crate) const fn is_empty(&self) -> bool {
+    self.memtable.is_none()
+  }
+
+  /// Returns the oldest retained version of the key.
+  ///
+  /// This walks the newest versions of the key until the first pruned one, so the cost is bounded by
+  /// the number of the retained versions of the key.
+  pub(crate) fn floor(&self, key: &KeyPointer<M::Key, M::Comparator>) -> u64 {
+    let Some(memtable) = self.memtable else {
+      return 0;
+    };
+
+    let Some(mut cursor) = memtable.get_versioned(self.latest, key) else {
+      return 0;
+    };
+
+    let mut floor = cursor.version();
+    let mut newer = 0;
+    while let Some(older) = cursor.next() {
+      newer += 1;
+      if older.key() != *key || !self.retention.retains(self.latest, newer, older.version()) {
+        break;
+      }
+
+      floor = older.version();
+      cursor = older;
+    }
+
+    floor
+  }
+
+  /// Returns `true` if the entry is pruned by the retention policy.
+  ///
+  /// The floor of the key is computed for each call, the iterators which visit many versions of a key
+  /// compute the floor once per key by [`floor`](Pruning::floor) instead.
+  #[inline]
+  pub(crate) fn prunes<'b, E>(&self, ent: &E) -> bool
+  where
+    E: VersionedMemtableEntry<'b, Key = M::Key, Comparator = M::Comparator>,
+  {
+    !self.is_empty() && ent.version() < self.floor(&ent.key())
+  }
+}
@@ -0,0 +1,107 @@
+use std::vec::Vec;
+
+use crate::memtable::BaseTable;
+
+use super::{KeyPointer, ValuePointer};
+
+/// A long-running transaction, which is started by `begin_transaction` of the writer.
+///
+/// The entries written in a transaction are durable as soon as they are written, but they are invisible
+/// until the transaction is committed. If the transaction is aborted, or the commit marker of the
+/// transaction is never appended (e.g. the process crashed), the entries are discarded when the WAL is replayed.
+///
+/// Dropping a transaction without committing it has the same effect as aborting it,
+/// except that no abort marker is appended.
+///
+/// # Memory
+///
+/// The transaction holds a pointer to every entry written in it until it is committed or aborted, which takes
+/// 80 bytes per entry on 64-bit targets apart from the WAL itself, and the replay holds 32 bytes per entry of
+/// a transaction until its commit marker is reached. The memory of a transaction therefore grows with
+/// the number of its entries and is not bounded by the WAL, e.g. a transaction of ten million entries holds
+/// about 800 MiB until it is committed. Bulk loads which do not need to be atomic as a whole should be split
+/// into several transactions, or written by [`insert_batch`](crate::base::Writer::insert_batch).
+///
+/// When the transaction is committed, the space of all its entries is reserved in the memtable before the commit
+/// marker is appended, so the entries are either all visible, or none of them is. The committed entries are ordered
+/// after the range tombstones written while the transaction was open.
+pub struct Transaction<M: BaseTable> {
+  id: u64,
+  wal: usize,
+  #[allow(clippy::type_complexity)]
+  entries: Vec<(
+    Option<u64>,
+    KeyPointer<M::Key, M::Comparator>,
+    Option<ValuePointer<M::Value>>,
+  )>,
+}
+
+impl<M: BaseTable> core::fmt::Debug for Transaction<M> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Transaction")
+      .field("id", &self.id)
+      .field("entries", &self.entries.len())
+      .finish()
+  }
+}
+
+impl<M: BaseTable> Transaction<M> {
+  #[inline]
+  pub(crate) const fn new(id: u64, wal: usize) -> Self {
+    Self {
+      id,
+      wal,
+      entries: Vec::new(),
+    }
+  }
+
+  /// Returns the id of the transaction.
+  #[inline]
+  pub const fn id(&self) -> u64 {
+    self.id
+  }
+
+  /// Returns the number of entries written in the transaction.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns `true` if no entry is written in the transaction.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Panics if the transaction is not started by the WAL at `wal`.
+  #[inline]
+  pub(crate) fn check_owner(&self, wal: usize) {
+    assert_eq!(
+      self.wal, wal,
+      "transaction {} is not started by this WAL",
+      self.id
+    );
+  }
+
+  #[inline]
+  pub(crate) fn push(
+    &mut self,
+    version: Option<u64>,
+    kp: KeyPointer<M::Key, M::Comparator>,
+    vp: Option<ValuePointer<M::Value>>,
+  ) {
+    self.entries.push((version, kp, vp));
+  }
+
+  #[allow(clippy::type_complexity)]
+  #[inline]
+  pub(crate) fn into_entries(
+    self,
+  ) -> Vec<(
+    Option<u64>,
+    KeyPointer<M::Key, M::Comparator>,
+    Option<ValuePointer<M::Value>>,
+  )> {
+    self.entries
+  }
+}